@@ -0,0 +1,59 @@
+use std::fmt::Display;
+
+use crate::{BinaryOperator, ExprNode};
+
+/// ## AST Node structure for assigning a new value to a place
+///
+/// `x = 1;`
+/// -> Store `1` in `x`.
+///
+/// `x += 1;`
+/// -> Compound assignment. Store `x + 1` in `x`.
+///
+/// `*p = 1;`
+/// -> Store `1` where `p` points to.
+#[derive(Clone, Debug)]
+pub struct Assignment {
+    target: Box<ExprNode>,
+
+    /// The operator of a compound assignment.
+    /// None for plain `=`
+    op: Option<BinaryOperator>,
+
+    value: Box<ExprNode>,
+}
+
+impl Assignment {
+    /// Create a new [Assignment]
+    pub fn new(target: ExprNode, op: Option<BinaryOperator>, value: ExprNode) -> Assignment {
+        Assignment {
+            target: Box::from(target),
+            op,
+            value: Box::from(value),
+        }
+    }
+
+    /// Get the place being assigned to
+    pub fn target(&self) -> &ExprNode {
+        &self.target
+    }
+
+    /// Get the operator of a compound assignment
+    pub fn op(&self) -> Option<BinaryOperator> {
+        self.op
+    }
+
+    /// Get the value being assigned
+    pub fn value(&self) -> &ExprNode {
+        &self.value
+    }
+}
+
+impl Display for Assignment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.op {
+            Some(op) => write!(f, "{} {}= {}", self.target, op, self.value),
+            None => write!(f, "{} = {}", self.target, self.value),
+        }
+    }
+}
@@ -7,7 +7,7 @@ use viper_core::token::{PunctuatorKind, Token};
 /// Binary expressions have two operands, a left and right, and 1 operator that binds to them
 ///
 /// eg: 1 + 2
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum BinaryOperator {
     /// '-'
     Minus,
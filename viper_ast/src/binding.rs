@@ -1,29 +1,61 @@
 use std::fmt::Display;
 
-use viper_core::_type::Type;
+use viper_core::{_type::Type, span::Span};
 
 use crate::Ident;
 
 /// Represents binding an identifier to a type
 /// let i: i32 = ...
 ///    |      |
+///
+/// Parameters can be declared mutable
+/// define inc(mut i: i32) ...
 #[derive(Clone, Debug, PartialEq)]
 pub struct Binding {
     ident: Ident,
     ty: Type,
+    mutable: bool,
+
+    /// Location of the identifier in the source code
+    span: Span,
 }
 
 impl Display for Binding {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.mutable {
+            write!(f, "mut ")?;
+        }
         write!(f, "{}: {}", self.ident, self.ty)
     }
 }
 
 impl Binding {
-    pub fn new(ident: String, ty: Type) -> Binding {
+    pub fn new(ident: String, ty: Type, mutable: bool, span: Span) -> Binding {
         Binding {
             ident,
-            ty
+            ty,
+            mutable,
+            span,
         }
     }
+
+    /// Get the identifier being bound
+    pub fn ident(&self) -> &Ident {
+        &self.ident
+    }
+
+    /// Get the type the identifier is bound to
+    pub fn ty(&self) -> &Type {
+        &self.ty
+    }
+
+    /// Whether the identifier can be assigned to
+    pub fn is_mutable(&self) -> bool {
+        self.mutable
+    }
+
+    /// Get the location of the identifier in the source code
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
 }
@@ -33,8 +33,9 @@ impl CodeBlock {
     pub fn add_expr(&mut self, expr: ExprNode) {
         match expr.inner() {
             Expr::Let(init) => {
-                let sym = init.to_symbol();
-                self.scope.as_ref().borrow_mut().add_symbol(init.name(), sym);
+                for sym in init.to_symbols() {
+                    self.scope.as_ref().borrow_mut().add_symbol(sym.get_key(), sym);
+                }
             }
             Expr::ProcedureDefinition(def) => {
                 self.scope.as_ref().borrow_mut().add_symbol(def.name(), def.to_symbol());
@@ -46,6 +47,11 @@ impl CodeBlock {
     }
 
 
+    /// Get the expressions contained in this block
+    pub fn exprs(&self) -> &Vec<ExprNode> {
+        &self.exprs
+    }
+
     /// Return a pointer to the scope of this [CodeBlock]
     pub fn scope(&self) -> Arc<RefCell<Scope>> {
         self.scope.clone()
@@ -1,4 +1,4 @@
-use std::{cell::RefCell, fmt::Display, rc::Rc};
+use std::{cell::{Ref, RefCell}, fmt::Display, rc::Rc};
 
 use crate::ExprNode;
 
@@ -40,6 +40,22 @@ impl Conditional {
             else_clause,
         }
     }
+
+    /// Get the condition of this clause.
+    /// None if this is an `else` clause
+    pub fn condition(&self) -> Option<Ref<'_, ExprNode>> {
+        self.condition.as_ref().map(|c| c.borrow())
+    }
+
+    /// Get the body of this clause
+    pub fn body(&self) -> &ExprNode {
+        &self.body
+    }
+
+    /// Get the `elif` or `else` clause that follows this one
+    pub fn else_clause(&self) -> Option<Ref<'_, ExprNode>> {
+        self.else_clause.as_ref().map(|c| c.borrow())
+    }
 }
 
 impl Display for Conditional {
@@ -0,0 +1,87 @@
+use std::{fmt::Display, sync::Arc};
+
+use viper_core::{_type::Type, source::SourceModule, span::Span, symbol::{Symbol, SymbolKind}};
+
+use crate::{ExprNode, Ident, Visibility};
+
+/// Represents a constant whose value is known at compile time.
+/// Constants can be defined at the top level or within a struct
+/// `const SIZE: u64 = 16;`
+/// `public const MAX: i32 = SIZE * 2;`
+#[derive(Clone, Debug)]
+pub struct ConstDef {
+    /// The name of the constant
+    name: Ident,
+
+    /// The type of the constant, which always has to be written out
+    ty: Type,
+
+    /// The expression that is evaluated to get the constant's value
+    value: Box<ExprNode>,
+
+    visibility: Visibility,
+
+    /// Location of the constant's name in the source code
+    span: Span,
+}
+
+impl ConstDef {
+    /// Create a new [ConstDef]
+    pub fn new(name: Ident, ty: Type, value: ExprNode, visibility: Visibility, span: Span) -> ConstDef {
+        ConstDef {
+            name,
+            ty,
+            value: Box::from(value),
+            visibility,
+            span,
+        }
+    }
+
+    /// Create and return a symbol to insert into a symbol table
+    pub fn to_symbol(&self) -> Symbol {
+        Symbol::new(
+            Arc::from(SourceModule::new_dummy()),
+            Arc::from(self.ty.clone()),
+            self.name.clone(),
+            self.span.clone(),
+            false,
+            SymbolKind::Constant,
+        )
+    }
+
+    /// Get the name of the constant
+    pub fn name(&self) -> &Ident {
+        &self.name
+    }
+
+    /// Get the type of the constant
+    pub fn ty(&self) -> &Type {
+        &self.ty
+    }
+
+    /// Get the expression that gives the constant its value
+    pub fn value(&self) -> &ExprNode {
+        &self.value
+    }
+
+    /// Get the visibility of the constant
+    pub fn visibility(&self) -> &Visibility {
+        &self.visibility
+    }
+
+    /// Get the location of the constant's name in the source code
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl Display for ConstDef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let vis_str = match &self.visibility {
+            Visibility::Public => "public ",
+            Visibility::Private => "",
+        };
+
+        write!(f, "{vis_str}const {}: {} = {}", self.name, self.ty, self.value)
+    }
+}
@@ -1,13 +1,51 @@
 use std::fmt::Display;
 
+use viper_core::span::Span;
 
+use crate::{ExprNode, Ident};
+
+/// Represents accessing a field of a struct value, or an element of a tuple
+/// `user.name`, `self.age`, `pair.0`
 #[derive(Clone, Debug)]
 pub struct Field {
-    name: String,
+    /// The value whose field is accessed
+    object: Box<ExprNode>,
+
+    /// Name of the field
+    name: Ident,
+
+    /// Location of the field's name in the source code
+    span: Span,
+}
+
+impl Field {
+    /// Create a new node for a field access
+    pub fn new(object: ExprNode, name: Ident, span: Span) -> Field {
+        Field {
+            object: Box::from(object),
+            name,
+            span,
+        }
+    }
+
+    /// Get the value whose field is accessed
+    pub fn object(&self) -> &ExprNode {
+        &self.object
+    }
+
+    /// Get the name of the field
+    pub fn name(&self) -> &Ident {
+        &self.name
+    }
+
+    /// Get the location of the field's name in the source code
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
 }
 
 impl Display for Field {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.name)
+        write!(f, "{}.{}", self.object, self.name)
     }
 }
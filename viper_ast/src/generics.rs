@@ -0,0 +1,69 @@
+use std::fmt::Display;
+
+use viper_core::{path::Path, span::Span};
+
+use crate::Ident;
+
+/// A generic parameter of a struct or procedure, which stands
+/// for a type that is given when the item is used
+/// `T` in `define max<T>(a: T, b: T): T`
+/// `T: Weapon + Show` in `define best<T: Weapon + Show>(a: *T): void`
+#[derive(Clone, Debug)]
+pub struct GenericParam {
+    name: Ident,
+
+    /// The interfaces that the type given for the parameter has to implement
+    bounds: Vec<Path>,
+
+    /// Location of the parameter's name in the source code
+    span: Span,
+}
+
+impl GenericParam {
+    /// Create a new [GenericParam]
+    pub fn new(name: Ident, bounds: Vec<Path>, span: Span) -> GenericParam {
+        GenericParam {
+            name,
+            bounds,
+            span,
+        }
+    }
+
+    /// Get the name of the parameter
+    pub fn name(&self) -> &Ident {
+        &self.name
+    }
+
+    /// Get the interfaces that the parameter's type has to implement
+    pub fn bounds(&self) -> &[Path] {
+        &self.bounds
+    }
+
+    /// Get the location of the parameter's name in the source code
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl Display for GenericParam {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.bounds.is_empty() {
+            return write!(f, "{}", self.name);
+        }
+
+        let bounds: Vec<String> = self.bounds.iter().map(|b| b.to_string()).collect();
+        write!(f, "{}: {}", self.name, bounds.join(" + "))
+    }
+}
+
+/// Write out a list of generic parameters the way they are declared.
+/// Nothing is written when there are none
+/// `<A, B>`, `<T: Weapon>`
+pub fn display_generics(generics: &[GenericParam]) -> String {
+    if generics.is_empty() {
+        return String::new();
+    }
+
+    let params: Vec<String> = generics.iter().map(|g| g.to_string()).collect();
+    return format!("<{}>", params.join(", "));
+}
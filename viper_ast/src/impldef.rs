@@ -0,0 +1,86 @@
+use std::{fmt::Display, sync::Arc};
+
+use viper_core::{_type::Type, path::Path, span::Span};
+
+use crate::{Binding, StructMethod};
+
+/// Represents the implementation of an interface for a struct
+/// impl Weapon for Sword {
+///     method damage(): i32 { return self.sharpness * 2; }
+/// }
+#[derive(Clone, Debug)]
+pub struct ImplDef {
+    /// The interface being implemented
+    interface: Path,
+
+    /// The struct that implements the interface
+    target: Path,
+
+    /// The methods of the interface, as they are for the struct.
+    /// They become methods of the struct
+    methods: Box<[StructMethod]>,
+
+    /// Location of the `impl` in the source code
+    span: Span,
+}
+
+impl ImplDef {
+    /// Create a new [ImplDef]
+    pub fn new(interface: Path, target: Path, methods: Box<[StructMethod]>, span: Span) -> ImplDef {
+        ImplDef {
+            interface,
+            target,
+            methods,
+            span,
+        }
+    }
+
+    /// Get the path to the interface being implemented
+    pub fn interface(&self) -> &Path {
+        &self.interface
+    }
+
+    /// Get the path to the struct that implements the interface
+    pub fn target(&self) -> &Path {
+        &self.target
+    }
+
+    /// Get the methods of the implementation
+    pub fn methods(&self) -> &[StructMethod] {
+        &self.methods
+    }
+
+    /// Get the location of the `impl` in the source code
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+
+    /// The implicit `self` parameter of one of the methods.
+    /// It points to the struct, the same as for the struct's own methods.
+    /// None for static methods
+    pub fn receiver(&self, method: &StructMethod) -> Option<Binding> {
+        if method.is_static() {
+            return None;
+        }
+
+        let ty = Type::Pointer {
+            mutable: method.is_mutating(),
+            inner: Arc::from(Type::Concrete { name: self.target.clone(), args: vec![] }),
+        };
+
+        return Some(Binding::new(String::from("self"), ty, false, method.span().clone()));
+    }
+}
+
+impl Display for ImplDef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut str = format!("impl {} for {} {}", self.interface, self.target, '{');
+
+        for method in self.methods.iter() {
+            str += format!("\n{method}").as_str();
+        }
+        str += "\n}\n";
+
+        write!(f, "{str}")
+    }
+}
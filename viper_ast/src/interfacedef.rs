@@ -0,0 +1,148 @@
+use std::fmt::Display;
+
+use viper_core::{_type::Type, span::Span};
+
+use crate::{Binding, Ident, Visibility};
+
+/// Represents an interface, which lists the methods that a
+/// type has to have to implement it
+/// interface Weapon {
+///     method damage(): i32;
+///     mut method swing(times: i32): void;
+/// }
+#[derive(Clone, Debug)]
+pub struct InterfaceDef {
+    /// The name of the interface
+    name: Ident,
+
+    /// The methods that implementations have to provide,
+    /// in the order they are declared
+    methods: Box<[InterfaceMethod]>,
+
+    visibility: Visibility,
+
+    /// Location of the interface's name in the source code
+    span: Span,
+}
+
+impl InterfaceDef {
+    /// Create a new [InterfaceDef]
+    pub fn new(name: Ident, methods: Box<[InterfaceMethod]>, visibility: Visibility, span: Span) -> InterfaceDef {
+        InterfaceDef {
+            name,
+            methods,
+            visibility,
+            span,
+        }
+    }
+
+    /// Get the name of the interface
+    pub fn name(&self) -> &Ident {
+        &self.name
+    }
+
+    /// Get the methods of the interface
+    pub fn methods(&self) -> &[InterfaceMethod] {
+        &self.methods
+    }
+
+    /// Get a method of the interface by name
+    pub fn method(&self, name: &str) -> Option<&InterfaceMethod> {
+        return self.methods.iter().find(|m| m.name() == name);
+    }
+
+    /// Get the visibility of the interface
+    pub fn visibility(&self) -> &Visibility {
+        &self.visibility
+    }
+
+    /// Get the location of the interface's name in the source code
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl Display for InterfaceDef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut str = String::new();
+        str += match &self.visibility {
+            Visibility::Public => "public ",
+            Visibility::Private => "private ",
+        };
+        str += format!("interface {} {}", self.name, '{').as_str();
+
+        for method in self.methods.iter() {
+            str += format!("\n    {method}").as_str();
+        }
+        str += "\n}\n";
+
+        write!(f, "{str}")
+    }
+}
+
+/// The signature of a method that an interface requires.
+/// Interface methods are always called on a value
+/// `mut method swing(times: i32): void;`
+#[derive(Clone, Debug)]
+pub struct InterfaceMethod {
+    name: Ident,
+    parameters: Box<[Binding]>,
+    ret: Type,
+
+    /// Whether the method can change the value it is called on
+    /// `mut method`
+    is_mutating: bool,
+
+    /// Location of the method's name in the source code
+    span: Span,
+}
+
+impl InterfaceMethod {
+    /// Create a new [InterfaceMethod]
+    pub fn new(name: Ident, parameters: Box<[Binding]>, ret: Type, is_mutating: bool, span: Span) -> InterfaceMethod {
+        InterfaceMethod {
+            name,
+            parameters,
+            ret,
+            is_mutating,
+            span,
+        }
+    }
+
+    /// Get the name of the method
+    pub fn name(&self) -> &Ident {
+        &self.name
+    }
+
+    /// Get the parameters of the method
+    pub fn parameters(&self) -> &[Binding] {
+        &self.parameters
+    }
+
+    /// Get the return type of the method
+    pub fn ret(&self) -> &Type {
+        &self.ret
+    }
+
+    /// Whether the method can change the value it is called on
+    pub fn is_mutating(&self) -> bool {
+        self.is_mutating
+    }
+
+    /// Get the location of the method's name in the source code
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl Display for InterfaceMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let params: Vec<String> = self.parameters.iter().map(|p| p.to_string()).collect();
+        let method = match self.is_mutating {
+            true => "mut method",
+            false => "method",
+        };
+
+        write!(f, "{method} {}({}): {};", self.name, params.join(", "), self.ret)
+    }
+}
@@ -1,5 +1,5 @@
-use std::fmt::Display;
-use viper_core::span::Span;
+use std::{fmt::Display, sync::atomic::{AtomicUsize, Ordering}};
+use viper_core::{_type::{ArrayLength, Type}, span::Span};
 
 pub mod objinit;
 pub use objinit::*;
@@ -34,6 +34,27 @@ pub use binding::*;
 pub mod variable_init;
 pub use variable_init::*;
 
+pub mod assignment;
+pub use assignment::*;
+
+pub mod constdef;
+pub use constdef::*;
+
+pub mod generics;
+pub use generics::*;
+
+pub mod interfacedef;
+pub use interfacedef::*;
+
+pub mod impldef;
+pub use impldef::*;
+
+pub mod rewrite;
+pub use rewrite::*;
+
+pub mod repr;
+pub use repr::*;
+
 //pub mod typeast;
 //pub use typeast::*;
 
@@ -43,6 +64,8 @@ pub use whileloop::*;
 pub mod proceduredef;
 pub use proceduredef::*;
 
+pub use viper_core::path::{Path, PathSegment};
+
 /// Whether something is private or public visible
 #[derive(Clone, Debug)]
 pub enum Visibility {
@@ -50,9 +73,17 @@ pub enum Visibility {
     Private,
 }
 
+/// Uniquely identifies a node in the Abstract Syntax Tree.
+/// Used as the key for information that passes attach to nodes
+pub type NodeId = usize;
+
+/// The id that will be given to the next node created
+static NEXT_NODE_ID: AtomicUsize = AtomicUsize::new(0);
+
 /// Represents a node in the Abstract Syntax tree for the Viper programming language
 #[derive(Debug, Clone)]
 pub struct Node<T> {
+    id: NodeId,
     _span: Span,
     inner: T,
 }
@@ -61,15 +92,37 @@ impl <T> Node<T> {
     /// Create a new AST node
     pub fn new(inner: T, span: Span) -> Node<T> {
         Node {
+            id: NEXT_NODE_ID.fetch_add(1, Ordering::Relaxed),
             inner,
             _span: span,
         }
     }
 
+    /// Get the unique id of this node.
+    /// Clones of a node share its id
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    /// Make a node that stands in for this one, with the same id
+    /// and location but with `inner` as what it holds
+    pub fn with_inner(&self, inner: T) -> Node<T> {
+        Node {
+            id: self.id,
+            _span: self._span.clone(),
+            inner,
+        }
+    }
+
     /// Accessor to the inner component of the Node
     pub fn inner(&self) -> &T {
         &self.inner
     }
+
+    /// Get the location of this node in the source code
+    pub fn span(&self) -> &Span {
+        &self._span
+    }
 }
 
 /// Represents expression types in Viper
@@ -87,23 +140,42 @@ pub type Ident = String;
 pub enum Expr {
     True,
     False,
+    Null,
     Integer(u64),
     Float(f64),
     StringLiteral(String),
     Return(Box<ExprNode>),
     Yield(Box<ExprNode>),
+    Ok(Box<ExprNode>),
+    Err(Box<ExprNode>),
+    Try(Box<ExprNode>),
+    Break,
+    Continue,
     ProcedureDefinition(ProcedureDef),
     Let(VariableInitialization),
+    Const(ConstDef),
+    Assignment(Assignment),
     WhileLoop(WhileLoop),
     If(Conditional),
     Identifier(String),
+    Path(Path),
     ProcedureCall(Box<ProcedureCall>),
     MethodCall(Box<MethodCall>),
     MemberFieldAccess(Box<Field>),
     BinaryOperation(BinaryOperator, Box<ExprNode>, Box<ExprNode>),
+    Index(Box<ExprNode>, Box<ExprNode>),
+    Tuple(Vec<ExprNode>),
+    ArrayLiteral(Vec<ExprNode>),
+    ArrayRepeat(Box<ExprNode>, ArrayLength),
     UnaryOperation(UnaryOperator, Box<ExprNode>),
+    Cast(Box<ExprNode>, Type),
+    SizeOf(Type),
+    AlignOf(Type),
+    OffsetOf(Type, Ident),
     CodeBlock(CodeBlock),
     StructDef(StructDef),
+    InterfaceDef(InterfaceDef),
+    ImplDef(ImplDef),
     ObjInitialization(ObjInit),
 } 
 
@@ -118,6 +190,9 @@ impl std::fmt::Display for Expr {
             Self::False => {
                 write!(f, "false")
             }
+            Self::Null => {
+                write!(f, "null")
+            }
             Self::Integer(value) => {
                 write!(f, "{value}")
             }
@@ -130,12 +205,30 @@ impl std::fmt::Display for Expr {
             Self::Yield(expr) => {
                 write!(f, "yield {expr}")
             }
+            Self::Ok(expr) => {
+                write!(f, "Ok({expr})")
+            }
+            Self::Err(expr) => {
+                write!(f, "Err({expr})")
+            }
+            Self::Try(expr) => {
+                write!(f, "{expr}?")
+            }
+            Self::Break => {
+                write!(f, "break")
+            }
+            Self::Continue => {
+                write!(f, "continue")
+            }
             Self::Float(value) => {
                 write!(f, "{value}")
             }
             Self::Identifier(name) => {
                 write!(f, "{name}")
             }
+            Self::Path(path) => {
+                write!(f, "{path}")
+            }
             Self::If(conditional) => {
                 write!(f, "{conditional}")
             }
@@ -160,15 +253,56 @@ impl std::fmt::Display for Expr {
             Self::BinaryOperation(op, lhs, rhs) => {
                 write!(f, "[{} {} {}]", lhs.inner, op, rhs.inner)
             }
+            Self::Index(object, index) => {
+                write!(f, "{}[{}]", object.inner, index.inner)
+            }
+            Self::Tuple(elements) => {
+                let elements: Vec<String> = elements.iter().map(|e| e.to_string()).collect();
+                match elements.len() {
+                    1 => write!(f, "({},)", elements[0]),
+                    _ => write!(f, "({})", elements.join(", ")),
+                }
+            }
+            Self::ArrayLiteral(elements) => {
+                let elements: Vec<String> = elements.iter().map(|e| e.to_string()).collect();
+                write!(f, "[{}]", elements.join(", "))
+            }
+            Self::ArrayRepeat(value, length) => {
+                write!(f, "[{}; {}]", value.inner, length)
+            }
             Self::Let(init) => {
                 write!(f, "{}", init)
             }
+            Self::Const(def) => {
+                write!(f, "{}", def)
+            }
+            Self::Assignment(assignment) => {
+                write!(f, "{}", assignment)
+            }
             Self::UnaryOperation(op, expr) => {
                 write!(f, "{}{}", op, expr.inner)
             }
+            Self::Cast(expr, ty) => {
+                write!(f, "({} as {})", expr.inner, ty)
+            }
+            Self::SizeOf(ty) => {
+                write!(f, "sizeof({ty})")
+            }
+            Self::AlignOf(ty) => {
+                write!(f, "alignof({ty})")
+            }
+            Self::OffsetOf(ty, field) => {
+                write!(f, "offsetof({ty}, {field})")
+            }
             Self::StructDef(structdef) => {
                 write!(f, "{structdef}")
             }
+            Self::InterfaceDef(def) => {
+                write!(f, "{def}")
+            }
+            Self::ImplDef(def) => {
+                write!(f, "{def}")
+            }
             Self::ObjInitialization(init) => {
                 write!(f, "{init}")
             }
@@ -206,6 +340,121 @@ pub enum IntegerType {
 }
 
 pub type ExprNode = Node<Expr>;
+
+impl ExprNode {
+    /// Call `f` on each of the sub-expressions directly contained in this node.
+    ///
+    /// Identifiers being declared by a `let` are not visited, only the
+    /// values they are initialized to.
+    pub fn for_each_child(&self, f: &mut dyn FnMut(&ExprNode)) {
+        match &self.inner {
+            Expr::Return(expr)
+            | Expr::Yield(expr)
+            | Expr::Ok(expr)
+            | Expr::Err(expr)
+            | Expr::Try(expr)
+            | Expr::UnaryOperation(_, expr)
+            | Expr::Cast(expr, _) => {
+                f(expr);
+            }
+            Expr::BinaryOperation(_, lhs, rhs) => {
+                f(lhs);
+                f(rhs);
+            }
+            Expr::Index(object, index) => {
+                f(object);
+                f(index);
+            }
+            Expr::Tuple(elements) | Expr::ArrayLiteral(elements) => {
+                for element in elements {
+                    f(element);
+                }
+            }
+            Expr::ArrayRepeat(value, _) => {
+                f(value);
+            }
+            Expr::ProcedureDefinition(def) => {
+                f(def.body());
+            }
+            Expr::Let(init) => {
+                for value in init.values() {
+                    f(value);
+                }
+            }
+            Expr::Const(def) => {
+                f(def.value());
+            }
+            Expr::Assignment(assignment) => {
+                f(assignment.target());
+                f(assignment.value());
+            }
+            Expr::WhileLoop(whileloop) => {
+                f(whileloop.condition());
+                f(whileloop.body());
+            }
+            Expr::If(conditional) => {
+                if let Some(condition) = conditional.condition() {
+                    f(&condition);
+                }
+                f(conditional.body());
+                if let Some(else_clause) = conditional.else_clause() {
+                    f(&else_clause);
+                }
+            }
+            Expr::ProcedureCall(call) => {
+                for arg in call.arguments() {
+                    f(arg);
+                }
+            }
+            Expr::MethodCall(call) => {
+                f(call.receiver());
+                for arg in call.arguments() {
+                    f(arg);
+                }
+            }
+            Expr::MemberFieldAccess(field) => {
+                f(field.object());
+            }
+            Expr::CodeBlock(block) => {
+                for expr in block.exprs() {
+                    f(expr);
+                }
+            }
+            Expr::ObjInitialization(init) => {
+                for field in init.initializations() {
+                    f(field.value());
+                }
+            }
+            Expr::StructDef(def) => {
+                for def in def.consts() {
+                    f(def.value());
+                }
+                for method in def.methods() {
+                    f(method.body());
+                }
+            }
+            Expr::ImplDef(def) => {
+                for method in def.methods() {
+                    f(method.body());
+                }
+            }
+            Expr::True
+            | Expr::False
+            | Expr::Null
+            | Expr::Break
+            | Expr::Continue
+            | Expr::Integer(_)
+            | Expr::Float(_)
+            | Expr::StringLiteral(_)
+            | Expr::Identifier(_)
+            | Expr::Path(_)
+            | Expr::SizeOf(_)
+            | Expr::AlignOf(_)
+            | Expr::OffsetOf(_, _)
+            | Expr::InterfaceDef(_) => {}
+        }
+    }
+}
 impl Display for ExprNode {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.inner)
@@ -1,12 +1,61 @@
 use std::fmt::Display;
 
+use viper_core::span::Span;
+
+use crate::{ExprNode, Ident};
+
+/// Represents calling a method on a value
+/// `user.print_name()`, `point.scale(2)`
 #[derive(Clone, Debug)]
 pub struct MethodCall {
-    pub name: String,
+    /// The value the method is called on
+    receiver: Box<ExprNode>,
+
+    /// Name of the method being called
+    name: Ident,
+
+    arguments: Vec<Box<ExprNode>>,
+
+    /// Location of the method's name in the source code
+    span: Span,
+}
+
+impl MethodCall {
+    /// Create a new node for a method call
+    pub fn new(receiver: ExprNode, name: Ident, arguments: Vec<Box<ExprNode>>, span: Span) -> MethodCall {
+        MethodCall {
+            receiver: Box::from(receiver),
+            name,
+            arguments,
+            span,
+        }
+    }
+
+    /// Get the value that the method is called on
+    pub fn receiver(&self) -> &ExprNode {
+        &self.receiver
+    }
+
+    /// Get the name of the method being called
+    pub fn name(&self) -> &Ident {
+        &self.name
+    }
+
+    /// Get the arguments passed to the method, not including the receiver
+    pub fn arguments(&self) -> &Vec<Box<ExprNode>> {
+        &self.arguments
+    }
+
+    /// Get the location of the method's name in the source code
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
 }
 
 impl Display for MethodCall {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}()", self.name)
+        let args: Vec<String> = self.arguments.iter().map(|arg| arg.to_string()).collect();
+
+        write!(f, "{}.{}({})", self.receiver, self.name, args.join(", "))
     }
 }
@@ -1,6 +1,8 @@
 use std::fmt::Display;
 
-use crate::{ExprNode, Ident};
+use viper_core::span::Span;
+
+use crate::{ExprNode, Ident, Path};
 
 
 /// Represents initializing an object in Viper
@@ -8,20 +10,35 @@ use crate::{ExprNode, Ident};
 ///     name: "Alex",
 ///     age: 5,
 /// };
+///
+/// let gun = Weapon::LaserGun {
+///     ammo: 100,
+/// };
 #[derive(Clone, Debug)]
 pub struct ObjInit {
-    name: Ident,
+    /// Path to the type being initialized
+    name: Path,
     initializations: Vec<FieldInit>,
 }
 
 impl ObjInit {
     /// Create a new ObjInit object
-    pub fn new(name: Ident, initializations: Vec<FieldInit>) -> ObjInit {
+    pub fn new(name: Path, initializations: Vec<FieldInit>) -> ObjInit {
         ObjInit {
             name,
             initializations,
         }
     }
+
+    /// Get the path to the type being initialized
+    pub fn name(&self) -> &Path {
+        &self.name
+    }
+
+    /// Get the field initializations
+    pub fn initializations(&self) -> &Vec<FieldInit> {
+        &self.initializations
+    }
 }
 
 impl Display for ObjInit {
@@ -41,15 +58,34 @@ impl Display for ObjInit {
 pub struct FieldInit {
     name: Ident,
     value: ExprNode,
+
+    /// Location of the field name in the source code
+    span: Span,
 }
 
 impl FieldInit {
-    pub fn new(name: Ident, value: ExprNode) -> FieldInit {
+    pub fn new(name: Ident, value: ExprNode, span: Span) -> FieldInit {
         FieldInit {
             name,
             value,
+            span,
         }
     }
+
+    /// Get the location of the field name in the source code
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+
+    /// Get the name of the field being initialized
+    pub fn name(&self) -> &Ident {
+        &self.name
+    }
+
+    /// Get the value the field is initialized to
+    pub fn value(&self) -> &ExprNode {
+        &self.value
+    }
 }
 
 impl Display for FieldInit {
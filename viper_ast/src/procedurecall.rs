@@ -1,20 +1,32 @@
 use std::fmt::Display;
-use crate::ExprNode;
+use crate::{ExprNode, Path};
 
 #[derive(Clone, Debug)]
 pub struct ProcedureCall {
-    name: String,
+    /// Path to the procedure being called
+    /// `foo(...)`, `std::io::print(...)`, `User::new(...)`
+    name: Path,
     arguments: Vec<Box<ExprNode>>,
 }
 
 impl ProcedureCall {
     /// Create a new node for a procedure call
-    pub fn new(name: String, arguments: Vec<Box<ExprNode>>) -> ProcedureCall {
+    pub fn new(name: Path, arguments: Vec<Box<ExprNode>>) -> ProcedureCall {
         ProcedureCall {
             name,
             arguments,
         }
     }
+
+    /// Get the path to the procedure being called
+    pub fn name(&self) -> &Path {
+        &self.name
+    }
+
+    /// Get the arguments passed to the procedure
+    pub fn arguments(&self) -> &Vec<Box<ExprNode>> {
+        &self.arguments
+    }
 }
 
 impl Display for ProcedureCall {
@@ -1,8 +1,8 @@
 use std::{fmt::Display, sync::Arc};
 
-use viper_core::{_type::Type, source::SourceModule, span::Span, symbol::Symbol};
+use viper_core::{_type::Type, source::SourceModule, span::Span, symbol::{Symbol, SymbolKind}};
 
-use crate::{Binding, ExprNode, Ident};
+use crate::{display_generics, Binding, ExprNode, GenericParam, Ident, Visibility};
 
 /// The variations of procedures that we can have in the Viper programming language
 #[derive(Clone, Debug, PartialEq)]
@@ -17,7 +17,7 @@ pub enum ProcedureKind {
 
     /// Lambda functions
     /// `
-    /// let lambda = |a, b|: i32 => {
+    /// let lambda: fn(i32, i32) -> i32 = |a, b|: i32 => {
     ///     return a + b;
     /// }
     /// `
@@ -36,6 +36,10 @@ pub struct ProcedureDef {
     /// The name of the procedure
     name: Ident,
 
+    /// The generic parameters, which are filled in by each call
+    /// `<T>` in `define max<T>(a: T, b: T): T`
+    generics: Vec<GenericParam>,
+
     /// The parameter list of what needs to be passed to the procedure
     parameters: Box<[Binding]>,
 
@@ -44,16 +48,53 @@ pub struct ProcedureDef {
 
     /// The return type of the procedure
     ret: Type,
+
+    /// Whether this is a top-level procedure or a lambda
+    kind: ProcedureKind,
+
+    /// Whether other modules can call the procedure.
+    /// Lambdas are always private
+    visibility: Visibility,
+
+    /// Location of the procedure's name in the source code
+    span: Span,
 }
 
 impl ProcedureDef {
     /// Create a new [ProcedureDef] object
-    pub fn new(name: Ident, parameters: Box<[Binding]>, body: Box<ExprNode>, ret: Type) -> ProcedureDef {
+    pub fn new(
+        name: Ident,
+        generics: Vec<GenericParam>,
+        parameters: Box<[Binding]>,
+        body: Box<ExprNode>,
+        ret: Type,
+        visibility: Visibility,
+        span: Span,
+    ) -> ProcedureDef {
+        ProcedureDef {
+            name,
+            generics,
+            parameters,
+            body,
+            ret,
+            kind: ProcedureKind::TopLevel,
+            visibility,
+            span,
+        }
+    }
+
+    /// Create a new [ProcedureDef] for a lambda.
+    /// Lambdas are anonymous, so the name is generated by the parser
+    pub fn new_lambda(name: Ident, parameters: Box<[Binding]>, body: Box<ExprNode>, ret: Type, span: Span) -> ProcedureDef {
         ProcedureDef {
             name,
+            generics: vec![],
             parameters,
             body,
-            ret
+            ret,
+            kind: ProcedureKind::Lambda,
+            visibility: Visibility::Private,
+            span,
         }
     }
 
@@ -61,28 +102,82 @@ impl ProcedureDef {
     pub fn to_symbol(&self) -> Symbol {
         Symbol::new(
             Arc::from(SourceModule::new_dummy()),
-            Arc::from(self.ret.clone()),
+            Arc::from(self.signature()),
             self.name.clone(),
-            Span::dummy(),
+            self.span.clone(),
             false,
+            SymbolKind::Procedure,
         )
     }
 
+    /// Get the type of the procedure from its parameters and return type
+    pub fn signature(&self) -> Type {
+        Type::Procedure {
+            name: self.name.clone(),
+            params: self.parameters.iter().map(|p| p.ty().clone()).collect(),
+            return_type: Arc::from(self.ret.clone()),
+        }
+    }
+
     /// Get the name of the procedure
     pub fn name(&self) -> String {
         self.name.clone()
     }
+
+    /// Get the generic parameters of the procedure
+    pub fn generics(&self) -> &[GenericParam] {
+        &self.generics
+    }
+
+    /// Get the parameters of the procedure
+    pub fn parameters(&self) -> &[Binding] {
+        &self.parameters
+    }
+
+    /// Get the code body of the procedure
+    pub fn body(&self) -> &ExprNode {
+        &self.body
+    }
+
+    /// Get the return type of the procedure
+    pub fn ret(&self) -> &Type {
+        &self.ret
+    }
+
+    /// Get the kind of procedure this is
+    pub fn kind(&self) -> &ProcedureKind {
+        &self.kind
+    }
+
+    /// Get the visibility of the procedure
+    pub fn visibility(&self) -> &Visibility {
+        &self.visibility
+    }
+
+    /// Get the location of the procedure's name in the source code
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
 }
 
 impl Display for ProcedureDef {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut procstr = format!("define {}(", self.name);
+        let mut procstr = match self.kind {
+            ProcedureKind::TopLevel => match self.visibility {
+                Visibility::Public => format!("public define {}{}(", self.name, display_generics(&self.generics)),
+                Visibility::Private => format!("define {}{}(", self.name, display_generics(&self.generics)),
+            },
+            ProcedureKind::Lambda => String::from("|"),
+        };
 
         let pit = self.parameters.iter();
         for param in pit.as_slice() {
             procstr += format!("{}, ", param).as_str();
         }
-        procstr += format!("): {} {}\n", self.ret, '{').as_str();
+        procstr += match self.kind {
+            ProcedureKind::TopLevel => format!("): {} {}\n", self.ret, '{'),
+            ProcedureKind::Lambda => format!("|: {} => {}\n", self.ret, '{'),
+        }.as_str();
 
         procstr += format!("{}", self.body).as_str();
         procstr += "}\n";
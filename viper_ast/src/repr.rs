@@ -0,0 +1,60 @@
+use std::fmt::Display;
+
+/// How a struct is laid out in memory, from the `#[repr(...)]` attributes before it
+/// `#[repr(C)]`, `#[repr(packed)]`, `#[repr(C, align(16))]`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Repr {
+    /// `C` keeps the fields in the order they are declared.
+    /// Otherwise they are ordered from the most to the least aligned,
+    /// which leaves the least padding between them
+    c: bool,
+
+    /// `packed` leaves out all padding, so every field is aligned to 1 byte
+    packed: bool,
+
+    /// `align(N)` aligns the struct to at least `N` bytes
+    align: Option<u64>,
+}
+
+impl Repr {
+    /// Create a new [Repr]
+    pub fn new(c: bool, packed: bool, align: Option<u64>) -> Repr {
+        Repr {
+            c,
+            packed,
+            align,
+        }
+    }
+
+    /// Whether the fields are kept in the order they are declared
+    pub fn is_c(&self) -> bool {
+        self.c
+    }
+
+    /// Whether the fields are laid out without padding
+    pub fn is_packed(&self) -> bool {
+        self.packed
+    }
+
+    /// Get the alignment that the struct is raised to, if any
+    pub fn align(&self) -> Option<u64> {
+        self.align
+    }
+}
+
+impl Display for Repr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut options = vec![];
+        if self.c {
+            options.push(String::from("C"));
+        }
+        if self.packed {
+            options.push(String::from("packed"));
+        }
+        if let Some(align) = self.align {
+            options.push(format!("align({align})"));
+        }
+
+        write!(f, "#[repr({})]", options.join(", "))
+    }
+}
@@ -0,0 +1,235 @@
+use std::{cell::RefCell, rc::Rc};
+
+use viper_core::{_type::Type, path::Path};
+
+use crate::{
+    Assignment, Binding, CodeBlock, Conditional, ConstDef, Expr, ExprNode, Field, FieldInit, ImplDef,
+    MethodCall, Node, NodeId, ObjInit, ProcedureCall, ProcedureDef, ProcedureKind, StructDef,
+    StructField, StructMethod, VariableInitialization, WhileLoop,
+};
+
+/// Changes the types and paths written within a tree of expressions.
+/// Used to make copies of generic items for each of their type arguments
+pub trait Rewriter {
+    /// Rewrite a type as it is written in the source,
+    /// like the type of a parameter or of a cast
+    fn rewrite_type(&mut self, ty: &Type) -> Type;
+
+    /// Rewrite the path of a call or a struct initializer.
+    /// `id` is the id of the call or initializer
+    fn rewrite_path(&mut self, id: NodeId, path: &Path) -> Path;
+
+    /// Replace an expression with one made by the rewriter, instead of copying it.
+    /// The rewriter copies the parts of the expression it keeps itself
+    fn replace(&mut self, expr: &ExprNode) -> Option<ExprNode>;
+}
+
+impl ExprNode {
+    /// Copy this node with its types and paths changed by `rewriter`.
+    ///
+    /// The nodes of the copy keep the ids of the nodes they are copied
+    /// from, so that what passes found out about them still applies
+    pub fn rewrite(&self, rewriter: &mut dyn Rewriter) -> ExprNode {
+        let boxed = |expr: &ExprNode, rewriter: &mut dyn Rewriter| Box::from(expr.rewrite(rewriter));
+        let all = |exprs: &[Box<ExprNode>], rewriter: &mut dyn Rewriter| {
+            exprs.iter().map(|expr| Box::from(expr.rewrite(rewriter))).collect::<Vec<Box<ExprNode>>>()
+        };
+
+        if let Some(replacement) = rewriter.replace(self) {
+            return replacement;
+        }
+
+        let inner = match &self.inner {
+            Expr::True
+            | Expr::False
+            | Expr::Null
+            | Expr::Break
+            | Expr::Continue
+            | Expr::Integer(_)
+            | Expr::Float(_)
+            | Expr::StringLiteral(_)
+            | Expr::Identifier(_)
+            | Expr::InterfaceDef(_) => self.inner.clone(),
+            Expr::Path(path) => Expr::Path(rewriter.rewrite_path(self.id, path)),
+            Expr::Return(value) => Expr::Return(boxed(value, rewriter)),
+            Expr::Yield(value) => Expr::Yield(boxed(value, rewriter)),
+            Expr::Ok(value) => Expr::Ok(boxed(value, rewriter)),
+            Expr::Err(value) => Expr::Err(boxed(value, rewriter)),
+            Expr::Try(value) => Expr::Try(boxed(value, rewriter)),
+            Expr::ProcedureDefinition(def) => Expr::ProcedureDefinition(def.rewrite(rewriter)),
+            Expr::Let(init) => {
+                Expr::Let(VariableInitialization::new(
+                    init.targets().clone(),
+                    init.dtype().map(|dtype| rewriter.rewrite_type(dtype)),
+                    init.is_mutable(),
+                    all(init.values(), rewriter),
+                ))
+            }
+            Expr::Const(def) => Expr::Const(def.rewrite(rewriter)),
+            Expr::Assignment(assignment) => {
+                Expr::Assignment(Assignment::new(
+                    assignment.target().rewrite(rewriter),
+                    assignment.op(),
+                    assignment.value().rewrite(rewriter),
+                ))
+            }
+            Expr::WhileLoop(whileloop) => {
+                Expr::WhileLoop(WhileLoop::new(
+                    boxed(whileloop.condition(), rewriter),
+                    boxed(whileloop.body(), rewriter),
+                ))
+            }
+            Expr::If(conditional) => {
+                let condition = conditional.condition()
+                    .map(|condition| Rc::from(RefCell::new(condition.rewrite(rewriter))));
+                let body = Rc::from(conditional.body().rewrite(rewriter));
+                let else_clause = conditional.else_clause()
+                    .map(|else_clause| Rc::from(RefCell::new(else_clause.rewrite(rewriter))));
+
+                Expr::If(Conditional::new(condition, body, else_clause))
+            }
+            Expr::ProcedureCall(call) => {
+                Expr::ProcedureCall(Box::from(ProcedureCall::new(
+                    rewriter.rewrite_path(self.id, call.name()),
+                    all(call.arguments(), rewriter),
+                )))
+            }
+            Expr::MethodCall(call) => {
+                Expr::MethodCall(Box::from(MethodCall::new(
+                    call.receiver().rewrite(rewriter),
+                    call.name().clone(),
+                    all(call.arguments(), rewriter),
+                    call.span().clone(),
+                )))
+            }
+            Expr::MemberFieldAccess(field) => {
+                Expr::MemberFieldAccess(Box::from(Field::new(
+                    field.object().rewrite(rewriter),
+                    field.name().clone(),
+                    field.span().clone(),
+                )))
+            }
+            Expr::BinaryOperation(op, lhs, rhs) => {
+                Expr::BinaryOperation(*op, boxed(lhs, rewriter), boxed(rhs, rewriter))
+            }
+            Expr::Index(object, index) => Expr::Index(boxed(object, rewriter), boxed(index, rewriter)),
+            Expr::Tuple(elements) => Expr::Tuple(elements.iter().map(|e| e.rewrite(rewriter)).collect()),
+            Expr::ArrayLiteral(elements) => Expr::ArrayLiteral(elements.iter().map(|e| e.rewrite(rewriter)).collect()),
+            Expr::ArrayRepeat(value, length) => Expr::ArrayRepeat(boxed(value, rewriter), length.clone()),
+            Expr::UnaryOperation(op, operand) => Expr::UnaryOperation(*op, boxed(operand, rewriter)),
+            Expr::Cast(value, ty) => Expr::Cast(boxed(value, rewriter), rewriter.rewrite_type(ty)),
+            Expr::SizeOf(ty) => Expr::SizeOf(rewriter.rewrite_type(ty)),
+            Expr::AlignOf(ty) => Expr::AlignOf(rewriter.rewrite_type(ty)),
+            Expr::OffsetOf(ty, field) => Expr::OffsetOf(rewriter.rewrite_type(ty), field.clone()),
+            Expr::CodeBlock(block) => {
+                let exprs = block.exprs().iter().map(|expr| expr.rewrite(rewriter)).collect();
+                Expr::CodeBlock(CodeBlock::new(exprs, block.scope()))
+            }
+            Expr::StructDef(def) => Expr::StructDef(def.rewrite(rewriter)),
+            Expr::ImplDef(def) => Expr::ImplDef(def.rewrite(rewriter)),
+            Expr::ObjInitialization(init) => {
+                let fields = init.initializations().iter()
+                    .map(|field| FieldInit::new(field.name().clone(), field.value().rewrite(rewriter), field.span().clone()))
+                    .collect();
+
+                Expr::ObjInitialization(ObjInit::new(rewriter.rewrite_path(self.id, init.name()), fields))
+            }
+        };
+
+        return Node {
+            id: self.id,
+            _span: self._span.clone(),
+            inner,
+        };
+    }
+}
+
+impl Binding {
+    /// Copy this binding with its type changed by `rewriter`
+    pub fn rewrite(&self, rewriter: &mut dyn Rewriter) -> Binding {
+        Binding::new(self.ident().clone(), rewriter.rewrite_type(self.ty()), self.is_mutable(), self.span().clone())
+    }
+}
+
+impl ProcedureDef {
+    /// Copy this procedure with the types and paths in it changed by `rewriter`
+    pub fn rewrite(&self, rewriter: &mut dyn Rewriter) -> ProcedureDef {
+        let params = self.parameters().iter().map(|param| param.rewrite(rewriter)).collect();
+        let body = Box::from(self.body().rewrite(rewriter));
+        let ret = rewriter.rewrite_type(self.ret());
+
+        match self.kind() {
+            ProcedureKind::TopLevel => {
+                ProcedureDef::new(
+                    self.name(),
+                    self.generics().to_vec(),
+                    params,
+                    body,
+                    ret,
+                    self.visibility().clone(),
+                    self.span().clone()
+                )
+            }
+            ProcedureKind::Lambda => ProcedureDef::new_lambda(self.name(), params, body, ret, self.span().clone()),
+        }
+    }
+}
+
+impl ConstDef {
+    /// Copy this constant with its type and value changed by `rewriter`
+    pub fn rewrite(&self, rewriter: &mut dyn Rewriter) -> ConstDef {
+        ConstDef::new(
+            self.name().clone(),
+            rewriter.rewrite_type(self.ty()),
+            self.value().rewrite(rewriter),
+            self.visibility().clone(),
+            self.span().clone()
+        )
+    }
+}
+
+impl StructDef {
+    /// Copy this struct with the types and paths in it changed by `rewriter`
+    pub fn rewrite(&self, rewriter: &mut dyn Rewriter) -> StructDef {
+        let fields = self.fields().iter()
+            .map(|field| StructField::new(field.binding().rewrite(rewriter), field.visibility().clone()))
+            .collect();
+
+        let methods = self.methods().iter().map(|method| method.rewrite(rewriter)).collect();
+
+        let consts = self.consts().iter().map(|def| def.rewrite(rewriter)).collect();
+
+        StructDef::new(
+            self.identifier().clone(),
+            self.generics().to_vec(),
+            fields,
+            methods,
+            consts,
+            self.visibility().clone(),
+            self.repr().clone(),
+        )
+    }
+}
+
+impl ImplDef {
+    /// Copy this implementation with the types and paths in its methods changed by `rewriter`
+    pub fn rewrite(&self, rewriter: &mut dyn Rewriter) -> ImplDef {
+        let methods = self.methods().iter().map(|method| method.rewrite(rewriter)).collect();
+        ImplDef::new(self.interface().clone(), self.target().clone(), methods, self.span().clone())
+    }
+}
+
+impl StructMethod {
+    /// Copy this method with the types and paths in it changed by `rewriter`
+    pub fn rewrite(&self, rewriter: &mut dyn Rewriter) -> StructMethod {
+        StructMethod::new(
+            self.name().clone(),
+            self.parameters().iter().map(|param| param.rewrite(rewriter)).collect(),
+            Box::from(self.body().rewrite(rewriter)),
+            rewriter.rewrite_type(self.ret()),
+            self.visibility().clone(),
+            self.kind(),
+            self.span().clone()
+        )
+    }
+}
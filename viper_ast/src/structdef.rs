@@ -1,8 +1,8 @@
-use std::fmt::Display;
+use std::{fmt::Display, sync::Arc};
 
-use viper_core::_type::Type;
+use viper_core::{_type::Type, path::Path, span::Span};
 
-use crate::{Binding, ExprNode, Ident, Visibility};
+use crate::{display_generics, Binding, ConstDef, ExprNode, GenericParam, Ident, Repr, Visibility};
 
 /// Represents a struct definition in Viper
 #[derive(Clone, Debug)]
@@ -10,44 +10,138 @@ pub struct StructDef {
     /// The identifier representing the struct
     identifier: Ident,
 
+    /// The generic parameters, which are given by each use of the struct
+    /// `<A, B>` in `struct Pair<A, B>`
+    generics: Vec<GenericParam>,
+
     /// The fields contained in the struct
     fields: Box<[StructField]>,
 
     /// The class methods
     methods: Box<[StructMethod]>,
 
+    /// The constants that belong to the struct
+    /// `User::MAX_AGE`
+    consts: Box<[ConstDef]>,
+
     visibility: Visibility,
+
+    /// How the struct is laid out in memory
+    /// `#[repr(C)]`
+    repr: Repr,
 }
 
 impl StructDef {
     /// Create a new [StructDef] object
     pub fn new(
         identifier: Ident, 
+        generics: Vec<GenericParam>,
         fields: Box<[StructField]>, 
         methods: Box<[StructMethod]>,
+        consts: Box<[ConstDef]>,
         visibility: Visibility,
+        repr: Repr,
     ) -> StructDef {
         StructDef {
             identifier,
+            generics,
             fields,
             methods,
+            consts,
             visibility,
+            repr,
+        }
+    }
+
+    /// Get the name of the struct
+    pub fn identifier(&self) -> &Ident {
+        &self.identifier
+    }
+
+    /// Get the generic parameters of the struct
+    pub fn generics(&self) -> &[GenericParam] {
+        &self.generics
+    }
+
+    /// Get the fields of the struct
+    pub fn fields(&self) -> &[StructField] {
+        &self.fields
+    }
+
+    /// Get the methods of the struct
+    pub fn methods(&self) -> &[StructMethod] {
+        &self.methods
+    }
+
+    /// Get the constants of the struct
+    pub fn consts(&self) -> &[ConstDef] {
+        &self.consts
+    }
+
+    /// Get the visibility of the struct
+    pub fn visibility(&self) -> &Visibility {
+        &self.visibility
+    }
+
+    /// Get how the struct is laid out in memory
+    pub fn repr(&self) -> &Repr {
+        &self.repr
+    }
+
+    /// The type of the struct within its own definition.
+    /// A generic struct has its generic parameters as its type arguments
+    /// `User`, `Pair<A, B>`
+    pub fn self_type(&self, span: &Span) -> Type {
+        let args = self.generics.iter()
+            .map(|g| Type::Concrete {
+                name: Path::from_ident(g.name().clone(), g.span().clone()),
+                args: vec![],
+            })
+            .collect();
+
+        return Type::Concrete {
+            name: Path::from_ident(self.identifier.clone(), span.clone()),
+            args,
+        };
+    }
+
+    /// The implicit `self` parameter of one of this struct's methods.
+    /// It points to the value the method is called on, and is only
+    /// a `*mut` pointer for `mut method`s.
+    /// For a generic struct it is `*Pair<A, B>`.
+    /// None for static methods
+    pub fn receiver(&self, method: &StructMethod) -> Option<Binding> {
+        if method.is_static() {
+            return None;
         }
+
+        let ty = Type::Pointer {
+            mutable: method.is_mutating(),
+            inner: Arc::from(self.self_type(method.span())),
+        };
+
+        return Some(Binding::new(String::from("self"), ty, false, method.span().clone()));
     }
 }
 
 impl Display for StructDef {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut str = String::new();
+        if self.repr != Repr::default() {
+            str += format!("{}\n", self.repr).as_str();
+        }
         str += match &self.visibility {
             Visibility::Public => "public ",
             Visibility::Private => "private ",
         };
-        str += format!("struct {} {}", self.identifier, '{').as_str();
+        str += format!("struct {}{} {}", self.identifier, display_generics(&self.generics), '{').as_str();
         
         for field in self.fields.iter() {
             str += format!("\n    {},", field).as_str();
         }
+        for def in self.consts.iter() {
+            str += format!("\n    {def};").as_str();
+        }
         for method in self.methods.iter() {
             str += format!("\n{method}").as_str();
         }
@@ -73,6 +167,16 @@ impl StructField {
             visibility,
         }
     }
+
+    /// Get the name and type of the field
+    pub fn binding(&self) -> &Binding {
+        &self.binding
+    }
+
+    /// Get the visibility of the field
+    pub fn visibility(&self) -> &Visibility {
+        &self.visibility
+    }
 }
 
 impl Display for StructField {
@@ -94,7 +198,26 @@ pub struct StructMethod {
     body: Box<ExprNode>,
     ret: Type,
     visibility: Visibility,
-    is_static: bool,
+    kind: MethodKind,
+
+    /// Location of the method's name in the source code
+    span: Span,
+}
+
+/// How a method in Viper is called
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MethodKind {
+    /// Called on the type rather than an instance
+    /// `static make`
+    Static,
+
+    /// Called on an instance without changing it
+    /// `method length`
+    Method,
+
+    /// Called on an instance and can change it
+    /// `mut method push`
+    Mutating,
 }
 
 impl StructMethod {
@@ -105,7 +228,8 @@ impl StructMethod {
         body: Box<ExprNode>,
         ret: Type,
         visibility: Visibility,
-        is_static: bool,
+        kind: MethodKind,
+        span: Span,
     ) -> StructMethod {
         StructMethod {
             name,
@@ -113,9 +237,55 @@ impl StructMethod {
             body,
             ret,
             visibility,
-            is_static,
+            kind,
+            span,
         }
     }
+
+    /// Get the name of the method
+    pub fn name(&self) -> &Ident {
+        &self.name
+    }
+
+    /// Get the parameters of the method
+    pub fn parameters(&self) -> &[Binding] {
+        &self.parameters
+    }
+
+    /// Get the code body of the method
+    pub fn body(&self) -> &ExprNode {
+        &self.body
+    }
+
+    /// Get the return type of the method
+    pub fn ret(&self) -> &Type {
+        &self.ret
+    }
+
+    /// Get the visibility of the method
+    pub fn visibility(&self) -> &Visibility {
+        &self.visibility
+    }
+
+    /// Get how the method is called
+    pub fn kind(&self) -> MethodKind {
+        self.kind
+    }
+
+    /// Whether the method is called on the type rather than an instance
+    pub fn is_static(&self) -> bool {
+        self.kind == MethodKind::Static
+    }
+
+    /// Whether the method can change the value it is called on
+    pub fn is_mutating(&self) -> bool {
+        self.kind == MethodKind::Mutating
+    }
+
+    /// Get the location of the method's name in the source code
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
 }
 
 impl Display for StructMethod {
@@ -127,9 +297,10 @@ impl Display for StructMethod {
             Visibility::Private => "private ",
         };
 
-        str += match self.is_static {
-            true => "static ",
-            false => "method ",
+        str += match self.kind {
+            MethodKind::Static => "static ",
+            MethodKind::Method => "method ",
+            MethodKind::Mutating => "mut method ",
         };
 
         str += format!("{} (", &self.name).as_str();
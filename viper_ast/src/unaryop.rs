@@ -10,7 +10,9 @@ use viper_core::token::{PunctuatorKind, Token};
 /// eg: !true -> !(true) -> false
 /// 
 /// eg: -1 -> -(1) -> negative 1
-#[derive(Clone, Debug)]
+///
+/// eg: *ptr -> the value that `ptr` points to
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum UnaryOperator {
     /// '-'
     Minus,
@@ -18,6 +20,12 @@ pub enum UnaryOperator {
     BinaryNot,
     /// '!'
     LogicalNot,
+    /// '&'
+    AddressOf,
+    /// '&mut'
+    AddressOfMut,
+    /// '*'
+    Deref,
     /// Not a unary operator
     InvalidUnary,
 }
@@ -30,6 +38,8 @@ impl From<Token> for UnaryOperator {
                     PunctuatorKind::Minus => UnaryOperator::Minus,
                     PunctuatorKind::Bang => UnaryOperator::LogicalNot,
                     PunctuatorKind::Tilde => UnaryOperator::BinaryNot,
+                    PunctuatorKind::Ampersand => UnaryOperator::AddressOf,
+                    PunctuatorKind::Star => UnaryOperator::Deref,
                     _ => UnaryOperator::InvalidUnary,
                 }
             }
@@ -44,6 +54,9 @@ impl Display for UnaryOperator {
             Self::Minus => write!(f, "-"),
             Self::BinaryNot=> write!(f, "~"),
             Self::LogicalNot=> write!(f, "!"),
+            Self::AddressOf => write!(f, "&"),
+            Self::AddressOfMut => write!(f, "&mut "),
+            Self::Deref => write!(f, "*"),
             Self::InvalidUnary=> write!(f, "Invalid Unary Operator"),
         }
     }
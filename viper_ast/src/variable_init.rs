@@ -1,8 +1,8 @@
 use std::{fmt::Display, sync::Arc};
 
-use viper_core::{_type::Type, source::SourceModule, span::Span, symbol::Symbol};
+use viper_core::{_type::Type, source::SourceModule, span::Span, symbol::{Symbol, SymbolKind}};
 
-use crate::ExprNode;
+use crate::{Expr, ExprNode, NodeId};
 
 /// ## AST Node structure for initializing a variable
 ///
@@ -14,10 +14,19 @@ use crate::ExprNode;
 ///
 /// `let i, j, k: i32 = 1, 2, 4;` 
 /// -> Declare i j and k and init them to 1, 2 and 4 respectively.
+///
+/// `let i = 0;`
+/// -> Declare `i` with its type inferred from its value.
+///
+/// `let mut i: i32 = 0;`
+/// -> Declare `i` so that it can be assigned to later.
+///
+/// `let (q, r): (i32, i32) = divide(7, 2);`
+/// -> Declare `q` and `r` from the elements of a tuple.
 #[derive(Clone, Debug)]
 pub struct VariableInitialization {
     targets: Vec<Box<ExprNode>>,
-    dtype: Type,
+    dtype: Option<Type>,
     // dtype: Token,
     mutable: bool,
     values: Vec<Box<ExprNode>>,
@@ -25,7 +34,7 @@ pub struct VariableInitialization {
 
 impl VariableInitialization {
     /// Create a new VariableInitialization
-    pub fn new(targets: Vec<Box<ExprNode>>, dtype: Type, mutable: bool, values: Vec<Box<ExprNode>>) -> VariableInitialization {
+    pub fn new(targets: Vec<Box<ExprNode>>, dtype: Option<Type>, mutable: bool, values: Vec<Box<ExprNode>>) -> VariableInitialization {
         VariableInitialization {
             targets,
             dtype,
@@ -34,30 +43,150 @@ impl VariableInitialization {
         }
     }
 
-    /// Create and return a symbol to insert into a symbol table
-    /// from the information in this declaration
-    pub fn to_symbol(&self) -> Symbol {
-        Symbol::new(
-            Arc::from(SourceModule::new_dummy()),
-            Arc::from(self.dtype.clone()), 
-            self.targets[0].to_string(), 
-            Span::dummy(), 
-            self.mutable
-        )
+    /// Create and return the symbols to insert into a symbol table
+    /// from the information in this declaration, one for each variable.
+    ///
+    /// Variables without a declared type get the type `void`
+    /// until the type checker infers the real one
+    pub fn to_symbols(&self) -> Vec<Symbol> {
+        self.bindings().into_iter().map(|binding| {
+            Symbol::new(
+                Arc::from(SourceModule::new_dummy()),
+                Arc::from(binding.ty.unwrap_or(Type::Void)),
+                binding.name,
+                binding.span,
+                self.mutable,
+                SymbolKind::Variable,
+            )
+        }).collect()
     }
 
+    /// Get the pattern on the left side, as it was written
+    /// `x`, `(q, r)`
     pub fn name(&self) -> String {
         self.targets[0].to_string().clone()
     }
+
+    /// Get the variables that the pattern declares, in the order they are written
+    /// `let (a, (b, c))` -> `a`, `b` and `c`
+    pub fn bindings(&self) -> Vec<LetBinding> {
+        let mut bindings = vec![];
+        collect_bindings(&self.targets[0], &mut vec![], self.dtype.as_ref(), &mut bindings);
+        return bindings;
+    }
+
+    /// Get the variables being initialized
+    pub fn targets(&self) -> &Vec<Box<ExprNode>> {
+        &self.targets
+    }
+
+    /// Get the declared type of the variable.
+    /// None if the type is left to be inferred
+    pub fn dtype(&self) -> Option<&Type> {
+        self.dtype.as_ref()
+    }
+
+    /// Whether the variables were declared with `let mut`
+    pub fn is_mutable(&self) -> bool {
+        self.mutable
+    }
+
+    /// Get the values that the targets are initialized to
+    pub fn values(&self) -> &Vec<Box<ExprNode>> {
+        &self.values
+    }
 }
 
 impl Display for VariableInitialization {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "let {} {}: {} = {}", 
-            if self.mutable {"mut"} else {""},
+        write!(f, "let {}{}", 
+            if self.mutable {"mut "} else {""},
             self.targets[0].inner,
-            self.dtype,
-            self.values[0].inner
-        )
+        )?;
+        if let Some(dtype) = &self.dtype {
+            write!(f, ": {}", dtype)?;
+        }
+        if let Some(value) = self.values.first() {
+            write!(f, " = {}", value.inner)?;
+        }
+        Ok(())
+    }
+}
+
+/// A variable declared by the pattern of a `let`
+/// `r` in `let (q, r): (i32, i32)` has the path `[1]` and the type `i32`
+#[derive(Clone, Debug)]
+pub struct LetBinding {
+    /// Name of the variable
+    name: String,
+
+    /// Id of the variable's name in the pattern.
+    /// The type checker records the type of the variable under it
+    id: NodeId,
+
+    /// Positions of the tuple elements that hold the variable's value,
+    /// from the outermost tuple inwards. Empty if the pattern is just a name
+    path: Vec<usize>,
+
+    /// Location of the variable's name in the source code
+    span: Span,
+
+    /// The part of the declared type that belongs to the variable.
+    /// None if there is no declared type, or it does not fit the pattern
+    ty: Option<Type>,
+}
+
+impl LetBinding {
+    /// Get the name of the variable
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Get the id of the variable's name in the pattern
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    /// Get the positions of the tuple elements that hold the variable's value
+    pub fn path(&self) -> &[usize] {
+        &self.path
+    }
+
+    /// Get the location of the variable's name in the source code
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+
+    /// Get the part of the declared type that belongs to the variable
+    pub fn ty(&self) -> Option<&Type> {
+        self.ty.as_ref()
+    }
+}
+
+/// Collect the variables of a pattern, with `path` leading to it
+/// and `ty` being the part of the declared type that it gets
+fn collect_bindings(pattern: &ExprNode, path: &mut Vec<usize>, ty: Option<&Type>, bindings: &mut Vec<LetBinding>) {
+    match &pattern.inner {
+        Expr::Tuple(elements) => {
+            for (i, element) in elements.iter().enumerate() {
+                let element_ty = match ty {
+                    Some(Type::Tuple(types)) if types.len() == elements.len() => Some(&types[i]),
+                    _ => None,
+                };
+
+                path.push(i);
+                collect_bindings(element, path, element_ty, bindings);
+                path.pop();
+            }
+        }
+        _ => {
+            bindings.push(LetBinding {
+                name: pattern.to_string(),
+                id: pattern.id(),
+                path: path.clone(),
+                span: pattern.span().clone(),
+                ty: ty.cloned(),
+            });
+        }
     }
 }
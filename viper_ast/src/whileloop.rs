@@ -26,4 +26,14 @@ impl WhileLoop {
             body,
         }
     }
+
+    /// Get the condition of the loop
+    pub fn condition(&self) -> &ExprNode {
+        &self.condition
+    }
+
+    /// Get the body of the loop
+    pub fn body(&self) -> &ExprNode {
+        &self.body
+    }
 }
@@ -1,16 +1,23 @@
 use std::{fmt::Display, sync::Arc};
 
+use crate::path::Path;
+
 
 /// Represents a type within the Abstract Syntax Tree
 /// let i: i32 = ...
 /// i32 is a node
+///
+/// These are the types as written in the source code.
+/// Semantic analysis lowers them into a [crate::types::TypeInterner]
 #[derive(Clone, Debug, PartialEq)]
 pub enum Type {
     Concrete {
-        name: String,
+        /// The path naming the type
+        /// i32, User, std::string etc
+        name: Path,
 
-        /// Arguments to the type. 
-        /// i32, [] etc
+        /// Arguments to a generic type
+        /// `Pair<i32, f64>` -> `i32`, `f64`
         args: Vec<Self>,
     },
     Procedure {
@@ -21,32 +28,136 @@ pub enum Type {
 
         /// Return type of the procedure
         return_type: Arc<Self>,
-    }
+    },
+
+    /// Raw pointer
+    /// `*T` or `*mut T`
+    Pointer {
+        mutable: bool,
+        inner: Arc<Self>,
+    },
+
+    /// Reference
+    /// `&T` or `&mut T`
+    Reference {
+        mutable: bool,
+        inner: Arc<Self>,
+    },
+
+    /// Fixed size array
+    /// `[T; N]`
+    Array {
+        element: Arc<Self>,
+        length: ArrayLength,
+    },
+
+    /// Slice of elements
+    /// `[T]`
+    Slice(Arc<Self>),
+
+    /// Tuple of types
+    /// `(A, B)`
+    /// `()` is the unit type
+    Tuple(Vec<Self>),
+
+    /// A value that may be missing, which is `null` when it is
+    /// `?*T`, `?i32`
+    Optional(Arc<Self>),
+
+    /// Function pointer
+    /// `fn(A, B) -> R`
+    Function {
+        params: Vec<Self>,
+        return_type: Arc<Self>,
+    },
+
+    /// A value of any type that implements an interface.
+    /// It has no size of its own, so it is only used behind a pointer
+    /// `dyn Weapon` in `*dyn Weapon`
+    Dyn(Path),
+
+    /// `void`
+    Void,
+
+    /// A type that was left out, for the type checker to work out.
+    /// The parameters of `|a, b|: i32 => { ... }` take the parameter
+    /// types of the `fn` type that the lambda is used as
+    Inferred,
 }
 
 impl Display for Type {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Concrete { name, args } => {
-                let mut str = String::from(format!("{name}"));
-                for arg in args {
-                    str += format!("{arg}").as_str();
+                if args.is_empty() {
+                    return write!(f, "{name}");
                 }
 
-                write!(f, "{str}")
+                let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+                write!(f, "{name}<{}>", args.join(", "))
             }
-            Self::Procedure { name, ref params, return_type } => {
-                let mut str = format!("Proc_{name}");
-
-                for p in params {
-                    str += format!("_{p}").as_ref();
+            Self::Procedure { name, params, return_type } => {
+                let params: Vec<String> = params.iter().map(|p| p.to_string()).collect();
+                write!(f, "define {name}({}): {return_type}", params.join(", "))
+            }
+            Self::Pointer { mutable, inner } => {
+                match mutable {
+                    true => write!(f, "*mut {inner}"),
+                    false => write!(f, "*{inner}"),
                 }
-
-                str += format!("_{return_type}").as_ref();
-
-                write!(f, "{str}")
+            }
+            Self::Reference { mutable, inner } => {
+                match mutable {
+                    true => write!(f, "&mut {inner}"),
+                    false => write!(f, "&{inner}"),
+                }
+            }
+            Self::Array { element, length } => {
+                write!(f, "[{element}; {length}]")
+            }
+            Self::Slice(element) => {
+                write!(f, "[{element}]")
+            }
+            Self::Tuple(elements) => {
+                let elements: Vec<String> = elements.iter().map(|e| e.to_string()).collect();
+                write!(f, "({})", elements.join(", "))
+            }
+            Self::Optional(inner) => {
+                write!(f, "?{inner}")
+            }
+            Self::Function { params, return_type } => {
+                let params: Vec<String> = params.iter().map(|p| p.to_string()).collect();
+                write!(f, "fn({}) -> {return_type}", params.join(", "))
+            }
+            Self::Dyn(interface) => {
+                write!(f, "dyn {interface}")
+            }
+            Self::Void => {
+                write!(f, "void")
+            }
+            Self::Inferred => {
+                write!(f, "_")
             }
         }
     }
 }
 
+/// The number of elements of an array type
+#[derive(Clone, Debug, PartialEq)]
+pub enum ArrayLength {
+    /// `[T; 4]`
+    Literal(usize),
+
+    /// A constant holding the length
+    /// `[T; SIZE]`, `[T; Buffer::SIZE]`
+    Named(Path),
+}
+
+impl Display for ArrayLength {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Literal(length) => write!(f, "{length}"),
+            Self::Named(path) => write!(f, "{path}"),
+        }
+    }
+}
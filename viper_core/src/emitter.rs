@@ -18,8 +18,25 @@ pub trait Emitter {
 
 
 /// Standard emitter that emits to StdErr
+#[derive(Default)]
 pub struct StdEmitter {
     last_error_code: Option<i32>,
+
+    /// Whether warnings are emitted as errors
+    /// `-D warnings`
+    deny_warnings: bool,
+}
+
+impl StdEmitter {
+    /// Create a new [StdEmitter]
+    pub fn new() -> StdEmitter {
+        StdEmitter::default()
+    }
+
+    /// Set whether warnings are emitted as errors
+    pub fn set_deny_warnings(&mut self, deny: bool) {
+        self.deny_warnings = deny;
+    }
 }
 
 impl Emitter for StdEmitter {
@@ -34,6 +51,51 @@ impl Emitter for StdEmitter {
     }
 
     fn emit_warning(&mut self, warning: ViperWarning) {
+        if self.deny_warnings {
+            self.emit_err(ViperError::DeniedWarning { warning });
+            return;
+        }
+
         eprintln!("{warning}")
     }
 }
+
+
+/// Emitter that stores the errors and warnings it receives.
+/// Mainly used for inspecting diagnostics when testing
+#[derive(Default)]
+pub struct BufferEmitter {
+    errors: Vec<ViperError>,
+    warnings: Vec<ViperWarning>,
+}
+
+impl BufferEmitter {
+    /// Create a new, empty [BufferEmitter]
+    pub fn new() -> BufferEmitter {
+        BufferEmitter::default()
+    }
+
+    /// Get the errors that have been emitted
+    pub fn errors(&self) -> &Vec<ViperError> {
+        &self.errors
+    }
+
+    /// Get the warnings that have been emitted
+    pub fn warnings(&self) -> &Vec<ViperWarning> {
+        &self.warnings
+    }
+}
+
+impl Emitter for BufferEmitter {
+    fn emit_err(&mut self, err: ViperError) {
+        self.errors.push(err);
+    }
+
+    fn last_emitted_error_code(&self) -> Option<i32> {
+        self.errors.last().map(|err| err.error_code())
+    }
+
+    fn emit_warning(&mut self, warning: ViperWarning) {
+        self.warnings.push(warning);
+    }
+}
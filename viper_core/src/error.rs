@@ -1,11 +1,473 @@
-use strum_macros::Display;
 use thiserror::Error;
 
+use crate::span::Span;
+
 /// An enumeration of errors types that can be encountered while compiling
-#[derive(Debug, Error, Display)]
+#[derive(Debug, Error)]
 pub enum ViperError {
+    #[error("IoError")]
     IoError,
+
+    #[error("ParserError")]
     ParserError,
+
+    /// Dereferencing something that is not a pointer
+    /// `*5`
+    #[error("{span}: cannot dereference a value of type `{ty}`")]
+    InvalidDereference {
+        ty: String,
+        span: Span,
+    },
+
+    /// Pointer arithmetic with operands that do not support it
+    /// `ptr * 2`, `ptr + 1.5`, `ptr_a + ptr_b`
+    #[error(transparent)]
+    InvalidPointerArithmetic(Box<InvalidPointerArithmetic>),
+
+    /// Casting between types that have no conversion
+    /// `user as i32`
+    #[error("{span}: cannot cast `{from}` as `{to}`")]
+    InvalidCast {
+        from: String,
+        to: String,
+        span: Span,
+    },
+
+    /// Referring to a name that has not been declared
+    #[error("{span}: cannot find `{name}` in this scope")]
+    UndefinedName {
+        name: String,
+        span: Span,
+    },
+
+    /// Referring to a type that has not been declared
+    #[error("{span}: cannot find type `{name}` in this scope")]
+    UndefinedType {
+        name: String,
+        span: Span,
+    },
+
+    /// Referring to a member that a type does not have
+    /// `User::nope`, `User { nope: 0 }`
+    #[error("{span}: `{parent}` has no member named `{member}`")]
+    UndefinedMember {
+        parent: String,
+        member: String,
+        span: Span,
+    },
+
+    /// Declaring the same name twice within one scope
+    #[error("{span}: `{name}` is defined multiple times\n{previous}: note: `{name}` is first defined here")]
+    DuplicateDefinition {
+        name: String,
+        span: Span,
+        previous: Span,
+    },
+
+    /// Using a variable before the `let` that declares it
+    #[error("{span}: `{name}` is used before it is declared\n{declared}: note: `{name}` is declared here")]
+    UseBeforeDeclaration {
+        name: String,
+        span: Span,
+        declared: Span,
+    },
+
+    /// An expression has a different type than the one required
+    #[error("{span}: mismatched types: expected `{expected}`, found `{found}`")]
+    MismatchedTypes {
+        expected: String,
+        found: String,
+        span: Span,
+    },
+
+    /// A binary operator used on operands it does not support
+    /// `true + 1`
+    #[error(transparent)]
+    InvalidBinaryOperands(Box<InvalidBinaryOperands>),
+
+    /// A unary operator used on an operand it does not support
+    /// `!1`
+    #[error("{span}: cannot apply unary `{op}` to `{ty}`")]
+    InvalidUnaryOperand {
+        op: String,
+        ty: String,
+        span: Span,
+    },
+
+    /// Calling a procedure with the wrong number of arguments
+    #[error("{span}: `{name}` takes {expected} argument(s) but {found} were supplied")]
+    ArgumentCountMismatch {
+        name: String,
+        expected: usize,
+        found: usize,
+        span: Span,
+    },
+
+    /// Calling something that is not a procedure
+    #[error("{span}: expected procedure, found `{ty}`")]
+    NotCallable {
+        ty: String,
+        span: Span,
+    },
+
+    /// Initializing a struct without giving a value for every field
+    #[error("{span}: missing field `{field}` in initializer of `{structure}`")]
+    MissingField {
+        structure: String,
+        field: String,
+        span: Span,
+    },
+
+    /// Giving a field a value twice in one struct initializer
+    /// `Point { x: 1, x: 2, y: 3 }`
+    #[error("{span}: field `{field}` of `{structure}` is initialized more than once")]
+    DuplicateField {
+        structure: String,
+        field: String,
+        span: Span,
+    },
+
+    /// A variable whose type cannot be inferred
+    #[error("{span}: type annotation needed for `{name}`")]
+    TypeAnnotationRequired {
+        name: String,
+        span: Span,
+    },
+
+    /// Using a private field or method from outside of the methods of its struct
+    /// `User { age: 22 }`, `User::secret()`
+    #[error(transparent)]
+    PrivateMember(Box<PrivateMember>),
+
+    /// Using `yield` where there is no block that produces a value
+    /// `define main(): i32 { yield 0; }`
+    #[error("{span}: `yield` outside of a block that produces a value")]
+    YieldOutsideBlock {
+        span: Span,
+    },
+
+    /// Branches of an `if` used as a value that yield different types
+    #[error("{span}: `if` and `else` have incompatible types: expected `{expected}`, found `{found}`")]
+    IncompatibleBranches {
+        expected: String,
+        found: String,
+        span: Span,
+    },
+
+    /// An `if` without an `else` used as a value, while its branches yield a value
+    /// `let i: i32 = if a { yield 1; };`
+    #[error("{span}: `if` may be missing an `else` clause: its branches yield `{ty}`, but without an `else` it is `void`")]
+    MissingElse {
+        ty: String,
+        span: Span,
+    },
+
+    /// Using `break` or `continue` outside of a loop
+    #[error("{span}: `{keyword}` outside of a loop")]
+    OutsideOfLoop {
+        keyword: String,
+        span: Span,
+    },
+
+    /// Calling a static method on a value instead of on its type
+    /// `user.new("Alex")`
+    #[error(transparent)]
+    StaticMethodOnValue(Box<StaticMethodOnValue>),
+
+    /// Calling a method through its type, without a value to call it on
+    /// `User::print_name()`
+    #[error("{span}: method `{method}` of `{parent}` must be called on a value: `value.{method}(...)`")]
+    MethodWithoutReceiver {
+        parent: String,
+        method: String,
+        span: Span,
+    },
+
+    /// Using `self` within a static method
+    #[error("{span}: `self` is not available in static method `{method}`\n{declared}: note: `{method}` is declared `static` here")]
+    SelfInStaticMethod {
+        method: String,
+        span: Span,
+        declared: Span,
+    },
+
+    /// Assigning to a variable that is not mutable
+    /// `let x = 0; x = 1;`
+    #[error("{span}: cannot assign to immutable variable `{name}`\n{declared}: help: consider making this mutable: `mut {name}`")]
+    AssignToImmutable {
+        name: String,
+        span: Span,
+        declared: Span,
+    },
+
+    /// Borrowing a variable that is not mutable as mutable
+    /// `let x = 0; let p = &mut x;`
+    #[error("{span}: cannot borrow `{name}` as mutable, as it is not declared as mutable\n{declared}: help: consider making this mutable: `mut {name}`")]
+    BorrowImmutableAsMutable {
+        name: String,
+        span: Span,
+        declared: Span,
+    },
+
+    /// Changing a value through a pointer that is not `*mut`
+    /// `let p: *i32 = &x; *p = 1;`
+    #[error("{span}: cannot change the value behind `{ty}`, as it is not a `*mut` pointer")]
+    ImmutablePointee {
+        ty: String,
+        span: Span,
+    },
+
+    /// Assigning to something that is not a place in memory
+    /// `1 = x`, `foo() = 2`
+    #[error("{span}: invalid left-hand side of assignment")]
+    InvalidAssignmentTarget {
+        span: Span,
+    },
+
+    /// Reading a variable that might not have been given a value yet
+    /// `let x: i32; if c { x = 1; } foo(x);`
+    #[error(transparent)]
+    UninitializedVariable(Box<UninitializedVariable>),
+
+    /// Arithmetic on constants whose value does not fit in its type
+    /// `const X: u8 = 255 + 1;`, `let y: i8 = 128;`
+    #[error("{span}: this expression overflows `{ty}`")]
+    ArithmeticOverflow {
+        ty: String,
+        span: Span,
+    },
+
+    /// Dividing or taking the remainder by a constant zero
+    /// `const X: i32 = 1 / 0;`, `x % 0`
+    #[error("{span}: attempt to divide by zero")]
+    DivisionByZero {
+        span: Span,
+    },
+
+    /// Shifting by a constant amount that is not less than the width of the type
+    /// `x << 70` with `x: i64`
+    #[error("{span}: attempt to shift `{ty}` by {amount}\nnote: `{ty}` can only be shifted by 0 to {max}")]
+    ShiftOverflow {
+        amount: i128,
+        ty: String,
+        max: u32,
+        span: Span,
+    },
+
+    /// Something in the value of a constant that can not be worked out while compiling
+    /// `const X: i32 = foo();`
+    #[error("{span}: this can not be evaluated at compile time\nnote: constants can only use literals, operators, casts and other constants")]
+    NotConstant {
+        span: Span,
+    },
+
+    /// A constant whose value refers back to itself
+    /// `const A: i32 = B; const B: i32 = A;`
+    #[error("{span}: the value of `{name}` depends on itself")]
+    ConstCycle {
+        name: String,
+        span: Span,
+    },
+
+    /// An array length that is not a constant holding a non-negative integer
+    /// `let a: [i32; x];`
+    #[error("{span}: `{name}` can not be used as an array length\nnote: array lengths have to be non-negative integer constants")]
+    InvalidArrayLength {
+        name: String,
+        span: Span,
+    },
+
+    /// A type given the wrong number of type arguments
+    /// `Pair<i32>` when `Pair` has two generic parameters
+    #[error("{span}: `{name}` takes {expected} type arguments but {found} were given")]
+    WrongTypeArgumentCount {
+        name: String,
+        expected: usize,
+        found: usize,
+        span: Span,
+    },
+
+    /// A generic item that keeps asking for copies of itself with bigger type arguments
+    /// `define f<T>(x: T): void { f((x, x)); }`
+    #[error("{span}: recursive instantiation of `{name}` does not end: its copies are nested more than {limit} deep")]
+    RecursiveInstantiation {
+        name: String,
+        limit: usize,
+        span: Span,
+    },
+
+    /// A generic parameter that nothing decides the type of
+    /// `define make<T>(): T` called as `make()`
+    #[error("{span}: cannot infer the type of `{param}` for `{name}`\nhelp: give the result a type, like `let x: i32 = ...`")]
+    CannotInferTypeArguments {
+        name: String,
+        param: String,
+        span: Span,
+    },
+
+    /// Using something other than an interface where one is required
+    /// `impl User for Sword`, `*dyn User`, `<T: User>`
+    #[error("{span}: expected interface, found `{name}`")]
+    NotAnInterface {
+        name: String,
+        span: Span,
+    },
+
+    /// Implementing an interface for something that can not have one
+    /// `impl Weapon for i32`, `impl Weapon for Pair`
+    #[error("{span}: `{name}` can not implement an interface\nnote: only structs without generic parameters can implement interfaces")]
+    InvalidImplTarget {
+        name: String,
+        span: Span,
+    },
+
+    /// An `impl` that leaves out a method of its interface
+    #[error(transparent)]
+    MissingInterfaceMethod(Box<MissingInterfaceMethod>),
+
+    /// An `impl` with a method that its interface does not declare
+    #[error("{span}: method `{method}` is not a member of interface `{interface}`")]
+    NotInterfaceMethod {
+        interface: String,
+        method: String,
+        span: Span,
+    },
+
+    /// A method of an `impl` with a different signature than its interface declares
+    #[error(transparent)]
+    InterfaceMethodMismatch(Box<InterfaceMethodMismatch>),
+
+    /// A type argument that does not implement an interface its parameter requires
+    /// `strongest<T: Weapon>` called with an `i32`
+    #[error("{span}: the type `{ty}` does not implement `{interface}`")]
+    UnsatisfiedBound {
+        ty: String,
+        interface: String,
+        span: Span,
+    },
+
+    /// A `dyn` type that is not behind a pointer
+    /// `let w: dyn Weapon = ...`
+    #[error("{span}: `{ty}` has no size known at compile time\nhelp: use it behind a pointer: `*{ty}`")]
+    UnsizedDyn {
+        ty: String,
+        span: Span,
+    },
+
+    /// A type whose size is needed, but is not known at compile time
+    /// `sizeof([u8])`, `struct Buffer { data: [u8] }`
+    #[error("{span}: `{ty}` has no size known at compile time")]
+    UnsizedType {
+        ty: String,
+        span: Span,
+    },
+
+    /// A struct that holds a value of itself, so its size would never end
+    /// `struct Node { next: Node }`
+    #[error("{span}: struct `{name}` holds a value of itself, so it has no end\nhelp: hold it behind a pointer: `?*{name}`")]
+    InfiniteSize {
+        name: String,
+        span: Span,
+    },
+
+    /// `#[repr(align(N))]` where `N` is not a power of two
+    #[error("{span}: `{name}` can not be aligned to {align} bytes\nhelp: alignments are powers of two")]
+    InvalidAlignment {
+        name: String,
+        align: u64,
+        span: Span,
+    },
+
+    /// `#[repr(packed, align(N))]`, which asks for both no padding and more of it
+    #[error("{span}: `{name}` can not be both `packed` and aligned with `align(N)`")]
+    PackedAndAligned {
+        name: String,
+        span: Span,
+    },
+
+    /// Indexing into a value that can not be indexed, or with something other than an integer
+    /// `true[0]`, `values[1.5]`
+    #[error("{span}: cannot index into `{ty}` with `{index}`")]
+    InvalidIndex {
+        ty: String,
+        index: String,
+        span: Span,
+    },
+
+    /// `null` used where a type that is not optional is expected
+    /// `let p: *i32 = null;`
+    #[error("{span}: `null` can not be used as `{ty}`\nhelp: only optional types can be `null`: `?{ty}`")]
+    NullToNonOptional {
+        ty: String,
+        span: Span,
+    },
+
+    /// `if let` on a value that is not optional, so there is nothing to unwrap
+    /// `if let x = 5 { ... }`
+    #[error("{span}: `if let` needs an optional value, found `{ty}`")]
+    NotOptional {
+        ty: String,
+        span: Span,
+    },
+
+    /// `Ok` or `Err` used where a type that is not a `Result` is expected
+    /// `let x: i32 = Ok(1);`
+    #[error("{span}: `{value}` makes a `Result`, but `{ty}` is expected")]
+    UnexpectedResult {
+        value: String,
+        ty: String,
+        span: Span,
+    },
+
+    /// `?` on a value that is not a `Result`
+    /// `let x = 5?;`
+    #[error("{span}: `?` can only be used on a `Result`, found `{ty}`")]
+    TryOnNonResult {
+        ty: String,
+        span: Span,
+    },
+
+    /// `?` in a procedure that can not return the error it passes on
+    /// `define main(): i32 { let x = parse(s)?; }`
+    #[error("{span}: `?` passes on an error of `{err}`, but the procedure returns `{ret}`\nhelp: the procedure has to return a `Result<_, {err}>`")]
+    TryIncompatibleReturn {
+        err: String,
+        ret: String,
+        span: Span,
+    },
+
+    /// Indexing into an array with a constant that is past its end
+    /// `values[3]` with `values: [i32; 3]`
+    #[error("{span}: index {index} is out of bounds for an array of length {length}")]
+    IndexOutOfBounds {
+        index: i128,
+        length: usize,
+        span: Span,
+    },
+
+    /// A destructuring `let` whose pattern does not have the shape of the value's type
+    /// `let (a, b): (i32, i32, i32) = ...;`
+    #[error("{span}: the pattern `{pattern}` does not match `{ty}`")]
+    PatternMismatch {
+        pattern: String,
+        ty: String,
+        span: Span,
+    },
+
+    /// A method named like an operator method that can not be used for the operator
+    /// `static op_add(a: Vec2, b: Vec2): Vec2`
+    #[error("{span}: operator method `{method}` must be declared as `{expected}`")]
+    InvalidOperatorMethod {
+        method: String,
+        expected: String,
+        span: Span,
+    },
+
+    /// A warning that is turned into an error by `-D warnings`
+    #[error("{warning}\nnote: `-D warnings` turns warnings into errors")]
+    DeniedWarning {
+        warning: ViperWarning,
+    },
 }
 
 impl ViperError {
@@ -16,11 +478,141 @@ impl ViperError {
 }
 
 
+/// Using a private member outside of its struct.
+/// Boxed inside of [ViperError::PrivateMember] to keep errors small
+#[derive(Debug, Error)]
+#[error("{span}: {kind} `{member}` of `{parent}` is private\n{declared}: note: `{member}` is declared here")]
+pub struct PrivateMember {
+    pub kind: String,
+    pub parent: String,
+    pub member: String,
+    pub span: Span,
+    pub declared: Span,
+}
+
+/// Calling a static method on a value.
+/// Boxed inside of [ViperError::StaticMethodOnValue] to keep errors small
+#[derive(Debug, Error)]
+#[error("{span}: cannot call static method `{parent}::{method}` on a value\n{declared}: note: `{method}` is declared `static` here")]
+pub struct StaticMethodOnValue {
+    pub parent: String,
+    pub method: String,
+    pub span: Span,
+    pub declared: Span,
+}
+
+/// Reading a variable that might not have a value yet.
+/// Boxed inside of [ViperError::UninitializedVariable] to keep errors small
+#[derive(Debug, Error)]
+#[error("{span}: use of possibly-uninitialized `{name}`\n{declared}: note: `{variable}` is declared here without a value")]
+pub struct UninitializedVariable {
+    pub name: String,
+    pub variable: String,
+    pub span: Span,
+    pub declared: Span,
+}
+
+/// An `impl` that leaves out a method of its interface.
+/// Boxed inside of [ViperError::MissingInterfaceMethod] to keep errors small
+#[derive(Debug, Error)]
+#[error("{span}: `{target}` is missing method `{method}` of `{interface}`\n{declared}: note: `{method}` is declared here")]
+pub struct MissingInterfaceMethod {
+    pub interface: String,
+    pub method: String,
+    pub target: String,
+    pub span: Span,
+    pub declared: Span,
+}
+
+/// A method of an `impl` whose signature differs from its interface.
+/// Boxed inside of [ViperError::InterfaceMethodMismatch] to keep errors small
+#[derive(Debug, Error)]
+#[error("{span}: method `{method}` does not match `{interface}`: expected `{expected}`, found `{found}`")]
+pub struct InterfaceMethodMismatch {
+    pub interface: String,
+    pub method: String,
+    pub expected: String,
+    pub found: String,
+    pub span: Span,
+}
+
+/// Pointer arithmetic with operands that do not support it.
+/// Boxed inside of [ViperError::InvalidPointerArithmetic] to keep errors small
+#[derive(Debug, Error)]
+#[error("{span}: invalid pointer arithmetic `{lhs} {op} {rhs}`")]
+pub struct InvalidPointerArithmetic {
+    pub op: String,
+    pub lhs: String,
+    pub rhs: String,
+    pub span: Span,
+}
+
+/// A binary operator used on operands it does not support.
+/// Boxed inside of [ViperError::InvalidBinaryOperands] to keep errors small
+#[derive(Debug, Error)]
+#[error("{span}: cannot apply `{op}` to `{lhs}` and `{rhs}`")]
+pub struct InvalidBinaryOperands {
+    pub op: String,
+    pub lhs: String,
+    pub rhs: String,
+    pub span: Span,
+}
+
 /// An enumeration of warnings that can be encountered while compiling
 #[derive(Debug, Error)]
 pub enum ViperWarning {
+    /// A procedure with a return type whose end can be reached without a `return`
+    /// `define f(c: bool): i32 { if c { return 1; } }`
+    #[error("{span}: warning: not all paths in `{name}` return a value")]
+    MissingReturn {
+        name: String,
+        span: Span,
+    },
 
-}
+    /// A statement that control can never reach
+    /// `return 0; foo();`
+    #[error("{span}: warning: unreachable statement")]
+    UnreachableCode {
+        span: Span,
+    },
+
+    /// A loop that can never be left
+    /// `while true { }`
+    #[error("{span}: warning: this loop never exits")]
+    InfiniteLoop {
+        span: Span,
+    },
 
+    /// A local variable whose value is never used
+    /// `let x = 1;`
+    #[error("{span}: warning: unused variable `{name}`\n{span}: help: if this is intentional, prefix it with an underscore: `_{name}`")]
+    UnusedVariable {
+        name: String,
+        span: Span,
+    },
 
+    /// A parameter that the procedure never uses
+    /// `define f(x: i32): void { }`
+    #[error("{span}: warning: unused parameter `{name}`\n{span}: help: if this is intentional, prefix it with an underscore: `_{name}`")]
+    UnusedParameter {
+        name: String,
+        span: Span,
+    },
 
+    /// A private field that is never read
+    /// `struct User { age: i32 }`
+    #[error("{span}: warning: field `{field}` of `{structure}` is never read")]
+    UnusedField {
+        structure: String,
+        field: String,
+        span: Span,
+    },
+
+    /// A private procedure that is never called or referred to
+    /// `define helper(): void { }`
+    #[error("{span}: warning: procedure `{name}` is never used")]
+    UnusedProcedure {
+        name: String,
+        span: Span,
+    },
+}
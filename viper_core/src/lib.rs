@@ -5,5 +5,6 @@ pub mod emitter;
 pub mod span;
 pub mod symbol;
 pub mod scope;
-pub mod datatype;
+pub mod types;
 pub mod _type;
+pub mod path;
@@ -0,0 +1,125 @@
+use std::fmt::Display;
+
+use crate::span::Span;
+
+/// A single segment of a [Path]
+/// `std::io::print`
+///  ^^^  ^^  ^^^^^
+#[derive(Clone, Debug)]
+pub struct PathSegment {
+    name: String,
+    span: Span,
+}
+
+impl PathSegment {
+    /// Create a new [PathSegment]
+    pub fn new(name: String, span: Span) -> PathSegment {
+        PathSegment {
+            name,
+            span,
+        }
+    }
+
+    /// Get the name of this segment
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the location of this segment in the source code
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+/// Represents a `::` separated path to an item
+/// `std::io::print`    -> procedure `print` in module `std::io`
+/// `User::new`         -> static method `new` of the type `User`
+/// `Weapon::LaserGun`  -> variant `LaserGun` of the enum `Weapon`
+///
+/// A plain identifier is a path with a single segment
+#[derive(Clone, Debug)]
+pub struct Path {
+    segments: Vec<PathSegment>,
+}
+
+impl Path {
+    /// Create a new [Path] from its segments
+    pub fn new(segments: Vec<PathSegment>) -> Path {
+        Path {
+            segments,
+        }
+    }
+
+    /// Create a path with a single segment
+    pub fn from_ident(name: String, span: Span) -> Path {
+        Path {
+            segments: vec![PathSegment::new(name, span)],
+        }
+    }
+
+    /// Get the segments that make up this path
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    /// Whether this path is just a single identifier
+    pub fn is_ident(&self) -> bool {
+        self.segments.len() == 1
+    }
+
+    /// Get the final segment of the path.
+    /// This is the name of the item being referred to
+    pub fn last(&self) -> &PathSegment {
+        self.segments.last().expect("Path must have at least one segment")
+    }
+
+    /// Get the path leading up to the final segment
+    /// `std::io::print` -> `std::io`
+    ///
+    /// None if this path is a single identifier
+    pub fn parent(&self) -> Option<Path> {
+        if self.is_ident() {
+            return None;
+        }
+
+        Some(Path {
+            segments: self.segments[..self.segments.len() - 1].to_vec(),
+        })
+    }
+
+    /// Add a segment onto the end of the path
+    pub fn push(&mut self, segment: PathSegment) {
+        self.segments.push(segment);
+    }
+
+    /// The span of the entire path
+    pub fn span(&self) -> Span {
+        let mut span = self.segments[0].span.clone();
+        for segment in &self.segments[1..] {
+            span = span + segment.span.clone();
+        }
+
+        span
+    }
+}
+
+/// Paths are equal when they name the same segments.
+/// Where they appear in the source code does not matter
+impl PartialEq for Path {
+    fn eq(&self, other: &Self) -> bool {
+        if self.segments.len() != other.segments.len() {
+            return false;
+        }
+
+        self.segments.iter()
+            .zip(other.segments.iter())
+            .all(|(a, b)| a.name == b.name)
+    }
+}
+
+impl Display for Path {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<&str> = self.segments.iter().map(|s| s.name.as_str()).collect();
+        write!(f, "{}", names.join("::"))
+    }
+}
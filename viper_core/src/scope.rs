@@ -28,6 +28,29 @@ impl Scope {
         self.symbol_map.insert(key, symbol);
     }
 
+    /// Look up a symbol declared directly in this scope
+    pub fn lookup_local(&self, key: &str) -> Option<Symbol> {
+        self.symbol_map.get(key).cloned()
+    }
+
+    /// Look up a symbol in this scope, and then in each of the
+    /// parent scopes until it is found
+    pub fn lookup(&self, key: &str) -> Option<Symbol> {
+        if let Some(symbol) = self.symbol_map.get(key) {
+            return Some(symbol.clone());
+        }
+
+        match &self.parent {
+            Some(parent) => parent.borrow().lookup(key),
+            None => None,
+        }
+    }
+
+    /// Get the parent [Scope] of this scope
+    pub fn parent(&self) -> Option<Arc<RefCell<Scope>>> {
+        self.parent.clone()
+    }
+
     /// Print the values in this table and all tables within this one
     pub fn print(&self, prefix: &str) {
         println!("Symbol Table:");
@@ -69,6 +69,11 @@ impl SourceModule {
         }
     }
 
+    /// Return a pointer to the module's scope
+    pub fn scope(&self) -> Arc<RefCell<Scope>> {
+        self.scope.clone()
+    }
+
     /// Get a reference to the list of source code files 
     /// for this module
     pub fn files(&self) -> &Vec<Arc<SourceFile>> {
@@ -1,10 +1,14 @@
-use std::ops::Add;
+use std::{fmt::Display, ops::Add, sync::Arc};
 
 /// Represents the starting and ending points of a code location
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Span {
     start: CodeLocation,
     end: CodeLocation,
+
+    /// Name of the source file the span is in.
+    /// An `Arc<String>` is a thin pointer, which keeps spans small
+    file: Option<Arc<String>>,
 }
 
 /// Represents a location within some source code
@@ -84,6 +88,7 @@ impl Span {
         Span {
             start: CodeLocation::new(starting_line, starting_column),
             end: CodeLocation::new(ending_line, ending_column),
+            file: None,
         }
     }
 
@@ -94,8 +99,25 @@ impl Span {
         Span {
             start: CodeLocation::new_dummy(),
             end: CodeLocation::new_dummy(),
+            file: None,
         }
     }
+
+    /// Get the line that this span starts on
+    pub fn line(&self) -> usize {
+        self.start.line
+    }
+
+    /// Set the source file that this span is in
+    pub fn in_file(mut self, file: Arc<String>) -> Span {
+        self.file = Some(file);
+        self
+    }
+
+    /// Get the name of the source file this span is in
+    pub fn file(&self) -> Option<&str> {
+        self.file.as_deref().map(|file| file.as_str())
+    }
 }
 
 /// Add two spanning objects together to create a span that 
@@ -106,6 +128,16 @@ impl Add for Span {
         Span {
             start: std::cmp::min(self.start, rhs.start),
             end: std::cmp::max(self.end, rhs.end),
+            file: self.file.or(rhs.file),
+        }
+    }
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.file {
+            Some(ref file) => write!(f, "{}:{}:{}", file, self.start.line, self.start.column),
+            None => write!(f, "{}:{}", self.start.line, self.start.column),
         }
     }
 }
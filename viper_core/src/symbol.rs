@@ -2,6 +2,29 @@ use std::{fmt::Display, sync::Arc};
 
 use crate::{_type::Type, source::SourceModule, span::Span};
 
+/// The kinds of items that a [Symbol] can name
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SymbolKind {
+    /// Variables and procedure parameters
+    Variable,
+
+    /// Procedures defined with `define`
+    Procedure,
+
+    /// Compile-time constants defined with `const`
+    Constant,
+
+    /// Types defined with `struct`
+    Struct,
+
+    /// Interfaces defined with `interface`
+    Interface,
+
+    /// Modules, whose items are reached through a path
+    /// `std` in `std::io::print`
+    Module,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Symbol {
     module: Arc<SourceModule>,
@@ -9,23 +32,52 @@ pub struct Symbol {
     dtype: Arc<Type>,
     span: Span,
     is_mutable: bool,
+    kind: SymbolKind,
 }
 
 impl Symbol {
     /// Create a new symbol
-    pub fn new(module: Arc<SourceModule>, dtype: Arc<Type>, name: String, span: Span, is_mutable: bool) -> Symbol {
+    pub fn new(
+        module: Arc<SourceModule>,
+        dtype: Arc<Type>,
+        name: String,
+        span: Span,
+        is_mutable: bool,
+        kind: SymbolKind,
+    ) -> Symbol {
         Symbol {
             module,
             name,
             dtype,
             span,
             is_mutable,
+            kind,
         }
     }
 
     pub fn get_key(&self) -> String {
         self.name.clone()
     }
+
+    /// Get the type of the symbol
+    pub fn dtype(&self) -> &Arc<Type> {
+        &self.dtype
+    }
+
+    /// Get the location the symbol was declared at
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+
+    /// Whether the symbol can be assigned to
+    pub fn is_mutable(&self) -> bool {
+        self.is_mutable
+    }
+
+    /// Get the kind of item the symbol names
+    pub fn kind(&self) -> SymbolKind {
+        self.kind
+    }
 }
 
 impl Display for Symbol {
@@ -1,5 +1,5 @@
 use lazy_static::lazy_static;
-use std::{collections::HashMap, fmt::Display, str::FromStr};
+use std::{collections::HashMap, fmt::Display, str::FromStr, sync::Arc};
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 use thiserror::Error;
@@ -66,6 +66,34 @@ impl PartialEq for Token {
     }
 }
 
+impl Token {
+    /// Get the location of the token in the source code
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Keyword(_, span)
+            | Self::Punctuator(_, _, span)
+            | Self::NumericLiteral(_, span)
+            | Self::StringLiteral(_, span)
+            | Self::Identifier(_, span)
+            | Self::Illegal(_, span) => span.clone(),
+            Self::EOF => Span::dummy(),
+        }
+    }
+
+    /// Mark the token as coming from the given source file
+    pub fn in_file(self, file: &Arc<String>) -> Token {
+        match self {
+            Self::Keyword(kind, span) => Self::Keyword(kind, span.in_file(file.clone())),
+            Self::Punctuator(kind, prec, span) => Self::Punctuator(kind, prec, span.in_file(file.clone())),
+            Self::NumericLiteral(value, span) => Self::NumericLiteral(value, span.in_file(file.clone())),
+            Self::StringLiteral(value, span) => Self::StringLiteral(value, span.in_file(file.clone())),
+            Self::Identifier(name, span) => Self::Identifier(name, span.in_file(file.clone())),
+            Self::Illegal(value, span) => Self::Illegal(value, span.in_file(file.clone())),
+            Self::EOF => Self::EOF,
+        }
+    }
+}
+
 impl PartialEq<PunctuatorKind> for &Token {
     fn eq(&self, other: &PunctuatorKind) -> bool {
         match self {
@@ -213,6 +241,7 @@ pub enum KeywordKind {
     /// Declarator keywords
     Define,
     Let,
+    Const,
     Mut,
     Return,
     Match,
@@ -224,6 +253,13 @@ pub enum KeywordKind {
     Export,
     Method,
     Static,
+    As,
+
+    /// Interfaces and their implementations
+    /// `interface Weapon`, `impl Weapon for Sword`, `*dyn Weapon`
+    Interface,
+    Impl,
+    Dyn,
 
     /// For referencing the 'self' pointer in
     KWSelf,
@@ -255,6 +291,29 @@ pub enum KeywordKind {
     U16,
     U32,
     U64,
+    Void,
+    Bool,
+
+    /// Function pointer types
+    Fn,
+
+    /// Boolean literals
+    True,
+    False,
+
+    /// The value of an optional type that holds nothing
+    Null,
+
+    /// The values of a `Result<T, E>`
+    /// `Ok(value)`, `Err(error)`
+    KWOk,
+    KWErr,
+
+    /// The layout of a type, known while compiling
+    /// `sizeof(T)`, `alignof(T)`, `offsetof(T, field)`
+    SizeOf,
+    AlignOf,
+    OffsetOf,
 }
 
 impl KeywordKind {
@@ -265,9 +324,14 @@ impl KeywordKind {
             Self::KWSelf => return "self",
             Self::Static => return "static",
             Self::Method => return "method",
+            Self::As => return "as",
+            Self::Interface => return "interface",
+            Self::Impl => return "impl",
+            Self::Dyn => return "dyn",
             Self::Defer => return "defer",
             Self::Yield => return "yield",
             Self::Let => return "let",
+            Self::Const => return "const",
             Self::Mut => return "mut",
             Self::Return => return "return",
             Self::Match => return "match",
@@ -299,6 +363,17 @@ impl KeywordKind {
             Self::U16 => return "u16",
             Self::U32 => return "u32",
             Self::U64 => return "u64",
+            Self::Void => return "void",
+            Self::Bool => return "bool",
+            Self::Fn => return "fn",
+            Self::True => return "true",
+            Self::False => return "false",
+            Self::Null => return "null",
+            Self::KWOk => return "Ok",
+            Self::KWErr => return "Err",
+            Self::SizeOf => return "sizeof",
+            Self::AlignOf => return "alignof",
+            Self::OffsetOf => return "offsetof",
         }
     }
 }
@@ -362,6 +437,7 @@ pub enum PunctuatorKind {
     RShiftEquals,
     LShiftEquals,
     BinNotEquals,
+    BinAndEquals,
     BinOrEquals,
     BinXorEquals,
 
@@ -380,6 +456,14 @@ pub enum PunctuatorKind {
     SemiColon,
     FatArrow,
     ThinArrow,
+
+    /// Marks an optional type, or passes the error of a `Result` on
+    /// `?*T`, `read(path)?`
+    Question,
+
+    /// Starts an attribute
+    /// `#[repr(C)]`
+    Hash,
 }
 
 impl PunctuatorKind {
@@ -414,6 +498,7 @@ impl PunctuatorKind {
             Self::RShiftEquals => return ">>=",
             Self::LShiftEquals => return "<<=",
             Self::BinNotEquals => return "~=",
+            Self::BinAndEquals => return "&=",
             Self::BinOrEquals => return "|=",
             Self::BinXorEquals => return "^=",
             Self::LParen => return "(",
@@ -429,6 +514,8 @@ impl PunctuatorKind {
             Self::SemiColon => return ";",
             Self::FatArrow => return "=>",
             Self::ThinArrow => return "->",
+            Self::Question => return "?",
+            Self::Hash => return "#",
         }
     }
 }
@@ -0,0 +1,558 @@
+use std::{collections::{HashMap, HashSet}, fmt::Display};
+
+use crate::{_type::{ArrayLength, Type}, path::Path};
+
+/// Whether an integer type can hold negative values
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Sign {
+    Signed,
+    Unsigned
+}
+
+/// Represents the size of a data type (in bits)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Size {
+    S8,
+    S16,
+    S32,
+    S64,
+}
+
+impl Size {
+    /// Get the number of bits of this size
+    pub fn bits(&self) -> u32 {
+        match self {
+            Self::S8 => 8,
+            Self::S16 => 16,
+            Self::S32 => 32,
+            Self::S64 => 64,
+        }
+    }
+}
+
+/// Cheap handle to a type in a [TypeInterner].
+///
+/// Every type is interned exactly once, so two handles
+/// from the same interner are equal exactly when the types
+/// they refer to are structurally equal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TypeId(u32);
+
+impl TypeId {
+    pub const VOID: TypeId = TypeId(0);
+    pub const BOOL: TypeId = TypeId(1);
+    pub const BYTE: TypeId = TypeId(2);
+    pub const I8: TypeId = TypeId(3);
+    pub const I16: TypeId = TypeId(4);
+    pub const I32: TypeId = TypeId(5);
+    pub const I64: TypeId = TypeId(6);
+    pub const U8: TypeId = TypeId(7);
+    pub const U16: TypeId = TypeId(8);
+    pub const U32: TypeId = TypeId(9);
+    pub const U64: TypeId = TypeId(10);
+    pub const F32: TypeId = TypeId(11);
+    pub const F64: TypeId = TypeId(12);
+}
+
+/// The semantic types of the Viper programming language
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TypeKind {
+    Void,
+    Bool,
+    Byte,
+    Integer(Sign, Size),
+    Float(Size),
+
+    /// `*T`, `*mut T`
+    /// References are represented as pointers
+    Pointer {
+        mutable: bool,
+        pointee: TypeId,
+    },
+
+    /// `[T; N]`
+    Array {
+        element: TypeId,
+        length: usize,
+    },
+
+    /// `[T]`
+    Slice(TypeId),
+
+    /// `(A, B)`
+    Tuple(Vec<TypeId>),
+
+    /// `?T`
+    /// A value of `T`, or `null`
+    Optional(TypeId),
+
+    /// `Result<T, E>`
+    /// Either `Ok` with a value of `T`, or `Err` with an error of `E`
+    Result {
+        ok: TypeId,
+        err: TypeId,
+    },
+
+    /// `fn(A, B) -> R`
+    Function {
+        params: Vec<TypeId>,
+        ret: TypeId,
+    },
+
+    /// A user defined struct, by name, with the type
+    /// arguments of a generic struct
+    /// `User`, `Pair<i32, f64>`
+    Struct(String, Vec<TypeId>),
+
+    /// A generic parameter, within the item that declares it.
+    /// Nothing is known about the type it stands for
+    /// `T` in `define max<T>(a: T, b: T): T`
+    Param(String),
+
+    /// A user defined enum, by name
+    Enum(String),
+
+    /// A value of any type that implements an interface, by the interface's name.
+    /// A pointer to it is a fat pointer: a pointer to the value together
+    /// with a pointer to the vtable of the value's type for the interface
+    /// `dyn Weapon`
+    Dyn(String),
+}
+
+/// The table that every semantic type is interned in.
+///
+/// Types are created with [TypeInterner::intern] and referred
+/// to through the [TypeId] that it hands back. The primitive types
+/// are always interned and have the fixed ids in [TypeId].
+pub struct TypeInterner {
+    kinds: Vec<TypeKind>,
+    ids: HashMap<TypeKind, TypeId>,
+
+    /// The struct and enum types, by name
+    nominal: HashMap<String, TypeId>,
+
+    /// The number of generic parameters of each generic struct, by name.
+    /// They only become types once they are given arguments
+    generic: HashMap<String, usize>,
+
+    /// The values of the constants that can be used as array lengths, by name
+    lengths: HashMap<String, usize>,
+
+    /// The interfaces, which can be used as `dyn` types
+    interfaces: HashSet<String>,
+}
+
+/// The primitive types, in the order of their fixed [TypeId]s
+const PRIMITIVES: [(&str, TypeKind); 13] = [
+    ("void", TypeKind::Void),
+    ("bool", TypeKind::Bool),
+    ("byte", TypeKind::Byte),
+    ("i8", TypeKind::Integer(Sign::Signed, Size::S8)),
+    ("i16", TypeKind::Integer(Sign::Signed, Size::S16)),
+    ("i32", TypeKind::Integer(Sign::Signed, Size::S32)),
+    ("i64", TypeKind::Integer(Sign::Signed, Size::S64)),
+    ("u8", TypeKind::Integer(Sign::Unsigned, Size::S8)),
+    ("u16", TypeKind::Integer(Sign::Unsigned, Size::S16)),
+    ("u32", TypeKind::Integer(Sign::Unsigned, Size::S32)),
+    ("u64", TypeKind::Integer(Sign::Unsigned, Size::S64)),
+    ("f32", TypeKind::Float(Size::S32)),
+    ("f64", TypeKind::Float(Size::S64)),
+];
+
+impl TypeInterner {
+    /// Create a new [TypeInterner] holding only the primitive types
+    pub fn new() -> TypeInterner {
+        let mut interner = TypeInterner {
+            kinds: vec![],
+            ids: HashMap::new(),
+            nominal: HashMap::new(),
+            generic: HashMap::new(),
+            lengths: HashMap::new(),
+            interfaces: HashSet::new(),
+        };
+
+        for (_, kind) in PRIMITIVES {
+            interner.intern(kind);
+        }
+
+        interner
+    }
+
+    /// Get the id of a primitive type from its name
+    /// `i32` -> [TypeId::I32]
+    pub fn primitive(name: &str) -> Option<TypeId> {
+        PRIMITIVES.iter()
+            .position(|(primitive, _)| *primitive == name)
+            .map(|i| TypeId(i as u32))
+    }
+
+    /// Get the id of a type, adding it to the table if it is not there yet
+    pub fn intern(&mut self, kind: TypeKind) -> TypeId {
+        if let Some(id) = self.ids.get(&kind) {
+            return *id;
+        }
+
+        let id = TypeId(self.kinds.len() as u32);
+        self.kinds.push(kind.clone());
+        self.ids.insert(kind, id);
+
+        id
+    }
+
+    /// Get what kind of type an id refers to
+    pub fn kind(&self, id: TypeId) -> &TypeKind {
+        &self.kinds[id.0 as usize]
+    }
+
+    /// Get the type of a pointer to `pointee`
+    pub fn pointer(&mut self, pointee: TypeId, mutable: bool) -> TypeId {
+        self.intern(TypeKind::Pointer { mutable, pointee })
+    }
+
+    /// Get the type of a `Result<ok, err>`
+    pub fn result(&mut self, ok: TypeId, err: TypeId) -> TypeId {
+        self.intern(TypeKind::Result { ok, err })
+    }
+
+    /// Get how many type arguments a generic type that is
+    /// built into the language takes, from its name
+    /// `Result` -> 2
+    pub fn builtin_generic(name: &str) -> Option<usize> {
+        match name {
+            "Result" => Some(2),
+            _ => None,
+        }
+    }
+
+    /// Get the type of a function
+    pub fn function(&mut self, params: Vec<TypeId>, ret: TypeId) -> TypeId {
+        self.intern(TypeKind::Function { params, ret })
+    }
+
+    /// Declare a struct type so that syntactic types can refer to it by name
+    pub fn declare_struct(&mut self, name: &str) -> TypeId {
+        let id = self.intern(TypeKind::Struct(name.to_string(), vec![]));
+        self.nominal.insert(name.to_string(), id);
+
+        id
+    }
+
+    /// Declare a generic struct with `params` generic parameters, so
+    /// that syntactic types can refer to it with type arguments
+    /// `struct Pair<A, B>` -> `Pair<i32, f64>`
+    pub fn declare_generic_struct(&mut self, name: &str, params: usize) {
+        self.generic.insert(name.to_string(), params);
+    }
+
+    /// Declare an enum type so that syntactic types can refer to it by name
+    pub fn declare_enum(&mut self, name: &str) -> TypeId {
+        let id = self.intern(TypeKind::Enum(name.to_string()));
+        self.nominal.insert(name.to_string(), id);
+
+        id
+    }
+
+    /// Declare an interface so that `dyn` types can refer to it by name
+    pub fn declare_interface(&mut self, name: &str) {
+        self.interfaces.insert(name.to_string());
+    }
+
+    /// Look up a declared struct or enum type by its path
+    pub fn nominal(&self, path: &Path) -> Option<TypeId> {
+        self.nominal.get(&path.to_string()).copied()
+    }
+
+    /// Define the value of a constant so that array types can use it as their length
+    /// `const SIZE: u64 = 4;` -> `[i32; SIZE]`
+    pub fn define_length(&mut self, name: &str, length: usize) {
+        self.lengths.insert(name.to_string(), length);
+    }
+
+    /// Look up the value of a constant defined with [TypeInterner::define_length]
+    pub fn length(&self, path: &Path) -> Option<usize> {
+        self.lengths.get(&path.to_string()).copied()
+    }
+
+    /// Lower a syntactic type onto the semantic type it represents.
+    ///
+    /// None if it names a type that has not been declared,
+    /// or an array length that is not a defined constant
+    pub fn lower(&mut self, ty: &Type) -> Option<TypeId> {
+        self.lower_with(ty, &HashMap::new())
+    }
+
+    /// Lower a syntactic type where the names in `params` stand for other types.
+    /// Used for the generic parameters of an item
+    /// `Pair<T, u8>` with `T` -> `i32` gives `Pair<i32, u8>`
+    pub fn lower_with(&mut self, ty: &Type, params: &HashMap<String, TypeId>) -> Option<TypeId> {
+        let kind = match ty {
+            Type::Concrete { name, args } => {
+                if name.is_ident() && args.is_empty() {
+                    if let Some(id) = TypeInterner::primitive(name.last().name()) {
+                        return Some(id);
+                    }
+                    if let Some(id) = params.get(name.last().name()) {
+                        return Some(*id);
+                    }
+                }
+
+                // `Result<T, E>`
+                if name.is_ident() && TypeInterner::builtin_generic(name.last().name()) == Some(args.len()) {
+                    let ok = self.lower_with(&args[0], params)?;
+                    let err = self.lower_with(&args[1], params)?;
+                    return Some(self.result(ok, err));
+                }
+
+                let name = name.to_string();
+                match self.generic.get(&name) {
+                    Some(count) if *count == args.len() => {
+                        TypeKind::Struct(name, self.lower_all(args, params)?)
+                    }
+                    Some(_) => return None,
+                    None if args.is_empty() => return self.nominal.get(&name).copied(),
+                    None => return None,
+                }
+            }
+            Type::Procedure { name: _, params: inputs, return_type }
+            | Type::Function { params: inputs, return_type } => {
+                TypeKind::Function {
+                    params: self.lower_all(inputs, params)?,
+                    ret: self.lower_with(return_type, params)?,
+                }
+            }
+            Type::Pointer { mutable, inner }
+            | Type::Reference { mutable, inner } => {
+                TypeKind::Pointer {
+                    mutable: *mutable,
+                    pointee: self.lower_with(inner, params)?,
+                }
+            }
+            Type::Array { element, length } => {
+                let length = match length {
+                    ArrayLength::Literal(length) => *length,
+                    ArrayLength::Named(path) => self.length(path)?,
+                };
+
+                TypeKind::Array {
+                    element: self.lower_with(element, params)?,
+                    length,
+                }
+            }
+            Type::Slice(element) => {
+                TypeKind::Slice(self.lower_with(element, params)?)
+            }
+            Type::Tuple(elements) => {
+                TypeKind::Tuple(self.lower_all(elements, params)?)
+            }
+            Type::Optional(inner) => {
+                TypeKind::Optional(self.lower_with(inner, params)?)
+            }
+            Type::Dyn(interface) => {
+                let name = interface.to_string();
+                if !self.interfaces.contains(&name) {
+                    return None;
+                }
+                TypeKind::Dyn(name)
+            }
+            Type::Void => {
+                return Some(TypeId::VOID);
+            }
+            Type::Inferred => return None,
+        };
+
+        Some(self.intern(kind))
+    }
+
+    /// Lower each of a list of syntactic types
+    fn lower_all(&mut self, types: &[Type], params: &HashMap<String, TypeId>) -> Option<Vec<TypeId>> {
+        types.iter().map(|ty| self.lower_with(ty, params)).collect()
+    }
+
+    /// Replace the generic parameters within a type with the types they stand for.
+    /// Parameters that are not in `params` are kept
+    /// `*Pair<T, u8>` with `T` -> `i32` gives `*Pair<i32, u8>`
+    pub fn substitute(&mut self, id: TypeId, params: &HashMap<String, TypeId>) -> TypeId {
+        let kind = match self.kind(id).clone() {
+            TypeKind::Param(name) => return params.get(&name).copied().unwrap_or(id),
+            TypeKind::Pointer { mutable, pointee } => {
+                TypeKind::Pointer { mutable, pointee: self.substitute(pointee, params) }
+            }
+            TypeKind::Array { element, length } => {
+                TypeKind::Array { element: self.substitute(element, params), length }
+            }
+            TypeKind::Slice(element) => TypeKind::Slice(self.substitute(element, params)),
+            TypeKind::Optional(inner) => TypeKind::Optional(self.substitute(inner, params)),
+            TypeKind::Result { ok, err } => {
+                TypeKind::Result { ok: self.substitute(ok, params), err: self.substitute(err, params) }
+            }
+            TypeKind::Tuple(elements) => TypeKind::Tuple(self.substitute_all(&elements, params)),
+            TypeKind::Function { params: inputs, ret } => {
+                TypeKind::Function {
+                    params: self.substitute_all(&inputs, params),
+                    ret: self.substitute(ret, params),
+                }
+            }
+            TypeKind::Struct(name, args) => TypeKind::Struct(name, self.substitute_all(&args, params)),
+            _ => return id,
+        };
+
+        self.intern(kind)
+    }
+
+    fn substitute_all(&mut self, ids: &[TypeId], params: &HashMap<String, TypeId>) -> Vec<TypeId> {
+        ids.iter().map(|id| self.substitute(*id, params)).collect()
+    }
+
+    /// Whether a type has no generic parameters within it
+    pub fn is_concrete(&self, id: TypeId) -> bool {
+        match self.kind(id) {
+            TypeKind::Param(_) => false,
+            TypeKind::Pointer { mutable: _, pointee } => self.is_concrete(*pointee),
+            TypeKind::Array { element, length: _ }
+            | TypeKind::Slice(element)
+            | TypeKind::Optional(element) => self.is_concrete(*element),
+            TypeKind::Tuple(ids) | TypeKind::Struct(_, ids) => ids.iter().all(|id| self.is_concrete(*id)),
+            TypeKind::Result { ok, err } => self.is_concrete(*ok) && self.is_concrete(*err),
+            TypeKind::Function { params, ret } => {
+                params.iter().all(|id| self.is_concrete(*id)) && self.is_concrete(*ret)
+            }
+            _ => true,
+        }
+    }
+
+    /// Whether this is an integer type.
+    /// `byte` counts as an integer
+    pub fn is_integer(&self, id: TypeId) -> bool {
+        self.integer(id).is_some()
+    }
+
+    /// Get the sign and size of an integer type.
+    /// `byte` is treated as `u8`
+    pub fn integer(&self, id: TypeId) -> Option<(Sign, Size)> {
+        match self.kind(id) {
+            TypeKind::Integer(sign, size) => Some((*sign, *size)),
+            TypeKind::Byte => Some((Sign::Unsigned, Size::S8)),
+            _ => None,
+        }
+    }
+
+    /// Whether this is a floating point type
+    pub fn is_float(&self, id: TypeId) -> bool {
+        matches!(self.kind(id), TypeKind::Float(_))
+    }
+
+    /// Whether this is an integer or floating point type
+    pub fn is_numeric(&self, id: TypeId) -> bool {
+        self.is_integer(id) || self.is_float(id)
+    }
+
+    /// Whether this is a pointer type
+    pub fn is_pointer(&self, id: TypeId) -> bool {
+        matches!(self.kind(id), TypeKind::Pointer { .. })
+    }
+
+    /// Whether this is an optional type
+    /// `?i32`
+    pub fn is_optional(&self, id: TypeId) -> bool {
+        matches!(self.kind(id), TypeKind::Optional(_))
+    }
+
+    /// Whether this is a pointer to a `dyn` value, which also carries a vtable
+    /// `*dyn Weapon`
+    pub fn is_fat_pointer(&self, id: TypeId) -> bool {
+        match self.kind(id) {
+            TypeKind::Pointer { mutable: _, pointee } => matches!(self.kind(*pointee), TypeKind::Dyn(_)),
+            _ => false,
+        }
+    }
+
+    /// Whether an optional type is stored as just the pointer it holds,
+    /// with a null pointer standing for `null`. Pointers that are not
+    /// optional are never null, so `?*T` is no bigger than `*T`
+    pub fn is_null_optimized(&self, id: TypeId) -> bool {
+        match self.kind(id) {
+            TypeKind::Optional(inner) => {
+                matches!(self.kind(*inner), TypeKind::Pointer { .. } | TypeKind::Function { .. })
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether a value of type `from` can be used where `to` is expected.
+    /// This is true when they are the same type, when a `*mut T`
+    /// is given where a `*T` is expected, or when a `T` is given
+    /// where a `?T` is expected
+    pub fn coerces(&self, from: TypeId, to: TypeId) -> bool {
+        if from == to {
+            return true;
+        }
+
+        match (self.kind(from), self.kind(to)) {
+            (
+                TypeKind::Pointer { mutable: true, pointee: a },
+                TypeKind::Pointer { mutable: false, pointee: b },
+            ) => a == b,
+            (TypeKind::Optional(a), TypeKind::Optional(b)) => self.coerces(*a, *b),
+            (_, TypeKind::Optional(inner)) => self.coerces(from, *inner),
+            _ => false,
+        }
+    }
+
+    /// Get a displayable form of a type
+    /// `*mut [i32; 4]`
+    pub fn display(&self, id: TypeId) -> TypeDisplay<'_> {
+        TypeDisplay {
+            interner: self,
+            id,
+        }
+    }
+}
+
+impl Default for TypeInterner {
+    fn default() -> Self {
+        TypeInterner::new()
+    }
+}
+
+/// Displays a type the way it would be written in source code
+pub struct TypeDisplay<'a> {
+    interner: &'a TypeInterner,
+    id: TypeId,
+}
+
+impl Display for TypeDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let show = |id: TypeId| self.interner.display(id);
+        let list = |ids: &[TypeId]| {
+            ids.iter().map(|id| show(*id).to_string()).collect::<Vec<String>>().join(", ")
+        };
+
+        match self.interner.kind(self.id) {
+            TypeKind::Void => write!(f, "void"),
+            TypeKind::Bool => write!(f, "bool"),
+            TypeKind::Byte => write!(f, "byte"),
+            TypeKind::Integer(sign, size) => {
+                match sign {
+                    Sign::Signed => write!(f, "i{}", size.bits()),
+                    Sign::Unsigned => write!(f, "u{}", size.bits()),
+                }
+            }
+            TypeKind::Float(size) => write!(f, "f{}", size.bits()),
+            TypeKind::Pointer { mutable, pointee } => {
+                match mutable {
+                    true => write!(f, "*mut {}", show(*pointee)),
+                    false => write!(f, "*{}", show(*pointee)),
+                }
+            }
+            TypeKind::Array { element, length } => write!(f, "[{}; {length}]", show(*element)),
+            TypeKind::Slice(element) => write!(f, "[{}]", show(*element)),
+            TypeKind::Tuple(elements) => write!(f, "({})", list(elements)),
+            TypeKind::Optional(inner) => write!(f, "?{}", show(*inner)),
+            TypeKind::Result { ok, err } => write!(f, "Result<{}, {}>", show(*ok), show(*err)),
+            TypeKind::Function { params, ret } => write!(f, "fn({}) -> {}", list(params), show(*ret)),
+            TypeKind::Struct(name, args) if !args.is_empty() => write!(f, "{name}<{}>", list(args)),
+            TypeKind::Struct(name, _) | TypeKind::Enum(name) | TypeKind::Param(name) => write!(f, "{name}"),
+            TypeKind::Dyn(interface) => write!(f, "dyn {interface}"),
+        }
+    }
+}
@@ -1,9 +1,11 @@
 use std::{cell::RefCell, ffi::OsStr, fs, path::PathBuf, sync::Arc};
 use colored::*;
-use viper_core::{scope::Scope, source::{SourceFile, SourceModule}};
+use viper_ast::ExprNode;
+use viper_core::{emitter::{Emitter, StdEmitter}, scope::Scope, source::{SourceFile, SourceModule}};
 
 // use viper_lexer::lexer::Lexer;
 use viper_parser::Parser;
+use viper_semantic::SemanticAnalyzer;
 
 #[derive(Clone, PartialEq)]
 pub struct BuildSystem {
@@ -12,6 +14,12 @@ pub struct BuildSystem {
 
     /// List of pointers to source code files
     modules: Option<Vec<Arc<SourceModule>>>,
+
+    /// Whether warnings are reported as errors
+    deny_warnings: bool,
+
+    /// Whether this is a release build, which leaves out runtime bounds checks
+    release: bool,
 }
 
 /*
@@ -46,6 +54,8 @@ impl BuildSystem {
                 BuildSystem {
                     path,
                     modules: Some(vec![module]),
+                    deny_warnings: false,
+                    release: false,
                 }
             }
 
@@ -57,6 +67,8 @@ impl BuildSystem {
                 BuildSystem {
                     path,
                     modules: None,
+                    deny_warnings: false,
+                    release: false,
                 }
             }
         }
@@ -64,14 +76,30 @@ impl BuildSystem {
 
     }
 
+    /// Set whether warnings are reported as errors
+    /// `-D warnings`
+    pub fn set_deny_warnings(&mut self, deny: bool) {
+        self.deny_warnings = deny;
+    }
+
+    /// Set whether this is a release build
+    /// `--release`
+    pub fn set_release(&mut self, release: bool) {
+        self.release = release;
+    }
+
     /// TODO: Build system for packages
-    pub fn build_project(&self) {
+    ///
+    /// Returns whether everything compiled without errors
+    pub fn build_project(&self) -> bool {
         match self.modules {
             Some(ref modules) => {
+                let mut success = true;
                 for module in modules {
                     println!("{}", format!("Compiling: {module}").bright_cyan());
-                    self.build_module(&module);
+                    success &= self.build_module(module);
                 }
+                return success;
             }
 
             None => {
@@ -79,26 +107,82 @@ impl BuildSystem {
                 match file {
                     Ok(source_file) => {
                         let file_ptr = Arc::new(source_file);
-                        self.compile_file(&file_ptr);
+                        return self.compile_file(&file_ptr);
                     }
 
                     Err(err) => {
                         println!("Error before compiling file: {}", err);
+                        return false;
                     }
                 }
             }
         }
     }
 
-    /// Build a module from the source code files in it
-    pub fn build_module(&self, module: &Arc<SourceModule>) {
+    /// Create the emitter that diagnostics are reported to
+    fn emitter(&self) -> StdEmitter {
+        let mut emitter = StdEmitter::new();
+        emitter.set_deny_warnings(self.deny_warnings);
+        return emitter;
+    }
+
+    /// Create the analyzer that checks the parsed files
+    fn analyzer(&self) -> SemanticAnalyzer<StdEmitter> {
+        let mut analyzer = SemanticAnalyzer::new(self.emitter());
+        analyzer.set_bounds_checks(!self.release);
+        return analyzer;
+    }
+
+    /// Build a module from the source code files in it.
+    /// Returns whether it compiled without errors
+    pub fn build_module(&self, module: &Arc<SourceModule>) -> bool {
+        let mut parsed = Vec::new();
         for file in module.files() {
-            self.compile_file(file);
+            parsed.push((file.clone(), self.parse_file(file)));
+        }
+
+        // Every file has to be declared before any of them are checked
+        // so that items can be used across files in the module
+        let mut analyzer = self.analyzer();
+        for (file, items) in &parsed {
+            analyzer.declare(file, items);
+        }
+        for (file, items) in &parsed {
+            analyzer.check(file, items);
         }
+        analyzer.finish();
+
+        let items: Vec<ExprNode> = parsed.into_iter().flat_map(|(_, items)| items).collect();
+        self.lower(&mut analyzer, &items);
+
+        return analyzer.emitter().last_emitted_error_code().is_none();
+    }
+
+    /// Fully compile a source code file.
+    /// Returns whether it compiled without errors
+    pub fn compile_file(&self, file: &Arc<SourceFile>) -> bool {
+        let items = self.parse_file(file);
+
+        let mut analyzer = self.analyzer();
+        analyzer.analyze_file(file, &items);
+        self.lower(&mut analyzer, &items);
+
+        return analyzer.emitter().last_emitted_error_code().is_none();
     }
 
-    /// Fully compile a source code file
-    pub fn compile_file(&self, file: &Arc<SourceFile>) {
+    /// Make the copies of the generic items that the checked items use,
+    /// which reports generic items that recurse without end.
+    /// There is no backend to generate code from the copies yet
+    fn lower(&self, analyzer: &mut SemanticAnalyzer<StdEmitter>, items: &[ExprNode]) {
+        if analyzer.emitter().last_emitted_error_code().is_some() {
+            return;
+        }
+
+        analyzer.monomorphize(items);
+    }
+
+    /// Parse all of the items in a source code file
+    fn parse_file(&self, file: &Arc<SourceFile>) -> Vec<ExprNode> {
         println!(
             "{}",
             format!(" -- Compiling file: {}", 
@@ -109,8 +193,12 @@ impl BuildSystem {
 
         let mut parser = Parser::new(file);
         
-        let stmt = parser.parse_top_level().unwrap();
-        println!("{}", stmt);
+        let items = parser.parse_program().unwrap();
+        for item in &items {
+            println!("{}", item);
+        }
+
+        return items;
     }
 }
 
@@ -15,14 +15,27 @@ pub struct Argument {
 
     #[clap(long= "dump-syntax")]
     pub dump_syntax: bool,
+
+    /// Turn a group of lints into errors
+    /// `-D warnings`
+    #[clap(short = 'D', value_name = "LINT", value_parser = ["warnings"])]
+    pub deny: Vec<String>,
+
+    /// Build without the checks that are only wanted while debugging,
+    /// like checking indices against the length of arrays
+    #[clap(long = "release")]
+    pub release: bool,
 }
 
 /// Run the compiler on that argument
 pub fn run(arg: Argument) -> ExitCode {
     let filepath = arg.file;
-    let builder = BuildSystem::new(filepath);
-
-    builder.build_project();
+    let mut builder = BuildSystem::new(filepath);
+    builder.set_deny_warnings(arg.deny.iter().any(|lint| lint == "warnings"));
+    builder.set_release(arg.release);
 
-    return ExitCode::SUCCESS;
+    match builder.build_project() {
+        true => return ExitCode::SUCCESS,
+        false => return ExitCode::FAILURE,
+    }
 }
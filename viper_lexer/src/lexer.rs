@@ -9,6 +9,9 @@ pub struct Lexer<'a> {
     /// Pointer to the source code file
     source_file: &'a Arc<SourceFile>,
 
+    /// Name of the source code file. Every token is marked with it
+    file_name: Arc<String>,
+
     /// Iterator that points to a location along the input source code
     code_iterator: Peekable<Chars<'a>>,
     
@@ -24,6 +27,10 @@ pub struct Lexer<'a> {
     /// The current number of characters that we have passed when tokenizing.
     /// This is used for creating substrings of parts of the source code
     position: usize,
+
+    /// Whether the last token was a '.'. A number right after one is the
+    /// index of a tuple element, so `t.0.1` is two indices and not `0.1`
+    after_dot: bool,
 }
 
 /// Implementation of the Lexer for the 'a lifetime
@@ -34,11 +41,13 @@ impl<'a> Lexer<'a> {
         let c = it.next().unwrap();
         let l = Lexer {
             source_file: source,
+            file_name: Arc::new(source.name().display().to_string()),
             code_iterator: it,
             line_number: 1,
             column: 1,
             current_char: c,
             position: 0,
+            after_dot: false,
         };
     
         return l;
@@ -109,15 +118,18 @@ impl<'a> Lexer<'a> {
     }
 
     /// Read a numeric value token and determine
-    /// if it is a floating point or integer value
-    pub fn read_number(&mut self) -> Token {
+    /// if it is a floating point or integer value.
+    /// Only integers are read when `fraction_allowed` is false
+    pub fn read_number(&mut self, fraction_allowed: bool) -> Token {
         let mut floating_point = false;
         let mut is_legal = true;
         let start_position = self.position.clone();
         let start_line = self.line_number.clone();
         let start_col = self.column.clone();
 
-        while char::is_digit(self.current_char, 10) {
+        // A '.' is only part of the number when a digit follows it
+        while char::is_digit(self.current_char, 10)
+            || (fraction_allowed && self.current_char == '.' && char::is_digit(self.peek_char(), 10)) {
             if self.current_char == '.' && floating_point == true {
                 is_legal = false;
             }
@@ -244,9 +256,16 @@ impl<'a> Lexer<'a> {
 
     /// Return a token from the source code
     pub fn next_token(&mut self) -> Token {
+        self.read_token().in_file(&self.file_name)
+    }
+
+    /// Read the next token from the source code input
+    fn read_token(&mut self) -> Token {
         let tok: Token;
         self.skip_whitespace();
 
+        let after_dot = std::mem::replace(&mut self.after_dot, false);
+
         match self.current_char {
             '"' => {
                 tok = self.read_string_literal();
@@ -318,6 +337,16 @@ impl<'a> Lexer<'a> {
                             Span::new(start_line, self.line_number, start_col, self.column)
                         );
                     }
+                    '>' => {
+                        self.read_char();
+                        let start_line = self.line_number.clone();
+                        let start_col = self.column.clone();
+                        tok = Token::Punctuator(
+                            PunctuatorKind::from_str("->").unwrap(), 
+                            None,
+                            Span::new(start_line, self.line_number, start_col, self.column)
+                        );
+                    }
                     _ => {
                         let start_line = self.line_number.clone();
                         let start_col = self.column.clone();
@@ -458,7 +487,7 @@ impl<'a> Lexer<'a> {
                         let start_col = self.column.clone();
                         tok = Token::Punctuator(
                             PunctuatorKind::from_str("&&").unwrap(), 
-                            Some(OperatorPrecedence::Comparison),
+                            Some(OperatorPrecedence::LogicalAndOr),
                             Span::new(start_line, self.line_number, start_col, self.column)
                         );
                     }
@@ -493,7 +522,7 @@ impl<'a> Lexer<'a> {
                         let start_col = self.column.clone();
                         tok = Token::Punctuator(
                             PunctuatorKind::from_str("||").unwrap(), 
-                            Some(OperatorPrecedence::Comparison),
+                            Some(OperatorPrecedence::LogicalAndOr),
                             Span::new(start_line, self.line_number, start_col, self.column)
                         );
                     }
@@ -655,6 +684,7 @@ impl<'a> Lexer<'a> {
             }
 
             '.' => {
+                self.after_dot = true;
                 let start_line = self.line_number.clone();
                 let start_col = self.column.clone();
                 tok = Token::Punctuator(
@@ -664,6 +694,26 @@ impl<'a> Lexer<'a> {
                 );
             }
             
+            '?' => {
+                let start_line = self.line_number.clone();
+                let start_col = self.column.clone();
+                tok = Token::Punctuator(
+                    PunctuatorKind::from_str("?").unwrap(), 
+                    None,
+                    Span::new(start_line, self.line_number, start_col, self.column)
+                );
+            }
+
+            '#' => {
+                let start_line = self.line_number.clone();
+                let start_col = self.column.clone();
+                tok = Token::Punctuator(
+                    PunctuatorKind::from_str("#").unwrap(), 
+                    None,
+                    Span::new(start_line, self.line_number, start_col, self.column)
+                );
+            }
+
             ';' => {
                 let start_line = self.line_number.clone();
                 let start_col = self.column.clone();
@@ -774,9 +824,9 @@ impl<'a> Lexer<'a> {
 
             _ => {
                 if char::is_digit(self.current_char, 10) {
-                    tok = self.read_number();
+                    tok = self.read_number(!after_dot);
                     return tok;
-                } else if char::is_alphabetic(self.current_char) {
+                } else if char::is_alphabetic(self.current_char) || self.current_char == '_' {
                     tok = self.read_identifier();
                     return tok;
                 } else {
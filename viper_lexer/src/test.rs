@@ -197,4 +197,58 @@ mod test {
             i += 1;
         }
     }
+
+    #[test]
+    fn lexer_float() {
+        let test_file = SourceFile::new_dummy("1.5 2 3.25;", "Test file");
+        let file_ptr = Arc::from(test_file);
+
+        let mut lexer = Lexer::new(&file_ptr);
+        let mut values = vec![];
+        let mut token = lexer.next_token();
+        while token != Token::EOF {
+            match token {
+                Token::NumericLiteral(value, _) => values.push(value.to_string()),
+                Token::Punctuator(PunctuatorKind::SemiColon, _, _) => {}
+                _ => panic!("Expected number, got {token}"),
+            }
+            token = lexer.next_token();
+        }
+
+        assert_eq!(values, vec![
+            "Floating Point: '1.5'",
+            "Integer: '2'",
+            "Floating Point: '3.25'",
+        ]);
+    }
+
+    #[test]
+    fn lexer_tuple_index() {
+        let test_file = SourceFile::new_dummy("t.0.1 1.5;", "Test file");
+        let file_ptr = Arc::from(test_file);
+
+        let mut lexer = Lexer::new(&file_ptr);
+        let mut tokens = vec![];
+        let mut token = lexer.next_token();
+        while token != Token::EOF {
+            match &token {
+                Token::NumericLiteral(value, _) => tokens.push(value.to_string()),
+                Token::Punctuator(PunctuatorKind::Dot, _, _) => tokens.push(String::from(".")),
+                Token::Identifier(name, _) => tokens.push(name.clone()),
+                Token::Punctuator(PunctuatorKind::SemiColon, _, _) => {}
+                _ => panic!("Unexpected token {token}"),
+            }
+            token = lexer.next_token();
+        }
+
+        // A number right after a `.` never has a fraction
+        assert_eq!(tokens, vec![
+            "t",
+            ".",
+            "Integer: '0'",
+            ".",
+            "Integer: '1'",
+            "Floating Point: '1.5'",
+        ]);
+    }
 }
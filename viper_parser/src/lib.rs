@@ -2,8 +2,8 @@ pub mod test;
 
 use std::{sync::Arc, rc::Rc, cell::RefCell};
 
-use viper_ast::{BinaryOperator, Binding, CodeBlock, Conditional, Expr, ExprNode, FieldInit, ObjInit, ProcedureCall, ProcedureDef, StructDef, StructField, StructMethod, UnaryOperator, VariableInitialization, Visibility, WhileLoop};
-use viper_core::{_type::Type, error::ViperError, scope::Scope, source::SourceFile, span::Span, symbol::Symbol, token::{KeywordKind, NumericValue, OperatorPrecedence, PunctuatorKind, Token}};
+use viper_ast::{Assignment, BinaryOperator, Binding, CodeBlock, Conditional, ConstDef, Expr, ExprNode, Field, FieldInit, GenericParam, ImplDef, InterfaceDef, InterfaceMethod, MethodCall, MethodKind, ObjInit, Path, PathSegment, ProcedureCall, ProcedureDef, Repr, StructDef, StructField, StructMethod, UnaryOperator, VariableInitialization, Visibility, WhileLoop};
+use viper_core::{_type::{ArrayLength, Type}, error::ViperError, scope::Scope, source::SourceFile, span::Span, symbol::Symbol, token::{KeywordKind, NumericValue, OperatorPrecedence, PunctuatorKind, Token}};
 use viper_lexer::lexer::Lexer;
 
 
@@ -21,6 +21,11 @@ pub struct Parser<'a> {
 
     /// Lookahead token for parsing
     _peek_token: Token,
+
+    /// Whether `identifier {` should be parsed as an object initialization.
+    /// This is turned off while parsing the condition of an `if` or `while`,
+    /// where the `{` starts the body instead
+    allow_obj_init: bool,
 }
 
 impl<'a> Parser<'a> {
@@ -31,12 +36,30 @@ impl<'a> Parser<'a> {
             source_file: source,
             current_token: Token::EOF,
             _peek_token: Token::EOF,
+            allow_obj_init: true,
         }
     }
 
+    /// Parse every top-level statement of a Viper source file
+    pub fn parse_program(&mut self) -> Result<Vec<ExprNode>, ViperError> {
+        self.advance()?;
+
+        let mut items = vec![];
+        while self.current_token != Token::EOF {
+            items.push(self.parse_item()?);
+        }
+
+        return Ok(items);
+    }
+
     /// Parse a top-level (Program Scope) statement of a Viper source file
     pub fn parse_top_level(&mut self) -> Result<ExprNode, ViperError> {
         self.advance()?;
+        return self.parse_item();
+    }
+
+    /// Parse the top-level statement at the current token
+    fn parse_item(&mut self) -> Result<ExprNode, ViperError> {
         match self.current_token {
             Token::Keyword(keyword, _) => {
                 match keyword {
@@ -47,20 +70,66 @@ impl<'a> Parser<'a> {
                         self.expect_punctuator(PunctuatorKind::SemiColon)?;
                         return expr;
                     }
+                    KeywordKind::Const => {
+                        println!("Parsing constant");
+                        return self.parse_const_item(Visibility::Private);
+                    }
                     KeywordKind::Define => {
                         println!("Parsing function definition");
-                        return self.parse_procedure_definition();
+                        return self.parse_procedure_definition(Visibility::Private);
                     }
-
                     KeywordKind::Struct => {
                         println!("Parsing struct definition");
-                        return self.parse_struct_def();
+                        return self.parse_struct_def(Visibility::Private, Repr::default());
+                    }
+                    KeywordKind::Interface => {
+                        println!("Parsing interface definition");
+                        return self.parse_interface_def(Visibility::Private);
+                    }
+                    KeywordKind::Impl => {
+                        println!("Parsing interface implementation");
+                        return self.parse_impl_def();
+                    }
+
+                    // `public define`, `public const`, `public struct`, `public interface`
+                    KeywordKind::Public => {
+                        self.expect_keyword(KeywordKind::Public)?;
+                        if &self.current_token == KeywordKind::Define {
+                            println!("Parsing public function definition");
+                            return self.parse_procedure_definition(Visibility::Public);
+                        }
+                        if &self.current_token == KeywordKind::Const {
+                            println!("Parsing public constant");
+                            return self.parse_const_item(Visibility::Public);
+                        }
+                        if &self.current_token == KeywordKind::Interface {
+                            println!("Parsing public interface definition");
+                            return self.parse_interface_def(Visibility::Public);
+                        }
+
+                        println!("Parsing public struct definition");
+                        return self.parse_struct_def(Visibility::Public, Repr::default());
                     }
                     _ => {
                         return Err(ViperError::ParserError);
                     }
                 }
             }
+
+            // Attributes come before the struct they apply to
+            // `#[repr(C)] public struct Header {...}`
+            Token::Punctuator(PunctuatorKind::Hash, _, _) => {
+                let repr = self.parse_repr()?;
+
+                let mut vis = Visibility::Private;
+                if &self.current_token == KeywordKind::Public {
+                    vis = Visibility::Public;
+                    self.expect_keyword(KeywordKind::Public)?;
+                }
+
+                println!("Parsing struct definition with attributes");
+                return self.parse_struct_def(vis, repr);
+            }
             _ => {
                 return Err(ViperError::ParserError);
             }
@@ -72,27 +141,26 @@ impl<'a> Parser<'a> {
     ///     name: string,
     ///     age: i32
     /// }
-    fn parse_struct_def(&mut self) -> Result<ExprNode, ViperError> {
-        let mut struct_vis = Visibility::Private;
-        if &self.current_token == KeywordKind::Public {
-            struct_vis = Visibility::Public;
-            self.expect_keyword(KeywordKind::Public)?;
-        }
-
+    /// `struct Pair<A, B> { first: A, second: B }`
+    /// Any `public` and attributes before it have already been eaten
+    fn parse_struct_def(&mut self, struct_vis: Visibility, repr: Repr) -> Result<ExprNode, ViperError> {
         self.expect_keyword(KeywordKind::Struct)?;
 
-        let ident = match self.current_token.clone() {
-            Token::Identifier(name, _span) => {
+        let (ident, ident_span) = match self.current_token.clone() {
+            Token::Identifier(name, span) => {
                 self.advance()?;
-                name
+                (name, span)
             }
             _ => {
                 return Err(ViperError::ParserError);
             }
         };
 
+        let generics = self.parse_generic_params()?;
+
         let mut fields = vec![];
         let mut methods = vec![];
+        let mut consts = vec![];
 
         self.expect_punctuator(PunctuatorKind::LSquirly)?;
 
@@ -111,10 +179,18 @@ impl<'a> Parser<'a> {
                 Token::Keyword(kind, _) => {
                     match kind {
                         KeywordKind::Method 
+                        | KeywordKind::Mut
                         | KeywordKind::Static => {
                             methods.push(self.parse_struct_method(vis)?);
                             continue;
                         }
+
+                        // Constants end with a `;` rather than a `,`
+                        KeywordKind::Const => {
+                            consts.push(self.parse_const_def(vis)?);
+                            self.expect_punctuator(PunctuatorKind::SemiColon)?;
+                            continue;
+                        }
                         _ => return Err(ViperError::ParserError),
                     }
                 }
@@ -122,7 +198,7 @@ impl<'a> Parser<'a> {
                 // If we are at an identifier, we should be
                 // parsing a field
                 Token::Identifier(_name, _span) => {
-                    let binding = self.parse_binding()?;
+                    let binding = self.parse_binding(false)?;
                     
                     fields.push(
                         StructField::new(binding, vis)
@@ -151,38 +227,677 @@ impl<'a> Parser<'a> {
         return Ok(ExprNode::new(
             Expr::StructDef(StructDef::new(
                     ident, 
+                    generics,
                     Box::from(fields.as_slice()), 
                     Box::from(methods.as_slice()),
+                    Box::from(consts.as_slice()),
                     struct_vis,
+                    repr,
                 )), 
-            Span::dummy()
+            ident_span
+        ));
+    }
+
+    /// Parse the attributes that say how a struct is laid out.
+    /// There can be any number of them, and their options add up
+    /// `#[repr(C)]`
+    /// `#[repr(packed)]`
+    /// `#[repr(C, align(16))]`
+    fn parse_repr(&mut self) -> Result<Repr, ViperError> {
+        let mut c = false;
+        let mut packed = false;
+        let mut align = None;
+
+        while &self.current_token == PunctuatorKind::Hash {
+            self.expect_punctuator(PunctuatorKind::Hash)?;
+            self.expect_punctuator(PunctuatorKind::LBrace)?;
+
+            match &self.current_token {
+                Token::Identifier(name, _) if name == "repr" => self.advance()?,
+                _ => {
+                    println!("Invalid attribute: '{}'. Expected `repr`", &self.current_token);
+                    return Err(ViperError::ParserError);
+                }
+            }
+            self.expect_punctuator(PunctuatorKind::LParen)?;
+
+            loop {
+                match self.current_token.clone() {
+                    Token::Identifier(name, _) if name == "C" => {
+                        self.advance()?;
+                        c = true;
+                    }
+                    Token::Identifier(name, _) if name == "packed" => {
+                        self.advance()?;
+                        packed = true;
+                    }
+                    Token::Identifier(name, _) if name == "align" => {
+                        self.advance()?;
+                        self.expect_punctuator(PunctuatorKind::LParen)?;
+                        match self.current_token {
+                            Token::NumericLiteral(NumericValue::Integer(value), _) => {
+                                self.advance()?; // eat the alignment
+                                align = Some(value);
+                            }
+                            _ => {
+                                println!("Invalid token: '{}'. Expected alignment", &self.current_token);
+                                return Err(ViperError::ParserError);
+                            }
+                        }
+                        self.expect_punctuator(PunctuatorKind::RParen)?;
+                    }
+                    _ => {
+                        println!("Invalid token: '{}'. Expected `C`, `packed` or `align(N)`", &self.current_token);
+                        return Err(ViperError::ParserError);
+                    }
+                }
+
+                if &self.current_token != PunctuatorKind::Comma {
+                    break;
+                }
+                self.expect_punctuator(PunctuatorKind::Comma)?;
+            }
+
+            self.expect_punctuator(PunctuatorKind::RParen)?;
+            self.expect_punctuator(PunctuatorKind::RBrace)?;
+        }
+
+        return Ok(Repr::new(c, packed, align));
+    }
+
+    /// Parse an interface definition
+    /// interface Weapon {
+    ///     method damage(): i32;
+    ///     mut method swing(times: i32): void;
+    /// }
+    /// Any `public` before it has already been eaten
+    fn parse_interface_def(&mut self, visibility: Visibility) -> Result<ExprNode, ViperError> {
+        self.expect_keyword(KeywordKind::Interface)?;
+
+        let (name, span) = match self.current_token.clone() {
+            Token::Identifier(name, span) => (name, span),
+            _ => {
+                println!("Invalid token: '{}'. Expected interface name", &self.current_token);
+                return Err(ViperError::ParserError);
+            }
+        };
+        self.advance()?; // eat the name
+
+        let mut methods = vec![];
+        self.expect_punctuator(PunctuatorKind::LSquirly)?;
+        while &self.current_token != PunctuatorKind::RSquirly {
+            let is_mutating = self.parse_mutability()?;
+            self.expect_keyword(KeywordKind::Method)?;
+
+            let (method, method_span) = match self.current_token.clone() {
+                Token::Identifier(method, span) => (method, span),
+                _ => {
+                    println!("Invalid token: '{}'. Expected method name", &self.current_token);
+                    return Err(ViperError::ParserError);
+                }
+            };
+            self.advance()?; // eat the method name
+
+            let params = self.parse_parameter_list()?;
+            self.expect_punctuator(PunctuatorKind::Colon)?;
+            let ret = self.parse_type()?;
+            self.expect_punctuator(PunctuatorKind::SemiColon)?;
+
+            methods.push(InterfaceMethod::new(method, Box::from(params.as_slice()), ret, is_mutating, method_span));
+        }
+        self.expect_punctuator(PunctuatorKind::RSquirly)?;
+
+        return Ok(ExprNode::new(
+            Expr::InterfaceDef(InterfaceDef::new(name, Box::from(methods.as_slice()), visibility, span.clone())),
+            span
+        ));
+    }
+
+    /// Parse the implementation of an interface for a struct
+    /// impl Weapon for Sword {
+    ///     method damage(): i32 { return self.sharpness; }
+    /// }
+    fn parse_impl_def(&mut self) -> Result<ExprNode, ViperError> {
+        let span = self.current_token.span();
+        self.expect_keyword(KeywordKind::Impl)?;
+
+        let interface = self.parse_path()?;
+        self.expect_keyword(KeywordKind::For)?;
+        let target = self.parse_path()?;
+
+        // The methods are reached through the interface,
+        // so they are as visible as it is
+        let mut methods = vec![];
+        self.expect_punctuator(PunctuatorKind::LSquirly)?;
+        while &self.current_token != PunctuatorKind::RSquirly {
+            methods.push(self.parse_struct_method(Visibility::Public)?);
+        }
+        self.expect_punctuator(PunctuatorKind::RSquirly)?;
+
+        return Ok(ExprNode::new(
+            Expr::ImplDef(ImplDef::new(interface, target, Box::from(methods.as_slice()), span.clone())),
+            span
         ));
     }
 
+    /// Parse a constant at the top level of a file
+    /// `const SIZE: u64 = 16;`
+    /// Any `public` before it has already been eaten
+    fn parse_const_item(&mut self, visibility: Visibility) -> Result<ExprNode, ViperError> {
+        let def = self.parse_const_def(visibility)?;
+        self.expect_punctuator(PunctuatorKind::SemiColon)?;
+
+        let span = def.span().clone();
+        return Ok(ExprNode::new(Expr::Const(def), span));
+    }
+
+    /// Parse the definition of a constant, without the `;` after it
+    /// `const SIZE: u64 = 16`
+    fn parse_const_def(&mut self, visibility: Visibility) -> Result<ConstDef, ViperError> {
+        self.expect_keyword(KeywordKind::Const)?;
+
+        let (name, span) = match self.current_token.clone() {
+            Token::Identifier(name, span) => (name, span),
+            _ => {
+                println!("Invalid token: '{}'. Expected constant name", &self.current_token);
+                return Err(ViperError::ParserError);
+            }
+        };
+        self.advance()?; // eat the name
+
+        // Constants always have their type written out
+        self.expect_punctuator(PunctuatorKind::Colon)?;
+        let ty = self.parse_type()?;
+
+        self.expect_punctuator(PunctuatorKind::EqualSign)?;
+        let value = self.parse_expr()?;
+
+        return Ok(ConstDef::new(name, ty, value, visibility, span));
+    }
+
     /// Parse the methods within a struct in Viper
     fn parse_struct_method(&mut self, vis: Visibility) -> Result<StructMethod, ViperError> {
-        let mut is_static = false;
-        if &self.current_token == KeywordKind::Static {
-            is_static = true;
+        let kind = if &self.current_token == KeywordKind::Static {
             self.expect_keyword(KeywordKind::Static)?;
+            MethodKind::Static
         } else {
+            // `mut method` can change the value it is called on
+            let is_mutating = self.parse_mutability()?;
             self.expect_keyword(KeywordKind::Method)?;
+            if is_mutating { MethodKind::Mutating } else { MethodKind::Method }
+        };
+
+        let (ident, ident_span) = match &self.current_token {
+            Token::Identifier(name, span) => {
+                (name.clone(), span.clone())
+            }
+            _ => return Err(ViperError::ParserError),
+        };
+
+        self.advance()?; // eat the identifier
+
+        let params = self.parse_parameter_list()?;
+        self.expect_punctuator(PunctuatorKind::Colon)?;
+
+        let ret = self.parse_type()?;
+        let body = self.parse_expr_block(Some(self.source_file.scope()))?;
+
+        return Ok(StructMethod::new(
+            ident, 
+            Box::from(params.as_slice()), 
+            Box::from(body), 
+            ret, 
+            vis, 
+            kind,
+            ident_span,
+        ));
+    }
+
+    /// Parse the parameters of a method, with the parentheses around them
+    /// `(a: i32, b: *User)`
+    fn parse_parameter_list(&mut self) -> Result<Vec<Binding>, ViperError> {
+        let mut params = vec![];
+
+        self.expect_punctuator(PunctuatorKind::LParen)?;
+        while &self.current_token != PunctuatorKind::RParen {
+            params.push(self.parse_parameter()?);
+
+            if &self.current_token != PunctuatorKind::Comma {
+                if &self.current_token == PunctuatorKind::RParen {
+                    break;
+                } else {
+                    return Err(ViperError::ParserError);
+                }
+            }
+
+            self.expect_punctuator(PunctuatorKind::Comma)?;
         }
+        self.expect_punctuator(PunctuatorKind::RParen)?;
 
-        let mut params: Vec<Binding> = vec![];
+        return Ok(params);
+    }
+
+    /// Parse a type AST node
+    /// `i32`, `User`, `std::string`, `Pair<i32, f64>`
+    /// `*T`, `*mut T`, `&T`, `&mut T`
+    /// `[T]`, `[T; N]`
+    /// `(A, B)`, `fn(A, B) -> R`, `void`
+    /// `dyn Weapon`
+    fn parse_type(&mut self) -> Result<Type, ViperError> {
+        let type_ast = self.current_token.clone();
 
-        let ident = match &self.current_token {
-            Token::Identifier(name, _span) => {
-                name.clone()
+        // Slice and array types
+        if &self.current_token == PunctuatorKind::LBrace {
+            return self.parse_type_slice();
+        }
+
+        // Tuple types
+        if &self.current_token == PunctuatorKind::LParen {
+            return self.parse_type_tuple();
+        }
+
+        // Pointer
+        // *[type]
+        // *mut [type]
+        if &self.current_token == PunctuatorKind::Star {
+            self.expect_punctuator(PunctuatorKind::Star)?;
+            let mutable = self.parse_mutability()?;
+            return Ok(Type::Pointer { mutable, inner: Arc::from(self.parse_type()?) });
+        }
+
+        // Optional
+        // ?[type]
+        if &self.current_token == PunctuatorKind::Question {
+            self.expect_punctuator(PunctuatorKind::Question)?;
+            return Ok(Type::Optional(Arc::from(self.parse_type()?)));
+        }
+
+        // Reference
+        // &[type]
+        // &mut [type]
+        if &self.current_token == PunctuatorKind::Ampersand {
+            self.expect_punctuator(PunctuatorKind::Ampersand)?;
+            let mutable = self.parse_mutability()?;
+            return Ok(Type::Reference { mutable, inner: Arc::from(self.parse_type()?) });
+        }
+
+        // `&&T` gets lexed as a single '&&' token, so we
+        // have to split it into two references ourselves
+        if &self.current_token == PunctuatorKind::LogicalAnd {
+            self.expect_punctuator(PunctuatorKind::LogicalAnd)?;
+            let mutable = self.parse_mutability()?;
+            let inner = Type::Reference { mutable, inner: Arc::from(self.parse_type()?) };
+            return Ok(Type::Reference { mutable: false, inner: Arc::from(inner) });
+        }
+
+        match &type_ast {
+            Token::Keyword(kind, span) => {
+                match kind {
+                    KeywordKind::Void => {
+                        self.advance()?;
+                        return Ok(Type::Void);
+                    }
+                    KeywordKind::Fn => {
+                        return self.parse_type_function();
+                    }
+                    KeywordKind::Dyn => {
+                        self.expect_keyword(KeywordKind::Dyn)?;
+                        return Ok(Type::Dyn(self.parse_path()?));
+                    }
+                    KeywordKind::Bool
+                    | KeywordKind::Byte
+                    | KeywordKind::F32
+                    | KeywordKind::F64
+                    | KeywordKind::I8
+                    | KeywordKind::I16
+                    | KeywordKind::I32
+                    | KeywordKind::I64
+                    | KeywordKind::U8
+                    | KeywordKind::U16
+                    | KeywordKind::U32
+                    | KeywordKind::U64 => {
+                        self.advance()?;
+                        return Ok(Type::Concrete { name: Path::from_ident(kind.as_str().to_string(), span.clone()), args: vec![] });
+                    }
+                    _ => {
+                        println!("Invalid token: '{}'. Expected type", &self.current_token);
+                        return Err(ViperError::ParserError);
+                    }
+                }
+            }
+
+            Token::Identifier(_name, _span) => {
+                let name = self.parse_path()?;
+                let args = self.parse_type_args()?;
+                return Ok(Type::Concrete { name, args });
+            }
+            _ => {
+                println!("Invalid token: '{}'. Expected type", &self.current_token);
+                return Err(ViperError::ParserError);
+            }
+        }
+    }
+
+    /// Parse the generic parameters of a struct or procedure, if it has any
+    /// `<T>`, `<A, B>`, `<T: Weapon + Show>`
+    fn parse_generic_params(&mut self) -> Result<Vec<GenericParam>, ViperError> {
+        let mut generics = vec![];
+        if &self.current_token != PunctuatorKind::LessThan {
+            return Ok(generics);
+        }
+
+        self.expect_punctuator(PunctuatorKind::LessThan)?;
+        while &self.current_token != PunctuatorKind::GreaterThan {
+            match self.current_token.clone() {
+                Token::Identifier(name, span) => {
+                    self.advance()?; // eat the name
+                    let bounds = self.parse_bounds()?;
+                    generics.push(GenericParam::new(name, bounds, span));
+                }
+                _ => {
+                    println!("Invalid token: '{}'. Expected generic parameter", &self.current_token);
+                    return Err(ViperError::ParserError);
+                }
+            }
+
+            if &self.current_token != PunctuatorKind::Comma {
+                break;
+            }
+
+            self.expect_punctuator(PunctuatorKind::Comma)?;
+        }
+        self.expect_punctuator(PunctuatorKind::GreaterThan)?;
+
+        return Ok(generics);
+    }
+
+    /// Parse the interfaces that a generic parameter is bound by, if it has any
+    /// `: Weapon + Show`
+    fn parse_bounds(&mut self) -> Result<Vec<Path>, ViperError> {
+        let mut bounds = vec![];
+        if &self.current_token != PunctuatorKind::Colon {
+            return Ok(bounds);
+        }
+
+        self.expect_punctuator(PunctuatorKind::Colon)?;
+        loop {
+            bounds.push(self.parse_path()?);
+
+            if &self.current_token != PunctuatorKind::Plus {
+                break;
+            }
+
+            self.expect_punctuator(PunctuatorKind::Plus)?;
+        }
+
+        return Ok(bounds);
+    }
+
+    /// Parse the arguments given to a generic type, if there are any
+    /// `<i32>` in `Box<i32>`
+    fn parse_type_args(&mut self) -> Result<Vec<Type>, ViperError> {
+        let mut args = vec![];
+        if &self.current_token != PunctuatorKind::LessThan {
+            return Ok(args);
+        }
+
+        self.expect_punctuator(PunctuatorKind::LessThan)?;
+        while !self.at_closing_angle() {
+            args.push(self.parse_type()?);
+
+            if &self.current_token != PunctuatorKind::Comma {
+                break;
+            }
+
+            self.expect_punctuator(PunctuatorKind::Comma)?;
+        }
+
+        // `Box<Box<i32>>` ends with a `>>` token, which closes
+        // two lists of arguments, so only eat the first half of it
+        if let Token::Punctuator(PunctuatorKind::RShift, _, span) = &self.current_token {
+            println!("Eating: '>' of '>>'");
+            self.current_token = Token::Punctuator(PunctuatorKind::GreaterThan, Some(OperatorPrecedence::Comparison), span.clone());
+            return Ok(args);
+        }
+
+        self.expect_punctuator(PunctuatorKind::GreaterThan)?;
+        return Ok(args);
+    }
+
+    /// Whether the current token closes a list of type arguments
+    fn at_closing_angle(&self) -> bool {
+        return &self.current_token == PunctuatorKind::GreaterThan
+            || &self.current_token == PunctuatorKind::RShift;
+    }
+
+    /// Parse an optional `mut` qualifier.
+    /// Returns whether it was present
+    fn parse_mutability(&mut self) -> Result<bool, ViperError> {
+        if &self.current_token == KeywordKind::Mut {
+            self.expect_keyword(KeywordKind::Mut)?;
+            return Ok(true);
+        }
+
+        return Ok(false);
+    }
+
+    /// Parse the slice and array types in the Viper programming language
+    /// `[T]`       -> slice of T
+    /// `[T; N]`    -> array of N elements of T
+    /// `[T; SIZE]` -> array with the constant `SIZE` as its length
+    fn parse_type_slice(&mut self) -> Result<Type, ViperError> {
+        self.expect_punctuator(PunctuatorKind::LBrace)?;
+
+        let element = Arc::from(self.parse_type()?);
+
+        if &self.current_token == PunctuatorKind::SemiColon {
+            self.expect_punctuator(PunctuatorKind::SemiColon)?;
+            let length = self.parse_array_length()?;
+
+            self.expect_punctuator(PunctuatorKind::RBrace)?;
+            return Ok(Type::Array { element, length });
+        }
+
+        self.expect_punctuator(PunctuatorKind::RBrace)?;
+        return Ok(Type::Slice(element));
+    }
+
+    /// Parse the length of an array type or repeat expression,
+    /// which is either a number or the name of a constant
+    /// `4`, `SIZE`, `Buffer::SIZE`
+    fn parse_array_length(&mut self) -> Result<ArrayLength, ViperError> {
+        match self.current_token {
+            Token::NumericLiteral(NumericValue::Integer(length), _) => {
+                self.advance()?; // eat the length
+                return Ok(ArrayLength::Literal(length as usize));
+            }
+            Token::Identifier(_, _) => return Ok(ArrayLength::Named(self.parse_path()?)),
+            _ => {
+                println!("Invalid token: '{}'. Expected array length", &self.current_token);
+                return Err(ViperError::ParserError);
+            }
+        }
+    }
+
+    /// Parse a tuple type in the Viper programming language
+    /// `(A, B)`
+    /// `(A,)` -> tuple with one element
+    /// `(A)`  -> just A
+    /// `()`   -> unit
+    fn parse_type_tuple(&mut self) -> Result<Type, ViperError> {
+        self.expect_punctuator(PunctuatorKind::LParen)?;
+
+        let mut elements = vec![];
+        let mut trailing_comma = false;
+
+        while &self.current_token != PunctuatorKind::RParen {
+            elements.push(self.parse_type()?);
+            trailing_comma = false;
+
+            if &self.current_token != PunctuatorKind::Comma {
+                if &self.current_token == PunctuatorKind::RParen {
+                    break;
+                } else {
+                    return Err(ViperError::ParserError);
+                }
+            }
+
+            self.expect_punctuator(PunctuatorKind::Comma)?;
+            trailing_comma = true;
+        }
+        self.expect_punctuator(PunctuatorKind::RParen)?;
+
+        if elements.len() == 1 && !trailing_comma {
+            return Ok(elements.remove(0));
+        }
+
+        return Ok(Type::Tuple(elements));
+    }
+
+    /// Parse a function pointer type in the Viper programming language
+    /// `fn(A, B) -> R`
+    /// `fn(A)` -> returns void
+    fn parse_type_function(&mut self) -> Result<Type, ViperError> {
+        self.expect_keyword(KeywordKind::Fn)?;
+        self.expect_punctuator(PunctuatorKind::LParen)?;
+
+        let mut params = vec![];
+        while &self.current_token != PunctuatorKind::RParen {
+            params.push(self.parse_type()?);
+
+            if &self.current_token != PunctuatorKind::Comma {
+                if &self.current_token == PunctuatorKind::RParen {
+                    break;
+                } else {
+                    return Err(ViperError::ParserError);
+                }
+            }
+
+            self.expect_punctuator(PunctuatorKind::Comma)?;
+        }
+        self.expect_punctuator(PunctuatorKind::RParen)?;
+
+        let return_type = if &self.current_token == PunctuatorKind::ThinArrow {
+            self.expect_punctuator(PunctuatorKind::ThinArrow)?;
+            self.parse_type()?
+        } else {
+            Type::Void
+        };
+
+        return Ok(Type::Function { params, return_type: Arc::from(return_type) });
+    }
+
+    /// Parse expressions at their tighest bindings
+    /// These 'primary' expressions get used to form larger ones
+    fn parse_primary_expr(&mut self) -> Result<ExprNode, ViperError> {
+        let expr = match self.current_token.clone() {
+            Token::Punctuator(kind, _precedence, _span) => {
+                match kind {
+                    PunctuatorKind::Bang
+                    | PunctuatorKind::Minus
+                    | PunctuatorKind::Tilde
+                    | PunctuatorKind::Ampersand
+                    | PunctuatorKind::LogicalAnd
+                    | PunctuatorKind::Star => {
+                        self.parse_expr_unary()
+                    }
+                    PunctuatorKind::Pipe
+                    | PunctuatorKind::LogicalOr => {
+                        self.parse_lambda()
+                    }
+                    PunctuatorKind::LSquirly => {
+                        self.parse_expr_block(Some(self.source_file.scope()))
+                    }
+                    PunctuatorKind::LParen => {
+                        self.parse_expr_tuple()
+                    }
+                    PunctuatorKind::LBrace => {
+                        self.parse_expr_array()
+                    }
+                    _ => Err(ViperError::ParserError)
+                }
+            }
+            
+            Token::Identifier(_name, _span) => {
+                println!("Parsing Identifier!");
+                self.parse_expr_identifier()
+            }
+
+            Token::NumericLiteral(value, _span) => {
+                self.parse_number_literal(value)
+            }
+
+            Token::StringLiteral(_literal, _span) => {
+                self.parse_string_literal()
+            }
+
+            Token::Keyword(KeywordKind::True, span) => {
+                self.advance()?;
+                Ok(ExprNode::new(Expr::True, span))
+            }
+
+            // `if`s and blocks produce the value that they `yield`
+            // `let i: i32 = if a { yield 1; } else { yield 2; };`
+            Token::Keyword(KeywordKind::If, _span) => {
+                self.parse_if(self.source_file.scope(), KeywordKind::If)
+            }
+
+            Token::Keyword(KeywordKind::False, span) => {
+                self.advance()?;
+                Ok(ExprNode::new(Expr::False, span))
+            }
+
+            Token::Keyword(KeywordKind::Null, span) => {
+                self.advance()?;
+                Ok(ExprNode::new(Expr::Null, span))
+            }
+
+            Token::Keyword(KeywordKind::KWOk, _span)
+            | Token::Keyword(KeywordKind::KWErr, _span) => {
+                self.parse_expr_result()
+            }
+
+            Token::Keyword(KeywordKind::SizeOf, _span)
+            | Token::Keyword(KeywordKind::AlignOf, _span)
+            | Token::Keyword(KeywordKind::OffsetOf, _span) => {
+                self.parse_expr_layout()
+            }
+
+            // `self` can not be declared by the user, so it
+            // is safe to treat it like any other variable
+            Token::Keyword(KeywordKind::KWSelf, span) => {
+                self.advance()?;
+                Ok(ExprNode::new(Expr::Identifier(String::from("self")), span))
+            }
+            
+            _ => {
+                Err(ViperError::ParserError)
             }
-            _ => return Err(ViperError::ParserError),
-        };
+        }?;
 
-        self.advance()?; // eat the identifier
+        return self.parse_expr_postfix(expr);
+    }
 
+    /// Parse a tuple expression or an expression in parentheses
+    /// `(a, b)`
+    /// `(a,)` -> tuple with one element
+    /// `(a)`  -> just a
+    /// `()`   -> empty tuple
+    fn parse_expr_tuple(&mut self) -> Result<ExprNode, ViperError> {
+        let start = self.current_token.span();
         self.expect_punctuator(PunctuatorKind::LParen)?;
+
+        // Struct literals are allowed again inside the parentheses,
+        // even in an `if` condition
+        let allow_obj_init = self.allow_obj_init;
+        self.allow_obj_init = true;
+
+        let mut elements = vec![];
+        let mut trailing_comma = false;
+
         while &self.current_token != PunctuatorKind::RParen {
-            params.push(self.parse_binding().unwrap());
+            elements.push(self.parse_expr()?);
+            trailing_comma = false;
 
             if &self.current_token != PunctuatorKind::Comma {
                 if &self.current_token == PunctuatorKind::RParen {
@@ -193,113 +908,231 @@ impl<'a> Parser<'a> {
             }
 
             self.expect_punctuator(PunctuatorKind::Comma)?;
+            trailing_comma = true;
         }
+        self.allow_obj_init = allow_obj_init;
+
+        let span = start + self.current_token.span();
         self.expect_punctuator(PunctuatorKind::RParen)?;
-        self.expect_punctuator(PunctuatorKind::Colon)?;
 
-        let ret = self.parse_type()?;
-        let body = self.parse_expr_block(Some(self.source_file.scope()))?;
+        if elements.len() == 1 && !trailing_comma {
+            return Ok(elements.remove(0));
+        }
 
-        return Ok(StructMethod::new(
-            ident, 
-            Box::from(params.as_slice()), 
-            Box::from(body), 
-            ret, 
-            vis, 
-            is_static
-        ));
+        return Ok(ExprNode::new(Expr::Tuple(elements), span));
     }
 
-    /// Parse a type AST node
-    fn parse_type(&mut self) -> Result<Type, ViperError> {
-        let type_ast = self.current_token.clone();
+    /// Parse a value of a `Result`
+    /// `Ok(value)`
+    /// `Err(error)`
+    fn parse_expr_result(&mut self) -> Result<ExprNode, ViperError> {
+        let start = self.current_token.span();
+        let is_ok = matches!(self.current_token, Token::Keyword(KeywordKind::KWOk, _));
+        self.advance()?; // eat the `Ok` or `Err`
+        self.expect_punctuator(PunctuatorKind::LParen)?;
 
-        // Slice types
-        if &self.current_token == PunctuatorKind::LBrace {
-            return self.parse_type_slice();
-        }
+        let allow_obj_init = self.allow_obj_init;
+        self.allow_obj_init = true;
+        let value = self.parse_expr();
+        self.allow_obj_init = allow_obj_init;
+        let value = Box::from(value?);
 
-        // Pointer
-        // *[type]
-        if &self.current_token == PunctuatorKind::Star {
-            self.expect_punctuator(PunctuatorKind::Star)?;
-            return Ok(Type::Concrete { name: "Ref".to_string(), args: vec![self.parse_type()?] });
-        }
+        let span = start + self.current_token.span();
+        self.expect_punctuator(PunctuatorKind::RParen)?;
 
-        // TODO: parse the remainder of the types
-        match &type_ast {
-            Token::Keyword(kind, _span) => {
-                self.advance()?;
-                // TODO: Parse the arguments to the type
-                return Ok(Type::Concrete { name: kind.as_str().to_string(), args: vec![] });
-            }
+        let inner = match is_ok {
+            true => Expr::Ok(value),
+            false => Expr::Err(value),
+        };
+        return Ok(ExprNode::new(inner, span));
+    }
 
-            Token::Identifier(name, _span) => {
-                self.advance()?;
-                // TODO: parse the arguments to the type
-                return Ok(Type::Concrete { name: name.clone(), args: vec![] });
-            }
+    /// Parse a question about the layout of a type, which is answered while compiling
+    /// `sizeof(T)`
+    /// `alignof(T)`
+    /// `offsetof(T, field)`
+    /// `offsetof((i32, u8), 1)` -> offset of the second element
+    fn parse_expr_layout(&mut self) -> Result<ExprNode, ViperError> {
+        let start = self.current_token.span();
+        let keyword = match self.current_token {
+            Token::Keyword(keyword, _) => keyword,
+            _ => return Err(ViperError::ParserError),
+        };
+        self.advance()?; // eat the keyword
+        self.expect_punctuator(PunctuatorKind::LParen)?;
+        let ty = self.parse_type()?;
+
+        let inner = match keyword {
+            KeywordKind::SizeOf => Expr::SizeOf(ty),
+            KeywordKind::AlignOf => Expr::AlignOf(ty),
             _ => {
-                return Err(ViperError::ParserError);
+                self.expect_punctuator(PunctuatorKind::Comma)?;
+                let field = match self.current_token.clone() {
+                    Token::Identifier(name, _) => name,
+                    Token::NumericLiteral(NumericValue::Integer(index), _) => index.to_string(),
+                    _ => {
+                        println!("Invalid token: '{}'. Expected field name", &self.current_token);
+                        return Err(ViperError::ParserError);
+                    }
+                };
+                self.advance()?; // eat the field
+                Expr::OffsetOf(ty, field)
             }
-        }
+        };
+
+        let span = start + self.current_token.span();
+        self.expect_punctuator(PunctuatorKind::RParen)?;
+
+        return Ok(ExprNode::new(inner, span));
     }
 
-    /// Parse the slice type in the Viper programming language
-    fn parse_type_slice(&mut self) -> Result<Type, ViperError> {
+    /// Parse an array literal, which lists its elements
+    /// or repeats one value for every element
+    /// `[1, 2, 3]`
+    /// `[0; 64]`   -> 64 zeroes
+    /// `[0; SIZE]` -> the constant `SIZE` as the length
+    /// `[]`        -> an empty array, which takes its type from the context
+    fn parse_expr_array(&mut self) -> Result<ExprNode, ViperError> {
+        let start = self.current_token.span();
         self.expect_punctuator(PunctuatorKind::LBrace)?;
 
-        let mut args = vec![];
+        if &self.current_token == PunctuatorKind::RBrace {
+            let span = start + self.current_token.span();
+            self.expect_punctuator(PunctuatorKind::RBrace)?;
+            return Ok(ExprNode::new(Expr::ArrayLiteral(vec![]), span));
+        }
 
-        while &self.current_token != PunctuatorKind::RBrace {
-            let ty = self.parse_type()?;
-            args.push(ty);
+        let allow_obj_init = self.allow_obj_init;
+        self.allow_obj_init = true;
 
-            if &self.current_token != PunctuatorKind::Comma {
-                if &self.current_token == PunctuatorKind::RBrace {
-                    break;
-                } else {
-                    return Err(ViperError::ParserError);
+        let first = self.parse_expr()?;
+        let inner = match &self.current_token == PunctuatorKind::SemiColon {
+            true => {
+                self.expect_punctuator(PunctuatorKind::SemiColon)?;
+                Expr::ArrayRepeat(Box::from(first), self.parse_array_length()?)
+            }
+            false => {
+                let mut elements = vec![first];
+                while &self.current_token == PunctuatorKind::Comma {
+                    self.expect_punctuator(PunctuatorKind::Comma)?;
+                    if &self.current_token == PunctuatorKind::RBrace {
+                        break;
+                    }
+                    elements.push(self.parse_expr()?);
                 }
+                Expr::ArrayLiteral(elements)
             }
-        }
+        };
+        self.allow_obj_init = allow_obj_init;
 
+        let span = start + self.current_token.span();
         self.expect_punctuator(PunctuatorKind::RBrace)?;
-        return Ok(Type::Concrete { name: "Slice".to_string(), args });
+
+        return Ok(ExprNode::new(inner, span));
     }
 
-    /// Parse expressions at their tighest bindings
-    /// These 'primary' expressions get used to form larger ones
-    fn parse_primary_expr(&mut self) -> Result<ExprNode, ViperError> {
-        match self.current_token.clone() {
-            Token::Punctuator(kind, _precedence, _span) => {
-                match kind {
-                    PunctuatorKind::Bang
-                    | PunctuatorKind::Minus
-                    | PunctuatorKind::Tilde => {
-                        self.parse_expr_unary()
-                    }
-                    _ => Err(ViperError::ParserError)
-                }
+    /// Parse any field accesses, method calls and indexing following an expression
+    /// `user.name`
+    /// `user.print_name()`
+    /// `self.position.scale(2)`
+    /// `matrix[i][j]`
+    /// `pair.0`
+    /// `read(path)?`
+    fn parse_expr_postfix(&mut self, mut expr: ExprNode) -> Result<ExprNode, ViperError> {
+        loop {
+            // Passes the error of a `Result` on to the caller
+            if &self.current_token == PunctuatorKind::Question {
+                let span = expr.span().clone() + self.current_token.span();
+                self.expect_punctuator(PunctuatorKind::Question)?;
+
+                expr = ExprNode::new(Expr::Try(Box::from(expr)), span);
+                continue;
             }
-            
-            Token::Identifier(_name, _span) => {
-                println!("Parsing Identifier!");
-                self.parse_expr_identifier()
+
+            if &self.current_token == PunctuatorKind::LBrace {
+                self.expect_punctuator(PunctuatorKind::LBrace)?;
+                let index = self.parse_expr()?;
+
+                let span = expr.span().clone() + self.current_token.span();
+                self.expect_punctuator(PunctuatorKind::RBrace)?;
+
+                expr = ExprNode::new(Expr::Index(Box::from(expr), Box::from(index)), span);
+                continue;
             }
 
-            Token::NumericLiteral(value, _span) => {
-                self.parse_number_literal(value)
+            if &self.current_token != PunctuatorKind::Dot {
+                break;
             }
+            self.expect_punctuator(PunctuatorKind::Dot)?;
 
-            Token::StringLiteral(_literal, _span) => {
-                self.parse_string_literal()
+            let (name, name_span) = match self.current_token.clone() {
+                Token::Identifier(name, span) => (name, span),
+                // Elements of a tuple are accessed by their position
+                Token::NumericLiteral(NumericValue::Integer(index), span) => (index.to_string(), span),
+                _ => {
+                    println!("Invalid token: '{}'. Expected field or method name", &self.current_token);
+                    return Err(ViperError::ParserError);
+                }
+            };
+            self.advance()?; // eat the name
+
+            let span = expr.span().clone() + name_span.clone();
+            expr = match &self.current_token == PunctuatorKind::LParen {
+                true => {
+                    let args = self.parse_arguments()?;
+                    ExprNode::new(
+                        Expr::MethodCall(Box::from(MethodCall::new(expr, name, args, name_span))),
+                        span
+                    )
+                }
+                false => {
+                    ExprNode::new(
+                        Expr::MemberFieldAccess(Box::from(Field::new(expr, name, name_span))),
+                        span
+                    )
+                }
+            };
+        }
+
+        return Ok(expr);
+    }
+
+    /// Parse the arguments passed to a procedure or method
+    /// `(a, b + 1, foo())`
+    fn parse_arguments(&mut self) -> Result<Vec<Box<ExprNode>>, ViperError> {
+        let mut args: Vec<Box<ExprNode>> = Vec::new();
+        self.expect_punctuator(PunctuatorKind::LParen)?;
+
+        // Parse the arguments
+        while &self.current_token != PunctuatorKind::RParen {
+            match self.parse_expr() {
+                Ok(expr) => {
+                    args.push(Box::from(expr));
+                }
+                Err (_err) =>{
+                    // If this errors assume that we did not parse an expression,
+                    // and that it is empty. 
+                    // This is not good and in the future we are going to have
+                    // a noop expression that will represent this
+                    continue;
+                }
             }
-            
-            _ => {
-                Err(ViperError::ParserError)
+
+            if &self.current_token != PunctuatorKind::Comma {
+                if &self.current_token == PunctuatorKind::RParen {
+                    break;
+                } else {
+                    // No comma, but no ')' is error
+                    return Err(ViperError::ParserError);
+                }
             }
+
+            self.expect_punctuator(PunctuatorKind::Comma)?;
         }
+
+        self.expect_punctuator(PunctuatorKind::RParen)?;
+
+        return Ok(args);
     }
 
     /// Parse a string liteal in Viper
@@ -318,40 +1151,87 @@ impl<'a> Parser<'a> {
     }
     
     /// Parse an expression for a unary operation
+    /// `-a`, `!a`, `~a`
+    /// `&a`, `&mut a`, `*a`
     fn parse_expr_unary(&mut self) -> Result<ExprNode, ViperError> {
-        let operator = UnaryOperator::from(self.current_token.clone());
+        let op_span = self.current_token.span();
+
+        // `&&a` gets lexed as a single '&&' token, so it
+        // is the address of the address of `a`
+        if &self.current_token == PunctuatorKind::LogicalAnd {
+            self.expect_punctuator(PunctuatorKind::LogicalAnd)?;
+            let operator = match self.parse_mutability()? {
+                true => UnaryOperator::AddressOfMut,
+                false => UnaryOperator::AddressOf,
+            };
+
+            let expr = self.parse_primary_expr()?;
+            let span = op_span + expr.span().clone();
+            let inner = ExprNode::new(Expr::UnaryOperation(operator, Box::from(expr)), span.clone());
+
+            return Ok(ExprNode::new(Expr::UnaryOperation(UnaryOperator::AddressOf, Box::from(inner)), span));
+        }
+
+        let mut operator = UnaryOperator::from(self.current_token.clone());
         self.advance()?; // eat the operator 
+
+        if operator == UnaryOperator::AddressOf && self.parse_mutability()? {
+            operator = UnaryOperator::AddressOfMut;
+        }
         
-        let expr = self.parse_expr().unwrap();
+        // Unary operators bind tighter than any binary operator,
+        // so only the primary expression is the operand
+        // `*p + 1` -> `(*p) + 1`
+        let expr = self.parse_primary_expr()?;
+        let span = op_span + expr.span().clone();
 
-        return Ok(ExprNode::new(Expr::UnaryOperation(operator, Box::from(expr)), Span::dummy()));
+        return Ok(ExprNode::new(Expr::UnaryOperation(operator, Box::from(expr)), span));
     }
 
     /// Parse a variable declaration statement
     /// `let...`
     fn parse_variable_initialization(&mut self) -> Result<ExprNode, ViperError> {
         self.advance()?; // Eat the `let` token
+        let mutable = self.parse_mutability()?;
 
         let ident_expr = self.parse_expr();
-        self.advance()?; // eat the ':'
+        if let Ok(pattern) = &ident_expr {
+            check_let_pattern(pattern)?;
+        }
 
-        let dtype = self.parse_type()?;
+        // The type can be left out to have it inferred
+        // `let i = 0;`
+        let dtype = match &self.current_token == PunctuatorKind::Colon {
+            true => {
+                self.advance()?; // eat the ':'
+                Some(self.parse_type()?)
+            }
+            false => None,
+        };
 //        let dtype = self.current_token.clone();
 //        self.advance()?;
 
-
-        self.advance()?; // eat the '='
-        let expr = self.parse_expr().unwrap();
+        // The value can be left out and assigned later
+        // `let i: i32;`
+        let values = match &self.current_token == PunctuatorKind::SemiColon {
+            true => vec![],
+            false => {
+                self.expect_punctuator(PunctuatorKind::EqualSign)?;
+                vec!(Box::from(self.parse_expr()?))
+            }
+        };
+        let ident_expr = ident_expr?;
+        let span = ident_expr.span().clone();
         
         return Ok(
             ExprNode::new(
                 Expr::Let(VariableInitialization::new(
-                    vec!(Box::from(ident_expr.unwrap())),
+                    vec!(Box::from(ident_expr)),
                     dtype,
-                    false,
-                    vec!(Box::from(expr))
+                    mutable,
+                    values,
                 )),
-                Span::dummy()
+                span
             )
         );
     }
@@ -381,6 +1261,18 @@ impl<'a> Parser<'a> {
                         self.expect_punctuator(PunctuatorKind::SemiColon)?;
                         return expr;
                     }
+                    KeywordKind::Break => {
+                        let span = self.current_token.span();
+                        self.expect_keyword(KeywordKind::Break)?;
+                        self.expect_punctuator(PunctuatorKind::SemiColon)?;
+                        return Ok(ExprNode::new(Expr::Break, span));
+                    }
+                    KeywordKind::Continue => {
+                        let span = self.current_token.span();
+                        self.expect_keyword(KeywordKind::Continue)?;
+                        self.expect_punctuator(PunctuatorKind::SemiColon)?;
+                        return Ok(ExprNode::new(Expr::Continue, span));
+                    }
                     KeywordKind::If => {
                         let expr = self.parse_if(scope, KeywordKind::If);
                         return expr;
@@ -393,20 +1285,65 @@ impl<'a> Parser<'a> {
                         self.expect_punctuator(PunctuatorKind::SemiColon)?;
                         return expr;
                     }
+                    // `self.age = age;`
+                    KeywordKind::KWSelf => {
+                        let expr = self.parse_assignment();
+                        self.expect_punctuator(PunctuatorKind::SemiColon)?;
+                        return expr;
+                    }
                     _ => {
                         return Err(ViperError::ParserError);
                     }
                 }
             }
 
+            // A block on its own is a statement, like an `if` or `while`
+            Token::Punctuator(PunctuatorKind::LSquirly, _, _) => {
+                return self.parse_expr_block(Some(scope));
+            }
+
             _ => {
-                let expr = self.parse_expr();
+                let expr = self.parse_assignment();
                 self.expect_punctuator(PunctuatorKind::SemiColon)?;
                 return expr;
             }
         }
     }
 
+    /// Parse an expression statement that may assign to its expression
+    /// `x = 1`
+    /// `x += 1`
+    /// `*p = 1`
+    fn parse_assignment(&mut self) -> Result<ExprNode, ViperError> {
+        let target = self.parse_expr()?;
+
+        let op = match &self.current_token {
+            Token::Punctuator(kind, _, _) => {
+                match kind {
+                    PunctuatorKind::EqualSign => None,
+                    PunctuatorKind::PlusEquals => Some(BinaryOperator::Plus),
+                    PunctuatorKind::MinusEquals => Some(BinaryOperator::Minus),
+                    PunctuatorKind::TimesEquals => Some(BinaryOperator::Times),
+                    PunctuatorKind::DivEquals => Some(BinaryOperator::Divide),
+                    PunctuatorKind::ModEquals => Some(BinaryOperator::Modulo),
+                    PunctuatorKind::LShiftEquals => Some(BinaryOperator::BShiftLeft),
+                    PunctuatorKind::RShiftEquals => Some(BinaryOperator::BShiftRight),
+                    PunctuatorKind::BinAndEquals => Some(BinaryOperator::BinAnd),
+                    PunctuatorKind::BinOrEquals => Some(BinaryOperator::BinOr),
+                    PunctuatorKind::BinXorEquals => Some(BinaryOperator::BinXor),
+                    _ => return Ok(target),
+                }
+            }
+            _ => return Ok(target),
+        };
+        self.advance()?; // eat the assignment operator
+
+        let value = self.parse_expr()?;
+        let span = target.span().clone() + value.span().clone();
+
+        return Ok(ExprNode::new(Expr::Assignment(Assignment::new(target, op, value)), span));
+    }
+
     /// Parse an if statement in Viper
     /// `
     /// if <condition> {...}
@@ -415,14 +1352,17 @@ impl<'a> Parser<'a> {
     /// } elif <condition> {
     /// } else {
     /// }
+    ///
+    /// if let <name> = <optional> {...}
     fn parse_if(&mut self, parent: Arc<RefCell<Scope>>, expected: KeywordKind) -> Result<ExprNode, ViperError> {
+        let span = self.current_token.span();
         self.expect_keyword(expected)?;
 
 
         // We only want to parse a condition if we are an `if` or `elif` expr.
         // If we are an `else` expr, there is no condition to be evaluated
         let condition = if KeywordKind::Else != expected {
-            Some(Rc::from(RefCell::new(self.parse_expr()?)))
+            Some(Rc::from(RefCell::new(self.parse_if_condition()?)))
         } else {
             None
         };
@@ -455,7 +1395,7 @@ impl<'a> Parser<'a> {
                 Rc::from(body),
                 else_clause,
             )),
-            Span::dummy()
+            span
         ));
     }
 
@@ -463,12 +1403,50 @@ impl<'a> Parser<'a> {
     /// `while [condition] {...}`
     /// `while 1 == 2-1 {...}`
     fn parse_while_loop(&mut self, parent: Arc<RefCell<Scope>>) -> Result<ExprNode, ViperError> {
+        let span = self.current_token.span();
         self.expect_keyword(KeywordKind::While)?;
 
-        let condition = Box::from(self.parse_expr()?);
+        let condition = Box::from(self.parse_condition()?);
         let body = Box::from(self.parse_expr_block(Some(Arc::from(parent)))?);
 
-        return Ok(ExprNode::new(Expr::WhileLoop(WhileLoop::new(condition, body)), Span::dummy()));
+        return Ok(ExprNode::new(Expr::WhileLoop(WhileLoop::new(condition, body)), span));
+    }
+
+    /// Parse the condition of an `if`, `elif` or `while`.
+    /// `if done { ... }` is a condition followed by a body,
+    /// not the initialization of an object named `done`
+    fn parse_condition(&mut self) -> Result<ExprNode, ViperError> {
+        let allow_obj_init = self.allow_obj_init;
+        self.allow_obj_init = false;
+
+        let condition = self.parse_expr();
+
+        self.allow_obj_init = allow_obj_init;
+        return condition;
+    }
+
+    /// Parse the condition of an `if` or `elif`, which can unwrap an optional value.
+    /// `if let p = maybe {...}` runs its body with `p` holding the value in `maybe`,
+    /// and only when `maybe` is not `null`
+    fn parse_if_condition(&mut self) -> Result<ExprNode, ViperError> {
+        match self.current_token {
+            Token::Keyword(KeywordKind::Let, _) => {}
+            _ => return self.parse_condition(),
+        }
+
+        let allow_obj_init = self.allow_obj_init;
+        self.allow_obj_init = false;
+        let condition = self.parse_variable_initialization();
+        self.allow_obj_init = allow_obj_init;
+
+        let condition = condition?;
+        match condition.inner() {
+            Expr::Let(init) if init.dtype().is_none() && init.values().len() == 1 => return Ok(condition),
+            _ => {
+                println!("Invalid condition: '{}'. Expected `let <name> = <value>`", condition.inner());
+                return Err(ViperError::ParserError);
+            }
+        }
     }
 
     fn parse_match(&mut self) -> Result<ExprNode, ViperError> {
@@ -487,31 +1465,38 @@ impl<'a> Parser<'a> {
     /// `return <expr>`
     /// `return 0`
     fn parse_return(&mut self) -> Result<ExprNode, ViperError> {
+        let span = self.current_token.span();
         self.expect_keyword(KeywordKind::Return)?;
 
         let expr = self.parse_expr()?;
-        return Ok(ExprNode::new(Expr::Return(Box::from(expr)) ,Span::dummy()));
+        let span = span + expr.span().clone();
+        return Ok(ExprNode::new(Expr::Return(Box::from(expr)), span));
     }
 
     /// Parse a yield expression in Viper
     /// `yield true`
     /// `yield i + 1`
     fn parse_yield(&mut self) -> Result<ExprNode, ViperError> {
+        let span = self.current_token.span();
         self.expect_keyword(KeywordKind::Yield)?;
         let expr = self.parse_expr()?;
+        let span = span + expr.span().clone();
         
-        return Ok(ExprNode::new(Expr::Yield(Box::from(expr)), Span::dummy()));
+        return Ok(ExprNode::new(Expr::Yield(Box::from(expr)), span));
     }
 
     /// Parse a procedure definition
-    /// This is for top-level procedures only not lambdas
-    fn parse_procedure_definition(&mut self) -> Result<ExprNode, ViperError> {
+    /// `define add(a: i32, b: i32): i32 { ... }`
+    /// `define max<T>(a: T, b: T): T { ... }`
+    /// This is for top-level procedures only not lambdas.
+    /// Any `public` before it has already been eaten
+    fn parse_procedure_definition(&mut self, visibility: Visibility) -> Result<ExprNode, ViperError> {
         self.advance()?; // eat 'define'
         let mut params: Vec<Binding> = vec![];
 
-        let ident = match self.current_token.clone() {
-            Token::Identifier(name, _span) => {
-                name
+        let (ident, ident_span) = match self.current_token.clone() {
+            Token::Identifier(name, span) => {
+                (name, span)
             }
             _ => {
                 return Err(ViperError::ParserError);
@@ -520,10 +1505,12 @@ impl<'a> Parser<'a> {
 
         self.advance()?; // eat the identifier
 
+        let generics = self.parse_generic_params()?;
+
         // Parse the parameters to the procedure
-        self.advance()?; // eat the '('
+        self.expect_punctuator(PunctuatorKind::LParen)?;
         while &self.current_token != PunctuatorKind::RParen {
-            params.push(self.parse_binding().unwrap());
+            params.push(self.parse_parameter().unwrap());
 
             if &self.current_token != PunctuatorKind::Comma {
                 if &self.current_token == PunctuatorKind::RParen {
@@ -549,11 +1536,71 @@ impl<'a> Parser<'a> {
         Ok(ExprNode::new(Expr::ProcedureDefinition(
             ProcedureDef::new(
                 ident.clone(), 
+                generics,
                 Box::from(params.as_slice()), 
                 Box::from(body), 
                 ret.clone(),
+                visibility,
+                ident_span.clone(),
+            )
+        ), ident_span))
+    }
+
+    /// Parse a lambda procedure
+    /// `|a: i32, b: i32|: i32 => { return a + b; }`
+    /// `||: i32 => { return 0; }`
+    /// `|a: i32| => { ... }` -> returns void
+    fn parse_lambda(&mut self) -> Result<ExprNode, ViperError> {
+        let start_span = self.current_token.span();
+        let mut params: Vec<Binding> = vec![];
+
+        // `||` gets lexed as a single token when there are no parameters
+        if &self.current_token == PunctuatorKind::LogicalOr {
+            self.expect_punctuator(PunctuatorKind::LogicalOr)?;
+        } else {
+            self.expect_punctuator(PunctuatorKind::Pipe)?;
+            while &self.current_token != PunctuatorKind::Pipe {
+                params.push(self.parse_lambda_parameter()?);
+
+                if &self.current_token != PunctuatorKind::Comma {
+                    if &self.current_token == PunctuatorKind::Pipe {
+                        break;
+                    } else {
+                        return Err(ViperError::ParserError);
+                    }
+                }
+
+                self.expect_punctuator(PunctuatorKind::Comma)?;
+            }
+            self.expect_punctuator(PunctuatorKind::Pipe)?;
+        }
+
+        let ret = if &self.current_token == PunctuatorKind::Colon {
+            self.expect_punctuator(PunctuatorKind::Colon)?;
+            self.parse_type()?
+        } else {
+            Type::Void
+        };
+
+        let arrow_span = self.current_token.span();
+        self.expect_punctuator(PunctuatorKind::FatArrow)?;
+
+        let body = self.parse_expr_block(Some(self.source_file.scope()))?;
+        let span = start_span + arrow_span;
+
+        // Every file of a module has its own parser, so the lambda is named
+        // after the id of its body, which no other node in the module shares
+        let name = format!("lambda{}", body.id());
+
+        Ok(ExprNode::new(Expr::ProcedureDefinition(
+            ProcedureDef::new_lambda(
+                name,
+                Box::from(params.as_slice()),
+                Box::from(body),
+                ret,
+                span.clone(),
             )
-        ), Span::dummy()))
+        ), span))
     }
 
     /// Parse a code expression block
@@ -566,9 +1613,15 @@ impl<'a> Parser<'a> {
     /// If no expression is yielded, then it yields
     /// the () unit type
     fn parse_expr_block(&mut self, parent: Option<Arc<RefCell<Scope>>>) -> Result<ExprNode, ViperError> {
+        let open_span = self.current_token.span();
         self.expect_punctuator(PunctuatorKind::LSquirly)?;
         let mut exprs = Vec::new();
         let scope = Arc::from(RefCell::new(Scope::new(parent)));
+
+        // Object initializations are fine inside of a block,
+        // even if the block is part of a condition
+        let allow_obj_init = self.allow_obj_init;
+        self.allow_obj_init = true;
       
         // Read the expressions within the block
         while self.current_token != PunctuatorKind::RSquirly {
@@ -576,8 +1629,9 @@ impl<'a> Parser<'a> {
                 Ok(expr) => {
                     match expr.inner() {
                         Expr::Let(init) => {
-                            let sym = init.to_symbol();
-                            scope.borrow_mut().add_symbol(sym.get_key(), sym);
+                            for sym in init.to_symbols() {
+                                scope.borrow_mut().add_symbol(sym.get_key(), sym);
+                            }
                             exprs.push(expr);
                         }
 
@@ -593,21 +1647,23 @@ impl<'a> Parser<'a> {
             }
             // block.add_expr(self.parse_expr_stmt(block.scope()).unwrap());
         }
+        let close_span = self.current_token.span();
         self.expect_punctuator(PunctuatorKind::RSquirly)?;
+        self.allow_obj_init = allow_obj_init;
         let block = CodeBlock::new(exprs, scope);
 
 
-        Ok(ExprNode::new(Expr::CodeBlock(block), Span::dummy()))
+        Ok(ExprNode::new(Expr::CodeBlock(block), open_span + close_span))
     }
 
     /// Parse a binding in Viper
     /// a [Binding] is binding a type to an identifier
     /// `i: i32`
     /// `j: User`
-    fn parse_binding(&mut self) -> Result<Binding, ViperError> {
-        let ident = match self.current_token.clone() {
-            Token::Identifier(name, _span) => {
-                name
+    fn parse_binding(&mut self, mutable: bool) -> Result<Binding, ViperError> {
+        let (ident, span) = match self.current_token.clone() {
+            Token::Identifier(name, span) => {
+                (name, span)
             }
             _ => {
                 println!("Invalid token: '{}'. Expected identifier", &self.current_token);
@@ -620,12 +1676,43 @@ impl<'a> Parser<'a> {
         let ty = &self.parse_type()?;
         
    
-        Ok(Binding::new(ident.into(), ty.clone()))
+        Ok(Binding::new(ident.into(), ty.clone(), mutable, span))
+    }
+
+    /// Parse a parameter of a procedure, method or lambda
+    /// `a: i32`
+    /// `mut a: i32`
+    fn parse_parameter(&mut self) -> Result<Binding, ViperError> {
+        let mutable = self.parse_mutability()?;
+        return self.parse_binding(mutable);
+    }
+
+    /// Parse a parameter of a lambda, whose type can be left out
+    /// for the type checker to work out
+    /// `a: i32`
+    /// `a`
+    fn parse_lambda_parameter(&mut self) -> Result<Binding, ViperError> {
+        let mutable = self.parse_mutability()?;
+        let (ident, span) = match self.current_token.clone() {
+            Token::Identifier(name, span) => (name, span),
+            _ => return Err(ViperError::ParserError),
+        };
+
+        self.advance()?; // eat the identifier
+
+        let ty = if &self.current_token == PunctuatorKind::Colon {
+            self.expect_punctuator(PunctuatorKind::Colon)?;
+            self.parse_type()?
+        } else {
+            Type::Inferred
+        };
+
+        return Ok(Binding::new(ident, ty, mutable, span));
     }
 
     /// Parse an expression
     fn parse_expr(&mut self) -> Result<ExprNode, ViperError> {
-        let mut lhs = self.parse_primary_expr()?;
+        let mut lhs = self.parse_expr_cast()?;
 
         while get_operator_precedence(&self.current_token).is_some() {
             lhs = self.parse_expr_binary(&mut lhs, &get_operator_precedence(&self.current_token).unwrap())?;
@@ -634,6 +1721,27 @@ impl<'a> Parser<'a> {
         return Ok(lhs);
     }
 
+    /// Parse a primary expression followed by any number of casts
+    /// `x as i64`
+    /// `p as u64 as *u8`
+    ///
+    /// Casts bind tighter than binary operators but looser than
+    /// unary ones: `-x as i64` -> `(-x) as i64`
+    fn parse_expr_cast(&mut self) -> Result<ExprNode, ViperError> {
+        let mut expr = self.parse_primary_expr()?;
+
+        while &self.current_token == KeywordKind::As {
+            let as_span = self.current_token.span();
+            self.expect_keyword(KeywordKind::As)?;
+
+            let ty = self.parse_type()?;
+            let span = expr.span().clone() + as_span;
+            expr = ExprNode::new(Expr::Cast(Box::from(expr), ty), span);
+        }
+
+        return Ok(expr);
+    }
+
     /// Parse an expression with an 'infix' operator
     /// a + b
     /// foo() - bar()
@@ -641,14 +1749,15 @@ impl<'a> Parser<'a> {
         let op = self.current_token.clone();
         self.advance()?;
 
-        let mut rhs = self.parse_primary_expr()?;
+        let mut rhs = self.parse_expr_cast()?;
+        let span = lhs.span().clone() + rhs.span().clone();
         let next_prec = match get_operator_precedence(&self.current_token) {
             Some(p) => p,
             None => {
                 return Ok(
                     ExprNode::new(
                         Expr::BinaryOperation(BinaryOperator::from(op.clone()), Box::from(lhs.clone()), Box::from(rhs)), 
-                        Span::dummy()
+                        span
                     )
                 );
             }
@@ -657,11 +1766,12 @@ impl<'a> Parser<'a> {
         if next_prec > *min_prec {
             rhs = self.parse_expr_binary(&mut rhs, &next_prec)?;
         }
+        let span = lhs.span().clone() + rhs.span().clone();
 
         return Ok(
             ExprNode::new(
                 Expr::BinaryOperation(BinaryOperator::from(op.clone()), Box::from(lhs.clone()), Box::from(rhs)), 
-                Span::dummy()
+                span
             )
         );
     }
@@ -670,10 +1780,9 @@ impl<'a> Parser<'a> {
         // Make sure that we are at an Identifier token
         match self.current_token.clone() {
             // We are... Parse an identifier expression
-            Token::Identifier(ident, span) => {
-                self.advance()?;
-                let mut args: Vec<Box<ExprNode>> = Vec::new();
-
+            Token::Identifier(_ident, _span) => {
+                let path = self.parse_path()?;
+                let span = path.span();
                 // Switch on the types of tokens to see what type of 
                 // expression we should be parsing
                 match &self.current_token {
@@ -681,53 +1790,24 @@ impl<'a> Parser<'a> {
                         match kind {
                             // "identifier(..." is a function call
                             PunctuatorKind::LParen => {
-                                self.expect_punctuator(PunctuatorKind::LParen)?;
-
-                                // Parse the arguments
-                                while &self.current_token != PunctuatorKind::RParen {
-                                    match self.parse_expr() {
-                                        Ok(expr) => {
-                                            args.push(Box::from(expr));
-                                        }
-                                        Err (_err) =>{
-                                            // If this errors assume that we did not parse an expression,
-                                            // and that it is empty. 
-                                            // This is not good and in the future we are going to have
-                                            // a noop expression that will represent this
-                                            continue;
-                                        }
-                                    }
-
-                                    if &self.current_token != PunctuatorKind::Comma {
-                                        if &self.current_token == PunctuatorKind::RParen {
-                                            break;
-                                        } else {
-                                            // No comma, but no ')' is error
-                                            return Err(ViperError::ParserError);
-                                        }
-                                    }
-
-                                    self.expect_punctuator(PunctuatorKind::Comma)?;
-                                }
-
-                                self.expect_punctuator(PunctuatorKind::RParen)?;
+                                let args = self.parse_arguments()?;
 
                                 // Return function call expression
                                 return Ok(ExprNode::new(
-                                        Expr::ProcedureCall(Box::from(ProcedureCall::new(ident, args))), 
+                                        Expr::ProcedureCall(Box::from(ProcedureCall::new(path, args))), 
                                         span
                                 ));
                             }
 
                             // "identifier {..." is an object initialization
-                            PunctuatorKind::LSquirly => {
+                            PunctuatorKind::LSquirly if self.allow_obj_init => {
                                 self.expect_punctuator(PunctuatorKind::LSquirly)?;
                                 let mut field_inits = vec![];
                                 
                                 while &self.current_token != PunctuatorKind::RSquirly {
-                                    let field_name = match &self.current_token {
-                                        Token::Identifier(name, _span) => {
-                                            name.clone()
+                                    let (field_name, field_span) = match &self.current_token {
+                                        Token::Identifier(name, span) => {
+                                            (name.clone(), span.clone())
                                         }
                                         _ => return Err(ViperError::ParserError),
                                     };
@@ -738,7 +1818,7 @@ impl<'a> Parser<'a> {
                                     let field_expr = self.parse_expr()?;
                                     
                                     field_inits.push(
-                                        FieldInit::new(field_name, field_expr)
+                                        FieldInit::new(field_name, field_expr, field_span)
                                     );
 
 
@@ -758,7 +1838,7 @@ impl<'a> Parser<'a> {
                                 self.expect_punctuator(PunctuatorKind::RSquirly)?;
 
                                 return Ok(ExprNode::new(
-                                    Expr::ObjInitialization(ObjInit::new(ident, field_inits))
+                                    Expr::ObjInitialization(ObjInit::new(path, field_inits))
                                     , span
                                 ));
                             }
@@ -771,12 +1851,13 @@ impl<'a> Parser<'a> {
                     }
                 }
 
-                // See if we are at a function call
-                if &self.current_token == PunctuatorKind::LParen {
-                }
-               
                 // Return normal identifier expr
-                Ok(ExprNode::new(Expr::Identifier(ident.clone()), span.clone()))
+                if path.is_ident() {
+                    return Ok(ExprNode::new(Expr::Identifier(path.last().name().to_string()), span));
+                }
+
+                // `a::b` is a path to an item in a module, type or enum
+                Ok(ExprNode::new(Expr::Path(path), span))
             }
 
             // We are not at an Identifier. Error
@@ -786,16 +1867,46 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Parse a path to an item
+    /// `foo`
+    /// `std::io::print`
+    /// `Weapon::LaserGun`
+    fn parse_path(&mut self) -> Result<Path, ViperError> {
+        let mut segments = vec![];
+
+        loop {
+            match self.current_token.clone() {
+                Token::Identifier(name, span) => {
+                    self.advance()?;
+                    segments.push(PathSegment::new(name, span));
+                }
+                _ => {
+                    println!("Invalid token: '{}'. Expected identifier", &self.current_token);
+                    return Err(ViperError::ParserError);
+                }
+            }
+
+            if &self.current_token != PunctuatorKind::DoubleColon {
+                break;
+            }
+
+            self.expect_punctuator(PunctuatorKind::DoubleColon)?;
+        }
+
+        return Ok(Path::new(segments));
+    }
+
     fn parse_number_literal(&mut self, value: NumericValue) -> Result<ExprNode, ViperError> {
         println!("Parsing number");
+        let span = self.current_token.span();
         self.advance()?;
         match value {
             NumericValue::Integer(value) => {
-                Ok(ExprNode::new(Expr::Integer(value), Span::dummy()))
+                Ok(ExprNode::new(Expr::Integer(value), span))
             }
             
             NumericValue::FloatingPoint(value) => {
-                Ok(ExprNode::new(Expr::Float(value), Span::dummy()))
+                Ok(ExprNode::new(Expr::Float(value), span))
             }
         }
     }
@@ -853,3 +1964,22 @@ fn get_operator_precedence(op: &Token) -> Option<OperatorPrecedence> {
         _ => None,
     }
 }
+
+/// Check that the left side of a `let` only names variables.
+/// Tuples of names destructure the value
+/// `let x`, `let (x, (y, z))`
+fn check_let_pattern(pattern: &ExprNode) -> Result<(), ViperError> {
+    match pattern.inner() {
+        Expr::Identifier(_) => return Ok(()),
+        Expr::Tuple(elements) => {
+            for element in elements {
+                check_let_pattern(element)?;
+            }
+            return Ok(());
+        }
+        _ => {
+            println!("Invalid pattern: '{}'. Expected a variable name or a tuple of them", pattern.inner());
+            return Err(ViperError::ParserError);
+        }
+    }
+}
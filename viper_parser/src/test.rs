@@ -3,7 +3,8 @@ mod tests {
     use std::sync::Arc;
 
     use crate::Parser;
-    use viper_core::source::SourceFile;
+    use viper_ast::{BinaryOperator, Expr, ProcedureKind, Repr, Visibility};
+    use viper_core::{_type::Type, source::SourceFile, span::Span, types::{TypeId, TypeInterner, TypeKind}};
 
     #[test]
     fn parser_simple() {
@@ -111,4 +112,825 @@ define main (argc: i32): i32 {
 
         parser.parse_top_level().unwrap();
     }
+
+    #[test]
+    fn parser_path() {
+        let test_file = SourceFile::new_dummy(
+            "let s: std::string = std::io::read(User::new(1, 2), Weapon::Sword);", 
+            "Test file"
+        );
+        let file_ptr = Arc::from(test_file);
+
+        let mut parser = Parser::new(&file_ptr);
+
+        let expr = parser.parse_top_level().unwrap();
+        let init = match expr.inner() {
+            Expr::Let(init) => init,
+            _ => panic!("Expected let statement"),
+        };
+
+        match init.dtype().unwrap() {
+            Type::Concrete { name, .. } => assert_eq!(name.to_string(), "std::string"),
+            _ => panic!("Expected concrete type"),
+        }
+
+        let call = match init.values()[0].inner() {
+            Expr::ProcedureCall(call) => call,
+            _ => panic!("Expected procedure call"),
+        };
+        assert_eq!(call.name().segments().len(), 3);
+        assert_eq!(call.name().parent().unwrap().to_string(), "std::io");
+
+        match call.arguments()[0].inner() {
+            Expr::ProcedureCall(call) => assert_eq!(call.name().to_string(), "User::new"),
+            _ => panic!("Expected static method call"),
+        }
+        match call.arguments()[1].inner() {
+            Expr::Path(path) => assert_eq!(path.last().name(), "Sword"),
+            _ => panic!("Expected path expression"),
+        }
+    }
+
+    #[test]
+    fn parser_path_obj_init() {
+        let test_file = SourceFile::new_dummy(
+r#"
+let gun: Weapon = Weapon::LaserGun {
+    ammo: 100,
+    bulletspeed: 10,
+};
+"#, 
+            "Test file"
+        );
+        let file_ptr = Arc::from(test_file);
+
+        let mut parser = Parser::new(&file_ptr);
+
+        let expr = parser.parse_top_level().unwrap();
+        let init = match expr.inner() {
+            Expr::Let(init) => init,
+            _ => panic!("Expected let statement"),
+        };
+
+        match init.values()[0].inner() {
+            Expr::ObjInitialization(obj) => {
+                assert_eq!(obj.name().to_string(), "Weapon::LaserGun");
+                assert_eq!(obj.initializations().len(), 2);
+            }
+            _ => panic!("Expected object initialization"),
+        }
+    }
+
+    #[test]
+    fn parser_types() {
+        let test_file = SourceFile::new_dummy(
+r#"
+define main(argc: i32, argv: [[u8]], p: *mut i32, r: &&u8, a: [i32; 4], t: (i32, f64), f: fn(i32, *u8) -> void): () {
+    return 0;
+}
+"#, 
+            "Test file"
+        );
+        let file_ptr = Arc::from(test_file);
+
+        let mut parser = Parser::new(&file_ptr);
+
+        parser.parse_top_level().unwrap();
+    }
+
+    #[test]
+    fn parser_type_lowering() {
+        let test_file = SourceFile::new_dummy(
+            "let a: [*mut u8; 16] = 0;", 
+            "Test file"
+        );
+        let file_ptr = Arc::from(test_file);
+
+        let mut parser = Parser::new(&file_ptr);
+
+        let expr = parser.parse_top_level().unwrap();
+        let init = match expr.inner() {
+            Expr::Let(init) => init,
+            _ => panic!("Expected let statement"),
+        };
+
+        assert_eq!(init.dtype().unwrap().to_string(), "[*mut u8; 16]");
+        let mut types = TypeInterner::new();
+        let array = types.lower(init.dtype().unwrap()).unwrap();
+        let pointer = types.pointer(TypeId::U8, true);
+        assert_eq!(types.kind(array), &TypeKind::Array { element: pointer, length: 16 });
+        assert_eq!(types.display(array).to_string(), "[*mut u8; 16]");
+    }
+
+    #[test]
+    fn parser_pointer_ops() {
+        let test_file = SourceFile::new_dummy(
+            "let i: *i32 = *p + &mut x - &&y;", 
+            "Test file"
+        );
+        let file_ptr = Arc::from(test_file);
+
+        let mut parser = Parser::new(&file_ptr);
+
+        let expr = parser.parse_top_level().unwrap();
+        let init = match expr.inner() {
+            Expr::Let(init) => init,
+            _ => panic!("Expected let statement"),
+        };
+
+        assert_eq!(init.values()[0].to_string(), "[[*p + &mut x] - &&y]");
+    }
+
+    #[test]
+    fn parser_cast() {
+        let test_file = SourceFile::new_dummy(
+            "let i: i64 = -a as i64 + p as u64 as *u8 * 2;", 
+            "Test file"
+        );
+        let file_ptr = Arc::from(test_file);
+
+        let mut parser = Parser::new(&file_ptr);
+
+        let expr = parser.parse_top_level().unwrap();
+        let init = match expr.inner() {
+            Expr::Let(init) => init,
+            _ => panic!("Expected let statement"),
+        };
+
+        assert_eq!(init.values()[0].to_string(), "[(-a as i64) + [((p as u64) as *u8) * 2]]");
+    }
+
+    #[test]
+    fn parser_lambda() {
+        let test_file = SourceFile::new_dummy(
+            "let add: fn(i32, i32) -> i32 = |a: i32, b: i32|: i32 => { return a + b; };", 
+            "Test file"
+        );
+        let file_ptr = Arc::from(test_file);
+
+        let mut parser = Parser::new(&file_ptr);
+
+        let expr = parser.parse_top_level().unwrap();
+        let init = match expr.inner() {
+            Expr::Let(init) => init,
+            _ => panic!("Expected let statement"),
+        };
+
+        match init.values()[0].inner() {
+            Expr::ProcedureDefinition(def) => {
+                assert_eq!(def.kind(), &ProcedureKind::Lambda);
+                assert_eq!(def.parameters().len(), 2);
+                assert_eq!(def.ret().to_string(), "i32");
+            }
+            _ => panic!("Expected lambda"),
+        }
+    }
+
+    #[test]
+    fn parser_lambda_inferred_params() {
+        let test_file = SourceFile::new_dummy(
+            "let add: fn(i32, i32) -> i32 = |a, mut b: i32|: i32 => { return a + b; };",
+            "Test file"
+        );
+        let file_ptr = Arc::from(test_file);
+
+        let mut parser = Parser::new(&file_ptr);
+
+        let expr = parser.parse_top_level().unwrap();
+        let init = match expr.inner() {
+            Expr::Let(init) => init,
+            _ => panic!("Expected let statement"),
+        };
+
+        match init.values()[0].inner() {
+            Expr::ProcedureDefinition(def) => {
+                let params: Vec<String> = def.parameters().iter().map(|p| p.to_string()).collect();
+                assert_eq!(params, vec!["a: _", "mut b: i32"]);
+            }
+            _ => panic!("Expected lambda"),
+        }
+    }
+
+    #[test]
+    fn parser_lambda_names() {
+        // Each file of a module is parsed by a parser of its own
+        let mut names = vec![];
+        for (code, name) in [
+            ("let f: fn() -> i32 = ||: i32 => { return 1; };", "a.viper"),
+            ("let g: fn() -> i32 = ||: i32 => { return 2; };", "b.viper"),
+        ] {
+            let file_ptr = Arc::from(SourceFile::new_dummy(code, name));
+            let mut parser = Parser::new(&file_ptr);
+
+            let expr = parser.parse_top_level().unwrap();
+            match expr.inner() {
+                Expr::Let(init) => match init.values()[0].inner() {
+                    Expr::ProcedureDefinition(def) => names.push(def.name()),
+                    _ => panic!("Expected lambda"),
+                },
+                _ => panic!("Expected let statement"),
+            }
+        }
+
+        assert!(names[0].starts_with("lambda"));
+        assert_ne!(names[0], names[1]);
+    }
+
+    #[test]
+    fn parser_assignment() {
+        let test_file = SourceFile::new_dummy(
+            "define bump(mut n: i32, p: *mut i32): void { let mut x = 1; x += n; *p = x; n <<= 2; }", 
+            "Test file"
+        );
+        let file_ptr = Arc::from(test_file);
+
+        let mut parser = Parser::new(&file_ptr);
+
+        let expr = parser.parse_top_level().unwrap();
+        let def = match expr.inner() {
+            Expr::ProcedureDefinition(def) => def,
+            _ => panic!("Expected procedure definition"),
+        };
+
+        assert!(def.parameters()[0].is_mutable());
+        assert!(!def.parameters()[1].is_mutable());
+
+        let body = match def.body().inner() {
+            Expr::CodeBlock(block) => block,
+            _ => panic!("Expected code block"),
+        };
+        let exprs: Vec<String> = body.exprs().iter().map(|e| e.to_string()).collect();
+        assert_eq!(exprs, vec!["let mut x = 1", "x += n", "*p = x", "n <<= 2"]);
+    }
+
+    #[test]
+    fn parser_uninitialized_let() {
+        let test_file = SourceFile::new_dummy(
+            "define f(): void { let x: i32; let mut p: Point; x = 1; }", 
+            "Test file"
+        );
+        let file_ptr = Arc::from(test_file);
+
+        let mut parser = Parser::new(&file_ptr);
+
+        let expr = parser.parse_top_level().unwrap();
+        let def = match expr.inner() {
+            Expr::ProcedureDefinition(def) => def,
+            _ => panic!("Expected procedure definition"),
+        };
+
+        let body = match def.body().inner() {
+            Expr::CodeBlock(block) => block,
+            _ => panic!("Expected code block"),
+        };
+        match body.exprs()[0].inner() {
+            Expr::Let(init) => assert!(init.values().is_empty()),
+            _ => panic!("Expected let"),
+        }
+
+        let exprs: Vec<String> = body.exprs().iter().map(|e| e.to_string()).collect();
+        assert_eq!(exprs, vec!["let x: i32", "let mut p: Point", "x = 1"]);
+    }
+
+    #[test]
+    fn parser_method_call() {
+        let test_file = SourceFile::new_dummy(
+            "let n: i32 = -user.position.scale(2, self.factor).x + User::new().age;", 
+            "Test file"
+        );
+        let file_ptr = Arc::from(test_file);
+
+        let mut parser = Parser::new(&file_ptr);
+
+        let expr = parser.parse_top_level().unwrap();
+        let init = match expr.inner() {
+            Expr::Let(init) => init,
+            _ => panic!("Expected let statement"),
+        };
+
+        assert_eq!(init.values()[0].to_string(), "[-user.position.scale(2, self.factor).x + User::new().age]");
+    }
+
+    #[test]
+    fn parser_mut_method() {
+        let test_file = SourceFile::new_dummy(
+            "struct Counter { count: i32, public mut method add(by: i32): void { self.count += by; } }", 
+            "Test file"
+        );
+        let file_ptr = Arc::from(test_file);
+
+        let mut parser = Parser::new(&file_ptr);
+
+        let expr = parser.parse_top_level().unwrap();
+        let def = match expr.inner() {
+            Expr::StructDef(def) => def,
+            _ => panic!("Expected struct definition"),
+        };
+
+        let method = &def.methods()[0];
+        assert!(method.is_mutating());
+        assert!(!method.is_static());
+        assert_eq!(def.receiver(method).unwrap().to_string(), "self: *mut Counter");
+    }
+
+    #[test]
+    fn parser_block_values() {
+        let test_file = SourceFile::new_dummy(
+            "define main(argc: i32): i32 { let a: i32 = if argc > 1 { yield 10; } else { yield 0; }; let b = { yield a + 1; }; return b; }", 
+            "Test file"
+        );
+        let file_ptr = Arc::from(test_file);
+
+        let mut parser = Parser::new(&file_ptr);
+
+        let expr = parser.parse_top_level().unwrap();
+        let body = match expr.inner() {
+            Expr::ProcedureDefinition(def) => def.body().clone(),
+            _ => panic!("Expected procedure definition"),
+        };
+        let exprs = match body.inner() {
+            Expr::CodeBlock(block) => block.exprs().clone(),
+            _ => panic!("Expected code block"),
+        };
+
+        let values: Vec<&Expr> = exprs[..2].iter()
+            .map(|expr| match expr.inner() {
+                Expr::Let(init) => init.values()[0].inner(),
+                _ => panic!("Expected let statement"),
+            })
+            .collect();
+        assert!(matches!(values[0], Expr::If(_)));
+        assert!(matches!(values[1], Expr::CodeBlock(_)));
+    }
+
+    #[test]
+    fn parser_const() {
+        let test_file = SourceFile::new_dummy(
+r#"
+const SIZE: u64 = 4 * 2;
+public const NAME: *u8 = "viper";
+
+struct Buffer {
+    data: [u8; Buffer::LEN],
+    public const LEN: u64 = SIZE;
+}
+
+let grid: [[i32; SIZE]; 2] = 0;
+"#,
+            "Test file"
+        );
+        let file_ptr = Arc::from(test_file);
+
+        let mut parser = Parser::new(&file_ptr);
+
+        let items = parser.parse_program().unwrap();
+        assert_eq!(items[0].to_string(), "const SIZE: u64 = [4 * 2]");
+        assert_eq!(items[1].to_string(), "public const NAME: *u8 = \"viper\"");
+
+        let def = match items[2].inner() {
+            Expr::StructDef(def) => def,
+            _ => panic!("Expected struct definition"),
+        };
+        assert_eq!(def.consts().len(), 1);
+        assert_eq!(def.consts()[0].to_string(), "public const LEN: u64 = SIZE");
+        assert_eq!(def.fields()[0].binding().ty().to_string(), "[u8; Buffer::LEN]");
+
+        match items[3].inner() {
+            Expr::Let(init) => assert_eq!(init.dtype().unwrap().to_string(), "[[i32; SIZE]; 2]"),
+            _ => panic!("Expected let statement"),
+        }
+    }
+
+    #[test]
+    fn parser_generics() {
+        let test_file = SourceFile::new_dummy(
+r#"
+struct Pair<A, B> {
+    first: A,
+    second: B,
+}
+
+define max<T>(a: T, b: T): T {
+    return a;
+}
+
+let nested: Pair<Pair<i32, u8>, *Pair<f64, bool>> = 0;
+let shifted: Pair<i32, Pair<u8, u8>> = 1 >> 2;
+"#,
+            "Test file"
+        );
+        let file_ptr = Arc::from(test_file);
+
+        let mut parser = Parser::new(&file_ptr);
+
+        let items = parser.parse_program().unwrap();
+        let def = match items[0].inner() {
+            Expr::StructDef(def) => def,
+            _ => panic!("Expected struct definition"),
+        };
+        assert_eq!(def.generics().len(), 2);
+        assert_eq!(def.fields()[1].binding().ty().to_string(), "B");
+        assert_eq!(def.self_type(&Span::dummy()).to_string(), "Pair<A, B>");
+
+        match items[1].inner() {
+            Expr::ProcedureDefinition(def) => {
+                assert_eq!(def.generics()[0].name(), "T");
+                assert!(def.to_string().starts_with("define max<T>(a: T"));
+            }
+            _ => panic!("Expected procedure definition"),
+        }
+
+        // `>>` closes two lists of type arguments
+        match items[2].inner() {
+            Expr::Let(init) => assert_eq!(init.dtype().unwrap().to_string(), "Pair<Pair<i32, u8>, *Pair<f64, bool>>"),
+            _ => panic!("Expected let statement"),
+        }
+        match items[3].inner() {
+            Expr::Let(init) => {
+                assert_eq!(init.dtype().unwrap().to_string(), "Pair<i32, Pair<u8, u8>>");
+                assert_eq!(init.values()[0].to_string(), "[1 >> 2]");
+            }
+            _ => panic!("Expected let statement"),
+        }
+    }
+
+    #[test]
+    fn parser_interfaces() {
+        let test_file = SourceFile::new_dummy(
+r#"
+public interface Weapon {
+    method damage(): i32;
+    mut method swing(times: i32): void;
+}
+
+impl Weapon for Sword {
+    method damage(): i32 {
+        return self.sharpness;
+    }
+
+    mut method swing(times: i32): void {
+        self.sharpness -= times;
+    }
+}
+
+define strongest<T: Weapon + Show, U>(a: *T, _b: U): *dyn Weapon {
+    return a;
+}
+"#,
+            "Test file"
+        );
+        let file_ptr = Arc::from(test_file);
+
+        let mut parser = Parser::new(&file_ptr);
+
+        let items = parser.parse_program().unwrap();
+        match items[0].inner() {
+            Expr::InterfaceDef(def) => {
+                assert_eq!(def.name(), "Weapon");
+                assert_eq!(def.methods().len(), 2);
+                assert!(def.methods()[1].is_mutating());
+                assert_eq!(def.methods()[1].to_string(), "mut method swing(times: i32): void;");
+            }
+            _ => panic!("Expected interface definition"),
+        }
+
+        match items[1].inner() {
+            Expr::ImplDef(def) => {
+                assert_eq!(def.interface().to_string(), "Weapon");
+                assert_eq!(def.target().to_string(), "Sword");
+                assert_eq!(def.methods().len(), 2);
+
+                let receiver = def.receiver(&def.methods()[1]).unwrap();
+                assert_eq!(receiver.ty().to_string(), "*mut Sword");
+            }
+            _ => panic!("Expected impl"),
+        }
+
+        match items[2].inner() {
+            Expr::ProcedureDefinition(def) => {
+                assert_eq!(def.generics()[0].bounds().len(), 2);
+                assert!(def.generics()[1].bounds().is_empty());
+                assert!(def.to_string().starts_with("define strongest<T: Weapon + Show, U>("));
+                assert_eq!(def.ret().to_string(), "*dyn Weapon");
+            }
+            _ => panic!("Expected procedure definition"),
+        }
+    }
+
+    #[test]
+    fn parser_index() {
+        let test_file = SourceFile::new_dummy(
+r#"
+define main(): void {
+    grid[1][i + 2].scale(2);
+    values[0] = 3;
+}
+"#,
+            "Test file"
+        );
+        let file_ptr = Arc::from(test_file);
+
+        let mut parser = Parser::new(&file_ptr);
+
+        let items = parser.parse_program().unwrap();
+        let body = match items[0].inner() {
+            Expr::ProcedureDefinition(def) => match def.body().inner() {
+                Expr::CodeBlock(block) => block.exprs().to_vec(),
+                _ => panic!("Expected block"),
+            },
+            _ => panic!("Expected procedure definition"),
+        };
+
+        // Indexing binds as tightly as method calls, from left to right
+        match body[0].inner() {
+            Expr::MethodCall(call) => match call.receiver().inner() {
+                Expr::Index(object, index) => {
+                    assert_eq!(object.to_string(), "grid[1]");
+                    assert!(matches!(index.inner(), Expr::BinaryOperation(_, _, _)));
+                }
+                _ => panic!("Expected index"),
+            },
+            _ => panic!("Expected method call"),
+        }
+
+        match body[1].inner() {
+            Expr::Assignment(assignment) => {
+                assert_eq!(assignment.target().to_string(), "values[0]");
+            }
+            _ => panic!("Expected assignment"),
+        }
+    }
+
+    #[test]
+    fn parser_tuple() {
+        let test_file = SourceFile::new_dummy(
+r#"
+define main(): void {
+    let (q, (r, s)): (i32, (i32, i32)) = (a, (b + 1, c,));
+    pair.0.1 = (a + b) * c;
+    let _unit = ();
+}
+"#,
+            "Test file"
+        );
+        let file_ptr = Arc::from(test_file);
+
+        let mut parser = Parser::new(&file_ptr);
+
+        let items = parser.parse_program().unwrap();
+        let body = match items[0].inner() {
+            Expr::ProcedureDefinition(def) => match def.body().inner() {
+                Expr::CodeBlock(block) => block.exprs().to_vec(),
+                _ => panic!("Expected block"),
+            },
+            _ => panic!("Expected procedure definition"),
+        };
+
+        match body[0].inner() {
+            Expr::Let(init) => {
+                assert_eq!(init.name(), "(q, (r, s))");
+                assert_eq!(init.values()[0].to_string(), "(a, ([b + 1], c))");
+
+                let bindings: Vec<(String, Vec<usize>, String)> = init.bindings().iter()
+                    .map(|b| (b.name().clone(), b.path().to_vec(), b.ty().unwrap().to_string()))
+                    .collect();
+                assert_eq!(bindings, vec![
+                    (String::from("q"), vec![0], String::from("i32")),
+                    (String::from("r"), vec![1, 0], String::from("i32")),
+                    (String::from("s"), vec![1, 1], String::from("i32")),
+                ]);
+            }
+            _ => panic!("Expected let"),
+        }
+
+        // Parentheses around a single expression only group it
+        match body[1].inner() {
+            Expr::Assignment(assignment) => {
+                assert_eq!(assignment.target().to_string(), "pair.0.1");
+                match assignment.value().inner() {
+                    Expr::BinaryOperation(BinaryOperator::Times, lhs, _) => {
+                        assert!(matches!(lhs.inner(), Expr::BinaryOperation(BinaryOperator::Plus, _, _)));
+                    }
+                    _ => panic!("Expected multiplication"),
+                }
+            }
+            _ => panic!("Expected assignment"),
+        }
+
+        match body[2].inner() {
+            Expr::Let(init) => assert!(matches!(init.values()[0].inner(), Expr::Tuple(elements) if elements.is_empty())),
+            _ => panic!("Expected let"),
+        }
+
+        // Only names can be declared
+        let file_ptr = Arc::from(SourceFile::new_dummy("let (a, b + 1): (i32, i32) = (1, 2);", "Test file"));
+        assert!(Parser::new(&file_ptr).parse_top_level().is_err());
+    }
+
+    #[test]
+    fn parser_array_literal() {
+        let test_file = SourceFile::new_dummy(
+r#"
+define main(): void {
+    let a = [1, 2 + x, 3,];
+    let b: [u8; 64] = [0; 64];
+    let c = [origin; Buffer::SIZE];
+    let e: [i32; 0] = [];
+    [[1, 2], [3, 4]][1][0];
+}
+"#,
+            "Test file"
+        );
+        let file_ptr = Arc::from(test_file);
+
+        let mut parser = Parser::new(&file_ptr);
+
+        let items = parser.parse_program().unwrap();
+        let body = match items[0].inner() {
+            Expr::ProcedureDefinition(def) => match def.body().inner() {
+                Expr::CodeBlock(block) => block.exprs().to_vec(),
+                _ => panic!("Expected block"),
+            },
+            _ => panic!("Expected procedure definition"),
+        };
+
+        let values: Vec<String> = body[..4].iter()
+            .map(|expr| match expr.inner() {
+                Expr::Let(init) => init.values()[0].to_string(),
+                _ => panic!("Expected let"),
+            })
+            .collect();
+        assert_eq!(values, vec!["[1, [2 + x], 3]", "[0; 64]", "[origin; Buffer::SIZE]", "[]"]);
+
+        match body[4].inner() {
+            Expr::Index(object, _) => match object.inner() {
+                Expr::Index(array, _) => assert!(matches!(array.inner(), Expr::ArrayLiteral(elements) if elements.len() == 2)),
+                _ => panic!("Expected index"),
+            },
+            _ => panic!("Expected index"),
+        }
+    }
+
+    #[test]
+    fn parser_optional() {
+        let test_file = SourceFile::new_dummy(
+r#"
+define main(): void {
+    let p: ?*i32 = null;
+    let q: ?[?u8; 4];
+    if let v = p {
+        *v;
+    }
+}
+"#,
+            "Test file"
+        );
+        let file_ptr = Arc::from(test_file);
+
+        let mut parser = Parser::new(&file_ptr);
+
+        let items = parser.parse_program().unwrap();
+        let body = match items[0].inner() {
+            Expr::ProcedureDefinition(def) => match def.body().inner() {
+                Expr::CodeBlock(block) => block.exprs().to_vec(),
+                _ => panic!("Expected block"),
+            },
+            _ => panic!("Expected procedure definition"),
+        };
+
+        let types: Vec<String> = body[..2].iter()
+            .map(|expr| match expr.inner() {
+                Expr::Let(init) => init.dtype().unwrap().to_string(),
+                _ => panic!("Expected let"),
+            })
+            .collect();
+        assert_eq!(types, vec!["?*i32", "?[?u8; 4]"]);
+
+        match body[0].inner() {
+            Expr::Let(init) => assert!(matches!(init.values()[0].inner(), Expr::Null)),
+            _ => panic!("Expected let"),
+        }
+
+        match body[2].inner() {
+            Expr::If(conditional) => match conditional.condition().unwrap().inner() {
+                Expr::Let(init) => {
+                    assert_eq!(init.name(), "v");
+                    assert_eq!(init.values()[0].to_string(), "p");
+                }
+                _ => panic!("Expected let condition"),
+            },
+            _ => panic!("Expected if"),
+        }
+    }
+
+    #[test]
+    fn parser_result() {
+        let test_file = SourceFile::new_dummy(
+r#"
+define parse(text: *u8): Result<i32, Error> {
+    let value = read(text)?.digits()? + 1;
+    if value < 0 {
+        return Err(Error { code: value });
+    }
+    return Ok(value);
+}
+"#,
+            "Test file"
+        );
+        let file_ptr = Arc::from(test_file);
+
+        let mut parser = Parser::new(&file_ptr);
+
+        let items = parser.parse_program().unwrap();
+        let def = match items[0].inner() {
+            Expr::ProcedureDefinition(def) => def,
+            _ => panic!("Expected procedure definition"),
+        };
+        assert_eq!(def.ret().to_string(), "Result<i32, Error>");
+
+        let body = match def.body().inner() {
+            Expr::CodeBlock(block) => block.exprs().to_vec(),
+            _ => panic!("Expected block"),
+        };
+
+        match body[0].inner() {
+            Expr::Let(init) => match init.values()[0].inner() {
+                Expr::BinaryOperation(_, lhs, _) => match lhs.inner() {
+                    Expr::Try(value) => assert!(matches!(value.inner(), Expr::MethodCall(call) if call.name() == "digits")),
+                    _ => panic!("Expected `?`"),
+                },
+                _ => panic!("Expected addition"),
+            },
+            _ => panic!("Expected let"),
+        }
+
+        match body[2].inner() {
+            Expr::Return(value) => assert_eq!(value.to_string(), "Ok(value)"),
+            _ => panic!("Expected return"),
+        }
+    }
+
+    #[test]
+    fn parser_repr_and_layout() {
+        let test_file = SourceFile::new_dummy(
+r#"
+#[repr(C, align(16))]
+public struct Header {
+    tag: u8,
+    length: u32,
+}
+
+#[repr(packed)]
+struct Packed {
+    tag: u8,
+}
+
+define main(): void {
+    let _s = sizeof(Header);
+    let _a = alignof(*dyn Weapon);
+    let _o = offsetof((i32, u8), 1);
+    let _f = offsetof(Header, length);
+}
+"#,
+            "Test file"
+        );
+        let file_ptr = Arc::from(test_file);
+
+        let mut parser = Parser::new(&file_ptr);
+
+        let items = parser.parse_program().unwrap();
+        match items[0].inner() {
+            Expr::StructDef(def) => {
+                assert_eq!(def.repr(), &Repr::new(true, false, Some(16)));
+                assert!(matches!(def.visibility(), Visibility::Public));
+                assert_eq!(def.fields().len(), 2);
+            }
+            _ => panic!("Expected struct definition"),
+        }
+        match items[1].inner() {
+            Expr::StructDef(def) => assert_eq!(def.repr(), &Repr::new(false, true, None)),
+            _ => panic!("Expected struct definition"),
+        }
+
+        let body = match items[2].inner() {
+            Expr::ProcedureDefinition(def) => match def.body().inner() {
+                Expr::CodeBlock(block) => block.exprs().to_vec(),
+                _ => panic!("Expected block"),
+            },
+            _ => panic!("Expected procedure definition"),
+        };
+
+        let values: Vec<String> = body.iter()
+            .map(|expr| match expr.inner() {
+                Expr::Let(init) => init.values()[0].to_string(),
+                _ => panic!("Expected let"),
+            })
+            .collect();
+        assert_eq!(values, vec![
+            "sizeof(Header)",
+            "alignof(*dyn Weapon)",
+            "offsetof((i32, u8), 1)",
+            "offsetof(Header, length)",
+        ]);
+    }
 }
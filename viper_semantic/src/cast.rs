@@ -0,0 +1,124 @@
+use viper_core::{error::ViperError, span::Span, types::{Sign, Size, TypeId, TypeInterner, TypeKind}};
+
+/// The conversions that an `as` cast can perform
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CastKind {
+    /// The value is used as-is.
+    /// Same type, or integers of the same size with a different sign
+    NoOp,
+
+    /// Widen a signed integer
+    /// `i8 as i32`
+    IntSignExtend,
+
+    /// Widen an unsigned integer
+    /// `u8 as i32`
+    IntZeroExtend,
+
+    /// Narrow an integer, dropping the high bits
+    /// `i64 as u8`
+    IntTruncate,
+
+    /// `i32 as f64`
+    IntToFloat,
+
+    /// `f64 as i32`
+    FloatToInt,
+
+    /// `f32 as f64`
+    FloatExtend,
+
+    /// `f64 as f32`
+    FloatTruncate,
+
+    /// `*T as u64`
+    PtrToInt,
+
+    /// `u64 as *T`
+    IntToPtr,
+
+    /// `*T as *U`
+    PtrToPtr,
+}
+
+/// Get the conversion that casting a value of type `from` to
+/// the type `to` performs.
+///
+/// Allowed casts are
+///  - integer <-> integer (widening and narrowing)
+///  - integer <-> float
+///  - float   <-> float
+///  - pointer <-> integer
+///  - pointer <-> pointer
+///
+/// `byte` is treated as `u8`. `*dyn` pointers also carry a vtable,
+/// so they can not be cast to or from anything else.
+pub fn check_cast(
+    types: &TypeInterner,
+    from: TypeId,
+    to: TypeId,
+    span: &Span,
+) -> Result<CastKind, ViperError> {
+    if from == to {
+        return Ok(CastKind::NoOp);
+    }
+
+    let kind = match (types.integer(from), types.integer(to)) {
+        _ if types.is_fat_pointer(from) || types.is_fat_pointer(to) => None,
+        (Some((from_sign, from_size)), Some((_, to_size))) => {
+            Some(integer_cast(from_sign, from_size, to_size))
+        }
+        (Some(_), None) => {
+            match types.kind(to) {
+                TypeKind::Float(_) => Some(CastKind::IntToFloat),
+                TypeKind::Pointer { .. } => Some(CastKind::IntToPtr),
+                _ => None,
+            }
+        }
+        (None, Some(_)) => {
+            match types.kind(from) {
+                TypeKind::Float(_) => Some(CastKind::FloatToInt),
+                TypeKind::Pointer { .. } => Some(CastKind::PtrToInt),
+                _ => None,
+            }
+        }
+        (None, None) => {
+            match (types.kind(from), types.kind(to)) {
+                (TypeKind::Float(from_size), TypeKind::Float(to_size)) => {
+                    match from_size.bits().cmp(&to_size.bits()) {
+                        std::cmp::Ordering::Less => Some(CastKind::FloatExtend),
+                        std::cmp::Ordering::Equal => Some(CastKind::NoOp),
+                        std::cmp::Ordering::Greater => Some(CastKind::FloatTruncate),
+                    }
+                }
+                (TypeKind::Pointer { .. }, TypeKind::Pointer { .. }) => Some(CastKind::PtrToPtr),
+                _ => None,
+            }
+        }
+    };
+
+    match kind {
+        Some(kind) => Ok(kind),
+        None => Err(ViperError::InvalidCast {
+            from: types.display(from).to_string(),
+            to: types.display(to).to_string(),
+            span: span.clone(),
+        }),
+    }
+}
+
+/// Classify a cast between two integer types.
+/// Widening extends based on the sign of the source value
+fn integer_cast(from_sign: Sign, from_size: Size, to_size: Size) -> CastKind {
+    match from_size.bits().cmp(&to_size.bits()) {
+        std::cmp::Ordering::Less => {
+            match from_sign {
+                Sign::Signed => CastKind::IntSignExtend,
+                Sign::Unsigned => CastKind::IntZeroExtend,
+            }
+        }
+        std::cmp::Ordering::Equal => CastKind::NoOp,
+        std::cmp::Ordering::Greater => CastKind::IntTruncate,
+    }
+}
+
@@ -0,0 +1,776 @@
+use std::{collections::{HashMap, HashSet}, ops::Range};
+
+use viper_ast::{
+    Binding, CodeBlock, Conditional, Expr, ExprNode, Ident, NodeId,
+    ProcedureDef, StructDef, UnaryOperator, WhileLoop,
+};
+use viper_core::{
+    _type::Type,
+    emitter::Emitter,
+    error::{ViperError, ViperWarning},
+    span::Span,
+    types::TypeInterner,
+};
+
+use crate::{init::InitializationChecker, lint, typecheck::TypeTable};
+
+/// Index of a [BasicBlock] within its [ControlFlowGraph]
+pub type BlockId = usize;
+
+/// Index of a [Variable] within its [ControlFlowGraph]
+pub type VariableId = usize;
+
+/// A parameter or local variable of the procedure
+#[derive(Clone, Debug)]
+pub struct Variable {
+    name: Ident,
+    mutable: bool,
+
+    /// Whether the variable gets a value where it is declared.
+    /// Only `let x: i32;` does not
+    has_value: bool,
+
+    /// Whether the variable is a parameter rather than a `let`
+    parameter: bool,
+
+    span: Span,
+}
+
+impl Variable {
+    /// Get the name of the variable
+    pub fn name(&self) -> &Ident {
+        &self.name
+    }
+
+    /// Whether the variable is declared as `mut`
+    pub fn is_mutable(&self) -> bool {
+        self.mutable
+    }
+
+    /// Whether the variable gets a value where it is declared
+    pub fn has_value(&self) -> bool {
+        self.has_value
+    }
+
+    /// Whether the variable is a parameter rather than a `let`
+    pub fn is_parameter(&self) -> bool {
+        self.parameter
+    }
+
+    /// Get the location of the declaration in the source code
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+/// A variable, or a field within it.
+/// `x`, `x.position.y`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Place {
+    pub variable: VariableId,
+    pub fields: Vec<Ident>,
+}
+
+/// Something that happens to a variable, in the order that it happens.
+///
+/// The `nodes` of a place are the expressions for the variable and each
+/// field along it: `x`, `x.position`, `x.position.y`
+#[derive(Clone, Debug)]
+pub enum Access {
+    /// A variable comes into scope
+    /// `let x: i32;`, `let x = 1;`, a parameter
+    Declare {
+        variable: VariableId,
+    },
+
+    /// The value of a place is used
+    /// `foo(x)`, `x.y + 1`, `&x`
+    Read {
+        place: Place,
+        nodes: Vec<NodeId>,
+        span: Span,
+    },
+
+    /// A place is given a new value.
+    /// Borrowing with `&mut` counts too, as the value can be written
+    /// through the pointer, like an out-parameter in C
+    /// `x = 1`, `x.y += 1`, `&mut x`
+    Write {
+        place: Place,
+        nodes: Vec<NodeId>,
+        span: Span,
+
+        /// Whether this is a `&mut` borrow rather than an assignment
+        borrowed: bool,
+    },
+}
+
+/// A run of statements that always execute one after another.
+/// Control can only enter at the start and leave at the end
+#[derive(Clone, Debug, Default)]
+pub struct BasicBlock {
+    statements: Vec<NodeId>,
+    accesses: Vec<Access>,
+    successors: Vec<BlockId>,
+}
+
+impl BasicBlock {
+    /// Get the ids of the statements in the block, in order
+    pub fn statements(&self) -> &[NodeId] {
+        &self.statements
+    }
+
+    /// Get what happens to variables within the block, in order
+    pub fn accesses(&self) -> &[Access] {
+        &self.accesses
+    }
+
+    /// Get the blocks that control can go to after this one
+    pub fn successors(&self) -> &[BlockId] {
+        &self.successors
+    }
+}
+
+/// A `while` loop within a [ControlFlowGraph]
+#[derive(Clone, Debug)]
+struct LoopInfo {
+    span: Span,
+
+    /// The block that checks the condition
+    header: BlockId,
+
+    /// The block that control goes to when the loop is left
+    after: BlockId,
+
+    /// Every block that belongs to the body of the loop
+    body: Range<BlockId>,
+}
+
+/// The control-flow graph of the body of a single procedure.
+///
+/// Every `return` goes to [ControlFlowGraph::EXIT]. Control that reaches
+/// the end of the body without a `return` ends up in [ControlFlowGraph::end].
+/// A `yield` goes to the block after the block it produces the value of.
+#[derive(Clone, Debug)]
+pub struct ControlFlowGraph {
+    blocks: Vec<BasicBlock>,
+
+    /// The parameters and local variables of the procedure
+    variables: Vec<Variable>,
+
+    /// The block that control is in after the last statement of the body
+    end: BlockId,
+
+    /// Every loop in the body
+    loops: Vec<LoopInfo>,
+
+    /// The statements of each block of code in the source,
+    /// with the basic block that each one starts in
+    sequences: Vec<Vec<(BlockId, Span)>>,
+
+    /// Every `break` or `continue` that is not in a loop
+    stray_jumps: Vec<(String, Span)>,
+}
+
+impl ControlFlowGraph {
+    /// The block that control starts in
+    pub const ENTRY: BlockId = 0;
+
+    /// The block that every `return` goes to
+    pub const EXIT: BlockId = 1;
+
+    /// Build the graph for the body of a procedure with the parameters `params`.
+    /// The variables of the procedure it is in are not tracked within lambdas
+    pub fn build(params: &[Binding], body: &ExprNode) -> ControlFlowGraph {
+        let mut builder = CfgBuilder {
+            blocks: vec![BasicBlock::default(), BasicBlock::default()],
+            current: ControlFlowGraph::ENTRY,
+            loops: vec![],
+            yield_targets: vec![],
+            loop_infos: vec![],
+            sequences: vec![],
+            variables: vec![],
+            scopes: vec![HashMap::new()],
+            stray_jumps: vec![],
+        };
+
+        for param in params {
+            builder.declare(Variable {
+                name: param.ident().clone(),
+                mutable: param.is_mutable(),
+                has_value: true,
+                parameter: true,
+                span: param.span().clone(),
+            });
+        }
+        builder.lower_branch(body, false);
+
+        return ControlFlowGraph {
+            blocks: builder.blocks,
+            variables: builder.variables,
+            end: builder.current,
+            loops: builder.loop_infos,
+            sequences: builder.sequences,
+            stray_jumps: builder.stray_jumps,
+        };
+    }
+
+    /// Get the basic blocks of the graph
+    pub fn blocks(&self) -> &[BasicBlock] {
+        &self.blocks
+    }
+
+    /// Get the parameters and local variables of the procedure
+    pub fn variables(&self) -> &[Variable] {
+        &self.variables
+    }
+
+    /// Get every `break` or `continue` that is not in a loop,
+    /// with the keyword that was used
+    pub fn stray_jumps(&self) -> &[(String, Span)] {
+        &self.stray_jumps
+    }
+
+    /// Find the blocks that control can come to each block from
+    pub fn predecessors(&self) -> Vec<Vec<BlockId>> {
+        let mut predecessors = vec![vec![]; self.blocks.len()];
+        for (block, info) in self.blocks.iter().enumerate() {
+            for next in &info.successors {
+                predecessors[*next].push(block);
+            }
+        }
+
+        return predecessors;
+    }
+
+    /// Get the block that control is in after the last statement of the body
+    pub fn end(&self) -> BlockId {
+        self.end
+    }
+
+    /// Find which blocks control can reach from the entry
+    pub fn reachable(&self) -> Vec<bool> {
+        let mut reachable = vec![false; self.blocks.len()];
+        let mut stack = vec![ControlFlowGraph::ENTRY];
+
+        while let Some(block) = stack.pop() {
+            if reachable[block] {
+                continue;
+            }
+
+            reachable[block] = true;
+            stack.extend(self.blocks[block].successors.iter().copied());
+        }
+
+        return reachable;
+    }
+
+    /// Whether control can reach the end of the body without a `return`
+    pub fn falls_off_end(&self) -> bool {
+        return self.reachable()[self.end];
+    }
+
+    /// Get the first statement that can not be reached in each block of code.
+    /// The statements that follow it are not reported, nor are blocks of code
+    /// that are unreachable as a whole
+    pub fn unreachable_statements(&self) -> Vec<Span> {
+        let reachable = self.reachable();
+
+        return self.sequences.iter()
+            .filter(|sequence| sequence.first().map(|(block, _)| reachable[*block]).unwrap_or(false))
+            .filter_map(|sequence| sequence.iter().find(|(block, _)| !reachable[*block]))
+            .map(|(_, span)| span.clone())
+            .collect();
+    }
+
+    /// Get the loops that can be entered but never left
+    pub fn infinite_loops(&self) -> Vec<Span> {
+        let reachable = self.reachable();
+
+        return self.loops.iter()
+            .filter(|info| reachable[info.header] && !reachable[info.after])
+            .filter(|info| {
+                // A `return`, or a `break` or `yield` out of an enclosing
+                // block, leaves the loop without going through `after`
+                let leaves = info.body.clone()
+                    .filter(|block| reachable[*block])
+                    .flat_map(|block| self.blocks[block].successors.iter())
+                    .any(|next| !info.body.contains(next) && *next != info.header);
+
+                !leaves
+            })
+            .map(|info| info.span.clone())
+            .collect();
+    }
+}
+
+/// Builds a [ControlFlowGraph] by walking the body of a procedure in order
+struct CfgBuilder {
+    blocks: Vec<BasicBlock>,
+
+    /// The block that statements are currently being added to
+    current: BlockId,
+
+    /// The header and after blocks of the enclosing loops, innermost last
+    loops: Vec<(BlockId, BlockId)>,
+
+    /// The blocks after each enclosing block used as a value, innermost last
+    yield_targets: Vec<BlockId>,
+
+    loop_infos: Vec<LoopInfo>,
+    sequences: Vec<Vec<(BlockId, Span)>>,
+    variables: Vec<Variable>,
+
+    /// The variables visible at the current point, innermost scope last
+    scopes: Vec<HashMap<Ident, VariableId>>,
+
+    stray_jumps: Vec<(String, Span)>,
+}
+
+impl CfgBuilder {
+    fn new_block(&mut self) -> BlockId {
+        self.blocks.push(BasicBlock::default());
+        return self.blocks.len() - 1;
+    }
+
+    fn edge(&mut self, from: BlockId, to: BlockId) {
+        self.blocks[from].successors.push(to);
+    }
+
+    /// Jump from the current block to `to`.
+    /// Anything after the jump goes in a new block that nothing leads to
+    fn jump(&mut self, to: BlockId) {
+        self.edge(self.current, to);
+        self.current = self.new_block();
+    }
+
+    fn access(&mut self, access: Access) {
+        self.blocks[self.current].accesses.push(access);
+    }
+
+    /// Bring a new variable into the innermost scope
+    fn declare(&mut self, variable: Variable) {
+        let id = self.variables.len();
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(variable.name.clone(), id);
+        }
+
+        self.variables.push(variable);
+        self.access(Access::Declare { variable: id });
+    }
+
+    fn lookup(&self, name: &str) -> Option<VariableId> {
+        return self.scopes.iter().rev().find_map(|scope| scope.get(name).copied());
+    }
+
+    /// Find the place that an expression names, with the nodes along it.
+    /// None when it is not a local variable or a field of one
+    fn place(&self, expr: &ExprNode) -> Option<(Place, Vec<NodeId>)> {
+        match expr.inner() {
+            Expr::Identifier(name) => {
+                let variable = self.lookup(name)?;
+                return Some((Place { variable, fields: vec![] }, vec![expr.id()]));
+            }
+            Expr::MemberFieldAccess(field) => {
+                let (mut place, mut nodes) = self.place(field.object())?;
+                place.fields.push(field.name().clone());
+                nodes.push(expr.id());
+                return Some((place, nodes));
+            }
+            _ => return None,
+        }
+    }
+
+    /// Add the statements of a block of code one after another
+    fn lower_sequence(&mut self, exprs: &[ExprNode]) {
+        self.scopes.push(HashMap::new());
+
+        let mut sequence = vec![];
+        for expr in exprs {
+            sequence.push((self.current, expr.span().clone()));
+            self.lower_stmt(expr);
+        }
+
+        self.sequences.push(sequence);
+        self.scopes.pop();
+    }
+
+    /// Add a statement, whose value is not used
+    fn lower_stmt(&mut self, expr: &ExprNode) {
+        self.blocks[self.current].statements.push(expr.id());
+
+        match expr.inner() {
+            Expr::CodeBlock(block) => self.lower_sequence(block.exprs()),
+            Expr::If(conditional) => self.lower_if(conditional, false),
+            _ => self.lower_expr(expr),
+        }
+    }
+
+    /// Add the body of a procedure, loop or branch.
+    /// It is a block used as a value when `value` is set
+    fn lower_branch(&mut self, body: &ExprNode, value: bool) {
+        match (body.inner(), value) {
+            (Expr::CodeBlock(block), true) => self.lower_value_block(block),
+            (Expr::CodeBlock(block), false) => self.lower_sequence(block.exprs()),
+            _ => self.lower_expr(body),
+        }
+    }
+
+    /// Add an expression, following any jumps within it
+    fn lower_expr(&mut self, expr: &ExprNode) {
+        match expr.inner() {
+            Expr::Return(value) => {
+                self.lower_expr(value);
+                self.jump(ControlFlowGraph::EXIT);
+            }
+
+            // `?` returns when it is given an error, and carries on otherwise
+            Expr::Try(value) => {
+                self.lower_expr(value);
+                self.edge(self.current, ControlFlowGraph::EXIT);
+            }
+            Expr::Yield(value) => {
+                self.lower_expr(value);
+
+                // A `yield` with nowhere to go is reported by the type checker
+                if let Some(after) = self.yield_targets.last() {
+                    self.jump(*after);
+                }
+            }
+            Expr::Break | Expr::Continue => {
+                let target = self.loops.last().map(|(header, after)| {
+                    match expr.inner() {
+                        Expr::Break => *after,
+                        _ => *header,
+                    }
+                });
+
+                match target {
+                    Some(target) => self.jump(target),
+                    None => self.stray_jumps.push((expr.to_string(), expr.span().clone())),
+                }
+            }
+            Expr::Let(init) => {
+                for value in init.values() {
+                    self.lower_expr(value);
+                }
+
+                for binding in init.bindings() {
+                    self.declare(Variable {
+                        name: binding.name().clone(),
+                        mutable: init.is_mutable(),
+                        has_value: !init.values().is_empty(),
+                        parameter: false,
+                        span: binding.span().clone(),
+                    });
+                }
+            }
+            Expr::Assignment(assignment) => {
+                self.lower_expr(assignment.value());
+
+                match self.place(assignment.target()) {
+                    Some((place, nodes)) => {
+                        let span = assignment.target().span().clone();
+
+                        // `x += 1` reads `x` before writing it
+                        if assignment.op().is_some() {
+                            self.access(Access::Read { place: place.clone(), nodes: nodes.clone(), span: span.clone() });
+                        }
+                        self.access(Access::Write { place, nodes, span, borrowed: false });
+                    }
+                    None => self.lower_expr(assignment.target()),
+                }
+            }
+            Expr::UnaryOperation(UnaryOperator::AddressOfMut, operand) => {
+                match self.place(operand) {
+                    Some((place, nodes)) => {
+                        self.access(Access::Write { place, nodes, span: operand.span().clone(), borrowed: true });
+                    }
+                    None => self.lower_expr(operand),
+                }
+            }
+            Expr::ProcedureCall(call) => {
+                expr.for_each_child(&mut |child| self.lower_expr(child));
+
+                // Calling a lambda reads the variable that holds it
+                let variable = match call.name().is_ident() {
+                    true => self.lookup(call.name().last().name()),
+                    false => None,
+                };
+                if let Some(variable) = variable {
+                    let place = Place { variable, fields: vec![] };
+                    self.access(Access::Read { place, nodes: vec![expr.id()], span: expr.span().clone() });
+                }
+            }
+            Expr::Identifier(_) | Expr::MemberFieldAccess(_) => {
+                match self.place(expr) {
+                    Some((place, nodes)) => {
+                        self.access(Access::Read { place, nodes, span: expr.span().clone() });
+                    }
+                    None => expr.for_each_child(&mut |child| self.lower_expr(child)),
+                }
+            }
+            Expr::If(conditional) => {
+                self.lower_if(conditional, true);
+            }
+            Expr::CodeBlock(block) => {
+                self.lower_value_block(block);
+            }
+            Expr::WhileLoop(whileloop) => {
+                self.lower_while(whileloop, expr.span());
+            }
+
+            // Lambdas get graphs of their own, but the variables they
+            // capture are read where the lambda is created
+            Expr::ProcedureDefinition(def) => {
+                self.lower_captures(def);
+            }
+
+            // Procedures and methods get graphs of their own
+            Expr::StructDef(_) | Expr::ImplDef(_) => {}
+
+            _ => {
+                expr.for_each_child(&mut |child| self.lower_expr(child));
+            }
+        }
+    }
+
+    /// Read every variable that a lambda uses from the procedure it is in
+    fn lower_captures(&mut self, def: &ProcedureDef) {
+        let mut locals: HashSet<Ident> = def.parameters().iter()
+            .map(|param| param.ident().clone())
+            .collect();
+
+        let mut captures = vec![];
+        collect_captures(def.body(), &mut locals, &mut captures);
+
+        for (name, node, span) in captures {
+            if let Some(variable) = self.lookup(&name) {
+                let place = Place { variable, fields: vec![] };
+                self.access(Access::Read { place, nodes: vec![node], span });
+            }
+        }
+    }
+
+    /// Add a block used as a value.
+    /// Its `yield`s and its end all lead to the block after it
+    fn lower_value_block(&mut self, block: &CodeBlock) {
+        let after = self.new_block();
+
+        self.yield_targets.push(after);
+        self.lower_sequence(block.exprs());
+        self.yield_targets.pop();
+
+        self.edge(self.current, after);
+        self.current = after;
+    }
+
+    /// Add an `if` and all of the branches that follow it.
+    /// The branches are blocks used as values when `value` is set
+    fn lower_if(&mut self, conditional: &Conditional, value: bool) {
+        let condition = match conditional.condition() {
+            Some(condition) => condition,
+
+            // `else` always runs its body
+            None => {
+                self.lower_branch(conditional.body(), value);
+                return;
+            }
+        };
+
+        // The variable of an `if let` is only in scope for the body
+        self.scopes.push(HashMap::new());
+        self.lower_expr(&condition);
+        let start = self.current;
+
+        let body = self.new_block();
+        self.edge(start, body);
+        self.current = body;
+        self.lower_branch(conditional.body(), value);
+        let body_end = self.current;
+        self.scopes.pop();
+
+        // Without an `else`, control goes straight past the `if`
+        let else_end = match conditional.else_clause() {
+            Some(else_clause) => {
+                let else_block = self.new_block();
+                self.edge(start, else_block);
+                self.current = else_block;
+
+                match else_clause.inner() {
+                    Expr::If(conditional) => self.lower_if(conditional, value),
+                    _ => self.lower_branch(&else_clause, value),
+                }
+                self.current
+            }
+            None => start,
+        };
+
+        let join = self.new_block();
+        self.edge(body_end, join);
+        self.edge(else_end, join);
+        self.current = join;
+    }
+
+    /// Add a `while` loop.
+    /// A loop whose condition is `true` can only be left from its body
+    fn lower_while(&mut self, whileloop: &WhileLoop, span: &Span) {
+        let header = self.new_block();
+        self.edge(self.current, header);
+        self.current = header;
+        self.lower_expr(whileloop.condition());
+        let condition_end = self.current;
+
+        let after = self.new_block();
+        let body = self.new_block();
+        self.edge(condition_end, body);
+        if !matches!(whileloop.condition().inner(), Expr::True) {
+            self.edge(condition_end, after);
+        }
+
+        self.loops.push((header, after));
+        self.current = body;
+        self.lower_branch(whileloop.body(), false);
+        self.edge(self.current, header);
+        self.loops.pop();
+
+        self.loop_infos.push(LoopInfo {
+            span: span.clone(),
+            header,
+            after,
+            body: body..self.blocks.len(),
+        });
+        self.current = after;
+    }
+}
+
+/// Find the identifiers in the body of a lambda that are not declared in it.
+/// `locals` holds the names that the lambda declares itself
+fn collect_captures(expr: &ExprNode, locals: &mut HashSet<Ident>, captures: &mut Vec<(Ident, NodeId, Span)>) {
+    match expr.inner() {
+        Expr::Identifier(name) => {
+            if !locals.contains(name) {
+                captures.push((name.clone(), expr.id(), expr.span().clone()));
+            }
+        }
+        Expr::ProcedureCall(call) => {
+            let name = call.name().last().name();
+            if call.name().is_ident() && !locals.contains(name) {
+                captures.push((name.to_string(), expr.id(), expr.span().clone()));
+            }
+            expr.for_each_child(&mut |child| collect_captures(child, locals, captures));
+        }
+        Expr::Let(init) => {
+            for value in init.values() {
+                collect_captures(value, locals, captures);
+            }
+            for binding in init.bindings() {
+                locals.insert(binding.name().clone());
+            }
+        }
+        Expr::ProcedureDefinition(def) => {
+            let mut inner = locals.clone();
+            inner.extend(def.parameters().iter().map(|param| param.ident().clone()));
+            collect_captures(def.body(), &mut inner, captures);
+        }
+        _ => {
+            expr.for_each_child(&mut |child| collect_captures(child, locals, captures));
+        }
+    }
+}
+
+/// Builds the control-flow graph of every procedure, method and lambda,
+/// reports missing returns, unreachable statements, infinite loops and
+/// unused variables, and checks that variables are assigned before they are read
+pub struct ControlFlowChecker<'a> {
+    emitter: &'a mut dyn Emitter,
+
+    /// Every struct defined in the module
+    structs: &'a HashMap<Ident, StructDef>,
+
+    /// Types of the checked expressions
+    types: &'a TypeInterner,
+    table: &'a TypeTable,
+}
+
+impl<'a> ControlFlowChecker<'a> {
+    /// Create a new [ControlFlowChecker].
+    /// This runs after type checking, which fills in `table`
+    pub fn new(
+        emitter: &'a mut dyn Emitter,
+        structs: &'a HashMap<Ident, StructDef>,
+        types: &'a TypeInterner,
+        table: &'a TypeTable,
+    ) -> ControlFlowChecker<'a> {
+        ControlFlowChecker {
+            emitter,
+            structs,
+            types,
+            table,
+        }
+    }
+
+    /// Check the top-level items of a file
+    pub fn check_items(&mut self, items: &[ExprNode]) {
+        for item in items {
+            self.check_expr(item);
+        }
+    }
+
+    /// Find every procedure within an expression, including lambdas
+    fn check_expr(&mut self, expr: &ExprNode) {
+        match expr.inner() {
+            Expr::ProcedureDefinition(def) => {
+                self.check_body(&def.name(), def.parameters(), def.ret(), def.body(), def.span());
+            }
+            Expr::StructDef(def) => {
+                for method in def.methods() {
+                    let params: Vec<Binding> = def.receiver(method).into_iter()
+                        .chain(method.parameters().iter().cloned())
+                        .collect();
+                    self.check_body(method.name(), &params, method.ret(), method.body(), method.span());
+                }
+            }
+            Expr::ImplDef(def) => {
+                for method in def.methods() {
+                    let params: Vec<Binding> = def.receiver(method).into_iter()
+                        .chain(method.parameters().iter().cloned())
+                        .collect();
+                    self.check_body(method.name(), &params, method.ret(), method.body(), method.span());
+                }
+            }
+            _ => {}
+        }
+
+        expr.for_each_child(&mut |child| self.check_expr(child));
+    }
+
+    fn check_body(&mut self, name: &str, params: &[Binding], ret: &Type, body: &ExprNode, span: &Span) {
+        let cfg = ControlFlowGraph::build(params, body);
+
+        for (keyword, span) in cfg.stray_jumps() {
+            self.emitter.emit_err(ViperError::OutsideOfLoop {
+                keyword: keyword.clone(),
+                span: span.clone(),
+            });
+        }
+
+        for span in cfg.unreachable_statements() {
+            self.emitter.emit_warning(ViperWarning::UnreachableCode { span });
+        }
+
+        for span in cfg.infinite_loops() {
+            self.emitter.emit_warning(ViperWarning::InfiniteLoop { span });
+        }
+
+        if !matches!(ret, Type::Void) && cfg.falls_off_end() {
+            self.emitter.emit_warning(ViperWarning::MissingReturn {
+                name: name.to_string(),
+                span: span.clone(),
+            });
+        }
+
+        InitializationChecker::new(self.emitter, self.structs, self.types, self.table).check(&cfg);
+        lint::check_unused_variables(&cfg, self.emitter);
+    }
+}
@@ -0,0 +1,452 @@
+use std::collections::{HashMap, HashSet};
+
+use viper_ast::{Expr, ExprNode, Ident, NodeId, ProcedureDef, ProcedureKind, StructDef, UnaryOperator};
+use viper_core::{_type::Type, types::{TypeId, TypeInterner, TypeKind}};
+
+use crate::typecheck::TypeTable;
+
+/// How a lambda captures a variable from the procedure it is defined in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaptureKind {
+    /// The value of the variable is copied into the lambda's environment
+    ByValue,
+
+    /// The lambda's environment stores a pointer to the variable.
+    /// Variables that the lambda assigns to or takes the address of are captured this way
+    ByReference,
+}
+
+/// A variable that a lambda captures
+#[derive(Clone, Debug, PartialEq)]
+pub struct Capture {
+    name: Ident,
+    ty: TypeId,
+    kind: CaptureKind,
+}
+
+impl Capture {
+    /// Get the name of the captured variable
+    pub fn name(&self) -> &Ident {
+        &self.name
+    }
+
+    /// Get the type of the captured variable, as the type checker worked it out
+    pub fn ty(&self) -> TypeId {
+        self.ty
+    }
+
+    /// Get how the variable is captured
+    pub fn kind(&self) -> CaptureKind {
+        self.kind
+    }
+}
+
+/// The variables captured by a single lambda.
+///
+/// A lambda gets lowered to a plain procedure plus an environment struct
+/// holding its captures:
+/// `
+/// struct lambda0_env {
+///     a: i32,         // captured by value
+///     b: *mut i32,    // captured by reference
+/// }
+///
+/// define lambda0(env: *mut lambda0_env, params...): ret { ... }
+/// `
+#[derive(Clone, Debug, PartialEq)]
+pub struct Closure {
+    name: Ident,
+    captures: Vec<Capture>,
+
+    /// The fields of the environment struct
+    env_fields: Vec<(Ident, TypeId)>,
+
+    /// The type of the procedure that the lambda gets lowered to.
+    /// None if the type of the lambda is not known
+    signature: Option<TypeId>,
+}
+
+impl Closure {
+    /// Get the name of the lambda
+    pub fn name(&self) -> &Ident {
+        &self.name
+    }
+
+    /// Get the variables that the lambda captures
+    pub fn captures(&self) -> &Vec<Capture> {
+        &self.captures
+    }
+
+    /// Name of the environment struct for the lambda
+    pub fn env_name(&self) -> String {
+        format!("{}_env", self.name)
+    }
+
+    /// The fields of the environment struct.
+    /// By-reference captures are stored as pointers to the variable
+    pub fn env_fields(&self) -> &[(Ident, TypeId)] {
+        &self.env_fields
+    }
+
+    /// The type of the procedure that the lambda gets lowered to.
+    /// It takes a pointer to its environment before its own parameters
+    pub fn lowered_signature(&self) -> Option<TypeId> {
+        self.signature
+    }
+}
+
+/// Finds every lambda within a procedure and determines
+/// which of the procedure's variables each one captures.
+///
+/// This runs after type checking, so that variables whose type
+/// was inferred are captured with the type that was worked out
+pub struct CaptureAnalyzer<'a> {
+    /// Every struct defined in the module, used to find the methods that mutate their receiver
+    structs: &'a HashMap<Ident, StructDef>,
+
+    /// Every semantic type in the module
+    types: &'a mut TypeInterner,
+
+    /// The types of the expressions and variables of the module
+    table: &'a TypeTable,
+
+    /// Local variables visible at the current point, innermost scope last.
+    /// Variables whose type is not known have none
+    scopes: Vec<HashMap<Ident, Option<TypeId>>>,
+
+    /// The lambdas found so far, outermost first
+    closures: Vec<Closure>,
+}
+
+impl<'a> CaptureAnalyzer<'a> {
+    /// Find the captures of every lambda defined within `procedure`
+    pub fn analyze(
+        procedure: &ExprNode,
+        structs: &'a HashMap<Ident, StructDef>,
+        types: &'a mut TypeInterner,
+        table: &'a TypeTable,
+    ) -> Vec<Closure> {
+        let mut analyzer = CaptureAnalyzer {
+            structs,
+            types,
+            table,
+            scopes: vec![],
+            closures: vec![],
+        };
+
+        if let Expr::ProcedureDefinition(def) = procedure.inner() {
+            analyzer.visit_procedure(procedure.id(), def);
+        }
+
+        return analyzer.closures;
+    }
+
+    /// Visit the body of a procedure with its parameters in scope
+    fn visit_procedure(&mut self, id: NodeId, procedure: &ProcedureDef) {
+        let types = self.params(id);
+        let params = procedure.parameters().iter().enumerate()
+            .map(|(i, p)| (p.ident().clone(), types.as_ref().map(|types| types[i])))
+            .collect();
+
+        self.scopes.push(params);
+        self.visit(procedure.body());
+        self.scopes.pop();
+    }
+
+    /// Get the parameter types of a procedure from its type
+    fn params(&self, id: NodeId) -> Option<Vec<TypeId>> {
+        match self.types.kind(self.table.get(id)?) {
+            TypeKind::Function { params, ret: _ } => Some(params.clone()),
+            _ => None,
+        }
+    }
+
+    fn visit(&mut self, expr: &ExprNode) {
+        match expr.inner() {
+            Expr::CodeBlock(block) => {
+                self.scopes.push(HashMap::new());
+                for expr in block.exprs() {
+                    self.visit(expr);
+                }
+                self.scopes.pop();
+            }
+            Expr::If(conditional) => {
+                self.scopes.push(HashMap::new());
+                if let Some(condition) = conditional.condition() {
+                    self.visit(&condition);
+                }
+                self.visit(conditional.body());
+                self.scopes.pop();
+
+                if let Some(else_clause) = conditional.else_clause() {
+                    self.visit(&else_clause);
+                }
+            }
+            Expr::Let(init) => {
+                for value in init.values() {
+                    self.visit(value);
+                }
+
+                if let Some(scope) = self.scopes.last_mut() {
+                    for binding in init.bindings() {
+                        scope.insert(binding.name().clone(), self.table.get(binding.id()));
+                    }
+                }
+            }
+            Expr::ProcedureDefinition(def) => {
+                // Nested top-level procedures cannot capture anything
+                if def.kind() == &ProcedureKind::Lambda {
+                    self.visit_lambda(expr.id(), def);
+                }
+            }
+            _ => {
+                expr.for_each_child(&mut |child| self.visit(child));
+            }
+        }
+    }
+
+    fn visit_lambda(&mut self, id: NodeId, lambda: &ProcedureDef) {
+        let mut free = FreeVariables::new(self.structs, self.types, &self.scopes);
+        free.visit_procedure(lambda);
+
+        let captures: Vec<Capture> = free.uses.iter()
+            .filter_map(|name| {
+                self.lookup(name).map(|ty| Capture {
+                    name: name.clone(),
+                    ty,
+                    kind: match free.by_reference.contains(name) {
+                        true => CaptureKind::ByReference,
+                        false => CaptureKind::ByValue,
+                    },
+                })
+            })
+            .collect();
+
+        let env_fields = captures.iter()
+            .map(|capture| {
+                let ty = match capture.kind {
+                    CaptureKind::ByValue => capture.ty,
+                    CaptureKind::ByReference => self.types.pointer(capture.ty, true),
+                };
+                (capture.name.clone(), ty)
+            })
+            .collect();
+
+        let signature = self.table.get(id).and_then(|ty| match self.types.kind(ty).clone() {
+            TypeKind::Function { params, ret } => Some((params, ret)),
+            _ => None,
+        });
+        let signature = signature.map(|(params, ret)| {
+            let env = self.types.intern(TypeKind::Struct(format!("{}_env", lambda.name()), vec![]));
+            let env = self.types.pointer(env, true);
+            let params = std::iter::once(env).chain(params).collect();
+            self.types.function(params, ret)
+        });
+
+        self.closures.push(Closure {
+            name: lambda.name(),
+            captures,
+            env_fields,
+            signature,
+        });
+
+        // Lambdas nested in this one can capture its variables too
+        self.visit_procedure(id, lambda);
+    }
+
+    /// Look up the type of a local variable in the enclosing scopes.
+    /// None if there is no such variable, or its type is not known
+    fn lookup(&self, name: &Ident) -> Option<TypeId> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name)).copied().flatten()
+    }
+}
+
+/// Collects the variables that a procedure uses but does not declare itself
+struct FreeVariables<'a> {
+    /// Every struct defined in the module
+    structs: &'a HashMap<Ident, StructDef>,
+
+    /// Every semantic type in the module
+    types: &'a TypeInterner,
+
+    /// Variables of the enclosing procedures that can be captured, with their types
+    outer: &'a [HashMap<Ident, Option<TypeId>>],
+
+    /// Variables declared within the procedure, innermost scope last
+    scopes: Vec<HashSet<Ident>>,
+
+    /// Free variables in the order that they are first used
+    uses: Vec<Ident>,
+
+    /// Free variables that are assigned to or have their address taken
+    by_reference: HashSet<Ident>,
+}
+
+impl<'a> FreeVariables<'a> {
+    fn new(
+        structs: &'a HashMap<Ident, StructDef>,
+        types: &'a TypeInterner,
+        outer: &'a [HashMap<Ident, Option<TypeId>>],
+    ) -> FreeVariables<'a> {
+        FreeVariables {
+            structs,
+            types,
+            outer,
+            scopes: vec![],
+            uses: vec![],
+            by_reference: HashSet::new(),
+        }
+    }
+
+    fn visit_procedure(&mut self, procedure: &ProcedureDef) {
+        self.scopes.push(procedure.parameters().iter().map(|p| p.ident().clone()).collect());
+        self.visit(procedure.body());
+        self.scopes.pop();
+    }
+
+    fn visit(&mut self, expr: &ExprNode) {
+        match expr.inner() {
+            Expr::Identifier(name) => {
+                self.use_variable(name);
+            }
+            Expr::Assignment(assignment) => {
+                // Assigning to a captured variable has to
+                // change the variable itself, not a copy
+                self.visit_place(assignment.target());
+                self.visit(assignment.value());
+            }
+            Expr::UnaryOperation(UnaryOperator::AddressOf | UnaryOperator::AddressOfMut, operand) => {
+                self.visit_place(operand);
+            }
+            Expr::ProcedureCall(call) => {
+                // Calling a local variable that holds a procedure
+                if call.name().is_ident() {
+                    self.use_variable(&call.name().last().name().to_string());
+                }
+
+                for arg in call.arguments() {
+                    self.visit(arg);
+                }
+            }
+            Expr::MethodCall(call) => {
+                // A `mut method` changes its receiver, so it has to
+                // be called on the variable itself and not on a copy
+                let mutating = self.place_struct(call.receiver())
+                    .and_then(|def| def.methods().iter().find(|m| m.name() == call.name()))
+                    .map(|method| method.is_mutating())
+                    .unwrap_or(false);
+
+                match mutating {
+                    true => self.visit_place(call.receiver()),
+                    false => self.visit(call.receiver()),
+                }
+
+                for arg in call.arguments() {
+                    self.visit(arg);
+                }
+            }
+            Expr::CodeBlock(block) => {
+                self.scopes.push(HashSet::new());
+                for expr in block.exprs() {
+                    self.visit(expr);
+                }
+                self.scopes.pop();
+            }
+            Expr::If(conditional) => {
+                self.scopes.push(HashSet::new());
+                if let Some(condition) = conditional.condition() {
+                    self.visit(&condition);
+                }
+                self.visit(conditional.body());
+                self.scopes.pop();
+
+                if let Some(else_clause) = conditional.else_clause() {
+                    self.visit(&else_clause);
+                }
+            }
+            Expr::Let(init) => {
+                for value in init.values() {
+                    self.visit(value);
+                }
+
+                if let Some(scope) = self.scopes.last_mut() {
+                    for binding in init.bindings() {
+                        scope.insert(binding.name().clone());
+                    }
+                }
+            }
+            Expr::ProcedureDefinition(def) => {
+                if def.kind() == &ProcedureKind::Lambda {
+                    self.visit_procedure(def);
+                }
+            }
+            _ => {
+                expr.for_each_child(&mut |child| self.visit(child));
+            }
+        }
+    }
+
+    /// Get the struct that a place holds, if it can be worked out from
+    /// the type of the captured variable and the declared types of the fields
+    /// `counter`, `game.counter`
+    fn place_struct(&self, place: &ExprNode) -> Option<&'a StructDef> {
+        let name = match place.inner() {
+            Expr::Identifier(name) => {
+                if self.scopes.iter().any(|scope| scope.contains(name)) {
+                    return None;
+                }
+
+                let ty = self.outer.iter().rev().find_map(|scope| scope.get(name)).copied().flatten()?;
+                match self.types.kind(ty) {
+                    TypeKind::Struct(name, _) => name.clone(),
+                    _ => return None,
+                }
+            }
+            Expr::MemberFieldAccess(field) => {
+                let def = self.place_struct(field.object())?;
+                match def.fields().iter().find(|f| f.binding().ident() == field.name())?.binding().ty() {
+                    Type::Concrete { name, .. } => name.to_string(),
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        };
+
+        return self.structs.get(&name);
+    }
+
+    /// Visit a place that is assigned to or has its address taken.
+    /// The variable that owns the place is captured by reference
+    /// `x`, `point.x`, `values[i]`
+    fn visit_place(&mut self, place: &ExprNode) {
+        match place.inner() {
+            Expr::Identifier(name) => {
+                if self.use_variable(name) {
+                    self.by_reference.insert(name.clone());
+                }
+            }
+            Expr::MemberFieldAccess(field) => {
+                self.visit_place(field.object());
+            }
+            Expr::Index(object, index) => {
+                self.visit_place(object);
+                self.visit(index);
+            }
+            _ => self.visit(place),
+        }
+    }
+
+    /// Record the use of a variable.
+    /// Returns whether the variable is free
+    fn use_variable(&mut self, name: &Ident) -> bool {
+        if self.scopes.iter().any(|scope| scope.contains(name)) {
+            return false;
+        }
+
+        if !self.uses.contains(name) {
+            self.uses.push(name.clone());
+        }
+
+        return true;
+    }
+}
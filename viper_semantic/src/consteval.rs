@@ -0,0 +1,703 @@
+use std::{cell::RefCell, collections::{HashMap, HashSet}, fmt::Display, sync::Arc};
+
+use viper_ast::{BinaryOperator, Binding, ConstDef, Expr, ExprNode, Ident, NodeId, UnaryOperator};
+use viper_core::{
+    emitter::Emitter,
+    error::ViperError,
+    scope::Scope,
+    span::Span,
+    types::{Sign, Size, TypeId, TypeInterner, TypeKind},
+};
+
+use crate::typecheck::{TypeTable, DEFAULT_INTEGER};
+
+/// The value of a constant expression
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConstValue {
+    /// Integers of every type, which always fit in an `i128`
+    Integer(i128),
+    Float(f64),
+    Bool(bool),
+    String(String),
+}
+
+impl Display for ConstValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Integer(value) => write!(f, "{value}"),
+            Self::Float(value) => write!(f, "{value}"),
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::String(value) => write!(f, "\"{value}\""),
+        }
+    }
+}
+
+/// Every `const` of a module, along with its value once it has been evaluated.
+///
+/// Top-level constants are keyed by their name and the constants of
+/// a struct by their path, `SIZE` and `Buffer::SIZE`.
+/// Constants holding a non-negative integer are given to the
+/// [TypeInterner] so that array types can use them as their length.
+#[derive(Default)]
+pub struct ConstTable {
+    defs: HashMap<String, ConstDef>,
+
+    /// The names of the constants in the order they were declared,
+    /// so that errors come out in the same order every time
+    order: Vec<String>,
+
+    /// The value of each constant that has been evaluated.
+    /// None when its value could not be worked out
+    values: HashMap<String, Option<ConstValue>>,
+}
+
+impl ConstTable {
+    /// Create a new [ConstTable] with no constants
+    pub fn new() -> ConstTable {
+        ConstTable::default()
+    }
+
+    /// Declare the constants in the top-level items of a file
+    pub fn declare(&mut self, items: &[ExprNode]) {
+        for item in items {
+            match item.inner() {
+                Expr::Const(def) => {
+                    self.insert(def.name().clone(), def);
+                }
+                Expr::StructDef(def) => {
+                    for constant in def.consts() {
+                        self.insert(format!("{}::{}", def.identifier(), constant.name()), constant);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn insert(&mut self, name: String, def: &ConstDef) {
+        // Duplicates are reported by the name resolver
+        if self.defs.contains_key(&name) {
+            return;
+        }
+
+        self.order.push(name.clone());
+        self.defs.insert(name, def.clone());
+    }
+
+    /// Whether there is a constant with this name
+    pub fn contains(&self, name: &str) -> bool {
+        return self.defs.contains_key(name);
+    }
+
+    /// Get the value of a constant.
+    /// None if it has not been evaluated or its value could not be worked out
+    pub fn value(&self, name: &str) -> Option<&ConstValue> {
+        return self.values.get(name)?.as_ref();
+    }
+
+    /// Evaluate every constant that has not been evaluated yet.
+    ///
+    /// `scope` is the scope of the module, used to tell names
+    /// that are not constants apart from names that do not exist
+    pub fn evaluate(&mut self, emitter: &mut dyn Emitter, types: &mut TypeInterner, scope: &Arc<RefCell<Scope>>) {
+        let mut evaluator = ConstEvaluator {
+            emitter,
+            types,
+            scope,
+            defs: &self.defs,
+            values: &mut self.values,
+            evaluating: vec![],
+            table: None,
+            locals: vec![],
+            bounds_checks: vec![],
+        };
+
+        for name in &self.order {
+            let span = self.defs[name].span().clone();
+            evaluator.constant(name, &span);
+        }
+
+        for name in &self.order {
+            if let Some(Some(ConstValue::Integer(value))) = self.values.get(name) {
+                if let Ok(length) = usize::try_from(*value) {
+                    types.define_length(name, length);
+                }
+            }
+        }
+    }
+}
+
+/// Finds arithmetic in procedures and variables that is known to go wrong
+/// while compiling, when it has operands that are constants or literals.
+///
+/// Reported are
+///  - values that overflow their type, `let x: u8 = 255 + 1;`
+///  - division and modulo by zero, `x / 0`
+///  - shifts by at least the width of the type, `x << 70`
+///  - constant indices past the end of an array, `values[3]` of a `[i32; 3]`
+///
+/// Indices that are only known at runtime are collected instead, so
+/// that the backend can check them against the length of the array.
+///
+/// Constants themselves are checked when they are evaluated.
+/// This runs after type checking, which gives the literals their types
+pub struct ArithmeticChecker<'a> {
+    evaluator: ConstEvaluator<'a>,
+}
+
+impl<'a> ArithmeticChecker<'a> {
+    /// Create a new [ArithmeticChecker].
+    /// The constants in `consts` must already have been evaluated
+    pub fn new(
+        emitter: &'a mut dyn Emitter,
+        types: &'a mut TypeInterner,
+        scope: &'a Arc<RefCell<Scope>>,
+        consts: &'a mut ConstTable,
+        table: &'a TypeTable,
+    ) -> ArithmeticChecker<'a> {
+        ArithmeticChecker {
+            evaluator: ConstEvaluator {
+                emitter,
+                types,
+                scope,
+                defs: &consts.defs,
+                values: &mut consts.values,
+                evaluating: vec![],
+                table: Some(table),
+                locals: vec![],
+                bounds_checks: vec![],
+            },
+        }
+    }
+
+    /// Check the top-level items of a file
+    pub fn check_items(&mut self, items: &[ExprNode]) {
+        for item in items {
+            self.evaluator.visit(item);
+        }
+    }
+
+    /// Get the index expressions whose index is only known at runtime,
+    /// in the order they were found
+    pub fn bounds_checks(&self) -> &[NodeId] {
+        return &self.evaluator.bounds_checks;
+    }
+}
+
+/// Works out the values of constant expressions.
+///
+/// Each expression is evaluated as the type that it is expected to
+/// have, so that `const X: u8 = 200 + 100;` is reported as overflowing
+/// `u8`. Operands that do not fit together are skipped over without
+/// an error, since the type checker reports them.
+struct ConstEvaluator<'a> {
+    emitter: &'a mut dyn Emitter,
+    types: &'a mut TypeInterner,
+    scope: &'a Arc<RefCell<Scope>>,
+
+    defs: &'a HashMap<String, ConstDef>,
+    values: &'a mut HashMap<String, Option<ConstValue>>,
+
+    /// The constants whose values are being worked out, innermost last.
+    /// Used to find constants that depend on themselves
+    evaluating: Vec<String>,
+
+    /// The types of checked expressions, when evaluating outside of constants.
+    /// Expressions then have the type that the type checker gave them, and
+    /// anything that is not constant is searched for constant parts
+    /// instead of being reported
+    table: Option<&'a TypeTable>,
+
+    /// The local variables in scope, innermost last.
+    /// They hide the constants with the same name
+    locals: Vec<HashSet<Ident>>,
+
+    /// Indexing into arrays and slices that can only be checked at runtime
+    bounds_checks: Vec<NodeId>,
+}
+
+impl ConstEvaluator<'_> {
+    /// Get the value of a constant, evaluating it if it has not been yet
+    fn constant(&mut self, name: &str, span: &Span) -> Option<ConstValue> {
+        if let Some(value) = self.values.get(name) {
+            return value.clone();
+        }
+
+        if self.evaluating.iter().any(|n| n == name) {
+            self.emitter.emit_err(ViperError::ConstCycle {
+                name: name.to_string(),
+                span: span.clone(),
+            });
+            return None;
+        }
+
+        let def = self.defs.get(name)?;
+        self.evaluating.push(name.to_string());
+        let ty = self.types.lower(def.ty());
+        let value = self.eval(def.value(), ty);
+        self.evaluating.pop();
+
+        self.values.insert(name.to_string(), value.clone());
+        return value;
+    }
+
+    /// Evaluate the arithmetic within an expression that is not constant.
+    /// Constants are skipped, as their values have already been checked
+    fn visit(&mut self, expr: &ExprNode) {
+        match expr.inner() {
+            Expr::Integer(_)
+            | Expr::UnaryOperation(_, _)
+            | Expr::BinaryOperation(_, _, _)
+            | Expr::Cast(_, _) => {
+                self.eval(expr, None);
+            }
+            Expr::Const(_) => {}
+            Expr::Index(object, index) => {
+                self.visit(object);
+                self.index(expr.id(), object, index, expr.span());
+            }
+            Expr::Let(init) => {
+                for value in init.values() {
+                    self.visit(value);
+                }
+                if let Some(locals) = self.locals.last_mut() {
+                    for binding in init.bindings() {
+                        locals.insert(binding.name().clone());
+                    }
+                }
+            }
+            Expr::CodeBlock(block) => {
+                self.locals.push(HashSet::new());
+                for expr in block.exprs() {
+                    self.visit(expr);
+                }
+                self.locals.pop();
+            }
+            Expr::If(conditional) => {
+                self.locals.push(HashSet::new());
+                if let Some(condition) = conditional.condition() {
+                    self.visit(&condition);
+                }
+                self.visit(conditional.body());
+                self.locals.pop();
+
+                if let Some(else_clause) = conditional.else_clause() {
+                    self.visit(&else_clause);
+                }
+            }
+            Expr::ProcedureDefinition(def) => {
+                self.visit_body(def.parameters().iter(), def.body());
+            }
+            Expr::StructDef(def) => {
+                for method in def.methods() {
+                    let receiver = def.receiver(method);
+                    self.visit_body(receiver.iter().chain(method.parameters()), method.body());
+                }
+            }
+            Expr::ImplDef(def) => {
+                for method in def.methods() {
+                    let receiver = def.receiver(method);
+                    self.visit_body(receiver.iter().chain(method.parameters()), method.body());
+                }
+            }
+            _ => {
+                expr.for_each_child(&mut |child| self.visit(child));
+            }
+        }
+    }
+
+    /// Check an index into an array against the array's length when the index is constant.
+    /// Indices into slices and indices that are not constant are checked at runtime
+    fn index(&mut self, id: NodeId, object: &ExprNode, index: &ExprNode, span: &Span) {
+        // Overloaded indexing calls a method, and anything else
+        // that can not be indexed was reported by the type checker
+        let ty = self.table.filter(|table| table.operator(id).is_none()).and_then(|table| table.get(object.id()));
+        let length = match ty.map(|ty| self.types.kind(ty)) {
+            Some(TypeKind::Array { element: _, length }) => Some(*length),
+            Some(TypeKind::Slice(_)) => None,
+            _ => {
+                self.visit(index);
+                return;
+            }
+        };
+
+        match (self.eval(index, None), length) {
+            (Some(ConstValue::Integer(value)), Some(length)) => {
+                if value < 0 || value >= length as i128 {
+                    self.emitter.emit_err(ViperError::IndexOutOfBounds {
+                        index: value,
+                        length,
+                        span: span.clone(),
+                    });
+                }
+            }
+            _ => self.bounds_checks.push(id),
+        }
+    }
+
+    /// Visit the body of a procedure with its parameters in scope
+    fn visit_body<'b>(&mut self, params: impl Iterator<Item = &'b Binding>, body: &ExprNode) {
+        self.locals.push(params.map(|param| param.ident().clone()).collect());
+        self.visit(body);
+        self.locals.pop();
+    }
+
+    /// Evaluate an expression that is expected to have the type `ty`.
+    /// Integers are only checked for overflow when the type is known
+    fn eval(&mut self, expr: &ExprNode, ty: Option<TypeId>) -> Option<ConstValue> {
+        let ty = match self.table {
+            Some(table) => table.get(expr.id()),
+            None => ty,
+        };
+
+        let span = expr.span();
+        match expr.inner() {
+            Expr::True => Some(ConstValue::Bool(true)),
+            Expr::False => Some(ConstValue::Bool(false)),
+            Expr::Integer(value) => self.integer(*value as i128, ty, span),
+            Expr::Float(value) => Some(ConstValue::Float(*value)),
+            Expr::StringLiteral(value) => Some(ConstValue::String(value.clone())),
+            Expr::Identifier(name) => self.named(name, name, expr),
+            Expr::Path(path) => self.named(&path.to_string(), path.segments()[0].name(), expr),
+            Expr::UnaryOperation(op, operand) => self.unary(*op, operand, ty, expr),
+            Expr::BinaryOperation(op, lhs, rhs) => self.binary(*op, lhs, rhs, ty, span),
+            Expr::Cast(value, to) => {
+                let value = self.eval(value, None);
+                let to = self.types.lower(to)?;
+                self.cast(value?, to, expr)
+            }
+            _ => self.not_constant(expr),
+        }
+    }
+
+    /// Get the value of a name, which has to be a constant.
+    /// `first` is the first segment of the name, used to
+    /// find out whether the name exists at all
+    fn named(&mut self, name: &str, first: &str, expr: &ExprNode) -> Option<ConstValue> {
+        if self.locals.iter().any(|locals| locals.contains(first)) {
+            return None;
+        }
+
+        if self.defs.contains_key(name) {
+            return self.constant(name, expr.span());
+        }
+
+        // Names that cannot be found are reported by the name resolver
+        let exists = self.scope.borrow().lookup(first).is_some();
+        match exists {
+            true => self.not_constant(expr),
+            false => None,
+        }
+    }
+
+    fn unary(&mut self, op: UnaryOperator, operand: &ExprNode, ty: Option<TypeId>, expr: &ExprNode) -> Option<ConstValue> {
+        let span = expr.span();
+
+        // A negative literal is checked as a whole,
+        // so `-128` fits in an `i8` even though `128` does not
+        if let (UnaryOperator::Minus, Expr::Integer(value)) = (op, operand.inner()) {
+            return self.integer(-(*value as i128), ty, span);
+        }
+
+        let value = match op {
+            UnaryOperator::Minus | UnaryOperator::BinaryNot => self.eval(operand, ty)?,
+            UnaryOperator::LogicalNot => self.eval(operand, None)?,
+            _ => return self.not_constant(expr),
+        };
+
+        match (op, value) {
+            (UnaryOperator::Minus, ConstValue::Integer(value)) => self.integer(-value, ty, span),
+            (UnaryOperator::Minus, ConstValue::Float(value)) => Some(ConstValue::Float(-value)),
+            (UnaryOperator::BinaryNot, ConstValue::Integer(value)) => {
+                match ty.and_then(|ty| self.types.integer(ty)) {
+                    Some((sign, size)) => Some(ConstValue::Integer(wrap(!value, sign, size))),
+                    None => Some(ConstValue::Integer(!value)),
+                }
+            }
+            (UnaryOperator::LogicalNot, ConstValue::Bool(value)) => Some(ConstValue::Bool(!value)),
+            _ => None,
+        }
+    }
+
+    fn binary(
+        &mut self,
+        op: BinaryOperator,
+        lhs: &ExprNode,
+        rhs: &ExprNode,
+        ty: Option<TypeId>,
+        span: &Span,
+    ) -> Option<ConstValue> {
+        match op {
+            BinaryOperator::LogicalAnd | BinaryOperator::LogicalOr => {
+                // The right side is only evaluated when it decides the result
+                let lhs = self.eval(lhs, None);
+                if let Some(ConstValue::Bool(value)) = lhs {
+                    if value == (op == BinaryOperator::LogicalOr) {
+                        return Some(ConstValue::Bool(value));
+                    }
+                }
+
+                let rhs = self.eval(rhs, None);
+                return match (lhs?, rhs?) {
+                    (ConstValue::Bool(_), ConstValue::Bool(value)) => Some(ConstValue::Bool(value)),
+                    _ => None,
+                };
+            }
+            BinaryOperator::LessThan
+            | BinaryOperator::GreaterThan
+            | BinaryOperator::LessThanEqualTo
+            | BinaryOperator::GreaterThanEqualTo
+            | BinaryOperator::EqualTo
+            | BinaryOperator::NotEqualTo => {
+                let lhs = self.eval(lhs, None);
+                let rhs = self.eval(rhs, None);
+                return compare(op, lhs?, rhs?).map(ConstValue::Bool);
+            }
+            BinaryOperator::BShiftLeft | BinaryOperator::BShiftRight => {
+                let lhs = self.eval(lhs, ty);
+                let rhs = self.eval(rhs, None);
+                return self.shift(op, lhs, rhs?, ty, span);
+            }
+            _ => {}
+        }
+
+        let lhs = self.eval(lhs, ty);
+        let rhs = self.eval(rhs, ty);
+
+        // Dividing by zero is wrong whatever is being divided
+        let divides = matches!(op, BinaryOperator::Divide | BinaryOperator::Modulo);
+        if divides && rhs == Some(ConstValue::Integer(0)) {
+            self.emitter.emit_err(ViperError::DivisionByZero {
+                span: span.clone(),
+            });
+            return None;
+        }
+
+        match (lhs?, rhs?) {
+            (ConstValue::Integer(lhs), ConstValue::Integer(rhs)) => {
+                let value = match op {
+                    BinaryOperator::Plus => lhs.checked_add(rhs),
+                    BinaryOperator::Minus => lhs.checked_sub(rhs),
+                    BinaryOperator::Times => lhs.checked_mul(rhs),
+                    BinaryOperator::Divide => lhs.checked_div(rhs),
+                    BinaryOperator::Modulo => lhs.checked_rem(rhs),
+                    BinaryOperator::BinAnd => Some(lhs & rhs),
+                    BinaryOperator::BinOr => Some(lhs | rhs),
+                    BinaryOperator::BinXor => Some(lhs ^ rhs),
+                    _ => return None,
+                };
+
+                match value {
+                    Some(value) => self.integer(value, ty, span),
+                    None => self.overflow(ty, span),
+                }
+            }
+            (ConstValue::Float(lhs), ConstValue::Float(rhs)) => {
+                let value = match op {
+                    BinaryOperator::Plus => lhs + rhs,
+                    BinaryOperator::Minus => lhs - rhs,
+                    BinaryOperator::Times => lhs * rhs,
+                    BinaryOperator::Divide => lhs / rhs,
+                    BinaryOperator::Modulo => lhs % rhs,
+                    _ => return None,
+                };
+                Some(ConstValue::Float(value))
+            }
+            _ => None,
+        }
+    }
+
+    /// Shift an integer of type `ty`.
+    /// Bits shifted out of the left of the type are dropped.
+    /// The amount is checked even when the value being shifted is not constant
+    fn shift(
+        &mut self,
+        op: BinaryOperator,
+        lhs: Option<ConstValue>,
+        rhs: ConstValue,
+        ty: Option<TypeId>,
+        span: &Span,
+    ) -> Option<ConstValue> {
+        // A literal with nothing to take its type from gets the default type
+        let ty = ty.unwrap_or(DEFAULT_INTEGER);
+        let (sign, size) = self.types.integer(ty)?;
+
+        let amount = match rhs {
+            ConstValue::Integer(amount) => amount,
+            _ => return None,
+        };
+        let amount = match u32::try_from(amount) {
+            Ok(amount) if amount < size.bits() => amount,
+            _ => {
+                self.emitter.emit_err(ViperError::ShiftOverflow {
+                    amount,
+                    ty: self.types.display(ty).to_string(),
+                    max: size.bits() - 1,
+                    span: span.clone(),
+                });
+                return None;
+            }
+        };
+
+        let value = match lhs? {
+            ConstValue::Integer(value) => value,
+            _ => return None,
+        };
+        let value = match op {
+            BinaryOperator::BShiftLeft => value << amount,
+            _ => value >> amount,
+        };
+        return Some(ConstValue::Integer(wrap(value, sign, size)));
+    }
+
+    /// Convert a value as `value as to` would
+    fn cast(&mut self, value: ConstValue, to: TypeId, expr: &ExprNode) -> Option<ConstValue> {
+        let int = self.types.integer(to);
+        let float = match self.types.kind(to) {
+            TypeKind::Float(size) => Some(*size),
+            _ => None,
+        };
+
+        match (value, int, float) {
+            // Integers wrap around to fit
+            (ConstValue::Integer(value), Some((sign, size)), _) => {
+                Some(ConstValue::Integer(wrap(value, sign, size)))
+            }
+            // Floats are rounded towards zero and clamped to the range of the type
+            (ConstValue::Float(value), Some((sign, size)), _) => {
+                let (min, max) = range(sign, size);
+                let value = match value.is_nan() {
+                    true => 0,
+                    false => (value as i128).clamp(min, max),
+                };
+                Some(ConstValue::Integer(value))
+            }
+            (ConstValue::Integer(value), _, Some(size)) => Some(ConstValue::Float(round(value as f64, size))),
+            (ConstValue::Float(value), _, Some(size)) => Some(ConstValue::Float(round(value, size))),
+            (ConstValue::Bool(_) | ConstValue::String(_), _, _) => self.not_constant(expr),
+            _ => None,
+        }
+    }
+
+    /// Make an integer of type `ty`, reporting it if it does not fit.
+    /// An integer that is expected to be a float becomes one
+    fn integer(&mut self, value: i128, ty: Option<TypeId>, span: &Span) -> Option<ConstValue> {
+        let ty = match ty {
+            Some(ty) => ty,
+            None => return Some(ConstValue::Integer(value)),
+        };
+
+        if self.types.is_float(ty) {
+            return Some(ConstValue::Float(value as f64));
+        }
+
+        match self.types.integer(ty) {
+            Some((sign, size)) => {
+                let (min, max) = range(sign, size);
+                if value < min || value > max {
+                    return self.overflow(Some(ty), span);
+                }
+                Some(ConstValue::Integer(value))
+            }
+            None => Some(ConstValue::Integer(value)),
+        }
+    }
+
+    fn overflow(&mut self, ty: Option<TypeId>, span: &Span) -> Option<ConstValue> {
+        let ty = match ty {
+            Some(ty) => self.types.display(ty).to_string(),
+            None => "i128".to_string(),
+        };
+
+        self.emitter.emit_err(ViperError::ArithmeticOverflow {
+            ty,
+            span: span.clone(),
+        });
+        return None;
+    }
+
+    /// Give up on an expression that is not constant.
+    /// Constants report it, anywhere else the expressions
+    /// within it are searched for constant parts
+    fn not_constant(&mut self, expr: &ExprNode) -> Option<ConstValue> {
+        match self.table {
+            Some(_) => match expr.inner() {
+                Expr::UnaryOperation(_, _) | Expr::Cast(_, _) => {
+                    expr.for_each_child(&mut |child| self.visit(child));
+                }
+                // Blocks and lambdas bring their own locals into scope
+                _ => self.visit(expr),
+            },
+            None => {
+                self.emitter.emit_err(ViperError::NotConstant {
+                    span: expr.span().clone(),
+                });
+            }
+        }
+
+        return None;
+    }
+}
+
+/// Compare two values, where an integer compared with a float is converted to one
+fn compare(op: BinaryOperator, lhs: ConstValue, rhs: ConstValue) -> Option<bool> {
+    let ordering = match (lhs, rhs) {
+        (ConstValue::Integer(lhs), ConstValue::Integer(rhs)) => lhs.partial_cmp(&rhs),
+        (ConstValue::Integer(lhs), ConstValue::Float(rhs)) => (lhs as f64).partial_cmp(&rhs),
+        (ConstValue::Float(lhs), ConstValue::Integer(rhs)) => lhs.partial_cmp(&(rhs as f64)),
+        (ConstValue::Float(lhs), ConstValue::Float(rhs)) => lhs.partial_cmp(&rhs),
+        (ConstValue::Bool(lhs), ConstValue::Bool(rhs)) if is_equality(op) => lhs.partial_cmp(&rhs),
+        (ConstValue::String(lhs), ConstValue::String(rhs)) if is_equality(op) => lhs.partial_cmp(&rhs),
+        _ => return None,
+    };
+
+    // NaN is not equal to anything, including itself
+    let ordering = match ordering {
+        Some(ordering) => ordering,
+        None => return Some(op == BinaryOperator::NotEqualTo),
+    };
+
+    match op {
+        BinaryOperator::LessThan => Some(ordering.is_lt()),
+        BinaryOperator::GreaterThan => Some(ordering.is_gt()),
+        BinaryOperator::LessThanEqualTo => Some(ordering.is_le()),
+        BinaryOperator::GreaterThanEqualTo => Some(ordering.is_ge()),
+        BinaryOperator::EqualTo => Some(ordering.is_eq()),
+        BinaryOperator::NotEqualTo => Some(ordering.is_ne()),
+        _ => None,
+    }
+}
+
+fn is_equality(op: BinaryOperator) -> bool {
+    return matches!(op, BinaryOperator::EqualTo | BinaryOperator::NotEqualTo);
+}
+
+/// Get the smallest and largest values of an integer type
+/// `i8` -> `(-128, 127)`
+pub fn range(sign: Sign, size: Size) -> (i128, i128) {
+    let bits = size.bits();
+    match sign {
+        Sign::Signed => (-(1 << (bits - 1)), (1 << (bits - 1)) - 1),
+        Sign::Unsigned => (0, (1 << bits) - 1),
+    }
+}
+
+/// Wrap an integer around to fit in an integer type, keeping its low bits
+/// `300` as `u8` -> `44`, `255` as `i8` -> `-1`
+pub fn wrap(value: i128, sign: Sign, size: Size) -> i128 {
+    let bits = size.bits();
+    let value = value & ((1 << bits) - 1);
+
+    match sign {
+        Sign::Signed if value >= 1 << (bits - 1) => value - (1 << bits),
+        _ => value,
+    }
+}
+
+/// Round a float to the precision of a float type
+fn round(value: f64, size: Size) -> f64 {
+    match size {
+        Size::S32 => value as f32 as f64,
+        _ => value,
+    }
+}
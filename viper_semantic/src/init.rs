@@ -0,0 +1,264 @@
+use std::collections::{HashMap, HashSet};
+
+use viper_ast::{Ident, NodeId, StructDef};
+use viper_core::{
+    _type::Type,
+    emitter::Emitter,
+    error::{UninitializedVariable, ViperError},
+    span::Span,
+    types::{TypeId, TypeInterner, TypeKind},
+};
+
+use crate::{
+    cfg::{Access, ControlFlowGraph, Place},
+    typecheck::TypeTable,
+};
+
+/// The places that have a value at a point in a procedure
+#[derive(Clone, Default, PartialEq)]
+struct State {
+    /// Places that have a value on every path to the point
+    must: HashSet<Place>,
+
+    /// Places that have a value on at least one path to the point
+    may: HashSet<Place>,
+}
+
+impl State {
+    /// Combine with the state at the end of another path to the same point
+    fn join(&mut self, other: &State) {
+        self.must.retain(|place| other.must.contains(place));
+        self.may.extend(other.may.iter().cloned());
+    }
+
+    fn assign(&mut self, place: Place) {
+        self.must.insert(place.clone());
+        self.may.insert(place);
+    }
+}
+
+/// Checks that variables declared without a value, `let x: i32;`,
+/// are assigned before they are read.
+///
+/// A struct variable counts as assigned once each of its fields is.
+/// A variable that is not `mut` can be assigned once after being
+/// declared without a value, but not again after that.
+/// Top-level variables are zeroed like globals in C, so only the
+/// locals and parameters of procedures are checked.
+pub struct InitializationChecker<'a> {
+    emitter: &'a mut dyn Emitter,
+
+    /// Every struct defined in the module, used to find their fields
+    structs: &'a HashMap<Ident, StructDef>,
+
+    /// Types of the checked expressions, used to tell structs
+    /// apart and find places that are behind a pointer
+    types: &'a TypeInterner,
+    table: &'a TypeTable,
+}
+
+impl<'a> InitializationChecker<'a> {
+    /// Create a new [InitializationChecker].
+    /// This runs after type checking, which fills in `table`
+    pub fn new(
+        emitter: &'a mut dyn Emitter,
+        structs: &'a HashMap<Ident, StructDef>,
+        types: &'a TypeInterner,
+        table: &'a TypeTable,
+    ) -> InitializationChecker<'a> {
+        InitializationChecker {
+            emitter,
+            structs,
+            types,
+            table,
+        }
+    }
+
+    /// Check the body of one procedure
+    pub fn check(&mut self, cfg: &ControlFlowGraph) {
+        let entry_states = self.solve(cfg);
+
+        // Blocks that control never reaches have no state and are not checked
+        for (block, state) in entry_states.into_iter().enumerate() {
+            if let Some(mut state) = state {
+                for access in cfg.blocks()[block].accesses() {
+                    self.transfer(cfg, &mut state, access, true);
+                }
+            }
+        }
+    }
+
+    /// Find the state at the start of each block that control can reach
+    fn solve(&mut self, cfg: &ControlFlowGraph) -> Vec<Option<State>> {
+        let mut states: Vec<Option<State>> = vec![None; cfg.blocks().len()];
+        states[ControlFlowGraph::ENTRY] = Some(State::default());
+
+        let mut worklist = vec![ControlFlowGraph::ENTRY];
+        while let Some(block) = worklist.pop() {
+            let mut state = match &states[block] {
+                Some(state) => state.clone(),
+                None => continue,
+            };
+
+            for access in cfg.blocks()[block].accesses() {
+                self.transfer(cfg, &mut state, access, false);
+            }
+
+            for next in cfg.blocks()[block].successors() {
+                let changed = match &mut states[*next] {
+                    Some(existing) => {
+                        let before = existing.clone();
+                        existing.join(&state);
+                        *existing != before
+                    }
+                    None => {
+                        states[*next] = Some(state.clone());
+                        true
+                    }
+                };
+
+                if changed {
+                    worklist.push(*next);
+                }
+            }
+        }
+
+        return states;
+    }
+
+    /// Apply one access to the state.
+    /// Problems are only reported when `report` is set
+    fn transfer(&mut self, cfg: &ControlFlowGraph, state: &mut State, access: &Access, report: bool) {
+        match access {
+            Access::Declare { variable } => {
+                // A declaration in a loop starts over on every iteration
+                state.must.retain(|place| place.variable != *variable);
+                state.may.retain(|place| place.variable != *variable);
+
+                if cfg.variables()[*variable].has_value() {
+                    state.assign(Place { variable: *variable, fields: vec![] });
+                }
+            }
+            Access::Read { place, nodes, span } => {
+                let (place, node, _) = self.behind_pointer(place, nodes);
+                self.read(cfg, state, place, node, span, report);
+            }
+            Access::Write { place, nodes, span, borrowed } => {
+                let (place, node, through_pointer) = self.behind_pointer(place, nodes);
+
+                // Writing through a pointer only reads the pointer itself
+                if through_pointer {
+                    self.read(cfg, state, place, node, span, report);
+                    return;
+                }
+
+                // Variables that are not `mut` can only get their first value.
+                // The mutability checker reports borrows and the variables
+                // declared with a value
+                let variable = &cfg.variables()[place.variable];
+                let first_value = variable.has_value() || *borrowed || !overlaps(&state.may, &place);
+                if report && !variable.is_mutable() && !first_value {
+                    self.emitter.emit_err(ViperError::AssignToImmutable {
+                        name: variable.name().clone(),
+                        span: span.clone(),
+                        declared: variable.span().clone(),
+                    });
+                }
+
+                state.assign(place);
+            }
+        }
+    }
+
+    fn read(&mut self, cfg: &ControlFlowGraph, state: &mut State, place: Place, node: NodeId, span: &Span, report: bool) {
+        if self.is_initialized(&state.must, &place, self.table.get(node)) {
+            return;
+        }
+
+        if report {
+            let variable = &cfg.variables()[place.variable];
+            self.emitter.emit_err(ViperError::UninitializedVariable(Box::new(UninitializedVariable {
+                name: display_place(variable.name(), &place),
+                variable: variable.name().clone(),
+                span: span.clone(),
+                declared: variable.span().clone(),
+            })));
+        }
+
+        // Only report the first read of each place
+        state.assign(place);
+    }
+
+    /// Cut a place off at the first field that is reached through a pointer.
+    /// `p.x` with `p: *Point` only reads `p`, the field belongs to the pointee.
+    /// Returns the place, the node for its end, and whether it was cut off
+    fn behind_pointer(&self, place: &Place, nodes: &[NodeId]) -> (Place, NodeId, bool) {
+        for (depth, node) in nodes.iter().enumerate().take(place.fields.len()) {
+            let is_pointer = self.table.get(*node)
+                .map(|ty| matches!(self.types.kind(ty), TypeKind::Pointer { .. }))
+                .unwrap_or(false);
+
+            if is_pointer {
+                let place = Place {
+                    variable: place.variable,
+                    fields: place.fields[..depth].to_vec(),
+                };
+                return (place, *node, true);
+            }
+        }
+
+        return (place.clone(), nodes[nodes.len() - 1], false);
+    }
+
+    /// Whether a place of type `ty` has a value, either because it or a
+    /// place containing it was assigned, or because all its fields were
+    fn is_initialized(&self, must: &HashSet<Place>, place: &Place, ty: Option<TypeId>) -> bool {
+        let assigned = (0..=place.fields.len()).any(|depth| {
+            must.contains(&Place {
+                variable: place.variable,
+                fields: place.fields[..depth].to_vec(),
+            })
+        });
+        if assigned {
+            return true;
+        }
+
+        let def = match ty.map(|ty| self.types.kind(ty)) {
+            Some(TypeKind::Struct(name, _)) => self.structs.get(name),
+            _ => None,
+        };
+
+        return match def {
+            Some(def) => def.fields().iter().all(|field| {
+                let mut fields = place.fields.clone();
+                fields.push(field.binding().ident().clone());
+
+                let ty = match field.binding().ty() {
+                    Type::Concrete { name, args: _ } => self.types.nominal(name),
+                    _ => None,
+                };
+                self.is_initialized(must, &Place { variable: place.variable, fields }, ty)
+            }),
+            None => false,
+        };
+    }
+}
+
+/// Whether any place in `places` shares memory with `place`
+fn overlaps(places: &HashSet<Place>, place: &Place) -> bool {
+    return places.iter().any(|other| {
+        let shorter = other.fields.len().min(place.fields.len());
+        other.variable == place.variable && other.fields[..shorter] == place.fields[..shorter]
+    });
+}
+
+/// Write a place out as it appears in the source
+/// `x`, `x.position.y`
+fn display_place(name: &str, place: &Place) -> String {
+    let mut str = name.to_string();
+    for field in &place.fields {
+        str += format!(".{field}").as_str();
+    }
+
+    return str;
+}
@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+
+use viper_ast::{Expr, ExprNode, Ident, ImplDef, InterfaceDef, StructDef};
+use viper_core::{emitter::Emitter, error::ViperError, span::Span};
+
+/// The interfaces of a module, and which structs implement them.
+///
+/// The methods of an `impl` become methods of its struct, so that
+/// they are called the same way as the struct's own methods.
+/// `impl`s can be in a different file than their struct, so they
+/// are only attached once every file of the module is declared.
+#[derive(Default)]
+pub struct ImplTable {
+    /// Every interface of the module, by name
+    interfaces: HashMap<Ident, InterfaceDef>,
+
+    /// Every `impl` of the module, in the order they were declared
+    impls: Vec<ImplDef>,
+
+    /// How many of the `impl`s have had their methods attached to their struct
+    attached: usize,
+
+    /// Where each struct implements each interface,
+    /// by the names of the struct and the interface
+    implemented: HashMap<(Ident, Ident), Span>,
+}
+
+impl ImplTable {
+    /// Create a new [ImplTable] with no interfaces
+    pub fn new() -> ImplTable {
+        ImplTable::default()
+    }
+
+    /// Declare the interfaces and `impl`s in the top-level items of a file
+    pub fn declare(&mut self, items: &[ExprNode]) {
+        for item in items {
+            match item.inner() {
+                Expr::InterfaceDef(def) => {
+                    // Duplicates are reported by the name resolver
+                    self.interfaces.entry(def.name().clone()).or_insert_with(|| def.clone());
+                }
+                Expr::ImplDef(def) => {
+                    self.impls.push(def.clone());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Add the methods of the `impl`s declared since the last call to their structs.
+    ///
+    /// A struct can only implement an interface once, and the methods
+    /// can not have the same name as another member of the struct.
+    /// `impl`s for something that is not a struct without generic
+    /// parameters are left for the name resolver to report
+    pub fn attach(&mut self, structs: &mut HashMap<Ident, StructDef>, emitter: &mut dyn Emitter) {
+        for def in &self.impls[self.attached..] {
+            let interface = def.interface().to_string();
+            let target = def.target().to_string();
+            let structure = match structs.get(&target) {
+                Some(structure) if structure.generics().is_empty() => structure,
+                _ => continue,
+            };
+            if !self.interfaces.contains_key(&interface) {
+                continue;
+            }
+
+            let key = (target.clone(), interface.clone());
+            if let Some(previous) = self.implemented.get(&key) {
+                emitter.emit_err(ViperError::DuplicateDefinition {
+                    name: format!("impl {interface} for {target}"),
+                    span: def.span().clone(),
+                    previous: previous.clone(),
+                });
+                continue;
+            }
+            self.implemented.insert(key, def.span().clone());
+
+            let mut methods = structure.methods().to_vec();
+            for method in def.methods() {
+                let previous = methods.iter()
+                    .find(|m| m.name() == method.name())
+                    .map(|m| m.span())
+                    .or_else(|| structure.consts().iter().find(|c| c.name() == method.name()).map(|c| c.span()));
+
+                match previous {
+                    Some(previous) => {
+                        emitter.emit_err(ViperError::DuplicateDefinition {
+                            name: method.name().clone(),
+                            span: method.span().clone(),
+                            previous: previous.clone(),
+                        });
+                    }
+                    None => methods.push(method.clone()),
+                }
+            }
+
+            let attached = StructDef::new(
+                structure.identifier().clone(),
+                vec![],
+                structure.fields().into(),
+                methods.into(),
+                structure.consts().into(),
+                structure.visibility().clone(),
+                structure.repr().clone(),
+            );
+            structs.insert(target, attached);
+        }
+
+        self.attached = self.impls.len();
+    }
+
+    /// Get an interface by name
+    pub fn interface(&self, name: &str) -> Option<&InterfaceDef> {
+        return self.interfaces.get(name);
+    }
+
+    /// Whether a struct implements an interface
+    pub fn implements(&self, structure: &str, interface: &str) -> bool {
+        return self.implemented.contains_key(&(structure.to_string(), interface.to_string()));
+    }
+
+    /// Get the vtable that a `*dyn` pointer to an interface uses
+    /// for a struct that implements it
+    pub fn vtable(&self, interface: &str, implementor: &str) -> Option<VTable> {
+        if !self.implements(implementor, interface) {
+            return None;
+        }
+
+        let methods = self.interface(interface)?.methods().iter()
+            .map(|method| format!("{implementor}::{}", method.name()))
+            .collect();
+
+        return Some(VTable {
+            interface: interface.to_string(),
+            implementor: implementor.to_string(),
+            methods,
+        });
+    }
+}
+
+/// The table of methods that a `*dyn` pointer carries along with the
+/// pointer to its value.
+///
+/// A `*dyn Weapon` pointing to a `Sword` is a fat pointer: the address of
+/// the `Sword` and the address of the vtable for `Sword` as a `Weapon`.
+/// Each method of the interface has a slot in the vtable, in the order the
+/// interface declares them, holding the struct's method of that name.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VTable {
+    interface: Ident,
+    implementor: Ident,
+
+    /// The method in each slot, as a path to the struct's method
+    /// `Sword::damage`
+    methods: Vec<Ident>,
+}
+
+impl VTable {
+    /// Get the name of the interface
+    pub fn interface(&self) -> &Ident {
+        &self.interface
+    }
+
+    /// Get the name of the struct that the vtable is for
+    pub fn implementor(&self) -> &Ident {
+        &self.implementor
+    }
+
+    /// Get the method in each slot of the vtable
+    pub fn methods(&self) -> &[Ident] {
+        &self.methods
+    }
+}
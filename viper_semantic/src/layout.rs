@@ -0,0 +1,433 @@
+use std::{cmp::Reverse, collections::HashMap};
+
+use viper_ast::{Expr, ExprNode, Ident, NodeId, StructDef};
+use viper_core::{
+    emitter::Emitter,
+    error::ViperError,
+    span::Span,
+    types::{TypeId, TypeInterner, TypeKind},
+};
+
+/// The size of a pointer in bytes. Fat pointers are two of them
+const POINTER_SIZE: u64 = 8;
+
+/// The size and alignment of a type in bytes, and where its fields go
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Layout {
+    size: u64,
+    align: u64,
+
+    /// The offset of each field of a struct or element of a tuple,
+    /// in the order they are declared. Empty for every other type
+    offsets: Vec<u64>,
+}
+
+impl Layout {
+    /// The layout of a value with no fields, aligned to its own size
+    fn scalar(size: u64) -> Layout {
+        Layout {
+            size,
+            align: size.max(1),
+            offsets: vec![],
+        }
+    }
+
+    /// Get the number of bytes that a value of the type takes,
+    /// which is always a multiple of its alignment
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Get the number of bytes that the address of a value has to be a multiple of
+    pub fn align(&self) -> u64 {
+        self.align
+    }
+
+    /// Get the offset of each field, in the order they are declared
+    pub fn offsets(&self) -> &[u64] {
+        &self.offsets
+    }
+}
+
+/// Why a type could not be laid out
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LayoutError {
+    /// The size of the type is not known while compiling
+    /// `[T]`, `dyn Weapon`, or a generic parameter
+    Unsized(TypeId),
+
+    /// A struct holds a value of itself, directly or within other types
+    Recursive(Ident),
+
+    /// `offsetof` of a field that the struct or tuple does not have
+    UndefinedField(TypeId, Ident),
+
+    /// A type within it names something that does not exist,
+    /// which is reported where the type is written
+    Invalid,
+}
+
+/// The layout of every type that has been laid out, and the value of every
+/// `sizeof`, `alignof` and `offsetof` outside of generic items.
+///
+/// Layouts only depend on the types, so they are the same on every target:
+///  - `bool` and `byte` take 1 byte, and integers and floats take their width
+///  - Pointers and functions take 8 bytes, and pointers to `dyn` values take 16
+///  - Arrays hold their elements one after another
+///  - Tuples are laid out like a `#[repr(C)]` struct of their elements
+///  - Structs put each field at the first offset that is a multiple of its
+///    alignment. Without `#[repr(C)]` the most aligned fields go first, which
+///    leaves the least padding. `#[repr(packed)]` leaves out all padding and
+///    `#[repr(align(N))]` raises the alignment of the struct
+///  - `?T` is a tag byte followed by the `T`, but `?*T` is just the pointer,
+///    with a null pointer for `null`
+///  - `Result<T, E>` is a tag byte followed by room for the larger of `T` and `E`
+///
+/// Every size is rounded up to a multiple of the alignment, so that
+/// each element of an array of the type is aligned.
+#[derive(Default)]
+pub struct LayoutTable {
+    layouts: HashMap<TypeId, Layout>,
+
+    /// The values of `sizeof`, `alignof` and `offsetof` expressions, by their node
+    values: HashMap<NodeId, u64>,
+}
+
+impl LayoutTable {
+    /// Create a new [LayoutTable] with nothing laid out
+    pub fn new() -> LayoutTable {
+        LayoutTable::default()
+    }
+
+    /// Get the layout of a type, if it has been laid out
+    pub fn get(&self, ty: TypeId) -> Option<&Layout> {
+        return self.layouts.get(&ty);
+    }
+
+    /// Get the value of a `sizeof`, `alignof` or `offsetof` expression.
+    /// Those within generic items are replaced by their values in each copy instead
+    pub fn value(&self, id: NodeId) -> Option<u64> {
+        return self.values.get(&id).copied();
+    }
+
+    /// Lay out a type, along with the types within it
+    pub fn layout(
+        &mut self,
+        types: &mut TypeInterner,
+        structs: &HashMap<Ident, StructDef>,
+        ty: TypeId,
+    ) -> Result<Layout, LayoutError> {
+        let mut engine = LayoutEngine {
+            types,
+            structs,
+            layouts: &mut self.layouts,
+            computing: vec![],
+        };
+
+        return engine.layout(ty);
+    }
+
+    /// Get the value of a `sizeof`, `alignof` or `offsetof` of the type `ty`
+    pub fn query(
+        &mut self,
+        types: &mut TypeInterner,
+        structs: &HashMap<Ident, StructDef>,
+        query: &Expr,
+        ty: TypeId,
+    ) -> Result<u64, LayoutError> {
+        let layout = self.layout(types, structs, ty)?;
+
+        let field = match query {
+            Expr::SizeOf(_) => return Ok(layout.size),
+            Expr::AlignOf(_) => return Ok(layout.align),
+            Expr::OffsetOf(_, field) => field,
+            _ => return Err(LayoutError::Invalid),
+        };
+
+        let index = match types.kind(ty) {
+            TypeKind::Struct(name, _) => structs.get(name)
+                .and_then(|def| def.fields().iter().position(|f| f.binding().ident() == field)),
+            TypeKind::Tuple(_) => field.parse::<usize>().ok().filter(|index| *index < layout.offsets.len()),
+            _ => None,
+        };
+
+        match index {
+            Some(index) => return Ok(layout.offsets[index]),
+            None => return Err(LayoutError::UndefinedField(ty, field.clone())),
+        }
+    }
+}
+
+/// Lays out one type at a time, keeping the layouts it works out
+struct LayoutEngine<'a> {
+    types: &'a mut TypeInterner,
+    structs: &'a HashMap<Ident, StructDef>,
+    layouts: &'a mut HashMap<TypeId, Layout>,
+
+    /// The structs that are being laid out, to find those that hold themselves
+    computing: Vec<TypeId>,
+}
+
+impl LayoutEngine<'_> {
+    fn layout(&mut self, ty: TypeId) -> Result<Layout, LayoutError> {
+        if let Some(layout) = self.layouts.get(&ty) {
+            return Ok(layout.clone());
+        }
+
+        let layout = match self.types.kind(ty).clone() {
+            TypeKind::Void => Layout::scalar(0),
+            TypeKind::Bool | TypeKind::Byte => Layout::scalar(1),
+            TypeKind::Integer(_, size) | TypeKind::Float(size) => Layout::scalar(size.bits() as u64 / 8),
+            TypeKind::Pointer { .. } if self.types.is_fat_pointer(ty) => {
+                Layout {
+                    size: 2 * POINTER_SIZE,
+                    align: POINTER_SIZE,
+                    offsets: vec![],
+                }
+            }
+            TypeKind::Pointer { .. } | TypeKind::Function { .. } => Layout::scalar(POINTER_SIZE),
+            TypeKind::Array { element, length } => {
+                let element = self.layout(element)?;
+                Layout {
+                    size: element.size * length as u64,
+                    align: element.align,
+                    offsets: vec![],
+                }
+            }
+            TypeKind::Tuple(elements) => {
+                let elements = self.layout_all(&elements)?;
+                place(&elements, false, false, None)
+            }
+            TypeKind::Optional(inner) if self.types.is_null_optimized(ty) => self.layout(inner)?,
+            TypeKind::Optional(inner) => tagged(&[self.layout(inner)?]),
+            TypeKind::Result { ok, err } => tagged(&[self.layout(ok)?, self.layout(err)?]),
+            TypeKind::Struct(name, args) => self.layout_struct(ty, &name, &args)?,
+            TypeKind::Slice(_)
+            | TypeKind::Dyn(_)
+            | TypeKind::Param(_)
+            | TypeKind::Enum(_) => return Err(LayoutError::Unsized(ty)),
+        };
+
+        self.layouts.insert(ty, layout.clone());
+        return Ok(layout);
+    }
+
+    fn layout_all(&mut self, types: &[TypeId]) -> Result<Vec<Layout>, LayoutError> {
+        return types.iter().map(|ty| self.layout(*ty)).collect();
+    }
+
+    /// Lay out a struct, with its type arguments in place of its generic parameters
+    fn layout_struct(&mut self, ty: TypeId, name: &Ident, args: &[TypeId]) -> Result<Layout, LayoutError> {
+        let structs = self.structs;
+        let def = structs.get(name).ok_or(LayoutError::Invalid)?;
+
+        if self.computing.contains(&ty) {
+            return Err(LayoutError::Recursive(name.clone()));
+        }
+
+        let params: HashMap<Ident, TypeId> = def.generics().iter()
+            .map(|g| g.name().clone())
+            .zip(args.iter().copied())
+            .collect();
+
+        self.computing.push(ty);
+        let fields = def.fields().iter()
+            .map(|field| match self.types.lower_with(field.binding().ty(), &params) {
+                Some(field) => self.layout(field),
+                None => Err(LayoutError::Invalid),
+            })
+            .collect::<Result<Vec<Layout>, LayoutError>>();
+        self.computing.pop();
+
+        let repr = def.repr();
+        return Ok(place(&fields?, !repr.is_c(), repr.is_packed(), repr.align()));
+    }
+}
+
+/// Place fields one after another, each at the first offset that is a multiple
+/// of its alignment. The most aligned fields go first when they can be `reordered`.
+/// The offsets are in the order that the fields are given
+fn place(fields: &[Layout], reordered: bool, packed: bool, align: Option<u64>) -> Layout {
+    let mut order: Vec<usize> = (0..fields.len()).collect();
+    if reordered && !packed {
+        // The sort is stable, so fields that are aligned the same keep their order
+        order.sort_by_key(|index| Reverse(fields[*index].align));
+    }
+
+    let mut offsets = vec![0; fields.len()];
+    let mut end = 0;
+    let mut struct_align = 1;
+    for index in order {
+        let field_align = match packed {
+            true => 1,
+            false => fields[index].align,
+        };
+
+        offsets[index] = align_to(end, field_align);
+        end = offsets[index] + fields[index].size;
+        struct_align = struct_align.max(field_align);
+    }
+
+    // Alignments that are not powers of two are reported by the layout checker
+    if let Some(align) = align.filter(|align| align.is_power_of_two()) {
+        struct_align = struct_align.max(align);
+    }
+
+    return Layout {
+        size: align_to(end, struct_align),
+        align: struct_align,
+        offsets,
+    };
+}
+
+/// Lay out a tag byte followed by room for the largest of `payloads`.
+/// The tag tells which of them is held
+fn tagged(payloads: &[Layout]) -> Layout {
+    let align = payloads.iter().map(|payload| payload.align).max().unwrap_or(1);
+    let size = payloads.iter().map(|payload| payload.size).max().unwrap_or(0);
+
+    return Layout {
+        size: align_to(align_to(1, align) + size, align),
+        align,
+        offsets: vec![],
+    };
+}
+
+/// Round an offset up to a multiple of `align`
+fn align_to(offset: u64, align: u64) -> u64 {
+    return offset.div_ceil(align) * align;
+}
+
+/// Lays out every struct that is not generic, and works out the value
+/// of every `sizeof`, `alignof` and `offsetof` outside of generic items.
+///
+/// Reported are
+///  - structs that hold a value of themselves, which would never end
+///  - fields and layout questions with types that have no size
+///  - `#[repr(align(N))]` where `N` is not a power of two, or on a `packed` struct
+///  - `offsetof` of a field that does not exist
+///
+/// Generic structs are laid out for each set of type arguments they are used
+/// with, and the layout questions in generic items are answered in each copy
+/// that the [crate::mono::Monomorphizer] makes of them.
+pub struct LayoutChecker<'a> {
+    emitter: &'a mut dyn Emitter,
+
+    /// Every struct that is visible from the file being checked
+    structs: &'a HashMap<Ident, StructDef>,
+
+    types: &'a mut TypeInterner,
+
+    /// Where the layouts and the values of layout questions are kept
+    layouts: &'a mut LayoutTable,
+}
+
+impl<'a> LayoutChecker<'a> {
+    /// Create a new [LayoutChecker] that keeps what it works out in `layouts`
+    pub fn new(
+        emitter: &'a mut dyn Emitter,
+        structs: &'a HashMap<Ident, StructDef>,
+        types: &'a mut TypeInterner,
+        layouts: &'a mut LayoutTable,
+    ) -> LayoutChecker<'a> {
+        LayoutChecker {
+            emitter,
+            structs,
+            types,
+            layouts,
+        }
+    }
+
+    /// Check the top-level items of a file
+    pub fn check_items(&mut self, items: &[ExprNode]) {
+        for item in items {
+            match item.inner() {
+                Expr::StructDef(def) => {
+                    self.check_repr(def, item.span());
+                    if def.generics().is_empty() {
+                        self.check_struct(def, item.span());
+                        self.check_expr(item);
+                    }
+                }
+                _ => self.check_expr(item),
+            }
+        }
+    }
+
+    fn check_expr(&mut self, expr: &ExprNode) {
+        match expr.inner() {
+            Expr::SizeOf(ty) | Expr::AlignOf(ty) | Expr::OffsetOf(ty, _) => {
+                // Types that do not exist are reported by the resolver
+                let ty = match self.types.lower(ty) {
+                    Some(ty) => ty,
+                    None => return,
+                };
+
+                match self.layouts.query(self.types, self.structs, expr.inner(), ty) {
+                    Ok(value) => {
+                        self.layouts.values.insert(expr.id(), value);
+                    }
+                    Err(err) => self.report(err, expr.span()),
+                }
+            }
+            Expr::ProcedureDefinition(def) if !def.generics().is_empty() => {}
+            _ => {
+                expr.for_each_child(&mut |child| self.check_expr(child));
+            }
+        }
+    }
+
+    /// Check that a `#[repr(align(N))]` can be used
+    fn check_repr(&mut self, def: &StructDef, span: &Span) {
+        let align = match def.repr().align() {
+            Some(align) => align,
+            None => return,
+        };
+
+        if !align.is_power_of_two() {
+            self.emitter.emit_err(ViperError::InvalidAlignment {
+                name: def.identifier().clone(),
+                align,
+                span: span.clone(),
+            });
+        }
+
+        if def.repr().is_packed() {
+            self.emitter.emit_err(ViperError::PackedAndAligned {
+                name: def.identifier().clone(),
+                span: span.clone(),
+            });
+        }
+    }
+
+    /// Lay out a struct that is not generic
+    fn check_struct(&mut self, def: &StructDef, span: &Span) {
+        let ty = self.types.intern(TypeKind::Struct(def.identifier().clone(), vec![]));
+        if let Err(err) = self.layouts.layout(self.types, self.structs, ty) {
+            self.report(err, span);
+        }
+    }
+
+    fn report(&mut self, err: LayoutError, span: &Span) {
+        let err = match err {
+            // A `dyn` value is reported by the resolver
+            LayoutError::Unsized(ty) if matches!(self.types.kind(ty), TypeKind::Dyn(_)) => return,
+            LayoutError::Unsized(ty) => ViperError::UnsizedType {
+                ty: self.types.display(ty).to_string(),
+                span: span.clone(),
+            },
+            LayoutError::Recursive(name) => ViperError::InfiniteSize {
+                name,
+                span: span.clone(),
+            },
+            LayoutError::UndefinedField(ty, field) => ViperError::UndefinedMember {
+                parent: self.types.display(ty).to_string(),
+                member: field,
+                span: span.clone(),
+            },
+            LayoutError::Invalid => return,
+        };
+
+        self.emitter.emit_err(err);
+    }
+}
@@ -1,10 +1,297 @@
-// use viper_ast::ExprNode;
+use std::{cell::RefCell, collections::{HashMap, HashSet}, sync::Arc};
 
+use cfg::ControlFlowChecker;
+use closure::{CaptureAnalyzer, Closure};
+use consteval::{ArithmeticChecker, ConstTable};
+use interface::{ImplTable, VTable};
+use layout::{LayoutChecker, LayoutTable};
+use lint::ItemUsage;
+use mono::Monomorphizer;
+use mutability::MutabilityChecker;
+use resolve::NameResolver;
+use typecheck::{TypeChecker, TypeTable};
+use visibility::VisibilityChecker;
+use viper_ast::{Expr, ExprNode, Ident, NodeId, ProcedureDef, StructDef};
+use viper_core::{emitter::Emitter, scope::Scope, source::SourceFile, types::TypeInterner};
 
-pub struct SemanticAnalyzer {
-    
+pub mod test;
+
+pub mod pointer;
+pub mod cast;
+pub mod closure;
+pub mod resolve;
+pub mod typecheck;
+pub mod mutability;
+pub mod visibility;
+pub mod cfg;
+pub mod init;
+pub mod lint;
+pub mod consteval;
+pub mod mono;
+pub mod interface;
+pub mod operator;
+pub mod layout;
+
+/// Runs the semantic passes over parsed source files
+/// and reports any problems to its [Emitter]
+pub struct SemanticAnalyzer<E: Emitter> {
+    emitter: E,
+
+    /// Every struct defined in the module being analyzed
+    structs: HashMap<Ident, StructDef>,
+
+    /// Every top-level procedure defined in the module
+    procedures: HashMap<Ident, ProcedureDef>,
+
+    /// Every semantic type in the module
+    interner: TypeInterner,
+
+    /// Types of the expressions that have been checked
+    types: TypeTable,
+
+    /// Which private items of the module have been used
+    usage: ItemUsage,
+
+    /// Every constant of the module, with its value once evaluated
+    consts: ConstTable,
+
+    /// The interfaces of the module and their implementations
+    impls: ImplTable,
+
+    /// Whether indices that are only known at runtime get checked against
+    /// the length of the array. On in debug builds and off in release builds
+    bounds_checked: bool,
+
+    /// The index expressions that the backend checks at runtime
+    bounds_checks: HashSet<NodeId>,
+
+    /// The memory layout of the types of the module
+    layouts: LayoutTable,
+
+    /// The lambdas of the module and the variables they capture
+    closures: Vec<Closure>,
+}
+
+impl<E: Emitter> SemanticAnalyzer<E> {
+    /// Create a new [SemanticAnalyzer] that reports to `emitter`
+    pub fn new(emitter: E) -> SemanticAnalyzer<E> {
+        SemanticAnalyzer {
+            emitter,
+            structs: HashMap::new(),
+            procedures: HashMap::new(),
+            interner: TypeInterner::new(),
+            types: TypeTable::new(),
+            usage: ItemUsage::new(),
+            consts: ConstTable::new(),
+            impls: ImplTable::new(),
+            bounds_checked: true,
+            bounds_checks: HashSet::new(),
+            layouts: LayoutTable::new(),
+            closures: vec![],
+        }
+    }
+
+    /// Set whether indices that are only known at runtime get checked
+    /// against the length of the array they index into
+    pub fn set_bounds_checks(&mut self, enabled: bool) {
+        self.bounds_checked = enabled;
+    }
+
+    /// Get the emitter that diagnostics are reported to
+    pub fn emitter(&self) -> &E {
+        return &self.emitter;
+    }
+
+    /// Get the table of semantic types
+    pub fn interner(&self) -> &TypeInterner {
+        return &self.interner;
+    }
+
+    /// Get the types of the expressions that have been checked
+    pub fn types(&self) -> &TypeTable {
+        return &self.types;
+    }
+
+    /// Get the constants of the module and their values
+    pub fn consts(&self) -> &ConstTable {
+        return &self.consts;
+    }
+
+    /// Get the interfaces of the module and what implements them
+    pub fn impls(&self) -> &ImplTable {
+        return &self.impls;
+    }
+
+    /// Get the index expressions that the backend has to check against the
+    /// length of the array or slice before indexing. Indices that are constants
+    /// are checked while compiling instead. Empty when bounds checks are off
+    pub fn bounds_checks(&self) -> &HashSet<NodeId> {
+        return &self.bounds_checks;
+    }
+
+    /// Get the memory layout of the types that have been laid out, and the
+    /// values of the `sizeof`, `alignof` and `offsetof` expressions
+    pub fn layouts(&self) -> &LayoutTable {
+        return &self.layouts;
+    }
+
+    /// Get every lambda of the module with the variables it captures,
+    /// in the order that they appear in each procedure
+    pub fn closures(&self) -> &[Closure] {
+        return &self.closures;
+    }
+
+    /// Get the vtable of every struct that is turned into a `*dyn` pointer
+    /// to an interface, sorted by interface and then by struct
+    pub fn vtables(&self) -> Vec<VTable> {
+        let mut used: Vec<(&Ident, &Ident)> = self.types.coercions()
+            .map(|(implementor, interface)| (interface, implementor))
+            .collect();
+        used.sort();
+        used.dedup();
+
+        return used.into_iter()
+            .filter_map(|(interface, implementor)| self.impls.vtable(interface, implementor))
+            .collect();
+    }
+
+    /// Declare the top-level items of a file in the scope of its module.
+    /// Every file in a module must be declared before any of them are
+    /// checked so that files can refer to each other's items
+    pub fn declare(&mut self, file: &Arc<SourceFile>, items: &[ExprNode]) {
+        resolve::declare_items(
+            &module_scope(file),
+            items,
+            &mut self.structs,
+            &mut self.procedures,
+            &mut self.emitter
+        );
+
+        for item in items {
+            match item.inner() {
+                Expr::StructDef(def) => {
+                    self.interner.declare_struct(def.identifier());
+                    if !def.generics().is_empty() {
+                        self.interner.declare_generic_struct(def.identifier(), def.generics().len());
+                    }
+                }
+                Expr::InterfaceDef(def) => {
+                    self.interner.declare_interface(def.name());
+                }
+                _ => {}
+            }
+        }
+
+        self.usage.declare(items);
+        self.consts.declare(items);
+        self.impls.declare(items);
+    }
+
+    /// Check the items of a file that has already been declared
+    pub fn check(&mut self, file: &Arc<SourceFile>, items: &[ExprNode]) {
+        // Every file is declared by now, so each `impl` can find its struct
+        self.impls.attach(&mut self.structs, &mut self.emitter);
+
+        // Constants are evaluated first so that types can use them as array lengths
+        let scope = module_scope(file);
+        self.consts.evaluate(&mut self.emitter, &mut self.interner, &scope);
+
+        let mut resolver = NameResolver::new(&mut self.emitter, &self.structs, &self.consts, file.scope());
+        resolver.resolve_items(items);
+
+        let mut checker = TypeChecker::new(
+            &mut self.emitter,
+            &self.structs,
+            &self.procedures,
+            &self.impls,
+            file.scope(),
+            &mut self.interner,
+            &mut self.types
+        );
+        checker.check_items(items);
+
+        // Captures take the types that the type checker worked out
+        for item in items {
+            if let Expr::ProcedureDefinition(_) = item.inner() {
+                let closures = CaptureAnalyzer::analyze(item, &self.structs, &mut self.interner, &self.types);
+                self.closures.extend(closures);
+            }
+        }
+
+        let mut arithmetic = ArithmeticChecker::new(
+            &mut self.emitter,
+            &mut self.interner,
+            &scope,
+            &mut self.consts,
+            &self.types
+        );
+        arithmetic.check_items(items);
+        if self.bounds_checked {
+            self.bounds_checks.extend(arithmetic.bounds_checks());
+        }
+
+        let mut layout = LayoutChecker::new(&mut self.emitter, &self.structs, &mut self.interner, &mut self.layouts);
+        layout.check_items(items);
+
+        let mut visibility = VisibilityChecker::new(&mut self.emitter, &self.structs, &self.interner, &self.types);
+        visibility.check_items(items);
+
+        let mut mutability = MutabilityChecker::new(
+            &mut self.emitter,
+            &self.structs,
+            &self.impls,
+            file.scope(),
+            &self.interner,
+            &self.types
+        );
+        mutability.check_items(items);
+
+        let mut control_flow = ControlFlowChecker::new(
+            &mut self.emitter,
+            &self.structs,
+            &self.interner,
+            &self.types
+        );
+        control_flow.check_items(items);
+
+        self.usage.collect(items, &self.structs, &self.interner, &self.types);
+    }
+
+    /// Report the problems that can only be found once every file
+    /// of the module has been checked, like unused private procedures
+    pub fn finish(&mut self) {
+        self.usage.report(&self.structs, &mut self.emitter);
+    }
+
+    /// Copy the checked items of a module with each generic procedure and
+    /// struct replaced by a copy for every set of type arguments it is used with,
+    /// each overloaded operator replaced by a call of its method, and each
+    /// `sizeof`, `alignof` and `offsetof` replaced by its value.
+    /// `items` are the items of every file of the module
+    pub fn monomorphize(&mut self, items: &[ExprNode]) -> Vec<ExprNode> {
+        let mut monomorphizer = Monomorphizer::new(
+            &mut self.emitter,
+            &mut self.interner,
+            &self.types,
+            &self.structs,
+            &mut self.layouts
+        );
+        return monomorphizer.monomorphize(items);
+    }
+
+    /// Declare, check and finish a single file
+    pub fn analyze_file(&mut self, file: &Arc<SourceFile>, items: &[ExprNode]) {
+        self.declare(file, items);
+        self.check(file, items);
+        self.finish();
+    }
 }
 
-impl SemanticAnalyzer {
-        
+/// Get the scope that a file's top-level items are declared in.
+/// This is the scope of the module the file belongs to, or the
+/// file's own scope when it is compiled on its own
+fn module_scope(file: &Arc<SourceFile>) -> Arc<RefCell<Scope>> {
+    let scope = file.scope();
+    let parent = scope.borrow().parent();
+    return parent.unwrap_or(scope);
 }
@@ -0,0 +1,194 @@
+use std::collections::{HashMap, HashSet};
+
+use viper_ast::{Expr, ExprNode, Ident, ProcedureKind, StructDef, Visibility};
+use viper_core::{emitter::Emitter, error::ViperWarning, span::Span, types::TypeInterner};
+
+use crate::{
+    cfg::{Access, ControlFlowGraph},
+    typecheck::{receiver_struct, TypeTable},
+};
+
+/// Warn about the parameters and local variables of a procedure
+/// that are never read or borrowed.
+/// Names starting with `_` are never warned about
+pub fn check_unused_variables(cfg: &ControlFlowGraph, emitter: &mut dyn Emitter) {
+    let mut used = vec![false; cfg.variables().len()];
+    for block in cfg.blocks() {
+        for access in block.accesses() {
+            match access {
+                Access::Read { place, .. } | Access::Write { place, borrowed: true, .. } => {
+                    used[place.variable] = true;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    for (variable, used) in cfg.variables().iter().zip(used) {
+        // The receiver of a method does not have to be used
+        if used || is_allowed(variable.name()) || variable.name() == "self" {
+            continue;
+        }
+
+        let name = variable.name().clone();
+        let span = variable.span().clone();
+        match variable.is_parameter() {
+            true => emitter.emit_warning(ViperWarning::UnusedParameter { name, span }),
+            false => emitter.emit_warning(ViperWarning::UnusedVariable { name, span }),
+        }
+    }
+}
+
+/// Whether a name opts out of unused warnings
+/// `_`, `_unused`
+fn is_allowed(name: &str) -> bool {
+    return name.starts_with('_');
+}
+
+/// Tracks which private procedures and fields of a module are used.
+///
+/// Other files of the module can use them, so the uses are collected
+/// from every file before anything is reported.
+/// `main` is where the program starts, so it counts as used.
+#[derive(Default)]
+pub struct ItemUsage {
+    /// The private top-level procedures, with where they are declared
+    procedures: Vec<(Ident, Span)>,
+
+    /// Procedures that are called or referred to,
+    /// other than from within their own body
+    used_procedures: HashSet<Ident>,
+
+    /// Fields that are read, by the name of their struct
+    used_fields: HashSet<(Ident, Ident)>,
+}
+
+impl ItemUsage {
+    /// Create a new [ItemUsage] with nothing declared
+    pub fn new() -> ItemUsage {
+        ItemUsage::default()
+    }
+
+    /// Declare the top-level items of a file
+    pub fn declare(&mut self, items: &[ExprNode]) {
+        for item in items {
+            if let Expr::ProcedureDefinition(def) = item.inner() {
+                if let Visibility::Private = def.visibility() {
+                    self.procedures.push((def.name(), def.span().clone()));
+                }
+            }
+        }
+    }
+
+    /// Find the uses of procedures and fields in the items of a file.
+    /// This runs after type checking, which fills in `table`
+    pub fn collect(
+        &mut self,
+        items: &[ExprNode],
+        structs: &HashMap<Ident, StructDef>,
+        types: &TypeInterner,
+        table: &TypeTable,
+    ) {
+        let mut collector = UsageCollector {
+            usage: self,
+            structs,
+            types,
+            table,
+            current: None,
+        };
+
+        for item in items {
+            collector.visit(item);
+        }
+    }
+
+    /// Warn about the private procedures and fields that were never used
+    pub fn report(&self, structs: &HashMap<Ident, StructDef>, emitter: &mut dyn Emitter) {
+        for (name, span) in &self.procedures {
+            if name == "main" || is_allowed(name) || self.used_procedures.contains(name) {
+                continue;
+            }
+
+            emitter.emit_warning(ViperWarning::UnusedProcedure {
+                name: name.clone(),
+                span: span.clone(),
+            });
+        }
+
+        // Sorted so that the warnings come out in the same order every time
+        let mut structs: Vec<&StructDef> = structs.values().collect();
+        structs.sort_by_key(|def| def.identifier());
+
+        for def in structs {
+            for field in def.fields() {
+                let name = field.binding().ident();
+                let used = self.used_fields.contains(&(def.identifier().clone(), name.clone()));
+                if used || is_allowed(name) || matches!(field.visibility(), Visibility::Public) {
+                    continue;
+                }
+
+                emitter.emit_warning(ViperWarning::UnusedField {
+                    structure: def.identifier().clone(),
+                    field: name.clone(),
+                    span: field.binding().span().clone(),
+                });
+            }
+        }
+    }
+}
+
+/// Walks the items of a file, recording what they use in an [ItemUsage]
+struct UsageCollector<'a> {
+    usage: &'a mut ItemUsage,
+    structs: &'a HashMap<Ident, StructDef>,
+    types: &'a TypeInterner,
+    table: &'a TypeTable,
+
+    /// The top-level procedure whose body is being walked.
+    /// A procedure calling itself does not make it used
+    current: Option<Ident>,
+}
+
+impl UsageCollector<'_> {
+    fn visit(&mut self, expr: &ExprNode) {
+        match expr.inner() {
+            Expr::Identifier(name) => {
+                self.use_procedure(name);
+            }
+            Expr::ProcedureCall(call) if call.name().is_ident() => {
+                self.use_procedure(call.name().last().name());
+            }
+            Expr::MemberFieldAccess(field) => {
+                let def = self.table.get(field.object().id())
+                    .and_then(|ty| receiver_struct(self.types, self.structs, ty));
+
+                if let Some(def) = def {
+                    self.usage.used_fields.insert((def.identifier().clone(), field.name().clone()));
+                }
+            }
+            Expr::Assignment(assignment) => {
+                // Assigning to a field is not reading it
+                if let (None, Expr::MemberFieldAccess(field)) = (assignment.op(), assignment.target().inner()) {
+                    self.visit(field.object());
+                    self.visit(assignment.value());
+                    return;
+                }
+            }
+            Expr::ProcedureDefinition(def) if def.kind() == &ProcedureKind::TopLevel => {
+                let outer = self.current.replace(def.name());
+                self.visit(def.body());
+                self.current = outer;
+                return;
+            }
+            _ => {}
+        }
+
+        expr.for_each_child(&mut |child| self.visit(child));
+    }
+
+    fn use_procedure(&mut self, name: &str) {
+        if self.current.as_deref() != Some(name) {
+            self.usage.used_procedures.insert(name.to_string());
+        }
+    }
+}
@@ -0,0 +1,451 @@
+use std::{collections::{HashMap, HashSet, VecDeque}, sync::Arc};
+
+use viper_ast::{
+    Assignment, BinaryOperator, Expr, ExprNode, Ident, MethodCall, NodeId, Path, PathSegment, ProcedureDef,
+    Rewriter, StructDef, UnaryOperator,
+};
+use viper_core::{
+    _type::{ArrayLength, Type},
+    emitter::Emitter,
+    error::ViperError,
+    span::Span,
+    types::{TypeId, TypeInterner, TypeKind},
+};
+
+use crate::{layout::LayoutTable, typecheck::TypeTable};
+
+/// How many copies of the same generic item can be nested within each other.
+/// Going past this means that the item keeps asking for copies of itself
+/// with bigger type arguments, like `f<T>` calling `f<(T, T)>`
+const RECURSION_LIMIT: usize = 16;
+
+/// A generic item together with the type arguments it is used with
+/// `max` with `i32`
+#[derive(Clone, Debug)]
+enum Instance {
+    Procedure(Ident, Vec<TypeId>),
+    Struct(Ident, Vec<TypeId>),
+}
+
+impl Instance {
+    /// Get the name of the generic item
+    fn name(&self) -> &Ident {
+        match self {
+            Self::Procedure(name, _) | Self::Struct(name, _) => name,
+        }
+    }
+}
+
+/// Makes a copy of each generic procedure and struct for every set of
+/// type arguments it is used with, so that nothing generic is left for
+/// code generation.
+///
+/// A copy is named after the item and its type arguments, the way the
+/// type is written: `max<i32>`, `Pair<i32, f64>`. Uses of generic items are
+/// renamed to the copy they need, and the generic items themselves are left
+/// out. Copies are made as they are found to be needed, so an instance that
+/// is only used from within another instance still gets made.
+///
+/// The nodes of a copy keep the ids of the generic item's nodes, so the
+/// types recorded for them are those of the generic body. Their concrete
+/// types are found by substituting the type arguments of the copy.
+///
+/// Operators that the type checker found to be overloaded are turned into
+/// calls of the methods that overload them along the way, and `sizeof`,
+/// `alignof` and `offsetof` are turned into their values, which only
+/// become known in the copies of generic items.
+///
+/// A generic item that asks for copies of itself without end is reported
+/// once its copies are nested [RECURSION_LIMIT] deep.
+pub struct Monomorphizer<'a> {
+    emitter: &'a mut dyn Emitter,
+
+    types: &'a mut TypeInterner,
+
+    /// The types and type arguments found by the type checker
+    table: &'a TypeTable,
+
+    /// Every struct of the module and their layouts, to answer layout questions
+    structs: &'a HashMap<Ident, StructDef>,
+    layouts: &'a mut LayoutTable,
+
+    /// The generic procedures and structs of the module, by name
+    generic: HashMap<Ident, &'a ExprNode>,
+
+    /// What the generic parameters of the item being copied stand for
+    bindings: HashMap<Ident, TypeId>,
+
+    /// Every instance that has been asked for, by the name of its copy
+    seen: HashSet<Ident>,
+
+    /// Instances that have been asked for but not made yet,
+    /// with the generic items of the copies that asked for them
+    queue: VecDeque<(Instance, Vec<Ident>)>,
+
+    /// The generic items of the copy being made and of the copies that asked for it
+    chain: Vec<Ident>,
+
+    /// Generic items whose recursion has been reported
+    too_deep: HashSet<Ident>,
+}
+
+impl<'a> Monomorphizer<'a> {
+    /// Create a new [Monomorphizer] using the types that were checked into `table`
+    pub fn new(
+        emitter: &'a mut dyn Emitter,
+        types: &'a mut TypeInterner,
+        table: &'a TypeTable,
+        structs: &'a HashMap<Ident, StructDef>,
+        layouts: &'a mut LayoutTable,
+    ) -> Monomorphizer<'a> {
+        Monomorphizer {
+            emitter,
+            types,
+            table,
+            structs,
+            layouts,
+            generic: HashMap::new(),
+            bindings: HashMap::new(),
+            seen: HashSet::new(),
+            queue: VecDeque::new(),
+            chain: vec![],
+            too_deep: HashSet::new(),
+        }
+    }
+
+    /// Copy the items of a module with every generic item replaced by its instances.
+    /// `items` are the items of every file of the module, so that
+    /// generic items can be used from any of its files
+    pub fn monomorphize(&mut self, items: &'a [ExprNode]) -> Vec<ExprNode> {
+        for item in items {
+            match item.inner() {
+                Expr::ProcedureDefinition(def) if !def.generics().is_empty() => {
+                    self.generic.insert(def.name(), item);
+                }
+                Expr::StructDef(def) if !def.generics().is_empty() => {
+                    self.generic.insert(def.identifier().clone(), item);
+                }
+                _ => {}
+            }
+        }
+
+        let mut output = vec![];
+        for item in items {
+            let is_generic = match item.inner() {
+                Expr::ProcedureDefinition(def) => !def.generics().is_empty(),
+                Expr::StructDef(def) => !def.generics().is_empty(),
+                _ => false,
+            };
+
+            if !is_generic {
+                output.push(item.rewrite(self));
+            }
+        }
+
+        while let Some((instance, mut chain)) = self.queue.pop_front() {
+            chain.push(instance.name().clone());
+            self.chain = chain;
+
+            if let Some(item) = self.instantiate(&instance) {
+                output.push(item);
+            }
+        }
+
+        return output;
+    }
+
+    /// Make the copy of a generic item for one set of type arguments
+    fn instantiate(&mut self, instance: &Instance) -> Option<ExprNode> {
+        let (name, args) = match instance {
+            Instance::Procedure(name, args) | Instance::Struct(name, args) => (name, args),
+        };
+        let item = *self.generic.get(name)?;
+
+        let inner = match item.inner() {
+            Expr::ProcedureDefinition(def) => {
+                self.bindings = bind(def.generics().iter().map(|g| g.name()), args);
+                let copy = def.rewrite(self);
+
+                Expr::ProcedureDefinition(ProcedureDef::new(
+                    self.procedure_name(name, args),
+                    vec![],
+                    copy.parameters().into(),
+                    Box::from(copy.body().clone()),
+                    copy.ret().clone(),
+                    copy.visibility().clone(),
+                    copy.span().clone(),
+                ))
+            }
+            Expr::StructDef(def) => {
+                self.bindings = bind(def.generics().iter().map(|g| g.name()), args);
+                let copy = def.rewrite(self);
+
+                Expr::StructDef(StructDef::new(
+                    self.struct_name(name, args),
+                    vec![],
+                    copy.fields().into(),
+                    copy.methods().into(),
+                    copy.consts().into(),
+                    copy.visibility().clone(),
+                    copy.repr().clone(),
+                ))
+            }
+            _ => return None,
+        };
+
+        self.bindings.clear();
+        return Some(ExprNode::new(inner, item.span().clone()));
+    }
+
+    /// Ask for an instance to be made, if it has not been already.
+    /// Returns the name of its copy, or None when the copy would be
+    /// nested too deep within copies of the same item
+    fn request(&mut self, instance: Instance, span: &Span) -> Option<Ident> {
+        let name = instance.name();
+        let nested = self.chain.iter().filter(|item| *item == name).count();
+        if nested >= RECURSION_LIMIT {
+            if self.too_deep.insert(name.clone()) {
+                self.emitter.emit_err(ViperError::RecursiveInstantiation {
+                    name: name.clone(),
+                    limit: RECURSION_LIMIT,
+                    span: span.clone(),
+                });
+            }
+            return None;
+        }
+
+        let mangled = match &instance {
+            Instance::Procedure(name, args) => self.procedure_name(name, args),
+            Instance::Struct(name, args) => self.struct_name(name, args),
+        };
+
+        if self.seen.insert(mangled.clone()) {
+            self.queue.push_back((instance, self.chain.clone()));
+        }
+        return Some(mangled);
+    }
+
+    /// Get the name of the copy of a generic procedure
+    /// `max<i32>`
+    fn procedure_name(&self, name: &Ident, args: &[TypeId]) -> Ident {
+        let args: Vec<String> = args.iter().map(|arg| self.types.display(*arg).to_string()).collect();
+        return format!("{name}<{}>", args.join(", "));
+    }
+
+    /// Get the name of the copy of a generic struct
+    /// `Pair<i32, f64>`
+    fn struct_name(&mut self, name: &Ident, args: &[TypeId]) -> Ident {
+        let id = self.types.intern(TypeKind::Struct(name.clone(), args.to_vec()));
+        return self.types.display(id).to_string();
+    }
+
+    /// Get the name of the copy of a generic struct, asking for it to be made
+    fn use_struct(&mut self, name: &Ident, args: &[TypeId], span: &Span) -> Ident {
+        return self.request(Instance::Struct(name.clone(), args.to_vec()), span)
+            .unwrap_or_else(|| name.clone());
+    }
+
+    /// Write a type out the way it would appear in the source,
+    /// naming the copies of generic structs
+    fn syntax(&mut self, id: TypeId, span: &Span) -> Type {
+        let named = |name: String| Type::Concrete {
+            name: Path::from_ident(name, span.clone()),
+            args: vec![],
+        };
+
+        match self.types.kind(id).clone() {
+            TypeKind::Void => Type::Void,
+            TypeKind::Bool
+            | TypeKind::Byte
+            | TypeKind::Integer(_, _)
+            | TypeKind::Float(_) => named(self.types.display(id).to_string()),
+            TypeKind::Pointer { mutable, pointee } => {
+                Type::Pointer { mutable, inner: Arc::from(self.syntax(pointee, span)) }
+            }
+            TypeKind::Array { element, length } => {
+                Type::Array { element: Arc::from(self.syntax(element, span)), length: ArrayLength::Literal(length) }
+            }
+            TypeKind::Slice(element) => Type::Slice(Arc::from(self.syntax(element, span))),
+            TypeKind::Optional(inner) => Type::Optional(Arc::from(self.syntax(inner, span))),
+            TypeKind::Result { ok, err } => {
+                Type::Concrete {
+                    name: Path::from_ident("Result".to_string(), span.clone()),
+                    args: vec![self.syntax(ok, span), self.syntax(err, span)],
+                }
+            }
+            TypeKind::Tuple(elements) => {
+                Type::Tuple(elements.iter().map(|e| self.syntax(*e, span)).collect())
+            }
+            TypeKind::Function { params, ret } => {
+                Type::Function {
+                    params: params.iter().map(|p| self.syntax(*p, span)).collect(),
+                    return_type: Arc::from(self.syntax(ret, span)),
+                }
+            }
+            TypeKind::Struct(name, args) if args.is_empty() => named(name),
+            TypeKind::Struct(name, args) => named(self.use_struct(&name, &args, span)),
+            TypeKind::Param(name) | TypeKind::Enum(name) => named(name),
+            TypeKind::Dyn(interface) => Type::Dyn(Path::from_ident(interface, span.clone())),
+        }
+    }
+
+    /// Whether a generic item is a struct
+    fn is_struct(&self, name: &Ident) -> bool {
+        return matches!(self.generic.get(name).map(|item| item.inner()), Some(Expr::StructDef(_)));
+    }
+
+    /// Get the concrete type arguments of a use of a generic item
+    fn concrete_args(&mut self, args: &[TypeId]) -> Vec<TypeId> {
+        return args.iter().map(|arg| self.types.substitute(*arg, &self.bindings)).collect();
+    }
+}
+
+impl Rewriter for Monomorphizer<'_> {
+    /// Put the type arguments in place of the generic parameters,
+    /// and name the copies of the generic structs that are used
+    fn rewrite_type(&mut self, ty: &Type) -> Type {
+        match ty {
+            Type::Concrete { name, args } => {
+                if name.is_ident() && args.is_empty() {
+                    if let Some(id) = self.bindings.get(name.last().name()).copied() {
+                        return self.syntax(id, &name.span());
+                    }
+                }
+
+                if args.is_empty() {
+                    return ty.clone();
+                }
+
+                let bindings = self.bindings.clone();
+                match self.types.lower_with(ty, &bindings) {
+                    Some(id) => self.syntax(id, &name.span()),
+                    None => ty.clone(),
+                }
+            }
+            Type::Procedure { name, params, return_type } => {
+                Type::Procedure {
+                    name: name.clone(),
+                    params: params.iter().map(|p| self.rewrite_type(p)).collect(),
+                    return_type: Arc::from(self.rewrite_type(return_type)),
+                }
+            }
+            Type::Function { params, return_type } => {
+                Type::Function {
+                    params: params.iter().map(|p| self.rewrite_type(p)).collect(),
+                    return_type: Arc::from(self.rewrite_type(return_type)),
+                }
+            }
+            Type::Pointer { mutable, inner } => {
+                Type::Pointer { mutable: *mutable, inner: Arc::from(self.rewrite_type(inner)) }
+            }
+            Type::Reference { mutable, inner } => {
+                Type::Reference { mutable: *mutable, inner: Arc::from(self.rewrite_type(inner)) }
+            }
+            Type::Array { element, length } => {
+                Type::Array { element: Arc::from(self.rewrite_type(element)), length: length.clone() }
+            }
+            Type::Slice(element) => Type::Slice(Arc::from(self.rewrite_type(element))),
+            Type::Optional(inner) => Type::Optional(Arc::from(self.rewrite_type(inner))),
+            Type::Tuple(elements) => Type::Tuple(elements.iter().map(|e| self.rewrite_type(e)).collect()),
+            Type::Dyn(_) | Type::Void | Type::Inferred => ty.clone(),
+        }
+    }
+
+    /// Turn an overloaded operator into a call of the method that overloads it.
+    /// The call stands in for the operator, so it keeps the operator's id
+    /// `a + b`  -> `a.op_add(b)`
+    /// `a != b` -> `!a.op_eq(b)`
+    /// `a < b`  -> `a.op_cmp(b) < 0`
+    /// `a += b` -> `a = a.op_add(b)`
+    /// `v[i]`   -> `v.op_index(i)`
+    ///
+    /// Layout questions are replaced by their values
+    /// `sizeof(T)` with `T` -> `i32` gives `4`
+    fn replace(&mut self, expr: &ExprNode) -> Option<ExprNode> {
+        if let Expr::SizeOf(ty) | Expr::AlignOf(ty) | Expr::OffsetOf(ty, _) = expr.inner() {
+            let bindings = self.bindings.clone();
+            let ty = self.types.lower_with(ty, &bindings)?;
+            let value = self.layouts.query(self.types, self.structs, expr.inner(), ty).ok()?;
+            return Some(expr.with_inner(Expr::Integer(value)));
+        }
+
+        let method = self.table.operator(expr.id())?.clone();
+        let span = expr.span().clone();
+        let call = |receiver: ExprNode, args: Vec<ExprNode>| {
+            let args = args.into_iter().map(Box::from).collect();
+            Expr::MethodCall(Box::from(MethodCall::new(receiver, method.clone(), args, span.clone())))
+        };
+
+        let inner = match expr.inner() {
+            Expr::BinaryOperation(op, lhs, rhs) => {
+                let result = call(lhs.rewrite(self), vec![rhs.rewrite(self)]);
+                match op {
+                    BinaryOperator::NotEqualTo => {
+                        Expr::UnaryOperation(UnaryOperator::LogicalNot, Box::from(ExprNode::new(result, span.clone())))
+                    }
+                    BinaryOperator::LessThan
+                    | BinaryOperator::GreaterThan
+                    | BinaryOperator::LessThanEqualTo
+                    | BinaryOperator::GreaterThanEqualTo => {
+                        let zero = ExprNode::new(Expr::Integer(0), span.clone());
+                        Expr::BinaryOperation(*op, Box::from(ExprNode::new(result, span.clone())), Box::from(zero))
+                    }
+                    _ => result,
+                }
+            }
+            Expr::Assignment(assignment) => {
+                let target = assignment.target().rewrite(self);
+                let value = call(target.clone(), vec![assignment.value().rewrite(self)]);
+                Expr::Assignment(Assignment::new(target, None, ExprNode::new(value, span.clone())))
+            }
+            Expr::Index(object, index) => call(object.rewrite(self), vec![index.rewrite(self)]),
+            Expr::UnaryOperation(_, operand) => call(operand.rewrite(self), vec![]),
+            _ => return None,
+        };
+
+        return Some(expr.with_inner(inner));
+    }
+
+    /// Point calls of generic procedures, and static methods and
+    /// initializers of generic structs, at the copy they need
+    /// `max(a, b)` -> `max<i32>(a, b)`
+    /// `Pair::new(1, 2.5)` -> `Pair<i32, f64>::new(1, 2.5)`
+    fn rewrite_path(&mut self, id: NodeId, path: &Path) -> Path {
+        let first = &path.segments()[0];
+        let name = first.name().to_string();
+
+        let args = match self.table.args(id) {
+            Some(args) => args.to_vec(),
+
+            // An initializer of a generic struct has the struct's type arguments in its type
+            None => match self.table.get(id).map(|ty| self.types.kind(ty)) {
+                Some(TypeKind::Struct(ty, args)) if *ty == name && path.is_ident() => args.clone(),
+                _ => return path.clone(),
+            },
+        };
+
+        if args.is_empty() || !self.generic.contains_key(&name) {
+            return path.clone();
+        }
+
+        let args = self.concrete_args(&args);
+        let instance = match path.is_ident() && !self.is_struct(&name) {
+            true => Instance::Procedure(name, args),
+            false => Instance::Struct(name, args),
+        };
+
+        let mangled = match self.request(instance, first.span()) {
+            Some(mangled) => mangled,
+            None => return path.clone(),
+        };
+
+        let mut segments = path.segments().to_vec();
+        segments[0] = PathSegment::new(mangled, first.span().clone());
+        return Path::new(segments);
+    }
+}
+
+/// Map the names of generic parameters onto their type arguments
+fn bind<'n>(names: impl Iterator<Item = &'n Ident>, args: &[TypeId]) -> HashMap<Ident, TypeId> {
+    return names.cloned().zip(args.iter().copied()).collect();
+}
@@ -0,0 +1,365 @@
+use std::{cell::RefCell, collections::HashMap, sync::Arc};
+
+use viper_ast::{Binding, Expr, ExprNode, Ident, NodeId, ProcedureKind, StructDef, UnaryOperator};
+use viper_core::{
+    emitter::Emitter,
+    error::ViperError,
+    scope::Scope,
+    span::Span,
+    symbol::SymbolKind,
+    types::{TypeInterner, TypeKind},
+};
+
+use crate::{interface::ImplTable, typecheck::{receiver_struct, TypeTable}};
+
+/// Whether a variable is mutable and where it is declared
+#[derive(Clone)]
+struct VariableInfo {
+    mutable: bool,
+
+    /// Whether the variable gets a value where it is declared.
+    /// Giving `let x: i32;` its first value is not a change
+    has_value: bool,
+
+    declared: Span,
+}
+
+/// The ways that a variable can be changed
+#[derive(Clone, Copy, PartialEq)]
+enum Mutation {
+    /// `x = 1`, `x += 1`
+    Assign,
+
+    /// `&mut x`, or calling a `mut method` on `x`
+    BorrowMut,
+}
+
+/// Checks that only mutable variables get changed.
+///
+/// Variables can only be assigned to, borrowed with `&mut` or have a
+/// `mut method` called on them when they are declared with `let mut`,
+/// or are `mut` parameters. An overloaded operator calls its method on
+/// its first operand, so that has to be mutable for a `mut method` too.
+/// Writing through a pointer `*p = 1` does not change `p` itself,
+/// so only the pointer has to be `*mut` for it.
+pub struct MutabilityChecker<'a> {
+    emitter: &'a mut dyn Emitter,
+
+    /// Every struct defined in the module
+    structs: &'a HashMap<Ident, StructDef>,
+
+    /// The interfaces of the module, for methods called through them
+    impls: &'a ImplTable,
+
+    /// The scope of the file being checked.
+    /// Top-level variables are looked up here
+    file_scope: Arc<RefCell<Scope>>,
+
+    /// Types of the checked expressions, used to tell
+    /// whether a place is behind a pointer
+    types: &'a TypeInterner,
+    table: &'a TypeTable,
+
+    /// Local variables visible at the current point, innermost scope last
+    locals: Vec<HashMap<Ident, VariableInfo>>,
+}
+
+impl<'a> MutabilityChecker<'a> {
+    /// Create a new [MutabilityChecker] for the file with the scope `file_scope`.
+    /// This runs after type checking, which fills in `table`
+    pub fn new(
+        emitter: &'a mut dyn Emitter,
+        structs: &'a HashMap<Ident, StructDef>,
+        impls: &'a ImplTable,
+        file_scope: Arc<RefCell<Scope>>,
+        types: &'a TypeInterner,
+        table: &'a TypeTable,
+    ) -> MutabilityChecker<'a> {
+        MutabilityChecker {
+            emitter,
+            structs,
+            impls,
+            file_scope,
+            types,
+            table,
+            locals: vec![],
+        }
+    }
+
+    /// Check the top-level items of a file
+    pub fn check_items(&mut self, items: &[ExprNode]) {
+        for item in items {
+            match item.inner() {
+                // Top-level variables are already in the file's scope
+                Expr::Let(init) => {
+                    for value in init.values() {
+                        self.check_expr(value);
+                    }
+                }
+                _ => self.check_expr(item),
+            }
+        }
+    }
+
+    fn check_expr(&mut self, expr: &ExprNode) {
+        match expr.inner() {
+            Expr::Let(init) => {
+                for value in init.values() {
+                    self.check_expr(value);
+                }
+
+                if let Some(scope) = self.locals.last_mut() {
+                    for binding in init.bindings() {
+                        scope.insert(binding.name().clone(), VariableInfo {
+                            mutable: init.is_mutable(),
+                            has_value: !init.values().is_empty(),
+                            declared: binding.span().clone(),
+                        });
+                    }
+                }
+            }
+            Expr::Assignment(assignment) => {
+                self.check_place(assignment.target(), Mutation::Assign);
+                self.check_expr(assignment.value());
+            }
+            Expr::UnaryOperation(UnaryOperator::AddressOfMut, operand) => {
+                self.check_place(operand, Mutation::BorrowMut);
+            }
+            Expr::MethodCall(call) => {
+                self.check_receiver(expr.id(), call.receiver(), Some(call.name()));
+                for arg in call.arguments() {
+                    self.check_expr(arg);
+                }
+            }
+
+            // Overloaded operators call their method on the first operand
+            Expr::BinaryOperation(_, lhs, rhs) | Expr::Index(lhs, rhs) => {
+                self.check_receiver(expr.id(), lhs, self.table.operator(expr.id()));
+                self.check_expr(rhs);
+            }
+            Expr::UnaryOperation(_, operand) => {
+                self.check_receiver(expr.id(), operand, self.table.operator(expr.id()));
+            }
+            Expr::CodeBlock(block) => {
+                self.locals.push(HashMap::new());
+                for expr in block.exprs() {
+                    self.check_expr(expr);
+                }
+                self.locals.pop();
+            }
+            // The variable of an `if let` is only in scope for the body
+            Expr::If(conditional) => {
+                self.locals.push(HashMap::new());
+                if let Some(condition) = conditional.condition() {
+                    self.check_expr(&condition);
+                }
+                self.check_expr(conditional.body());
+                self.locals.pop();
+
+                if let Some(else_clause) = conditional.else_clause() {
+                    self.check_expr(&else_clause);
+                }
+            }
+            Expr::ProcedureDefinition(def) => {
+                let is_lambda = def.kind() == &ProcedureKind::Lambda;
+                self.check_body(def.parameters(), def.body(), is_lambda);
+            }
+            Expr::StructDef(def) => {
+                for method in def.methods() {
+                    let params: Vec<Binding> = def.receiver(method).into_iter()
+                        .chain(method.parameters().iter().cloned())
+                        .collect();
+                    self.check_body(&params, method.body(), false);
+                }
+            }
+            Expr::ImplDef(def) => {
+                for method in def.methods() {
+                    let params: Vec<Binding> = def.receiver(method).into_iter()
+                        .chain(method.parameters().iter().cloned())
+                        .collect();
+                    self.check_body(&params, method.body(), false);
+                }
+            }
+            _ => {
+                expr.for_each_child(&mut |child| self.check_expr(child));
+            }
+        }
+    }
+
+    /// Check a body with its parameters in scope.
+    /// Lambdas can see the locals of the procedure they are in
+    fn check_body(&mut self, params: &[Binding], body: &ExprNode, sees_locals: bool) {
+        let outer_locals = match sees_locals {
+            true => None,
+            false => Some(std::mem::take(&mut self.locals)),
+        };
+
+        self.locals.push(
+            params.iter()
+                .map(|p| (p.ident().clone(), VariableInfo {
+                    mutable: p.is_mutable(),
+                    has_value: true,
+                    declared: p.span().clone(),
+                }))
+                .collect()
+        );
+        self.check_expr(body);
+        self.locals.pop();
+
+        if let Some(outer_locals) = outer_locals {
+            self.locals = outer_locals;
+        }
+    }
+
+    /// Check a place in memory that is being changed.
+    /// `x`, `*p`, `point.x`, `self.position.x`
+    fn check_place(&mut self, target: &ExprNode, mutation: Mutation) {
+        match target.inner() {
+            Expr::Identifier(name) => {
+                self.require_mutable(name, target.span(), mutation);
+            }
+            Expr::UnaryOperation(UnaryOperator::Deref, pointer) => {
+                self.require_mut_pointer(pointer);
+                self.check_expr(pointer);
+            }
+            // Elements belong to the array they are in. Indexing a
+            // struct calls its `op_index`, which gives a value and not a place
+            Expr::Index(object, index) if self.table.operator(target.id()).is_none() => {
+                self.check_place(object, mutation);
+                self.check_expr(index);
+            }
+            Expr::MemberFieldAccess(field) => {
+                // Fields of a struct behind a pointer belong to the pointee,
+                // otherwise they belong to the struct value itself
+                match self.is_pointer(field.object()) {
+                    true => {
+                        self.require_mut_pointer(field.object());
+                        self.check_expr(field.object());
+                    }
+                    false => self.check_place(field.object(), mutation),
+                }
+            }
+            _ => {
+                // Borrowing a temporary value is fine, it has no other owner
+                if mutation == Mutation::Assign {
+                    self.emitter.emit_err(ViperError::InvalidAssignmentTarget {
+                        span: target.span().clone(),
+                    });
+                }
+                self.check_expr(target);
+            }
+        }
+    }
+
+    /// Report changing a value through a pointer that is not `*mut`
+    fn require_mut_pointer(&mut self, pointer: &ExprNode) {
+        let ty = match self.table.get(pointer.id()) {
+            Some(ty) => ty,
+            None => return,
+        };
+
+        if let TypeKind::Pointer { mutable: false, .. } = self.types.kind(ty) {
+            self.emitter.emit_err(ViperError::ImmutablePointee {
+                ty: self.types.display(ty).to_string(),
+                span: pointer.span().clone(),
+            });
+        }
+    }
+
+    /// Whether an expression was checked to have a pointer type
+    fn is_pointer(&self, expr: &ExprNode) -> bool {
+        return self.table.get(expr.id())
+            .map(|ty| self.types.is_pointer(ty))
+            .unwrap_or(false);
+    }
+
+    /// Check the receiver of a call of the method `method`.
+    /// A `mut method` gets a `*mut` pointer to its receiver.
+    /// Receivers that already are pointers are passed as-is
+    fn check_receiver(&mut self, id: NodeId, receiver: &ExprNode, method: Option<&Ident>) {
+        let is_mutating = method.is_some_and(|method| self.is_mutating(id, receiver, method));
+
+        match (is_mutating, self.is_pointer(receiver)) {
+            (true, true) => {
+                self.require_mut_pointer(receiver);
+                self.check_expr(receiver);
+            }
+            (true, false) => self.check_place(receiver, Mutation::BorrowMut),
+            (false, _) => self.check_expr(receiver),
+        }
+    }
+
+    /// Whether a method called on `receiver` is a `mut method`.
+    /// `id` is the id of the call, which is used to find calls made through an interface
+    fn is_mutating(&self, id: NodeId, receiver: &ExprNode, method: &Ident) -> bool {
+        if let Some((interface, slot)) = self.table.dispatch(id) {
+            return self.impls.interface(interface)
+                .map(|interface| interface.methods()[slot].is_mutating())
+                .unwrap_or(false);
+        }
+
+        let def = self.table.get(receiver.id())
+            .and_then(|ty| receiver_struct(self.types, self.structs, ty));
+
+        return def
+            .and_then(|def| def.methods().iter().find(|m| m.name() == method))
+            .map(|method| method.is_mutating())
+            .unwrap_or(false);
+    }
+
+    /// Report a mutation of a variable that is not mutable
+    fn require_mutable(&mut self, name: &Ident, span: &Span, mutation: Mutation) {
+        let info = match self.lookup(name) {
+            Some(Some(info)) => info,
+
+            // Procedures are not variables, so they cannot be changed at all
+            Some(None) => {
+                if mutation == Mutation::Assign {
+                    self.emitter.emit_err(ViperError::InvalidAssignmentTarget {
+                        span: span.clone(),
+                    });
+                }
+                return;
+            }
+
+            // Undefined names have already been reported
+            None => return,
+        };
+
+        // Variables declared without a value can be assigned once.
+        // The initialization checker reports assigning them again
+        if info.mutable || (mutation == Mutation::Assign && !info.has_value) {
+            return;
+        }
+
+        let (name, span, declared) = (name.clone(), span.clone(), info.declared);
+        match mutation {
+            Mutation::Assign => {
+                self.emitter.emit_err(ViperError::AssignToImmutable { name, span, declared });
+            }
+            Mutation::BorrowMut => {
+                self.emitter.emit_err(ViperError::BorrowImmutableAsMutable { name, span, declared });
+            }
+        }
+    }
+
+    /// Look up a name, locals first and then the items of the module.
+    /// The inner value is None if the name is not a variable
+    fn lookup(&self, name: &Ident) -> Option<Option<VariableInfo>> {
+        if let Some(info) = self.locals.iter().rev().find_map(|scope| scope.get(name)) {
+            return Some(Some(info.clone()));
+        }
+
+        let symbol = self.file_scope.borrow().lookup(name)?;
+        match symbol.kind() {
+            SymbolKind::Variable => Some(Some(VariableInfo {
+                mutable: symbol.is_mutable(),
+
+                // Top-level variables are zeroed when they have no value
+                has_value: true,
+                declared: symbol.span().clone(),
+            })),
+            _ => Some(None),
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use viper_ast::{BinaryOperator, UnaryOperator};
+use viper_core::{error::ViperError, span::Span, types::{TypeId, TypeInterner}};
+
+/// The method that indexing into a struct value calls
+/// `values[i]` -> `values.op_index(i)`
+pub const INDEX_METHOD: &str = "op_index";
+
+/// Get the method that a struct defines to overload a binary operator
+/// `a + b`  -> `a.op_add(b)`
+/// `a != b` -> `!a.op_eq(b)`
+/// `a < b`  -> `a.op_cmp(b) < 0`
+///
+/// `==` and `!=` share `op_eq`, and the comparisons share `op_cmp`, which
+/// returns a negative, zero or positive `i32` like C's `strcmp`.
+/// `&&` and `||` can not be overloaded, since they only evaluate
+/// their right side when they need to
+pub fn binary_method(op: BinaryOperator) -> Option<&'static str> {
+    let method = match op {
+        BinaryOperator::Plus => "op_add",
+        BinaryOperator::Minus => "op_sub",
+        BinaryOperator::Times => "op_mul",
+        BinaryOperator::Divide => "op_div",
+        BinaryOperator::Modulo => "op_mod",
+        BinaryOperator::BinAnd => "op_bitand",
+        BinaryOperator::BinOr => "op_bitor",
+        BinaryOperator::BinXor => "op_bitxor",
+        BinaryOperator::BShiftLeft => "op_shl",
+        BinaryOperator::BShiftRight => "op_shr",
+        BinaryOperator::EqualTo | BinaryOperator::NotEqualTo => "op_eq",
+        BinaryOperator::LessThan
+        | BinaryOperator::GreaterThan
+        | BinaryOperator::LessThanEqualTo
+        | BinaryOperator::GreaterThanEqualTo => "op_cmp",
+        BinaryOperator::LogicalAnd
+        | BinaryOperator::LogicalOr
+        | BinaryOperator::InvalidBinary => return None,
+    };
+
+    return Some(method);
+}
+
+/// Get the method that a struct defines to overload a unary operator
+/// `-a` -> `a.op_neg()`
+/// `~a` -> `a.op_bitnot()`
+pub fn unary_method(op: UnaryOperator) -> Option<&'static str> {
+    match op {
+        UnaryOperator::Minus => Some("op_neg"),
+        UnaryOperator::BinaryNot => Some("op_bitnot"),
+        _ => None,
+    }
+}
+
+/// Get the number of operands that an operator method takes besides its
+/// receiver, and the type it has to return when that is fixed.
+/// None if `name` is not the name of an operator method
+fn operator_signature(name: &str) -> Option<(usize, Option<TypeId>)> {
+    match name {
+        "op_neg" | "op_bitnot" => Some((0, None)),
+        "op_eq" => Some((1, Some(TypeId::BOOL))),
+        "op_cmp" => Some((1, Some(TypeId::I32))),
+        "op_add" | "op_sub" | "op_mul" | "op_div" | "op_mod" | "op_bitand" | "op_bitor" | "op_bitxor"
+        | "op_shl" | "op_shr" | INDEX_METHOD => Some((1, None)),
+        _ => None,
+    }
+}
+
+/// Whether a method can be used for the operator it is named after.
+/// It has to be called on a value, so it can not be static, and it takes
+/// one parameter for each operand other than the receiver.
+/// `ret` is the type the method returns, if it could be worked out
+pub fn fits_operator(name: &str, is_static: bool, params: usize, ret: Option<TypeId>) -> bool {
+    let (operands, returns) = match operator_signature(name) {
+        Some(signature) => signature,
+        None => return false,
+    };
+
+    let returns_expected = match (returns, ret) {
+        (Some(returns), Some(ret)) => returns == ret,
+        _ => true,
+    };
+    return !is_static && params == operands && returns_expected;
+}
+
+/// Check that a method named like an operator method can be used for its operator.
+/// Methods with any other name are always fine
+pub fn check_operator_method(
+    types: &TypeInterner,
+    name: &str,
+    is_static: bool,
+    params: usize,
+    ret: Option<TypeId>,
+    span: &Span,
+) -> Result<(), ViperError> {
+    let (operands, returns) = match operator_signature(name) {
+        Some(signature) => signature,
+        None => return Ok(()),
+    };
+
+    if fits_operator(name, is_static, params, ret) {
+        return Ok(());
+    }
+
+    let params = vec!["_"; operands].join(", ");
+    let ret = returns.map(|ty| types.display(ty).to_string()).unwrap_or_else(|| String::from("_"));
+    return Err(ViperError::InvalidOperatorMethod {
+        method: name.to_string(),
+        expected: format!("method {name}({params}): {ret}"),
+        span: span.clone(),
+    });
+}
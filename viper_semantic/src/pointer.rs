@@ -0,0 +1,117 @@
+use viper_ast::{BinaryOperator, UnaryOperator};
+use viper_core::{
+    error::{InvalidPointerArithmetic, ViperError},
+    span::Span,
+    types::{TypeId, TypeInterner, TypeKind},
+};
+
+/// The type of the difference between two pointers
+/// `ptr_a - ptr_b`
+pub const POINTER_DIFFERENCE: TypeId = TypeId::I64;
+
+/// Get the type that a pointer unary operator produces
+/// `&x`     -> `*T`
+/// `&mut x` -> `*mut T`
+/// `*p`     -> `T`
+///
+/// Returns None if the operator does not deal with pointers
+pub fn pointer_unary(
+    types: &mut TypeInterner,
+    op: UnaryOperator,
+    operand: TypeId,
+    span: &Span,
+) -> Option<Result<TypeId, ViperError>> {
+    match op {
+        UnaryOperator::AddressOf => {
+            Some(Ok(types.pointer(operand, false)))
+        }
+        UnaryOperator::AddressOfMut => {
+            Some(Ok(types.pointer(operand, true)))
+        }
+        UnaryOperator::Deref => {
+            Some(dereference(types, operand, span))
+        }
+        _ => None,
+    }
+}
+
+/// Get the type of the value that a pointer points to.
+/// `void` and `*dyn` pointers have no sized pointee to read, so they cannot be dereferenced
+fn dereference(types: &TypeInterner, operand: TypeId, span: &Span) -> Result<TypeId, ViperError> {
+    match types.kind(operand) {
+        TypeKind::Pointer { mutable: _, pointee } if has_size(types, *pointee) => {
+            return Ok(*pointee);
+        }
+        _ => {
+            return Err(ViperError::InvalidDereference {
+                ty: types.display(operand).to_string(),
+                span: span.clone(),
+            });
+        }
+    }
+}
+
+/// Get the type that pointer arithmetic produces
+/// `ptr + n`, `n + ptr`, `ptr - n` -> the pointer type, offset by `n` elements
+/// `ptr_a - ptr_b`                 -> the number of elements between the pointers
+///
+/// Offsets are counted in elements of the pointee, not bytes, so the
+/// pointee must have a size. This rules out `void` and `*dyn` pointers.
+///
+/// Returns None if neither operand is a pointer or the operator is a
+/// comparison, which is not arithmetic.
+pub fn pointer_arithmetic(
+    types: &TypeInterner,
+    op: BinaryOperator,
+    lhs: TypeId,
+    rhs: TypeId,
+    span: &Span,
+) -> Option<Result<TypeId, ViperError>> {
+    if !types.is_pointer(lhs) && !types.is_pointer(rhs) {
+        return None;
+    }
+
+    match op {
+        BinaryOperator::LessThan
+        | BinaryOperator::GreaterThan
+        | BinaryOperator::EqualTo
+        | BinaryOperator::NotEqualTo
+        | BinaryOperator::LessThanEqualTo
+        | BinaryOperator::GreaterThanEqualTo => return None,
+        _ => {}
+    }
+
+    let result = match (op, types.kind(lhs), types.kind(rhs)) {
+        (BinaryOperator::Plus | BinaryOperator::Minus, TypeKind::Pointer { mutable: _, pointee }, _)
+            if types.is_integer(rhs) && has_size(types, *pointee) => {
+            Some(lhs)
+        }
+        (BinaryOperator::Plus, _, TypeKind::Pointer { mutable: _, pointee })
+            if types.is_integer(lhs) && has_size(types, *pointee) => {
+            Some(rhs)
+        }
+        (
+            BinaryOperator::Minus,
+            TypeKind::Pointer { mutable: _, pointee: a },
+            TypeKind::Pointer { mutable: _, pointee: b },
+        ) if a == b && has_size(types, *a) => {
+            Some(POINTER_DIFFERENCE)
+        }
+        _ => None,
+    };
+
+    match result {
+        Some(ty) => Some(Ok(ty)),
+        None => Some(Err(ViperError::InvalidPointerArithmetic(Box::new(InvalidPointerArithmetic {
+            op: op.to_string(),
+            lhs: types.display(lhs).to_string(),
+            rhs: types.display(rhs).to_string(),
+            span: span.clone(),
+        })))),
+    }
+}
+
+/// Whether a pointer's pointee has a size that is known at compile time
+fn has_size(types: &TypeInterner, pointee: TypeId) -> bool {
+    return pointee != TypeId::VOID && !matches!(types.kind(pointee), TypeKind::Dyn(_));
+}
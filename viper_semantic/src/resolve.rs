@@ -0,0 +1,777 @@
+use std::{cell::RefCell, collections::HashMap, sync::Arc};
+
+use crate::consteval::{ConstTable, ConstValue};
+
+use viper_ast::{
+    Binding, Expr, ExprNode, GenericParam, Ident, ImplDef, InterfaceDef, ObjInit, Path, ProcedureDef, StructDef,
+    StructMethod,
+};
+use viper_core::{
+    _type::{ArrayLength, Type},
+    emitter::Emitter,
+    error::ViperError,
+    scope::Scope,
+    source::SourceModule,
+    span::Span,
+    symbol::{Symbol, SymbolKind},
+    types::TypeInterner,
+};
+
+/// Declare the top-level items of a file into the module's scope.
+///
+/// Top-level items are visible throughout the whole module regardless of
+/// the order they are defined in, so every file of a module gets declared
+/// before any of them are resolved.
+pub fn declare_items(
+    scope: &Arc<RefCell<Scope>>,
+    items: &[ExprNode],
+    structs: &mut HashMap<Ident, StructDef>,
+    procedures: &mut HashMap<Ident, ProcedureDef>,
+    emitter: &mut dyn Emitter,
+) {
+    for item in items {
+        let symbol = match item.inner() {
+            Expr::Let(init) => {
+                for symbol in init.to_symbols() {
+                    declare(scope, symbol, emitter);
+                }
+                continue;
+            }
+            Expr::ProcedureDefinition(def) => {
+                procedures.insert(def.name(), def.clone());
+                def.to_symbol()
+            }
+            Expr::Const(def) => def.to_symbol(),
+            Expr::StructDef(def) => {
+                structs.insert(def.identifier().clone(), def.clone());
+                struct_symbol(def, item.span())
+            }
+            Expr::InterfaceDef(def) => interface_symbol(def),
+            _ => continue,
+        };
+
+        declare(scope, symbol, emitter);
+    }
+}
+
+/// Create the symbol that names a struct type
+fn struct_symbol(def: &StructDef, span: &Span) -> Symbol {
+    Symbol::new(
+        Arc::from(SourceModule::new_dummy()),
+        Arc::from(Type::Concrete {
+            name: Path::from_ident(def.identifier().clone(), span.clone()),
+            args: vec![],
+        }),
+        def.identifier().clone(),
+        span.clone(),
+        false,
+        SymbolKind::Struct,
+    )
+}
+
+/// Create the symbol that names an interface
+fn interface_symbol(def: &InterfaceDef) -> Symbol {
+    Symbol::new(
+        Arc::from(SourceModule::new_dummy()),
+        Arc::from(Type::Dyn(Path::from_ident(def.name().clone(), def.span().clone()))),
+        def.name().clone(),
+        def.span().clone(),
+        false,
+        SymbolKind::Interface,
+    )
+}
+
+/// Modules that come with the compiler and can be used without being declared
+const BUILTIN_MODULES: [&str; 1] = ["std"];
+
+/// Create the symbol of a module that comes with the compiler, if `name` is one
+fn builtin_module(name: &Ident, span: &Span) -> Option<Symbol> {
+    if !BUILTIN_MODULES.contains(&name.as_str()) {
+        return None;
+    }
+
+    return Some(Symbol::new(
+        Arc::from(SourceModule::new_dummy()),
+        Arc::from(Type::Void),
+        name.clone(),
+        span.clone(),
+        false,
+        SymbolKind::Module,
+    ));
+}
+
+/// Add a symbol to a scope, reporting it if the
+/// scope already has a symbol with the same name
+fn declare(scope: &Arc<RefCell<Scope>>, symbol: Symbol, emitter: &mut dyn Emitter) {
+    let key = symbol.get_key();
+    if let Some(previous) = scope.borrow().lookup_local(&key) {
+        emitter.emit_err(ViperError::DuplicateDefinition {
+            name: key,
+            span: symbol.span().clone(),
+            previous: previous.span().clone(),
+        });
+        return;
+    }
+
+    scope.borrow_mut().add_symbol(key, symbol);
+}
+
+/// Resolves every name used in a file to the item it refers to.
+///
+/// Names are looked up through the chain of scopes from the innermost
+/// block out to the file and then the module. Local variables are
+/// declared in the order they appear, so a variable cannot be used
+/// before its `let`.
+pub struct NameResolver<'a> {
+    emitter: &'a mut dyn Emitter,
+
+    /// Every struct defined in the module
+    structs: &'a HashMap<Ident, StructDef>,
+
+    /// Every constant of the module, used to check array lengths
+    consts: &'a ConstTable,
+
+    /// The innermost scope at the current point
+    scope: Arc<RefCell<Scope>>,
+
+    /// Variables declared later in each enclosing block, innermost last.
+    /// Used to tell a use before declaration apart from an undefined name
+    pending: Vec<HashMap<Ident, Span>>,
+
+    /// The static method being resolved, if any.
+    /// Used to explain why `self` cannot be found
+    static_method: Option<(Ident, Span)>,
+
+    /// Generic parameters of the items being resolved, which can be used as types
+    generics: Vec<Ident>,
+}
+
+impl<'a> NameResolver<'a> {
+    /// Create a new [NameResolver] that resolves names starting from the file's scope
+    pub fn new(
+        emitter: &'a mut dyn Emitter,
+        structs: &'a HashMap<Ident, StructDef>,
+        consts: &'a ConstTable,
+        file_scope: Arc<RefCell<Scope>>,
+    ) -> NameResolver<'a> {
+        NameResolver {
+            emitter,
+            structs,
+            consts,
+            scope: file_scope,
+            pending: vec![],
+            static_method: None,
+            generics: vec![],
+        }
+    }
+
+    /// Resolve the names used in the top-level items of a file
+    pub fn resolve_items(&mut self, items: &[ExprNode]) {
+        for item in items {
+            match item.inner() {
+                // Top-level variables are already declared, so only
+                // their types and values need resolving
+                Expr::Let(init) => {
+                    if let Some(dtype) = init.dtype() {
+                        self.resolve_type(dtype);
+                    }
+                    for value in init.values() {
+                        self.resolve_expr(value);
+                    }
+                }
+                Expr::Const(def) => {
+                    self.resolve_type(def.ty());
+                    self.resolve_expr(def.value());
+                }
+                _ => self.resolve_expr(item),
+            }
+        }
+    }
+
+    fn resolve_expr(&mut self, expr: &ExprNode) {
+        match expr.inner() {
+            Expr::Identifier(name) => {
+                match &self.static_method {
+                    Some((method, declared)) if name == "self" => {
+                        self.emitter.emit_err(ViperError::SelfInStaticMethod {
+                            method: method.clone(),
+                            span: expr.span().clone(),
+                            declared: declared.clone(),
+                        });
+                    }
+                    _ => {
+                        self.resolve_name(name, expr.span());
+                    }
+                }
+            }
+            Expr::Path(path) => {
+                self.resolve_path(path);
+            }
+            Expr::ProcedureCall(call) => {
+                self.resolve_path(call.name());
+                for arg in call.arguments() {
+                    self.resolve_expr(arg);
+                }
+            }
+            Expr::ObjInitialization(init) => {
+                self.resolve_obj_init(init);
+            }
+            Expr::Let(init) => {
+                if let Some(dtype) = init.dtype() {
+                    self.resolve_type(dtype);
+                }
+                for value in init.values() {
+                    self.resolve_expr(value);
+                }
+
+                for symbol in init.to_symbols() {
+                    if let Some(pending) = self.pending.last_mut() {
+                        pending.remove(&symbol.get_key());
+                    }
+                    declare(&self.scope, symbol, self.emitter);
+                }
+            }
+            Expr::CodeBlock(block) => {
+                let pending = block.exprs().iter()
+                    .flat_map(|expr| match expr.inner() {
+                        Expr::Let(init) => init.bindings(),
+                        _ => vec![],
+                    })
+                    .map(|binding| (binding.name().clone(), binding.span().clone()))
+                    .collect();
+
+                self.enter_scope();
+                self.pending.push(pending);
+                for expr in block.exprs() {
+                    self.resolve_expr(expr);
+                }
+                self.pending.pop();
+                self.exit_scope();
+            }
+            // The variable of an `if let` is only in scope for the body
+            Expr::If(conditional) => {
+                self.enter_scope();
+                self.pending.push(HashMap::new());
+                if let Some(condition) = conditional.condition() {
+                    self.resolve_expr(&condition);
+                }
+                self.resolve_expr(conditional.body());
+                self.pending.pop();
+                self.exit_scope();
+
+                if let Some(else_clause) = conditional.else_clause() {
+                    self.resolve_expr(&else_clause);
+                }
+            }
+            Expr::ProcedureDefinition(def) => {
+                self.resolve_procedure(def);
+            }
+            Expr::StructDef(def) => {
+                self.resolve_struct(def);
+            }
+            Expr::InterfaceDef(def) => {
+                self.resolve_interface(def);
+            }
+            Expr::ImplDef(def) => {
+                self.resolve_impl(def);
+            }
+            Expr::Cast(value, ty) => {
+                self.resolve_expr(value);
+                self.resolve_type(ty);
+            }
+            Expr::SizeOf(ty) | Expr::AlignOf(ty) | Expr::OffsetOf(ty, _) => {
+                self.resolve_type(ty);
+            }
+            Expr::ArrayRepeat(value, length) => {
+                self.resolve_expr(value);
+                if let ArrayLength::Named(path) = length {
+                    self.resolve_length(path);
+                }
+            }
+            _ => {
+                expr.for_each_child(&mut |child| self.resolve_expr(child));
+            }
+        }
+    }
+
+    /// Resolve a procedure with its parameters in scope
+    fn resolve_procedure(&mut self, def: &ProcedureDef) {
+        let outer = self.enter_generics(def.generics());
+        for param in def.parameters() {
+            self.resolve_type(param.ty());
+        }
+        self.resolve_type(def.ret());
+
+        self.enter_scope();
+        for param in def.parameters() {
+            self.declare_variable(param.ident(), param.ty(), param.span());
+        }
+        self.resolve_expr(def.body());
+        self.exit_scope();
+        self.generics.truncate(outer);
+    }
+
+    /// Resolve the types of a struct's fields, the values of its
+    /// constants and the bodies of its methods.
+    /// Constants can not use the struct's generic parameters,
+    /// as their value is the same for every type argument
+    fn resolve_struct(&mut self, def: &StructDef) {
+        let outer = self.enter_generics(def.generics());
+        let mut fields: HashMap<&Ident, &Span> = HashMap::new();
+        for field in def.fields() {
+            let binding = field.binding();
+            self.resolve_type(binding.ty());
+
+            match fields.get(binding.ident()) {
+                Some(previous) => {
+                    self.emitter.emit_err(ViperError::DuplicateDefinition {
+                        name: binding.ident().clone(),
+                        span: binding.span().clone(),
+                        previous: (*previous).clone(),
+                    });
+                }
+                None => {
+                    fields.insert(binding.ident(), binding.span());
+                }
+            }
+        }
+
+        // Methods and constants are both reached through `Struct::name`
+        let mut methods: HashMap<&Ident, &Span> = HashMap::new();
+        let generics = self.generics.split_off(outer);
+        for constant in def.consts() {
+            match methods.get(constant.name()) {
+                Some(previous) => {
+                    self.emitter.emit_err(ViperError::DuplicateDefinition {
+                        name: constant.name().clone(),
+                        span: constant.span().clone(),
+                        previous: (*previous).clone(),
+                    });
+                }
+                None => {
+                    methods.insert(constant.name(), constant.span());
+                }
+            }
+
+            self.resolve_type(constant.ty());
+            self.resolve_expr(constant.value());
+        }
+        self.generics.extend(generics);
+
+        for method in def.methods() {
+            match methods.get(method.name()) {
+                Some(previous) => {
+                    self.emitter.emit_err(ViperError::DuplicateDefinition {
+                        name: method.name().clone(),
+                        span: method.span().clone(),
+                        previous: (*previous).clone(),
+                    });
+                }
+                None => {
+                    methods.insert(method.name(), method.span());
+                }
+            }
+
+            self.resolve_method(def.receiver(method), method);
+        }
+        self.generics.truncate(outer);
+    }
+
+    /// Resolve the types in the method signatures of an interface
+    fn resolve_interface(&mut self, def: &InterfaceDef) {
+        let mut methods: HashMap<&Ident, &Span> = HashMap::new();
+        for method in def.methods() {
+            match methods.get(method.name()) {
+                Some(previous) => {
+                    self.emitter.emit_err(ViperError::DuplicateDefinition {
+                        name: method.name().clone(),
+                        span: method.span().clone(),
+                        previous: (*previous).clone(),
+                    });
+                }
+                None => {
+                    methods.insert(method.name(), method.span());
+                }
+            }
+
+            for param in method.parameters() {
+                self.resolve_type(param.ty());
+            }
+            self.resolve_type(method.ret());
+        }
+    }
+
+    /// Resolve the interface and struct of an `impl`, and the bodies of its methods.
+    /// Only structs without generic parameters can implement an interface
+    fn resolve_impl(&mut self, def: &ImplDef) {
+        self.resolve_interface_path(def.interface());
+
+        let target = def.target().to_string();
+        let symbol = self.scope.borrow().lookup(&target);
+        match symbol.map(|symbol| symbol.kind()) {
+            Some(SymbolKind::Struct) => {
+                let generic = self.structs.get(&target).map(|s| !s.generics().is_empty()).unwrap_or(false);
+                if generic {
+                    self.emitter.emit_err(ViperError::InvalidImplTarget {
+                        name: target,
+                        span: def.target().span(),
+                    });
+                }
+            }
+            Some(_) => {
+                self.emitter.emit_err(ViperError::InvalidImplTarget {
+                    name: target,
+                    span: def.target().span(),
+                });
+            }
+            None => {
+                self.emitter.emit_err(ViperError::UndefinedType {
+                    name: target,
+                    span: def.target().span(),
+                });
+            }
+        }
+
+        let mut methods: HashMap<&Ident, &Span> = HashMap::new();
+        for method in def.methods() {
+            match methods.get(method.name()) {
+                Some(previous) => {
+                    self.emitter.emit_err(ViperError::DuplicateDefinition {
+                        name: method.name().clone(),
+                        span: method.span().clone(),
+                        previous: (*previous).clone(),
+                    });
+                }
+                None => {
+                    methods.insert(method.name(), method.span());
+                }
+            }
+
+            self.resolve_method(def.receiver(method), method);
+        }
+    }
+
+    /// Resolve a path that names an interface
+    /// `Weapon` in `impl Weapon for Sword`, `*dyn Weapon` and `<T: Weapon>`
+    fn resolve_interface_path(&mut self, path: &Path) {
+        let name = path.to_string();
+        let symbol = self.scope.borrow().lookup(&name);
+
+        match symbol.map(|symbol| symbol.kind()) {
+            Some(SymbolKind::Interface) => {}
+            Some(_) => {
+                self.emitter.emit_err(ViperError::NotAnInterface {
+                    name,
+                    span: path.span(),
+                });
+            }
+            None => {
+                self.emitter.emit_err(ViperError::UndefinedType {
+                    name,
+                    span: path.span(),
+                });
+            }
+        }
+    }
+
+    /// Bring the generic parameters of an item into scope, reporting any
+    /// that are declared twice or are bound by something that is not an
+    /// interface. Returns how many were in scope before, so that they can
+    /// be taken out of scope again
+    fn enter_generics(&mut self, generics: &[GenericParam]) -> usize {
+        let outer = self.generics.len();
+        for (i, param) in generics.iter().enumerate() {
+            for bound in param.bounds() {
+                self.resolve_interface_path(bound);
+            }
+
+            if let Some(previous) = generics[..i].iter().find(|p| p.name() == param.name()) {
+                self.emitter.emit_err(ViperError::DuplicateDefinition {
+                    name: param.name().clone(),
+                    span: param.span().clone(),
+                    previous: previous.span().clone(),
+                });
+                continue;
+            }
+
+            self.generics.push(param.name().clone());
+        }
+
+        return outer;
+    }
+
+    /// Resolve a struct method with its parameters in scope.
+    /// Methods that are not static also get an implicit `self`, the `receiver`
+    fn resolve_method(&mut self, receiver: Option<Binding>, method: &StructMethod) {
+        for param in method.parameters() {
+            self.resolve_type(param.ty());
+        }
+        self.resolve_type(method.ret());
+
+        self.enter_scope();
+        if let Some(receiver) = receiver {
+            self.declare_variable(receiver.ident(), receiver.ty(), receiver.span());
+        }
+        for param in method.parameters() {
+            self.declare_variable(param.ident(), param.ty(), param.span());
+        }
+
+        if method.is_static() {
+            self.static_method = Some((method.name().clone(), method.span().clone()));
+        }
+        self.resolve_expr(method.body());
+        self.static_method = None;
+
+        self.exit_scope();
+    }
+
+    /// Resolve the type being initialized and the names of its fields
+    fn resolve_obj_init(&mut self, init: &ObjInit) {
+        if let Some(def) = self.resolve_struct_path(init.name()) {
+            for field in init.initializations() {
+                let exists = def.fields().iter()
+                    .any(|f| f.binding().ident() == field.name());
+
+                if !exists {
+                    self.emitter.emit_err(ViperError::UndefinedMember {
+                        parent: def.identifier().clone(),
+                        member: field.name().clone(),
+                        span: field.span().clone(),
+                    });
+                }
+            }
+        }
+
+        for field in init.initializations() {
+            self.resolve_expr(field.value());
+        }
+    }
+
+    /// Resolve a path that names a struct type
+    fn resolve_struct_path(&mut self, path: &Path) -> Option<&'a StructDef> {
+        let name = path.to_string();
+        let symbol = self.scope.borrow().lookup(&name);
+
+        match symbol {
+            Some(symbol) if symbol.kind() == SymbolKind::Struct => {
+                return self.structs.get(&name);
+            }
+            _ => {
+                self.emitter.emit_err(ViperError::UndefinedType {
+                    name,
+                    span: path.span(),
+                });
+                return None;
+            }
+        }
+    }
+
+    /// Resolve a syntactic type and all of the types within it
+    fn resolve_type(&mut self, ty: &Type) {
+        match ty {
+            Type::Concrete { name, args } => {
+                let builtin = name.is_ident() && (
+                    TypeInterner::primitive(name.last().name()).is_some()
+                    || self.generics.iter().any(|g| g == name.last().name())
+                );
+
+                let builtin_generic = match name.is_ident() {
+                    true => TypeInterner::builtin_generic(name.last().name()),
+                    false => None,
+                };
+
+                let expected = match (builtin, builtin_generic) {
+                    (true, _) => 0,
+                    (false, Some(count)) => count,
+                    (false, None) => self.resolve_struct_path(name).map(|def| def.generics().len()).unwrap_or(args.len()),
+                };
+
+                if args.len() != expected {
+                    self.emitter.emit_err(ViperError::WrongTypeArgumentCount {
+                        name: name.to_string(),
+                        expected,
+                        found: args.len(),
+                        span: name.span(),
+                    });
+                }
+
+                for arg in args {
+                    self.resolve_type(arg);
+                }
+            }
+            Type::Procedure { name: _, params, return_type }
+            | Type::Function { params, return_type } => {
+                for param in params {
+                    self.resolve_type(param);
+                }
+                self.resolve_type(return_type);
+            }
+            Type::Pointer { mutable: _, inner }
+            | Type::Reference { mutable: _, inner } => {
+                match inner.as_ref() {
+                    Type::Dyn(interface) => self.resolve_interface_path(interface),
+                    _ => self.resolve_type(inner),
+                }
+            }
+            // A `dyn` type is only valid behind a pointer, which is handled above
+            Type::Dyn(interface) => {
+                self.resolve_interface_path(interface);
+                self.emitter.emit_err(ViperError::UnsizedDyn {
+                    ty: ty.to_string(),
+                    span: interface.span(),
+                });
+            }
+            Type::Array { element, length } => {
+                self.resolve_type(element);
+                if let ArrayLength::Named(path) = length {
+                    self.resolve_length(path);
+                }
+            }
+            Type::Slice(element) | Type::Optional(element) => {
+                self.resolve_type(element);
+            }
+            Type::Tuple(elements) => {
+                for element in elements {
+                    self.resolve_type(element);
+                }
+            }
+            Type::Void | Type::Inferred => {}
+        }
+    }
+
+    /// Resolve the constant that gives an array its length.
+    /// It has to hold a non-negative integer
+    fn resolve_length(&mut self, path: &Path) {
+        let name = path.to_string();
+        let valid = match self.consts.value(&name) {
+            Some(ConstValue::Integer(value)) => *value >= 0,
+            Some(_) => false,
+
+            // A constant whose value could not be worked out has already been reported
+            None if self.consts.contains(&name) => true,
+            None => {
+                let first = &path.segments()[0];
+                if self.resolve_name(&first.name().to_string(), first.span()).is_none() {
+                    return;
+                }
+                false
+            }
+        };
+
+        if !valid {
+            self.emitter.emit_err(ViperError::InvalidArrayLength {
+                name,
+                span: path.span(),
+            });
+        }
+    }
+
+    /// Resolve a path to an item
+    /// `foo`            -> a variable or procedure
+    /// `User::new`      -> a method of the struct `User`
+    /// `User::MAX`      -> a constant of the struct `User`
+    /// `std::io::print` -> an item of the module `std`
+    fn resolve_path(&mut self, path: &Path) {
+        if path.is_ident() {
+            self.resolve_name(&path.last().name().to_string(), &path.span());
+            return;
+        }
+
+        let first = &path.segments()[0];
+        let symbol = match self.resolve_name(&first.name().to_string(), first.span()) {
+            Some(symbol) => symbol,
+            None => return,
+        };
+
+        // The items of a builtin module are linked in with the program,
+        // so the rest of a path into one cannot be checked here
+        if symbol.kind() == SymbolKind::Module {
+            return;
+        }
+
+        let member = &path.segments()[1];
+        let found = match symbol.kind() {
+            SymbolKind::Struct => {
+                self.structs.get(first.name())
+                    .map(|def| {
+                        def.methods().iter().any(|m| m.name() == member.name())
+                            || def.consts().iter().any(|c| c.name() == member.name())
+                    })
+                    .unwrap_or(false)
+            }
+            _ => false,
+        };
+
+        // Nothing has members that are more than one level deep yet
+        if !found || path.segments().len() > 2 {
+            let index = if found { 2 } else { 1 };
+            let member = &path.segments()[index];
+            self.emitter.emit_err(ViperError::UndefinedMember {
+                parent: Path::new(path.segments()[..index].to_vec()).to_string(),
+                member: member.name().to_string(),
+                span: member.span().clone(),
+            });
+        }
+    }
+
+    /// Look up a name in the current scope chain.
+    /// Builtin modules are found when no scope declares the name.
+    /// Reports the name if it cannot be found
+    fn resolve_name(&mut self, name: &Ident, span: &Span) -> Option<Symbol> {
+        let symbol = self.scope.borrow().lookup(name);
+        if symbol.is_some() {
+            return symbol;
+        }
+
+        let module = builtin_module(name, span);
+        if module.is_some() {
+            return module;
+        }
+
+        let declared = self.pending.iter().rev().find_map(|pending| pending.get(name));
+        match declared {
+            Some(declared) => {
+                self.emitter.emit_err(ViperError::UseBeforeDeclaration {
+                    name: name.clone(),
+                    span: span.clone(),
+                    declared: declared.clone(),
+                });
+            }
+            None => {
+                self.emitter.emit_err(ViperError::UndefinedName {
+                    name: name.clone(),
+                    span: span.clone(),
+                });
+            }
+        }
+
+        return None;
+    }
+
+    /// Declare a variable in the current scope
+    fn declare_variable(&mut self, name: &Ident, ty: &Type, span: &Span) {
+        let symbol = Symbol::new(
+            Arc::from(SourceModule::new_dummy()),
+            Arc::from(ty.clone()),
+            name.clone(),
+            span.clone(),
+            false,
+            SymbolKind::Variable,
+        );
+
+        declare(&self.scope, symbol, self.emitter);
+    }
+
+    /// Start a new scope within the current one
+    fn enter_scope(&mut self) {
+        self.scope = Arc::from(RefCell::new(Scope::new(Some(self.scope.clone()))));
+    }
+
+    /// Return to the parent of the current scope
+    fn exit_scope(&mut self) {
+        let parent = self.scope.borrow().parent();
+        if let Some(parent) = parent {
+            self.scope = parent;
+        }
+    }
+}
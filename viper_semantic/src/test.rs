@@ -0,0 +1,2199 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use viper_ast::{BinaryOperator, Expr, ExprNode, Path, UnaryOperator};
+    use viper_core::{emitter::BufferEmitter, error::{ViperError, ViperWarning}, source::SourceFile, span::Span, types::{TypeId, TypeInterner, TypeKind}};
+    use viper_parser::Parser;
+
+    use crate::{
+        cast::{check_cast, CastKind},
+        closure::CaptureKind,
+        consteval::ConstValue,
+        pointer::{pointer_arithmetic, pointer_unary, POINTER_DIFFERENCE},
+        SemanticAnalyzer,
+    };
+
+    /// Parse and analyze a whole file, collecting its diagnostics
+    fn analyze(code: &'static str) -> BufferEmitter {
+        return analyze_items(code).0.emitter;
+    }
+
+    /// Parse and analyze a whole file, keeping the parsed items
+    fn analyze_items(code: &'static str) -> (SemanticAnalyzer<BufferEmitter>, Vec<ExprNode>) {
+        let file_ptr = Arc::from(SourceFile::new_dummy(code, "Test file"));
+        let mut parser = Parser::new(&file_ptr);
+        let items = parser.parse_program().unwrap();
+
+        let mut analyzer = SemanticAnalyzer::new(BufferEmitter::new());
+        analyzer.analyze_file(&file_ptr, &items);
+
+        return (analyzer, items);
+    }
+
+    #[test]
+    fn semantic_pointer_unary() {
+        let mut types = TypeInterner::new();
+        let int = TypeId::I32;
+        let ptr = types.pointer(int, false);
+        let ptr_mut = types.pointer(int, true);
+        let span = Span::dummy();
+
+        assert_eq!(pointer_unary(&mut types, UnaryOperator::AddressOf, int, &span).unwrap().unwrap(), ptr);
+        assert_eq!(pointer_unary(&mut types, UnaryOperator::AddressOfMut, int, &span).unwrap().unwrap(), ptr_mut);
+        assert_eq!(pointer_unary(&mut types, UnaryOperator::Deref, ptr_mut, &span).unwrap().unwrap(), int);
+        assert!(pointer_unary(&mut types, UnaryOperator::Deref, int, &span).unwrap().is_err());
+        assert!(pointer_unary(&mut types, UnaryOperator::Minus, int, &span).is_none());
+    }
+
+    #[test]
+    fn semantic_pointer_arithmetic() {
+        let mut types = TypeInterner::new();
+        let int = TypeId::U64;
+        let ptr = types.pointer(TypeId::BYTE, false);
+        let void_ptr = types.pointer(TypeId::VOID, false);
+        let span = Span::dummy();
+
+        assert_eq!(pointer_arithmetic(&types, BinaryOperator::Plus, ptr, int, &span).unwrap().unwrap(), ptr);
+        assert_eq!(pointer_arithmetic(&types, BinaryOperator::Plus, int, ptr, &span).unwrap().unwrap(), ptr);
+        assert_eq!(pointer_arithmetic(&types, BinaryOperator::Minus, ptr, ptr, &span).unwrap().unwrap(), POINTER_DIFFERENCE);
+
+        assert!(pointer_arithmetic(&types, BinaryOperator::Minus, int, ptr, &span).unwrap().is_err());
+        assert!(pointer_arithmetic(&types, BinaryOperator::Plus, ptr, ptr, &span).unwrap().is_err());
+        assert!(pointer_arithmetic(&types, BinaryOperator::Times, ptr, int, &span).unwrap().is_err());
+        assert!(pointer_arithmetic(&types, BinaryOperator::Plus, void_ptr, int, &span).unwrap().is_err());
+        assert!(pointer_arithmetic(&types, BinaryOperator::EqualTo, ptr, ptr, &span).is_none());
+        assert!(pointer_arithmetic(&types, BinaryOperator::Plus, int, int, &span).is_none());
+    }
+
+    #[test]
+    fn semantic_casts() {
+        let mut types = TypeInterner::new();
+        let (i8, u32, i64, f32, f64) = (TypeId::I8, TypeId::U32, TypeId::I64, TypeId::F32, TypeId::F64);
+        let ptr = types.pointer(u32, false);
+        let byte_ptr = types.pointer(TypeId::BYTE, true);
+        let user = types.declare_struct("User");
+        let array = types.intern(TypeKind::Array { element: i8, length: 4 });
+        let span = Span::dummy();
+
+        assert_eq!(check_cast(&types, u32, i64, &span).unwrap(), CastKind::IntZeroExtend);
+        assert_eq!(check_cast(&types, i8, u32, &span).unwrap(), CastKind::IntSignExtend);
+        assert_eq!(check_cast(&types, i64, TypeId::BYTE, &span).unwrap(), CastKind::IntTruncate);
+        assert_eq!(check_cast(&types, TypeId::BYTE, i8, &span).unwrap(), CastKind::NoOp);
+        assert_eq!(check_cast(&types, u32, f64, &span).unwrap(), CastKind::IntToFloat);
+        assert_eq!(check_cast(&types, f32, i8, &span).unwrap(), CastKind::FloatToInt);
+        assert_eq!(check_cast(&types, f64, f32, &span).unwrap(), CastKind::FloatTruncate);
+        assert_eq!(check_cast(&types, ptr, i64, &span).unwrap(), CastKind::PtrToInt);
+        assert_eq!(check_cast(&types, i64, ptr, &span).unwrap(), CastKind::IntToPtr);
+        assert_eq!(check_cast(&types, ptr, byte_ptr, &span).unwrap(), CastKind::PtrToPtr);
+
+        assert!(check_cast(&types, ptr, f64, &span).is_err());
+        assert!(check_cast(&types, user, i64, &span).is_err());
+        assert!(check_cast(&types, array, ptr, &span).is_err());
+    }
+
+    #[test]
+    fn semantic_type_interning() {
+        let mut types = TypeInterner::new();
+        let a = types.pointer(TypeId::I32, true);
+        let b = types.intern(TypeKind::Pointer { mutable: true, pointee: TypeId::I32 });
+        let c = types.pointer(TypeId::I32, false);
+        let f = types.function(vec![a, TypeId::BOOL], TypeId::VOID);
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert!(types.coerces(a, c));
+        assert!(!types.coerces(c, a));
+        assert_eq!(TypeInterner::primitive("u16"), Some(TypeId::U16));
+        assert_eq!(types.display(f).to_string(), "fn(*mut i32, bool) -> void");
+    }
+
+    #[test]
+    fn semantic_lambda_captures() {
+        let (analyzer, _) = analyze_items(
+r#"
+define main(argc: i32): i32 {
+    let count = 0;
+    let scale: i32 = 2;
+    let f: fn(i32) -> i32 = |a: i32|: i32 => {
+        let g: fn() -> *i32 = ||: *i32 => {
+            return &count;
+        };
+        return a * scale + argc;
+    };
+    return f(1);
+}
+"#);
+        assert!(analyzer.emitter().errors().is_empty(), "{:?}", analyzer.emitter().errors());
+
+        let closures = analyzer.closures();
+        assert_eq!(closures.len(), 2);
+
+        let outer: Vec<(&str, CaptureKind)> = closures[0].captures().iter()
+            .map(|c| (c.name().as_str(), c.kind()))
+            .collect();
+        assert!(closures[0].name().starts_with("lambda"));
+        assert_ne!(closures[0].name(), closures[1].name());
+        assert_eq!(outer, vec![
+            ("count", CaptureKind::ByReference),
+            ("scale", CaptureKind::ByValue),
+            ("argc", CaptureKind::ByValue),
+        ]);
+
+        let inner: Vec<(&str, CaptureKind)> = closures[1].captures().iter()
+            .map(|c| (c.name().as_str(), c.kind()))
+            .collect();
+        assert_eq!(inner, vec![("count", CaptureKind::ByReference)]);
+
+        let types = analyzer.interner();
+        let fields: Vec<String> = closures[0].env_fields().iter()
+            .map(|(name, ty)| format!("{name}: {}", types.display(*ty)))
+            .collect();
+        assert_eq!(fields, vec!["count: *mut i32", "scale: i32", "argc: i32"]);
+
+        let signature = types.display(closures[0].lowered_signature().unwrap()).to_string();
+        assert_eq!(signature, format!("fn(*mut {}, i32) -> i32", closures[0].env_name()));
+    }
+
+    #[test]
+    fn semantic_lambda_index_captures() {
+        let (analyzer, _) = analyze_items(
+r#"
+define main(argc: i32): i32 {
+    let mut values: [i32; 3] = [1, 2, 3];
+    let mut grid: [[i32; 2]; 2] = [[0; 2], [0; 2]];
+    let i: i32 = 1;
+    let f: fn() -> void = ||: void => {
+        values[i] = 5;
+        grid[0][i] = argc;
+    };
+    f();
+    return values[0];
+}
+"#);
+
+        let closures = analyzer.closures();
+        let captures: Vec<(&str, CaptureKind)> = closures[0].captures().iter()
+            .map(|c| (c.name().as_str(), c.kind()))
+            .collect();
+        assert_eq!(captures, vec![
+            ("values", CaptureKind::ByReference),
+            ("i", CaptureKind::ByValue),
+            ("grid", CaptureKind::ByReference),
+            ("argc", CaptureKind::ByValue),
+        ]);
+    }
+
+    #[test]
+    fn semantic_lambda_method_captures() {
+        let (analyzer, _) = analyze_items(
+r#"
+struct Counter {
+    public count: i32,
+
+    public mut method
+    bump(): void {
+        self.count += 1;
+    }
+
+    public method
+    get(): i32 {
+        return self.count;
+    }
+}
+
+struct Game {
+    public counter: Counter,
+}
+
+define main(argc: i32): i32 {
+    let mut c: Counter = Counter { count: 0 };
+    let d: Counter = Counter { count: 0 };
+    let mut game: Game = Game { counter: Counter { count: 0 } };
+    let f: fn() -> i32 = ||: i32 => {
+        c.bump();
+        game.counter.bump();
+        return d.get();
+    };
+    return f();
+}
+"#);
+
+        let closures = analyzer.closures();
+        let captures: Vec<(&str, CaptureKind)> = closures[0].captures().iter()
+            .map(|c| (c.name().as_str(), c.kind()))
+            .collect();
+        assert_eq!(captures, vec![
+            ("c", CaptureKind::ByReference),
+            ("game", CaptureKind::ByReference),
+            ("d", CaptureKind::ByValue),
+        ]);
+    }
+
+    #[test]
+    fn semantic_lambda_inferred_params() {
+        let emitter = analyze(
+r#"
+define main(): i32 {
+    let add: fn(i32, i32) -> i32 = |a, b|: i32 => {
+        return a + b;
+    };
+    let scale: fn(f64) -> f64 = |x|: f64 => {
+        return x * 2.0;
+    };
+    let wrong: fn(i32) -> bool = |a|: bool => {
+        return a;
+    };
+    let untyped = |c|: i32 => {
+        return 1;
+    };
+    return add(1, 2);
+}
+"#);
+
+        let errors = emitter.errors();
+        assert_eq!(errors.len(), 2, "{errors:?}");
+        assert!(matches!(&errors[0], ViperError::MismatchedTypes { expected, found, .. } if expected == "bool" && found == "i32"));
+        assert!(matches!(&errors[1], ViperError::TypeAnnotationRequired { name, .. } if name == "c"));
+    }
+
+    #[test]
+    fn semantic_resolve_valid() {
+        let emitter = analyze(
+r#"
+struct User {
+    age: u32,
+
+    public static
+    new(age: u32): User {
+        return User { age: age };
+    }
+}
+
+define main(argc: i32): i32 {
+    let user: User = User::new(22);
+    let total: i32 = later(argc) + LIMIT;
+    return total;
+}
+
+define later(x: i32): i32 {
+    return x;
+}
+
+let LIMIT: i32 = 10;
+"#);
+
+        assert!(emitter.errors().is_empty(), "{:?}", emitter.errors());
+    }
+
+    #[test]
+    fn semantic_resolve_undefined() {
+        let emitter = analyze(
+r#"
+define main(argc: i32): Missing {
+    return nope + User::new(1);
+}
+"#);
+
+        let errors = emitter.errors();
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(&errors[0], ViperError::UndefinedType { name, .. } if name == "Missing"));
+        assert!(matches!(&errors[1], ViperError::UndefinedName { name, .. } if name == "nope"));
+        assert!(matches!(&errors[2], ViperError::UndefinedName { name, .. } if name == "User"));
+    }
+
+    #[test]
+    fn semantic_resolve_duplicate() {
+        let emitter = analyze(
+r#"
+define main(argc: i32): i32 {
+    let a: i32 = 0;
+    let a: i32 = 1;
+    return a;
+}
+
+define main(): i32 {
+    return 0;
+}
+"#);
+
+        let errors = emitter.errors();
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(&errors[0], ViperError::DuplicateDefinition { name, .. } if name == "main"));
+        match &errors[1] {
+            ViperError::DuplicateDefinition { name, span, previous } => {
+                assert_eq!(name, "a");
+                assert_eq!(span.line(), 4);
+                assert_eq!(previous.line(), 3);
+            }
+            other => panic!("Expected duplicate definition, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn semantic_resolve_use_before_declaration() {
+        let emitter = analyze(
+r#"
+define main(argc: i32): i32 {
+    let a: i32 = b;
+    let b: i32 = 1;
+    return a;
+}
+"#);
+
+        let errors = emitter.errors();
+        assert_eq!(errors.len(), 1);
+        match &errors[0] {
+            ViperError::UseBeforeDeclaration { name, span, declared } => {
+                assert_eq!(name, "b");
+                assert_eq!(span.line(), 3);
+                assert_eq!(declared.line(), 4);
+            }
+            other => panic!("Expected use before declaration, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn semantic_resolve_members() {
+        let emitter = analyze(
+r#"
+struct User {
+    public age: u32,
+}
+
+define main(argc: i32): i32 {
+    let user: User = User { age: 1, height: 2 };
+    return User::new(1);
+}
+"#);
+
+        let errors = emitter.errors();
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(&errors[0], ViperError::UndefinedMember { member, .. } if member == "height"));
+        assert!(matches!(&errors[1], ViperError::UndefinedMember { parent, member, .. } if parent == "User" && member == "new"));
+    }
+
+    #[test]
+    fn semantic_resolve_module_paths() {
+        let emitter = analyze(
+r#"
+define main(argc: i32): i32 {
+    std::io::print("Hello, World\n");
+    io::print("Hello");
+    return argc;
+}
+"#);
+
+        let errors = emitter.errors();
+        assert_eq!(errors.len(), 1, "{errors:?}");
+        assert!(matches!(&errors[0], ViperError::UndefinedName { name, .. } if name == "io"));
+    }
+
+    #[test]
+    fn semantic_typecheck_valid() {
+        let (analyzer, items) = analyze_items(
+r#"
+struct Point {
+    public x: i64,
+    public y: i64,
+}
+
+define scale(p: Point, by: i64): Point {
+    return Point { x: by, y: by * 2 };
+}
+
+define main(argc: i32): i32 {
+    let small: u8 = 1 + 2;
+    let wide = 1 + small as i64;
+    let ratio: f32 = 1.5;
+    let p = scale(Point { x: wide, y: 0 }, 3);
+    let ok = argc < 10 && !false;
+    let either = argc == 1 || argc == 2;
+    let between = argc > 0 && argc < 10 || ok;
+    if ok && either && between {
+        return argc;
+    }
+    return 0;
+}
+"#);
+
+        assert!(analyzer.emitter().errors().is_empty(), "{:?}", analyzer.emitter().errors());
+
+        let body = match items[2].inner() {
+            Expr::ProcedureDefinition(def) => def.body(),
+            _ => panic!("Expected procedure"),
+        };
+        let block = match body.inner() {
+            Expr::CodeBlock(block) => block,
+            _ => panic!("Expected block"),
+        };
+        let value_type = |i: usize| {
+            match block.exprs()[i].inner() {
+                Expr::Let(init) => analyzer.types().get(init.values()[0].id()),
+                _ => panic!("Expected let"),
+            }
+        };
+
+        let point = analyzer.interner().nominal(&Path::from_ident("Point".to_string(), Span::dummy()));
+        assert_eq!(value_type(0), Some(TypeId::U8));
+        assert_eq!(value_type(1), Some(TypeId::I64));
+        assert_eq!(value_type(2), Some(TypeId::F32));
+        assert_eq!(value_type(3), point);
+        assert_eq!(value_type(4), Some(TypeId::BOOL));
+        assert_eq!(value_type(5), Some(TypeId::BOOL));
+        assert_eq!(value_type(6), Some(TypeId::BOOL));
+    }
+
+    #[test]
+    fn semantic_typecheck_errors() {
+        let emitter = analyze(
+r#"
+struct Point {
+    public x: i64,
+    public y: i64,
+}
+
+define add(a: i32, b: i32): i32 {
+    return a + b;
+}
+
+define main(argc: i32): i32 {
+    let a: u8 = argc;
+    let b = true + 1;
+    let c = add(1);
+    let p = Point { x: 1.5 };
+    let q = Point { x: 1, x: 2, y: 3 };
+    while argc {
+        return 0;
+    }
+    return false;
+}
+"#);
+
+        let errors = emitter.errors();
+        assert_eq!(errors.len(), 8, "{errors:?}");
+        assert!(matches!(&errors[0], ViperError::MismatchedTypes { found, .. } if found == "i32"));
+        assert!(matches!(&errors[1], ViperError::InvalidBinaryOperands(err) if err.op == "+"));
+        assert!(matches!(&errors[2], ViperError::ArgumentCountMismatch { expected: 2, found: 1, .. }));
+        assert!(matches!(&errors[3], ViperError::MismatchedTypes { found, .. } if found == "f64"));
+        assert!(matches!(&errors[4], ViperError::MissingField { field, .. } if field == "y"));
+        match &errors[5] {
+            ViperError::DuplicateField { structure, field, span } => {
+                assert_eq!((structure.as_str(), field.as_str()), ("Point", "x"));
+                assert_eq!(span.line(), 16);
+            }
+            other => panic!("Expected duplicate field, got {other:?}"),
+        }
+        assert!(matches!(&errors[6], ViperError::MismatchedTypes { expected, .. } if expected == "bool"));
+        assert!(matches!(&errors[7], ViperError::MismatchedTypes { found, .. } if found == "bool"));
+    }
+
+    #[test]
+    fn semantic_mutability_valid() {
+        let emitter = analyze(
+r#"
+let mut COUNTER: i32 = 0;
+
+define bump(mut n: i32, p: *mut i32): i32 {
+    let mut total = n;
+    total += 1;
+    n = total * 2;
+    *p = n;
+    let q: *mut i32 = &mut total;
+    COUNTER += 1;
+    return total;
+}
+"#);
+
+        assert!(emitter.errors().is_empty(), "{:?}", emitter.errors());
+    }
+
+    #[test]
+    fn semantic_mutability_errors() {
+        let emitter = analyze(
+r#"
+let LIMIT: i32 = 10;
+
+define main(argc: i32): i32 {
+    let x = 1;
+    x = 2;
+    argc += 1;
+    let p: *mut i32 = &mut x;
+    LIMIT = 0;
+    main = main;
+    return x;
+}
+"#);
+
+        let errors = emitter.errors();
+        assert_eq!(errors.len(), 5, "{errors:?}");
+        assert!(matches!(&errors[0], ViperError::AssignToImmutable { name, .. } if name == "x"));
+        assert!(matches!(&errors[1], ViperError::AssignToImmutable { name, .. } if name == "argc"));
+        assert!(matches!(&errors[2], ViperError::BorrowImmutableAsMutable { name, .. } if name == "x"));
+        assert!(matches!(&errors[3], ViperError::AssignToImmutable { name, .. } if name == "LIMIT"));
+        assert!(matches!(&errors[4], ViperError::InvalidAssignmentTarget { .. }));
+        assert_eq!(errors[0].to_string().lines().count(), 2);
+    }
+
+    #[test]
+    fn semantic_visibility() {
+        let emitter = analyze(
+r#"
+struct User {
+    public name: *u8,
+    age: u32,
+
+    public static
+    new(name: *u8): User {
+        return User { name: name, age: User::start() };
+    }
+
+    static
+    start(): u32 {
+        return 18;
+    }
+}
+
+define main(argc: i32): i32 {
+    let alex: User = User { name: "Alex", age: 22 };
+    let ok: User = User::new("Sam");
+    let age: u32 = User::start();
+    return argc;
+}
+"#);
+
+        let errors = emitter.errors();
+        assert_eq!(errors.len(), 2, "{errors:?}");
+        assert!(matches!(&errors[0], ViperError::PrivateMember(err) if err.kind == "field" && err.member == "age"));
+        assert!(matches!(&errors[1], ViperError::PrivateMember(err) if err.kind == "method" && err.member == "start"));
+        assert!(errors[0].to_string().lines().nth(1).unwrap().starts_with("Test file:4:"));
+    }
+
+    #[test]
+    fn semantic_methods_valid() {
+        let emitter = analyze(
+r#"
+struct Counter {
+    count: i32,
+
+    public static
+    new(): Counter {
+        return Counter { count: 0 };
+    }
+
+    public method
+    get(): i32 {
+        return self.count;
+    }
+
+    public mut method
+    add(by: i32): void {
+        self.count += by;
+    }
+}
+
+define main(argc: i32): i32 {
+    let mut c: Counter = Counter::new();
+    c.add(argc);
+    let p: *mut Counter = &mut c;
+    p.add(1);
+    return c.get() + p.get();
+}
+"#);
+
+        assert!(emitter.errors().is_empty(), "{:?}", emitter.errors());
+    }
+
+    #[test]
+    fn semantic_methods_errors() {
+        let emitter = analyze(
+r#"
+struct Counter {
+    count: i32,
+
+    public static
+    new(): Counter {
+        return Counter { count: self.count };
+    }
+
+    public method
+    get(): i32 {
+        self.count = 1;
+        return self.count;
+    }
+
+    public mut method
+    reset(): void {
+        self.count = 0;
+    }
+}
+
+define main(argc: i32): i32 {
+    let c: Counter = Counter::new();
+    let d: Counter = c.new();
+    c.reset();
+    let n: i32 = Counter::get();
+    return c.missing;
+}
+"#);
+
+        let errors = emitter.errors();
+        assert_eq!(errors.len(), 6, "{errors:?}");
+        assert!(matches!(&errors[0], ViperError::SelfInStaticMethod { method, .. } if method == "new"));
+        assert!(matches!(&errors[1], ViperError::StaticMethodOnValue(err) if err.method == "new"));
+        assert!(matches!(&errors[2], ViperError::MethodWithoutReceiver { method, .. } if method == "get"));
+        assert!(matches!(&errors[3], ViperError::UndefinedMember { parent, member, .. } if parent == "Counter" && member == "missing"));
+        assert!(matches!(&errors[4], ViperError::ImmutablePointee { ty, .. } if ty == "*Counter"));
+        assert!(matches!(&errors[5], ViperError::BorrowImmutableAsMutable { name, .. } if name == "c"));
+    }
+
+    #[test]
+    fn semantic_yield_valid() {
+        let (analyzer, items) = analyze_items(
+r#"
+define main(argc: i32): i32 {
+    let small: u8 = if argc > 1 {
+        yield 10;
+    } elif argc > 0 {
+        yield 5;
+    } else {
+        return 0;
+    };
+    let total = {
+        let a: i64 = 90;
+        yield a + small as i64;
+    };
+    let unit = {
+        small + 1;
+    };
+    if argc > 2 {
+        let nothing = 0;
+    }
+    return argc;
+}
+"#);
+
+        assert!(analyzer.emitter().errors().is_empty(), "{:?}", analyzer.emitter().errors());
+
+        let body = match items[0].inner() {
+            Expr::ProcedureDefinition(def) => def.body().clone(),
+            _ => panic!("Expected procedure definition"),
+        };
+        let exprs = match body.inner() {
+            Expr::CodeBlock(block) => block.exprs().clone(),
+            _ => panic!("Expected code block"),
+        };
+        let value_type = |index: usize| match exprs[index].inner() {
+            Expr::Let(init) => analyzer.types().get(init.values()[0].id()),
+            _ => panic!("Expected let statement"),
+        };
+
+        assert_eq!(value_type(0), Some(TypeId::U8));
+        assert_eq!(value_type(1), Some(TypeId::I64));
+        assert_eq!(value_type(2), Some(TypeId::VOID));
+    }
+
+    #[test]
+    fn semantic_yield_errors() {
+        let emitter = analyze(
+r#"
+define main(argc: i32): i32 {
+    let a = if argc > 1 {
+        yield 1;
+    } else {
+        yield true;
+    };
+    let b: i32 = if argc > 2 {
+        yield 2;
+    };
+    let c = {
+        yield 1;
+        yield 2.5;
+    };
+    yield 3;
+    return argc;
+}
+"#);
+
+        let errors = emitter.errors();
+        assert_eq!(errors.len(), 4, "{errors:?}");
+        assert!(matches!(&errors[0], ViperError::MismatchedTypes { expected, found, .. } if expected == "i32" && found == "bool"));
+        assert!(matches!(&errors[1], ViperError::MissingElse { ty, .. } if ty == "i32"));
+        assert!(matches!(&errors[2], ViperError::MismatchedTypes { expected, found, .. } if expected == "i32" && found == "f64"));
+        assert!(matches!(&errors[3], ViperError::YieldOutsideBlock { .. }));
+    }
+
+    #[test]
+    fn semantic_control_flow_valid() {
+        let emitter = analyze(
+r#"
+public define sign(x: i32): i32 {
+    if x > 0 {
+        return 1;
+    } elif x < 0 {
+        return -1;
+    } else {
+        return 0;
+    }
+}
+
+public define find(limit: i32): i32 {
+    let mut i = 0;
+    while true {
+        if i > limit {
+            return i;
+        }
+        i += 1;
+    }
+}
+
+public define count(limit: i32): i32 {
+    let mut i = 0;
+    while i < limit {
+        if i == 5 {
+            break;
+        }
+        i += 1;
+        continue;
+    }
+    return i;
+}
+"#);
+
+        assert!(emitter.errors().is_empty(), "{:?}", emitter.errors());
+        assert!(emitter.warnings().is_empty(), "{:?}", emitter.warnings());
+    }
+
+    #[test]
+    fn semantic_control_flow_warnings() {
+        let emitter = analyze(
+r#"
+public define half(c: bool): i32 {
+    if c {
+        return 1;
+    }
+}
+
+public define spin(): void {
+    let mut n = 0;
+    while true {
+        n += 1;
+    }
+    n = 2;
+}
+
+public define early(): i32 {
+    return 0;
+    let _a = 1;
+    let _b = 2;
+}
+
+public define stray(): void {
+    break;
+}
+"#);
+
+        let errors = emitter.errors();
+        assert_eq!(errors.len(), 1, "{errors:?}");
+        assert!(matches!(&errors[0], ViperError::OutsideOfLoop { keyword, .. } if keyword == "break"));
+
+        let warnings = emitter.warnings();
+        assert_eq!(warnings.len(), 4, "{warnings:?}");
+        assert!(matches!(&warnings[0], ViperWarning::MissingReturn { name, .. } if name == "half"));
+        assert!(matches!(&warnings[1], ViperWarning::UnreachableCode { span } if span.line() == 13));
+        assert!(matches!(&warnings[2], ViperWarning::InfiniteLoop { span } if span.line() == 10));
+        assert!(matches!(&warnings[3], ViperWarning::UnreachableCode { span } if span.line() == 18));
+    }
+
+    #[test]
+    fn semantic_initialization_valid() {
+        let emitter = analyze(
+r#"
+public struct Point {
+    public x: i32,
+    public y: i32,
+}
+
+define pick(c: bool): i32 {
+    let x: i32;
+    if c {
+        x = 1;
+    } else {
+        x = 2;
+    }
+    return x;
+}
+
+define build(): Point {
+    let p: Point;
+    p.x = 1;
+    p.y = 2;
+    return p;
+}
+
+define fill(out: *mut i32): void {
+    *out = 3;
+}
+
+define out_param(): i32 {
+    let mut n: i32;
+    fill(&mut n);
+    return n;
+}
+
+define in_loop(limit: i32): i32 {
+    let mut total = 0;
+    while total < limit {
+        let step: i32;
+        step = 1;
+        total += step;
+    }
+    return total;
+}
+"#);
+
+        assert!(emitter.errors().is_empty(), "{:?}", emitter.errors());
+    }
+
+    #[test]
+    fn semantic_initialization_errors() {
+        let emitter = analyze(
+r#"
+public struct Point {
+    public x: i32,
+    public y: i32,
+}
+
+define maybe(c: bool): i32 {
+    let x: i32;
+    if c {
+        x = 1;
+    }
+    return x;
+}
+
+define partial(): Point {
+    let p: Point;
+    p.x = 1;
+    return p;
+}
+
+define twice(c: bool): void {
+    let x: i32;
+    if c {
+        x = 1;
+    }
+    x = 2;
+}
+
+define captured(): void {
+    let n: i32;
+    let f = ||: i32 => {
+        return n;
+    };
+}
+
+define untyped(): void {
+    let y;
+}
+"#);
+
+        let errors = emitter.errors();
+        assert_eq!(errors.len(), 5, "{errors:?}");
+        assert!(matches!(&errors[0], ViperError::TypeAnnotationRequired { name, .. } if name == "y"));
+        assert!(matches!(&errors[1], ViperError::UninitializedVariable(err) if err.name == "x"));
+        assert!(matches!(&errors[2], ViperError::UninitializedVariable(err) if err.name == "p"));
+        assert!(matches!(&errors[3], ViperError::AssignToImmutable { name, .. } if name == "x"));
+        assert!(matches!(&errors[4], ViperError::UninitializedVariable(err) if err.name == "n"));
+    }
+
+    #[test]
+    fn semantic_lint_unused() {
+        let emitter = analyze(
+r#"
+struct User {
+    public name: i32,
+    age: i32,
+    secret: i32,
+    _spare: i32,
+
+    public static
+    new(): User {
+        return User { name: 1, age: 2, secret: 3, _spare: 4 };
+    }
+
+    public mut method
+    forget(): void {
+        self.secret = 0;
+    }
+
+    public method
+    get_age(): i32 {
+        return self.age;
+    }
+}
+
+define fill(out: *mut i32): void {
+    *out = 1;
+}
+
+define helper(x: i32, _y: i32): i32 {
+    let unused = 1;
+    let _ignored = 2;
+    let mut out: i32;
+    fill(&mut out);
+    return helper(x, 0);
+}
+
+define _spare(): void {
+}
+
+define main(): i32 {
+    let mut u = User::new();
+    u.forget();
+    let f = |a: i32|: i32 => {
+        return u.get_age();
+    };
+    return u.name + f(1);
+}
+"#);
+
+        assert!(emitter.errors().is_empty(), "{:?}", emitter.errors());
+
+        let warnings = emitter.warnings();
+        assert_eq!(warnings.len(), 4, "{warnings:?}");
+        assert!(matches!(&warnings[0], ViperWarning::UnusedVariable { name, .. } if name == "unused"));
+        assert!(matches!(&warnings[1], ViperWarning::UnusedParameter { name, .. } if name == "a"));
+        assert!(matches!(&warnings[2], ViperWarning::UnusedProcedure { name, .. } if name == "helper"));
+        assert!(matches!(&warnings[3], ViperWarning::UnusedField { field, .. } if field == "secret"));
+    }
+
+    #[test]
+    fn semantic_const_valid() {
+        let (analyzer, _) = analyze_items(
+r#"
+const SIZE: u64 = 4 * 2;
+const HALF: u64 = SIZE / 2;
+const MIN: i8 = -128;
+const MASK: u8 = ~0;
+const SHIFTED: u16 = 1 << 15;
+const RATIO: f64 = 1.5 * 2.0;
+const BIG: bool = SIZE > 4 && !false;
+const WRAPPED: u8 = 300 as u8;
+const TRUNCATED: i32 = 2.9 as i32;
+const GREETING: *u8 = "hi";
+const SAME: bool = GREETING == "hi";
+
+struct Buffer {
+    public data: [u8; Buffer::LEN],
+    public const LEN: u64 = HALF + 1;
+}
+
+define main(): i32 {
+    let _grid: [[i32; SIZE]; Buffer::LEN];
+    let _len: u64 = Buffer::LEN;
+    return 0;
+}
+"#);
+
+        let errors = analyzer.emitter().errors();
+        assert!(errors.is_empty(), "{errors:?}");
+
+        let consts = analyzer.consts();
+        assert_eq!(consts.value("SIZE"), Some(&ConstValue::Integer(8)));
+        assert_eq!(consts.value("HALF"), Some(&ConstValue::Integer(4)));
+        assert_eq!(consts.value("MIN"), Some(&ConstValue::Integer(-128)));
+        assert_eq!(consts.value("MASK"), Some(&ConstValue::Integer(255)));
+        assert_eq!(consts.value("SHIFTED"), Some(&ConstValue::Integer(32768)));
+        assert_eq!(consts.value("RATIO"), Some(&ConstValue::Float(3.0)));
+        assert_eq!(consts.value("BIG"), Some(&ConstValue::Bool(true)));
+        assert_eq!(consts.value("WRAPPED"), Some(&ConstValue::Integer(44)));
+        assert_eq!(consts.value("TRUNCATED"), Some(&ConstValue::Integer(2)));
+        assert_eq!(consts.value("SAME"), Some(&ConstValue::Bool(true)));
+        assert_eq!(consts.value("Buffer::LEN"), Some(&ConstValue::Integer(5)));
+
+        let size = Path::from_ident("SIZE".to_string(), Span::dummy());
+        assert_eq!(analyzer.interner().length(&size), Some(8));
+        assert_eq!(analyzer.interner().length(&Path::from_ident("RATIO".to_string(), Span::dummy())), None);
+    }
+
+    #[test]
+    fn semantic_const_errors() {
+        let emitter = analyze(
+r#"
+const A: u8 = 200 + 100;
+const B: i32 = 1 / 0;
+define f(): i32 {
+    return 1;
+}
+const C: i32 = f();
+const D: i32 = E + 1;
+const E: i32 = D;
+const F: i8 = 128;
+const G: f64 = 1.5;
+
+struct Hidden {
+    const SECRET: i32 = 1;
+}
+const H: i32 = Hidden::SECRET;
+
+let n: i32 = 3;
+let a: [i32; G];
+let b: [i32; n];
+"#);
+
+        let errors = emitter.errors();
+        assert_eq!(errors.len(), 8, "{errors:?}");
+        assert!(matches!(&errors[0], ViperError::ArithmeticOverflow { ty, .. } if ty == "u8"));
+        assert!(matches!(&errors[1], ViperError::DivisionByZero { .. }));
+        assert!(matches!(&errors[2], ViperError::NotConstant { .. }));
+        assert!(matches!(&errors[3], ViperError::ConstCycle { name, .. } if name == "D"));
+        assert!(matches!(&errors[4], ViperError::ArithmeticOverflow { ty, .. } if ty == "i8"));
+        assert!(matches!(&errors[5], ViperError::InvalidArrayLength { name, .. } if name == "G"));
+        assert!(matches!(&errors[6], ViperError::InvalidArrayLength { name, .. } if name == "n"));
+        assert!(matches!(&errors[7], ViperError::PrivateMember(err) if err.kind == "constant" && err.member == "SECRET"));
+    }
+
+    #[test]
+    fn semantic_const_visibility_in_types() {
+        let emitter = analyze(
+r#"
+struct Hidden {
+    const SECRET: i32 = 4;
+    public data: [u8; Hidden::SECRET],
+}
+
+struct Open {
+    public data: [u8; Hidden::SECRET],
+}
+
+let table: [i32; Hidden::SECRET];
+
+define fill(buffer: *[u8; Hidden::SECRET]): void {
+    let zeros = [0; Hidden::SECRET];
+    let raw = buffer as *u8;
+    let back = raw as *[u8; Hidden::SECRET];
+}
+"#);
+
+        let errors = emitter.errors();
+        assert_eq!(errors.len(), 5, "{errors:?}");
+        for (error, line) in errors.iter().zip([8, 11, 13, 14, 16]) {
+            match error {
+                ViperError::PrivateMember(err) => {
+                    assert_eq!((err.kind.as_str(), err.member.as_str()), ("constant", "SECRET"));
+                    assert_eq!(err.span.line(), line);
+                }
+                other => panic!("Expected private member, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn semantic_arithmetic_checks() {
+        let emitter = analyze(
+r#"
+const BITS: u32 = 8;
+const WIDE: u32 = BITS * 4;
+
+define main(x: i64, y: u8): i32 {
+    let _a: u8 = 255 + 1;
+    let _b: i8 = -128;
+    let _c: i8 = 128;
+    let _d = x / 0;
+    let _e = y % 0;
+    let _f = x << 70;
+    let _g = 1 << WIDE;
+    let _h = x >> 3;
+    let _i: i32 = 70000 * 70000;
+    let _j = 1.0 / 0.0;
+    {
+        let BITS = 100;
+        let _k = y << BITS;
+    }
+    return 0;
+}
+"#);
+
+        let errors = emitter.errors();
+        assert_eq!(errors.len(), 7, "{errors:?}");
+        assert!(matches!(&errors[0], ViperError::ArithmeticOverflow { ty, .. } if ty == "u8"));
+        assert!(matches!(&errors[1], ViperError::ArithmeticOverflow { ty, .. } if ty == "i8"));
+        assert!(matches!(&errors[2], ViperError::DivisionByZero { .. }));
+        assert!(matches!(&errors[3], ViperError::DivisionByZero { .. }));
+        assert!(matches!(&errors[4], ViperError::ShiftOverflow { amount: 70, ty, max: 63, .. } if ty == "i64"));
+        assert!(matches!(&errors[5], ViperError::ShiftOverflow { amount: 32, ty, max: 31, .. } if ty == "i32"));
+        assert!(matches!(&errors[6], ViperError::ArithmeticOverflow { ty, .. } if ty == "i32"));
+    }
+
+    #[test]
+    fn semantic_generics_valid() {
+        let (analyzer, items) = analyze_items(
+r#"
+struct Pair<A, B> {
+    public first: A,
+    public second: B,
+
+    public static new(first: A, second: B): Pair<A, B> {
+        return Pair { first: first, second: second };
+    }
+
+    public method get_first(): A {
+        return self.first;
+    }
+}
+
+define pick<T>(a: T, _b: T): T {
+    return a;
+}
+
+define wrap<T>(value: T): Pair<T, bool> {
+    return Pair { first: pick(value, value), second: true };
+}
+
+define main(x: u8): i32 {
+    let a: u8 = pick(x, 2);
+    let b = pick(1.5, 2.5);
+    let p = Pair::new(x, b);
+    let q: Pair<i64, f32> = Pair { first: 1, second: 2.0 };
+    let _c: f64 = p.second;
+    let _d: i64 = q.get_first();
+    let w = wrap(a);
+    let _e: bool = w.second;
+    return 0;
+}
+"#);
+
+        let errors = analyzer.emitter().errors();
+        assert!(errors.is_empty(), "{errors:?}");
+
+        let main = match items[3].inner() {
+            Expr::ProcedureDefinition(def) => def,
+            _ => panic!("Expected procedure definition"),
+        };
+        let body = match main.body().inner() {
+            Expr::CodeBlock(block) => block.exprs(),
+            _ => panic!("Expected block"),
+        };
+
+        // The type arguments worked out for each call are recorded against it
+        let call_args = |index: usize| match body[index].inner() {
+            Expr::Let(init) => analyzer.types().args(init.values()[0].id()).map(|args| args.to_vec()),
+            _ => panic!("Expected let statement"),
+        };
+        assert_eq!(call_args(0), Some(vec![TypeId::U8]));
+        assert_eq!(call_args(1), Some(vec![TypeId::F64]));
+        assert_eq!(call_args(2), Some(vec![TypeId::U8, TypeId::F64]));
+
+        let display = |index: usize| match body[index].inner() {
+            Expr::Let(init) => {
+                let ty = analyzer.types().get(init.values()[0].id()).unwrap();
+                analyzer.interner().display(ty).to_string()
+            }
+            _ => panic!("Expected let statement"),
+        };
+        assert_eq!(display(2), "Pair<u8, f64>");
+        assert_eq!(display(3), "Pair<i64, f32>");
+        assert_eq!(display(6), "Pair<u8, bool>");
+    }
+
+    #[test]
+    fn semantic_generics_errors() {
+        let emitter = analyze(
+r#"
+struct Pair<A, B> {
+    public first: A,
+    public second: B,
+}
+
+struct Cell<T, T> {
+    public value: T,
+}
+
+define make<T>(n: i32): T {
+    return make(n);
+}
+
+define add<T>(a: T, b: T): T {
+    return a + b;
+}
+
+define pick<T>(a: T, _b: T): T {
+    return a;
+}
+
+define main(x: u8, y: i64): i32 {
+    let _a: Pair<i32> = Pair { first: 1, second: 2 };
+    let _b: Pair = Pair { first: 1, second: 2 };
+    let _c = make(1);
+    let _d: f32 = make(1);
+    let _e = pick(x, y);
+    let _f = pick;
+    return 0;
+}
+"#);
+
+        let errors = emitter.errors();
+        assert_eq!(errors.len(), 7, "{errors:?}");
+        assert!(matches!(&errors[0], ViperError::DuplicateDefinition { name, .. } if name == "T"));
+        assert!(matches!(&errors[1], ViperError::WrongTypeArgumentCount { name, expected: 2, found: 1, .. } if name == "Pair"));
+        assert!(matches!(&errors[2], ViperError::WrongTypeArgumentCount { name, expected: 2, found: 0, .. } if name == "Pair"));
+        assert!(matches!(&errors[3], ViperError::InvalidBinaryOperands(err) if err.lhs == "T"));
+        assert!(matches!(&errors[4], ViperError::CannotInferTypeArguments { name, param, .. } if name == "make" && param == "T"));
+        assert!(matches!(&errors[5], ViperError::MismatchedTypes { expected, found, .. } if expected == "u8" && found == "i64"));
+        assert!(matches!(&errors[6], ViperError::CannotInferTypeArguments { name, .. } if name == "pick"));
+    }
+
+    #[test]
+    fn semantic_monomorphize() {
+        let (mut analyzer, items) = analyze_items(
+r#"
+struct Pair<A, B> {
+    public first: A,
+    public second: B,
+
+    public static new(first: A, second: B): Pair<A, B> {
+        return Pair { first: first, second: second };
+    }
+}
+
+define pick<T>(a: T, _b: T): T {
+    return a;
+}
+
+define wrap<T>(value: T): Pair<T, bool> {
+    return Pair::new(pick(value, value), true);
+}
+
+define main(x: u8): i32 {
+    let _a: u8 = pick(x, 2);
+    let _p: Pair<i64, f32> = Pair { first: 1, second: 2.0 };
+    let _w = wrap(1.5);
+    return 0;
+}
+"#);
+
+        let errors = analyzer.emitter().errors();
+        assert!(errors.is_empty(), "{errors:?}");
+
+        let output = analyzer.monomorphize(&items);
+        let mut names: Vec<String> = output.iter()
+            .map(|item| match item.inner() {
+                Expr::ProcedureDefinition(def) => def.name(),
+                Expr::StructDef(def) => def.identifier().clone(),
+                _ => panic!("Expected procedure or struct"),
+            })
+            .collect();
+        names.sort();
+        assert_eq!(names, [
+            "Pair<f64, bool>",
+            "Pair<i64, f32>",
+            "main",
+            "pick<f64>",
+            "pick<u8>",
+            "wrap<f64>",
+        ]);
+
+        let main = output.iter().find(|item| item.to_string().starts_with("define main")).unwrap();
+        let main = main.to_string();
+        assert!(main.contains("let _a: u8 = pick<u8>(x, 2)"), "{main}");
+        assert!(main.contains("let _p: Pair<i64, f32> = Pair<i64, f32> {"), "{main}");
+
+        let wrap = output.iter().find(|item| item.to_string().starts_with("define wrap<f64>")).unwrap();
+        let wrap = wrap.to_string();
+        assert!(wrap.contains("value: f64"), "{wrap}");
+        assert!(wrap.contains("): Pair<f64, bool>"), "{wrap}");
+        assert!(wrap.contains("Pair<f64, bool>::new(pick<f64>(value, value), true)"), "{wrap}");
+
+        // Nothing generic is left
+        for item in &output {
+            match item.inner() {
+                Expr::ProcedureDefinition(def) => assert!(def.generics().is_empty()),
+                Expr::StructDef(def) => assert!(def.generics().is_empty()),
+                _ => {}
+            }
+        }
+    }
+
+    #[test]
+    fn semantic_monomorphize_recursion_limit() {
+        let (mut analyzer, items) = analyze_items(
+r#"
+define f<T>(x: T, n: i32): i32 {
+    if n > 0 {
+        return f((x, x), n - 1);
+    }
+    return 0;
+}
+
+define count<T>(x: T, n: i32): i32 {
+    if n > 0 {
+        return count(x, n - 1);
+    }
+    return 0;
+}
+
+define main(): i32 {
+    return f(1, 3) + count(1, 3);
+}
+"#);
+
+        let errors = analyzer.emitter().errors();
+        assert!(errors.is_empty(), "{errors:?}");
+
+        let output = analyzer.monomorphize(&items);
+        let errors = analyzer.emitter().errors();
+        assert_eq!(errors.len(), 1, "{errors:?}");
+        match &errors[0] {
+            ViperError::RecursiveInstantiation { name, limit, span } => {
+                assert_eq!(name, "f");
+                assert_eq!(*limit, 16);
+                assert_eq!(span.line(), 4);
+            }
+            other => panic!("Expected recursive instantiation, got {other:?}"),
+        }
+
+        // Plain recursion only needs the one copy
+        let copies = output.iter().filter(|item| item.to_string().starts_with("define count<")).count();
+        assert_eq!(copies, 1);
+        let copies = output.iter().filter(|item| item.to_string().starts_with("define f<")).count();
+        assert_eq!(copies, 16);
+    }
+
+    #[test]
+    fn semantic_interfaces_valid() {
+        let (mut analyzer, items) = analyze_items(
+r#"
+interface Weapon {
+    method damage(): i32;
+    mut method swing(times: i32): void;
+}
+
+struct Sword {
+    public sharpness: i32,
+}
+
+struct Bow {
+    public arrows: i32,
+}
+
+impl Weapon for Sword {
+    method damage(): i32 {
+        return self.sharpness * 2;
+    }
+
+    mut method swing(times: i32): void {
+        self.sharpness -= times;
+    }
+}
+
+impl Weapon for Bow {
+    method damage(): i32 {
+        return self.arrows;
+    }
+
+    mut method swing(_times: i32): void { }
+}
+
+define total<T: Weapon>(a: *T, b: *T): i32 {
+    return a.damage() + b.damage();
+}
+
+define use_dyn(w: *mut dyn Weapon): i32 {
+    w.swing(2);
+    return w.damage();
+}
+
+define main(): i32 {
+    let mut s = Sword { sharpness: 3 };
+    let b = Bow { arrows: 10 };
+    let d: *dyn Weapon = &b;
+    let _x = total(&s, &s);
+    let _y = use_dyn(&mut s);
+    let _z = d.damage();
+    s.swing(1);
+    return s.damage();
+}
+"#);
+
+        let errors = analyzer.emitter().errors();
+        assert!(errors.is_empty(), "{errors:?}");
+
+        // `w.swing(2)` goes through the second slot of the vtable
+        let use_dyn = match items[6].inner() {
+            Expr::ProcedureDefinition(def) => def,
+            _ => panic!("Expected procedure definition"),
+        };
+        let swing = match use_dyn.body().inner() {
+            Expr::CodeBlock(block) => &block.exprs()[0],
+            _ => panic!("Expected block"),
+        };
+        assert_eq!(analyzer.types().dispatch(swing.id()), Some((&String::from("Weapon"), 1)));
+
+        // and is called on a fat pointer
+        let receiver = match swing.inner() {
+            Expr::MethodCall(call) => analyzer.types().get(call.receiver().id()).unwrap(),
+            _ => panic!("Expected method call"),
+        };
+        assert!(analyzer.interner().is_fat_pointer(receiver));
+
+        // Only the structs that are turned into `*dyn` pointers need a vtable
+        let vtables = analyzer.vtables();
+        assert_eq!(vtables.len(), 2);
+        assert_eq!(vtables[0].implementor(), "Bow");
+        assert_eq!(vtables[0].methods(), ["Bow::damage", "Bow::swing"]);
+        assert_eq!(vtables[1].implementor(), "Sword");
+        assert_eq!(vtables[1].interface(), "Weapon");
+
+        // Bound methods are called on the struct in each copy of a generic procedure
+        let output = analyzer.monomorphize(&items);
+        let total = output.iter().find(|item| item.to_string().starts_with("define total<Sword>")).unwrap();
+        assert!(total.to_string().contains("a: *Sword"), "{total}");
+        assert!(output.iter().any(|item| matches!(item.inner(), Expr::ImplDef(_))));
+    }
+
+    #[test]
+    fn semantic_interfaces_errors() {
+        let emitter = analyze(
+r#"
+interface Weapon {
+    method damage(): i32;
+    mut method swing(times: i32): void;
+}
+
+struct Sword {
+    public sharpness: i32,
+}
+
+struct Shield {
+    public method damage(): i32 {
+        return 0;
+    }
+}
+
+struct Pair<A> {
+    public first: A,
+}
+
+impl Weapon for Sword {
+    method damage(): i64 {
+        return 1;
+    }
+
+    method parry(): void { }
+}
+
+impl Weapon for Shield {
+    method damage(): i32 {
+        return 1;
+    }
+
+    mut method swing(_times: i32): void { }
+}
+
+impl Weapon for Pair { }
+
+impl Sword for Sword { }
+
+define best<T: Weapon>(a: *T): i32 {
+    return a.damage();
+}
+
+define main(): i32 {
+    let x: i32 = 1;
+    let _a = best(&x);
+    let s = Sword { sharpness: 1 };
+    let w: *dyn Weapon = &s;
+    let _m: *mut dyn Weapon = &s;
+    w.swing(1);
+    let _v: dyn Weapon = *w;
+    return 0;
+}
+"#);
+
+        let errors = emitter.errors();
+        assert_eq!(errors.len(), 11, "{errors:?}");
+        assert!(matches!(&errors[0], ViperError::DuplicateDefinition { name, .. } if name == "damage"));
+        assert!(matches!(&errors[1], ViperError::InvalidImplTarget { name, .. } if name == "Pair"));
+        assert!(matches!(&errors[2], ViperError::NotAnInterface { name, .. } if name == "Sword"));
+        assert!(matches!(&errors[3], ViperError::UnsizedDyn { ty, .. } if ty == "dyn Weapon"));
+        assert!(matches!(&errors[4], ViperError::MissingInterfaceMethod(err) if err.method == "swing" && err.target == "Sword"));
+        assert!(matches!(
+            &errors[5],
+            ViperError::InterfaceMethodMismatch(err) if err.expected == "method (): i32" && err.found == "method (): i64"
+        ));
+        assert!(matches!(&errors[6], ViperError::NotInterfaceMethod { method, .. } if method == "parry"));
+        assert!(matches!(&errors[7], ViperError::UnsatisfiedBound { ty, interface, .. } if ty == "i32" && interface == "Weapon"));
+        assert!(matches!(&errors[8], ViperError::MismatchedTypes { expected, found, .. } if expected == "*mut dyn Weapon" && found == "*Sword"));
+        assert!(matches!(&errors[9], ViperError::InvalidDereference { ty, .. } if ty == "*dyn Weapon"));
+        assert!(matches!(&errors[10], ViperError::ImmutablePointee { ty, .. } if ty == "*dyn Weapon"));
+    }
+
+    #[test]
+    fn semantic_operators_valid() {
+        let (mut analyzer, items) = analyze_items(
+r#"
+struct Vec2 {
+    public x: f64,
+    public y: f64,
+
+    public method op_add(other: Vec2): Vec2 {
+        return Vec2 { x: self.x + other.x, y: self.y + other.y };
+    }
+
+    public method op_mul(factor: f64): Vec2 {
+        return Vec2 { x: self.x * factor, y: self.y * factor };
+    }
+
+    public method op_neg(): Vec2 {
+        return Vec2 { x: -self.x, y: -self.y };
+    }
+
+    public method op_eq(other: Vec2): bool {
+        return self.x == other.x;
+    }
+
+    public method op_cmp(other: Vec2): i32 {
+        if self.x < other.x {
+            return -1;
+        }
+        return 0;
+    }
+
+    public method op_index(i: i32): f64 {
+        if i == 0 {
+            return self.x;
+        }
+        return self.y;
+    }
+}
+
+interface Scale {
+    method op_mul(factor: f64): f64;
+}
+
+struct Meters {
+    public value: f64,
+}
+
+impl Scale for Meters {
+    method op_mul(factor: f64): f64 {
+        return self.value * factor;
+    }
+}
+
+define doubled<T: Scale>(value: T): f64 {
+    return value * 2.0;
+}
+
+define last(mut values: [i32; 3]): i32 {
+    values[2] = values[0] + values[1];
+    return values[2];
+}
+
+define main(): i32 {
+    let a = Vec2 { x: 1.0, y: 2.0 };
+    let b = Vec2 { x: 3.0, y: 4.0 };
+    let mut c = a + b * 2.0;
+    c += a;
+    let _n = -c;
+    let _same = a == b;
+    let _diff = a != b;
+    let _less = a < b;
+    let _x: f64 = c[0];
+    let _m = doubled(Meters { value: 1.5 });
+    return 0;
+}
+"#);
+
+        let errors = analyzer.emitter().errors();
+        assert!(errors.is_empty(), "{errors:?}");
+
+        // `a + b * 2.0` calls `op_add`, and gives what it returns
+        let main = match items[6].inner() {
+            Expr::ProcedureDefinition(def) => def,
+            _ => panic!("Expected procedure definition"),
+        };
+        let (sum, less) = match main.body().inner() {
+            Expr::CodeBlock(block) => match (block.exprs()[2].inner(), block.exprs()[7].inner()) {
+                (Expr::Let(sum), Expr::Let(less)) => (sum.values()[0].clone(), less.values()[0].clone()),
+                _ => panic!("Expected lets"),
+            },
+            _ => panic!("Expected block"),
+        };
+        assert_eq!(analyzer.types().operator(sum.id()), Some(&String::from("op_add")));
+        assert_eq!(analyzer.types().operator(less.id()), Some(&String::from("op_cmp")));
+        assert_eq!(analyzer.types().get(less.id()), Some(TypeId::BOOL));
+
+        // Operators on primitives are left alone
+        let doubled = match items[5].inner() {
+            Expr::ProcedureDefinition(def) => def,
+            _ => panic!("Expected procedure definition"),
+        };
+        assert!(analyzer.types().operator(doubled.body().id()).is_none());
+
+        // Overloaded operators become calls of their methods
+        let output = analyzer.monomorphize(&items);
+        let main = output.iter().find(|item| item.to_string().starts_with("define main")).unwrap().to_string();
+        for call in ["a.op_add(b.op_mul(2))", "c.op_add(a)", "c.op_neg()", "a.op_eq(b)", "!a.op_eq(b)", "[a.op_cmp(b) < 0]", "c.op_index(0)"] {
+            assert!(main.contains(call), "{call} in {main}");
+        }
+
+        let doubled = output.iter().find(|item| item.to_string().starts_with("define doubled<Meters>")).unwrap();
+        assert!(doubled.to_string().contains("value.op_mul(2)"), "{doubled}");
+
+        let last = output.iter().find(|item| item.to_string().starts_with("define last")).unwrap();
+        assert!(last.to_string().contains("[values[0] + values[1]]"), "{last}");
+    }
+
+    #[test]
+    fn semantic_operators_errors() {
+        let emitter = analyze(
+r#"
+struct Vec2 {
+    public x: f64,
+
+    public static op_add(a: Vec2, _b: Vec2): Vec2 {
+        return a;
+    }
+
+    public method op_eq(_other: Vec2): i32 {
+        return 0;
+    }
+
+    method op_sub(other: Vec2): Vec2 {
+        return other;
+    }
+
+    public mut method op_mul(factor: f64): Vec2 {
+        self.x *= factor;
+        return Vec2 { x: self.x };
+    }
+
+    public method op_index(_i: i32): f64 {
+        return self.x;
+    }
+}
+
+define fill(values: [i32; 2], i: i32): i32 {
+    values[0] = 1;
+    return i[1.5];
+}
+
+define main(): i32 {
+    let a = Vec2 { x: 1.0 };
+    let _b = a + a;
+    let _c = a - a;
+    let _d = a * true;
+    let _e = true[0];
+    a[0] = 2.0;
+    return 0;
+}
+"#);
+
+        let errors = emitter.errors();
+        assert_eq!(errors.len(), 10, "{errors:?}");
+        assert!(matches!(
+            &errors[0],
+            ViperError::InvalidOperatorMethod { method, expected, .. } if method == "op_add" && expected == "method op_add(_): _"
+        ));
+        assert!(matches!(
+            &errors[1],
+            ViperError::InvalidOperatorMethod { method, expected, .. } if method == "op_eq" && expected == "method op_eq(_): bool"
+        ));
+        assert!(matches!(&errors[2], ViperError::InvalidIndex { ty, index, .. } if ty == "i32" && index == "f64"));
+        assert!(matches!(&errors[3], ViperError::InvalidBinaryOperands(err) if err.op == "+" && err.lhs == "Vec2"));
+        assert!(matches!(&errors[4], ViperError::MismatchedTypes { expected, found, .. } if expected == "f64" && found == "bool"));
+        assert!(matches!(&errors[5], ViperError::InvalidIndex { ty, .. } if ty == "bool"));
+        assert!(matches!(&errors[6], ViperError::PrivateMember(err) if err.member == "op_sub"));
+        assert!(matches!(&errors[7], ViperError::AssignToImmutable { name, .. } if name == "values"));
+
+        // `op_mul` is a `mut method`, and `op_index` gives a value rather than a place
+        assert!(matches!(&errors[8], ViperError::BorrowImmutableAsMutable { name, .. } if name == "a"));
+        assert!(matches!(&errors[9], ViperError::InvalidAssignmentTarget { .. }));
+    }
+
+    #[test]
+    fn semantic_tuples_valid() {
+        let emitter = analyze(
+r#"
+let (width, height): (u32, u32) = (640, 480);
+
+define divide(a: i32, b: i32): (i32, i32) {
+    return (a / b, a % b);
+}
+
+define main(): i32 {
+    let (q, r) = divide(7, 2);
+    let mut pair: (u8, (bool, f64)) = (1, (true, 2.5));
+    pair.1.0 = false;
+
+    let p = &pair;
+    let _f: f64 = p.1.1;
+    let _size: u32 = width * height;
+    return q + r;
+}
+"#);
+
+        assert!(emitter.errors().is_empty(), "{:?}", emitter.errors());
+        assert!(emitter.warnings().is_empty(), "{:?}", emitter.warnings());
+    }
+
+    #[test]
+    fn semantic_tuples_errors() {
+        let emitter = analyze(
+r#"
+define pair(): (i32, bool) {
+    return (1, true);
+}
+
+define main(): i32 {
+    let (a, _b, _c) = pair();
+    let (x, y) = pair();
+    let _z: i32 = y;
+    let _w = x.0;
+    let t = pair();
+    let _v = t.2;
+    t.0 = 3;
+    let _u: (i32, i32) = (1, true);
+    return a;
+}
+"#);
+
+        let errors = emitter.errors();
+        assert_eq!(errors.len(), 6, "{errors:?}");
+        assert!(matches!(
+            &errors[0],
+            ViperError::PatternMismatch { pattern, ty, .. } if pattern == "(a, _b, _c)" && ty == "(i32, bool)"
+        ));
+        assert!(matches!(&errors[1], ViperError::MismatchedTypes { expected, found, .. } if expected == "i32" && found == "bool"));
+        assert!(matches!(&errors[2], ViperError::UndefinedMember { parent, member, .. } if parent == "i32" && member == "0"));
+        assert!(matches!(&errors[3], ViperError::UndefinedMember { parent, member, .. } if parent == "(i32, bool)" && member == "2"));
+        assert!(matches!(&errors[4], ViperError::MismatchedTypes { expected, found, .. } if expected == "(i32, i32)" && found == "(i32, bool)"));
+        assert!(matches!(&errors[5], ViperError::AssignToImmutable { name, .. } if name == "t"));
+    }
+
+    #[test]
+    fn semantic_arrays_valid() {
+        let code = r#"
+const SIZE: u64 = 4;
+
+define main(): i32 {
+    let primes = [2, 3, 5, 7];
+    let bytes: [u8; 64] = [0; 64];
+    let grid: [[i32; SIZE]; 2] = [[1; SIZE], [0; SIZE]];
+    let _none: [i32; 0] = [];
+    let mut i = 3;
+    let _b: u8 = bytes[63];
+    i = i - 1;
+    return primes[i] + grid[1][3] + primes[SIZE - 1];
+}
+"#;
+        let (analyzer, items) = analyze_items(code);
+        let errors = analyzer.emitter().errors();
+        assert!(errors.is_empty(), "{errors:?}");
+
+        // Only `primes[i]` has to be checked while running
+        let body = match items[1].inner() {
+            Expr::ProcedureDefinition(def) => match def.body().inner() {
+                Expr::CodeBlock(block) => block.exprs().to_vec(),
+                _ => panic!("Expected block"),
+            },
+            _ => panic!("Expected procedure definition"),
+        };
+        let checked = match body[7].inner() {
+            Expr::Return(value) => match value.inner() {
+                Expr::BinaryOperation(_, lhs, _) => match lhs.inner() {
+                    Expr::BinaryOperation(_, lhs, _) => lhs.id(),
+                    _ => panic!("Expected addition"),
+                },
+                _ => panic!("Expected addition"),
+            },
+            _ => panic!("Expected return"),
+        };
+        assert_eq!(analyzer.bounds_checks().iter().copied().collect::<Vec<_>>(), vec![checked]);
+
+        // Release builds leave them out
+        let file_ptr = Arc::from(SourceFile::new_dummy(code, "Test file"));
+        let items = Parser::new(&file_ptr).parse_program().unwrap();
+        let mut analyzer = SemanticAnalyzer::new(BufferEmitter::new());
+        analyzer.set_bounds_checks(false);
+        analyzer.analyze_file(&file_ptr, &items);
+        assert!(analyzer.emitter().errors().is_empty());
+        assert!(analyzer.bounds_checks().is_empty());
+    }
+
+    #[test]
+    fn semantic_arrays_errors() {
+        let emitter = analyze(
+r#"
+define main(): i32 {
+    let values = [1, 2, 3];
+    let n = 2;
+    let _a = values[3];
+    let _b = values[-1];
+    let _c: [i32; 4] = [1, 2, 3];
+    let _d = [1, true];
+    let _e: [u8; 2] = [0; n];
+    let _f = [];
+    let _g: [i32; 1] = [];
+    return values[1] + n;
+}
+"#);
+
+        let errors = emitter.errors();
+        assert_eq!(errors.len(), 7, "{errors:?}");
+        assert!(matches!(&errors[0], ViperError::InvalidArrayLength { name, .. } if name == "n"));
+        assert!(matches!(
+            &errors[1],
+            ViperError::MismatchedTypes { expected, found, .. } if expected == "[i32; 4]" && found == "[i32; 3]"
+        ));
+        assert!(matches!(&errors[2], ViperError::MismatchedTypes { expected, found, .. } if expected == "i32" && found == "bool"));
+        assert!(matches!(&errors[3], ViperError::TypeAnnotationRequired { name, .. } if name == "_f"));
+        assert!(matches!(
+            &errors[4],
+            ViperError::MismatchedTypes { expected, found, .. } if expected == "[i32; 1]" && found == "[i32; 0]"
+        ));
+        assert!(matches!(&errors[5], ViperError::IndexOutOfBounds { index: 3, length: 3, .. }));
+        assert!(matches!(&errors[6], ViperError::IndexOutOfBounds { index: -1, length: 3, .. }));
+    }
+
+    #[test]
+    fn semantic_optionals_valid() {
+        let emitter = analyze(
+r#"
+define first<T>(items: ?*T): ?*T {
+    return items;
+}
+
+define main(): i32 {
+    let x = 5;
+    let mut p: ?*i32 = null;
+    p = &x;
+    let q = first(p);
+    let _r: ?*i32 = first(&x);
+    let _s: ?i32 = 3;
+    if let v = q {
+        return *v;
+    }
+    if let v = p {
+        return *v + 1;
+    } else {
+        return 0;
+    }
+}
+"#);
+
+        let errors = emitter.errors();
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn semantic_optionals_errors() {
+        let emitter = analyze(
+r#"
+define main(): i32 {
+    let x = 5;
+    let p: ?*i32 = &x;
+    let _q: *i32 = null;
+    let _n = null;
+    if let v = x {
+        v;
+    }
+    if let v = p {
+        *v;
+    }
+    v;
+    return *p;
+}
+"#);
+
+        let errors = emitter.errors();
+        assert_eq!(errors.len(), 5, "{errors:?}");
+        assert!(matches!(&errors[0], ViperError::UndefinedName { name, .. } if name == "v"));
+        assert!(matches!(&errors[1], ViperError::NullToNonOptional { ty, .. } if ty == "*i32"));
+        assert!(matches!(&errors[2], ViperError::TypeAnnotationRequired { name, .. } if name == "_n"));
+        assert!(matches!(&errors[3], ViperError::NotOptional { ty, .. } if ty == "i32"));
+        assert!(matches!(&errors[4], ViperError::InvalidDereference { ty, .. } if ty == "?*i32"));
+    }
+
+    #[test]
+    fn semantic_optional_null_comparisons() {
+        let emitter = analyze(
+r#"
+struct Point {
+    public x: i32,
+}
+
+define main(): i32 {
+    let x = 5;
+    let p: ?*i32 = &x;
+    let n: ?i32 = 3;
+    let point: ?Point = Point { x: 1 };
+    if p != null && null != n {
+        return 1;
+    }
+    if point == null || null == point {
+        return 2;
+    }
+    let _same = n == n;
+    return 0;
+}
+"#);
+
+        let errors = emitter.errors();
+        assert_eq!(errors.len(), 1, "{errors:?}");
+        assert!(matches!(&errors[0], ViperError::InvalidBinaryOperands(err) if err.lhs == "?i32" && err.rhs == "?i32"));
+    }
+
+    #[test]
+    fn semantic_optional_null_optimization() {
+        let mut types = TypeInterner::new();
+        let ptr = types.pointer(TypeId::I32, true);
+        let function = types.function(vec![], TypeId::VOID);
+        let optional_ptr = types.intern(TypeKind::Optional(ptr));
+        let optional_fn = types.intern(TypeKind::Optional(function));
+        let optional_int = types.intern(TypeKind::Optional(TypeId::I32));
+
+        assert!(types.is_null_optimized(optional_ptr));
+        assert!(types.is_null_optimized(optional_fn));
+        assert!(!types.is_null_optimized(optional_int));
+        assert!(!types.is_null_optimized(ptr));
+
+        let (analyzer, items) = analyze_items(
+r#"
+define main(): u64 {
+    let _a = sizeof(?*i32);
+    let _b = sizeof(?fn() -> void);
+    let _c = sizeof(?i64);
+    return 0;
+}
+"#);
+        assert!(analyzer.emitter().errors().is_empty(), "{:?}", analyzer.emitter().errors());
+
+        let body = match items[0].inner() {
+            Expr::ProcedureDefinition(def) => def.body(),
+            _ => panic!("Expected procedure"),
+        };
+        let values: Vec<Option<u64>> = match body.inner() {
+            Expr::CodeBlock(block) => block.exprs()[..3].iter()
+                .map(|expr| match expr.inner() {
+                    Expr::Let(init) => analyzer.layouts().value(init.values()[0].id()),
+                    _ => panic!("Expected let"),
+                })
+                .collect(),
+            _ => panic!("Expected block"),
+        };
+
+        // A null pointer stands for `null`, so only `?i64` needs a tag
+        assert_eq!(values, vec![Some(8), Some(8), Some(16)]);
+    }
+
+    #[test]
+    fn semantic_results_valid() {
+        let emitter = analyze(
+r#"
+define parse(digit: i32): Result<i32, i32> {
+    if digit < 0 {
+        return Err(digit);
+    }
+    return Ok(digit * 2);
+}
+
+define sum(a: i32, b: i32): Result<i32, i32> {
+    let total = parse(a)? + parse(b)?;
+    let checked: Result<i32, i32> = Ok(total);
+    return Ok(checked? + 1);
+}
+
+define wrap<T>(value: T): Result<T, bool> {
+    return Ok(value);
+}
+
+define main(): Result<i32, bool> {
+    let w = wrap(5)?;
+    let _s = sum(w, 2);
+    return Ok(w);
+}
+"#);
+
+        let errors = emitter.errors();
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn semantic_results_errors() {
+        let emitter = analyze(
+r#"
+define parse(digit: i32): Result<i32, i32> {
+    return Ok(digit);
+}
+
+define main(): i32 {
+    let _a: i32 = Ok(1);
+    let _b = Err(2);
+    let _c = 5?;
+    let _d = parse(1)?;
+    let _e: Result<i32, bool> = Err(3);
+    let _f: Result<i32> = Ok(1);
+    return 0;
+}
+"#);
+
+        let errors = emitter.errors();
+        assert_eq!(errors.len(), 6, "{errors:?}");
+        assert!(matches!(&errors[0], ViperError::WrongTypeArgumentCount { name, expected: 2, found: 1, .. } if name == "Result"));
+        assert!(matches!(&errors[1], ViperError::UnexpectedResult { value, ty, .. } if value == "Ok" && ty == "i32"));
+        assert!(matches!(&errors[2], ViperError::TypeAnnotationRequired { name, .. } if name == "_b"));
+        assert!(matches!(&errors[3], ViperError::TryOnNonResult { ty, .. } if ty == "i32"));
+        assert!(matches!(&errors[4], ViperError::TryIncompatibleReturn { err, ret, .. } if err == "i32" && ret == "i32"));
+        assert!(matches!(&errors[5], ViperError::MismatchedTypes { expected, found, .. } if expected == "bool" && found == "i32"));
+    }
+
+    #[test]
+    fn semantic_layout_valid() {
+        let (mut analyzer, items) = analyze_items(
+r#"
+interface Weapon {
+    method damage(): i32;
+}
+
+#[repr(C)]
+struct Header {
+    tag: u8,
+    length: u32,
+    flags: u16,
+}
+
+struct Reordered {
+    tag: u8,
+    length: u32,
+    flags: u16,
+}
+
+#[repr(packed)]
+struct Packed {
+    tag: u8,
+    length: u32,
+}
+
+#[repr(align(16))]
+struct Aligned {
+    value: u8,
+}
+
+struct Node {
+    value: i32,
+    next: ?*Node,
+}
+
+struct Pair<A, B> {
+    first: A,
+    second: B,
+}
+
+define size<T>(_value: T): u64 {
+    return sizeof(T);
+}
+
+define main(): u64 {
+    let _a = sizeof(Header);
+    let _b = offsetof(Header, flags);
+    let _c = sizeof(Reordered);
+    let _d = offsetof(Reordered, tag);
+    let _e = sizeof(Packed);
+    let _f = offsetof(Packed, length);
+    let _g = alignof(Packed);
+    let _h = sizeof(Aligned);
+    let _i = alignof(Aligned);
+    let _j = sizeof(Node);
+    let _k = sizeof(?i32);
+    let _l = sizeof(Result<u8, u64>);
+    let _m = sizeof(*dyn Weapon);
+    let _n = sizeof([u16; 5]);
+    let _o = offsetof(Pair<u8, u32>, first);
+    let _p = offsetof((u8, u32), 1);
+    return size(5);
+}
+"#);
+        let errors = analyzer.emitter().errors();
+        assert!(errors.is_empty(), "{errors:?}");
+
+        let main = items.iter()
+            .find_map(|item| match item.inner() {
+                Expr::ProcedureDefinition(def) if def.name() == "main" => Some(def),
+                _ => None,
+            })
+            .unwrap();
+        let body = match main.body().inner() {
+            Expr::CodeBlock(block) => block.exprs().to_vec(),
+            _ => panic!("Expected block"),
+        };
+
+        let values: Vec<Option<u64>> = body[..16].iter()
+            .map(|expr| match expr.inner() {
+                Expr::Let(init) => analyzer.layouts().value(init.values()[0].id()),
+                _ => panic!("Expected let"),
+            })
+            .collect();
+        let expected = [12, 8, 8, 6, 5, 1, 1, 16, 16, 16, 8, 16, 16, 10, 4, 4];
+        assert_eq!(values, expected.map(Some));
+
+        // `sizeof(T)` only has a value in each copy of `size`
+        let output = analyzer.monomorphize(&items);
+        let size = output.iter().find(|item| item.to_string().starts_with("define size<i32>")).unwrap();
+        let size = size.to_string();
+        assert!(size.contains("return 4"), "{size}");
+    }
+
+    #[test]
+    fn semantic_layout_errors() {
+        let emitter = analyze(
+r#"
+struct List {
+    value: i32,
+    rest: List,
+}
+
+struct Left {
+    right: Right,
+}
+
+struct Right {
+    left: ?Left,
+}
+
+#[repr(align(3))]
+struct Odd {
+    value: u8,
+}
+
+#[repr(packed, align(8))]
+struct Both {
+    value: u8,
+}
+
+struct Buffer {
+    data: [u8],
+}
+
+define main(): void {
+    let _a = sizeof([i32]);
+    let _b = offsetof(Odd, missing);
+    let _c = offsetof(i32, value);
+}
+"#);
+
+        let errors = emitter.errors();
+        assert_eq!(errors.len(), 9, "{errors:?}");
+        assert!(matches!(&errors[0], ViperError::InfiniteSize { name, .. } if name == "List"));
+        assert!(matches!(&errors[1], ViperError::InfiniteSize { name, .. } if name == "Left"));
+        assert!(matches!(&errors[2], ViperError::InfiniteSize { name, .. } if name == "Right"));
+        assert!(matches!(&errors[3], ViperError::InvalidAlignment { name, align: 3, .. } if name == "Odd"));
+        assert!(matches!(&errors[4], ViperError::PackedAndAligned { name, .. } if name == "Both"));
+        assert!(matches!(&errors[5], ViperError::UnsizedType { ty, .. } if ty == "[u8]"));
+        assert!(matches!(&errors[6], ViperError::UnsizedType { ty, .. } if ty == "[i32]"));
+        assert!(matches!(&errors[7], ViperError::UndefinedMember { parent, member, .. } if parent == "Odd" && member == "missing"));
+        assert!(matches!(&errors[8], ViperError::UndefinedMember { parent, member, .. } if parent == "i32" && member == "value"));
+    }
+}
@@ -0,0 +1,1989 @@
+use std::{cell::RefCell, collections::{HashMap, HashSet}, sync::Arc};
+
+use viper_ast::{
+    BinaryOperator, Binding, CodeBlock, Conditional, Expr, ExprNode, Field, GenericParam, Ident, ImplDef,
+    InterfaceDef, InterfaceMethod, MethodCall, NodeId, ObjInit, Path, ProcedureCall, ProcedureDef, ProcedureKind,
+    StructDef, StructMethod, UnaryOperator, VariableInitialization,
+};
+use viper_core::{
+    _type::{ArrayLength, Type},
+    emitter::Emitter,
+    error::{
+        InterfaceMethodMismatch, InvalidBinaryOperands, MissingInterfaceMethod, StaticMethodOnValue, ViperError,
+    },
+    scope::Scope,
+    span::Span,
+    symbol::SymbolKind,
+    types::{TypeId, TypeInterner, TypeKind},
+};
+
+use crate::{
+    cast::check_cast,
+    interface::ImplTable,
+    operator::{binary_method, check_operator_method, fits_operator, unary_method, INDEX_METHOD},
+    pointer::{pointer_arithmetic, pointer_unary},
+};
+
+/// The type of integer literals that have nothing to infer their type from
+pub const DEFAULT_INTEGER: TypeId = TypeId::I32;
+
+/// The type of float literals that have nothing to infer their type from
+pub const DEFAULT_FLOAT: TypeId = TypeId::F64;
+
+/// The types of expressions, keyed by the [NodeId] of the expression.
+///
+/// Expressions that could not be given a type because of an
+/// earlier error have no entry.
+#[derive(Default)]
+pub struct TypeTable {
+    types: HashMap<NodeId, TypeId>,
+
+    /// The type arguments worked out for each call of a generic procedure
+    /// or of a static method of a generic struct, keyed by the call
+    args: HashMap<NodeId, Vec<TypeId>>,
+
+    /// The interface and vtable slot of each method call made through an
+    /// interface, on a `*dyn` pointer or on a value of a bound generic parameter
+    dispatch: HashMap<NodeId, (Ident, usize)>,
+
+    /// The struct and interface of each pointer to a struct that
+    /// is turned into a `*dyn` pointer, keyed by the pointer
+    coercions: HashMap<NodeId, (Ident, Ident)>,
+
+    /// The method that each operator used on a struct value calls, keyed by
+    /// the operation. Compound assignments are keyed by the assignment
+    operators: HashMap<NodeId, Ident>,
+}
+
+impl TypeTable {
+    /// Create a new, empty [TypeTable]
+    pub fn new() -> TypeTable {
+        TypeTable::default()
+    }
+
+    /// Record the type of a node
+    pub fn insert(&mut self, id: NodeId, ty: TypeId) {
+        self.types.insert(id, ty);
+    }
+
+    /// Get the type of a node
+    pub fn get(&self, id: NodeId) -> Option<TypeId> {
+        return self.types.get(&id).copied();
+    }
+
+    /// Record the type arguments of a generic call
+    pub fn insert_args(&mut self, id: NodeId, args: Vec<TypeId>) {
+        self.args.insert(id, args);
+    }
+
+    /// Get the type arguments of a generic call
+    pub fn args(&self, id: NodeId) -> Option<&[TypeId]> {
+        return self.args.get(&id).map(|args| args.as_slice());
+    }
+
+    /// Record the interface method that a method call goes to
+    pub fn insert_dispatch(&mut self, id: NodeId, interface: Ident, slot: usize) {
+        self.dispatch.insert(id, (interface, slot));
+    }
+
+    /// Get the interface and vtable slot of a method call made through an interface
+    pub fn dispatch(&self, id: NodeId) -> Option<(&Ident, usize)> {
+        return self.dispatch.get(&id).map(|(interface, slot)| (interface, *slot));
+    }
+
+    /// Record a pointer to a struct being turned into a `*dyn` pointer
+    pub fn insert_coercion(&mut self, id: NodeId, implementor: Ident, interface: Ident) {
+        self.coercions.insert(id, (implementor, interface));
+    }
+
+    /// Get the struct and interface of a pointer that is turned into a `*dyn` pointer
+    pub fn coercion(&self, id: NodeId) -> Option<(&Ident, &Ident)> {
+        return self.coercions.get(&id).map(|(implementor, interface)| (implementor, interface));
+    }
+
+    /// Get every pair of struct and interface that a `*dyn` pointer is made from
+    pub fn coercions(&self) -> impl Iterator<Item = (&Ident, &Ident)> {
+        return self.coercions.values().map(|(implementor, interface)| (implementor, interface));
+    }
+
+    /// Record the method that an operator calls
+    pub fn insert_operator(&mut self, id: NodeId, method: Ident) {
+        self.operators.insert(id, method);
+    }
+
+    /// Get the method that an operator calls, if it is overloaded
+    pub fn operator(&self, id: NodeId) -> Option<&Ident> {
+        return self.operators.get(&id);
+    }
+}
+
+/// Computes the type of every expression in a file and checks that
+/// the types agree with each other.
+///
+/// Integer and float literals take their type from the context they
+/// are used in. `let i: u8 = 1;` makes the `1` a `u8`. Literals with no
+/// context default to [DEFAULT_INTEGER] and [DEFAULT_FLOAT].
+///
+/// The body of a generic item is checked once, with each generic parameter
+/// as a [TypeKind::Param] that only the methods of its bounds are known of.
+/// Uses of the item work out its type arguments from the values given to it,
+/// which have to implement the bounds.
+///
+/// A pointer to a struct can be used where a `*dyn` pointer to an
+/// interface it implements is expected.
+///
+/// Operators used on a struct value call the method of the struct that
+/// overloads them, like `op_add` for `+`. See [crate::operator].
+///
+/// This runs after name resolution, so names that cannot be found are
+/// skipped over instead of being reported again.
+pub struct TypeChecker<'a> {
+    emitter: &'a mut dyn Emitter,
+
+    /// Every struct defined in the module
+    structs: &'a HashMap<Ident, StructDef>,
+
+    /// Every top-level procedure defined in the module
+    procedures: &'a HashMap<Ident, ProcedureDef>,
+
+    /// The interfaces of the module and what implements them
+    impls: &'a ImplTable,
+
+    /// The scope of the file being checked.
+    /// Top-level items are looked up here
+    file_scope: Arc<RefCell<Scope>>,
+
+    /// Where the types themselves are interned
+    types: &'a mut TypeInterner,
+
+    /// Where the type of each expression gets recorded
+    table: &'a mut TypeTable,
+
+    /// Local variables visible at the current point, innermost scope last.
+    /// Variables whose type could not be worked out are None
+    locals: Vec<HashMap<Ident, Option<TypeId>>>,
+
+    /// The return type of the procedure being checked
+    return_type: Option<TypeId>,
+
+    /// Blocks used as values that enclose the current point, innermost last.
+    /// A `yield` produces the value of the innermost one
+    yield_targets: Vec<YieldTarget>,
+
+    /// The generic parameters of the item being checked, as [TypeKind::Param]s
+    generics: HashMap<Ident, TypeId>,
+
+    /// The interfaces that bound each generic parameter in [TypeChecker::generics]
+    bounds: HashMap<Ident, Vec<Ident>>,
+}
+
+/// The method that an operator used on a struct value, or on a value
+/// of a generic parameter bound by an interface, calls
+enum Overload<'a> {
+    /// A method of the struct, with the struct's type arguments
+    Method(&'a StructMethod, HashMap<Ident, TypeId>),
+
+    /// A method of an interface, by its slot in the interface's vtable
+    Interface(&'a InterfaceDef, usize),
+}
+
+/// A generic procedure, or a static method of a generic struct, that a call names
+struct GenericCallee<'a> {
+    generics: &'a [GenericParam],
+    params: &'a [Binding],
+    ret: &'a Type,
+}
+
+/// A block used as a value, which gets its type from its `yield`s
+struct YieldTarget {
+    /// The type that the context wants the block to have
+    expected: Option<TypeId>,
+
+    /// The type of the block, once a `yield` has been seen
+    ty: Option<TypeId>,
+
+    /// Whether the block has a `yield`
+    yielded: bool,
+}
+
+impl<'a> TypeChecker<'a> {
+    /// Create a new [TypeChecker] that records types in `table`
+    pub fn new(
+        emitter: &'a mut dyn Emitter,
+        structs: &'a HashMap<Ident, StructDef>,
+        procedures: &'a HashMap<Ident, ProcedureDef>,
+        impls: &'a ImplTable,
+        file_scope: Arc<RefCell<Scope>>,
+        types: &'a mut TypeInterner,
+        table: &'a mut TypeTable,
+    ) -> TypeChecker<'a> {
+        TypeChecker {
+            emitter,
+            structs,
+            procedures,
+            impls,
+            file_scope,
+            types,
+            table,
+            locals: vec![],
+            return_type: None,
+            yield_targets: vec![],
+            generics: HashMap::new(),
+            bounds: HashMap::new(),
+        }
+    }
+
+    /// Check the top-level items of a file
+    pub fn check_items(&mut self, items: &[ExprNode]) {
+        for item in items {
+            match item.inner() {
+                // Top-level variables can be used from anywhere in the
+                // module, so their type has to be written out
+                Expr::Let(init) => {
+                    match init.dtype() {
+                        Some(dtype) => {
+                            let dtype = self.types.lower(dtype);
+                            for value in init.values() {
+                                self.check_against(value, dtype);
+                            }
+                            if let Some(dtype) = dtype {
+                                self.check_pattern(init, dtype, item.span());
+                            }
+                        }
+                        None => {
+                            self.emitter.emit_err(ViperError::TypeAnnotationRequired {
+                                name: init.name(),
+                                span: item.span().clone(),
+                            });
+                        }
+                    }
+                    self.table.insert(item.id(), TypeId::VOID);
+                }
+                _ => {
+                    self.check_expr(item, None);
+                }
+            }
+        }
+    }
+
+    /// Check an expression that must have the type `expected`.
+    /// Nothing is required of the expression when `expected` is None
+    fn check_against(&mut self, expr: &ExprNode, expected: Option<TypeId>) -> Option<TypeId> {
+        let found = self.check_expr(expr, expected)?;
+        let expected = match expected {
+            Some(expected) => expected,
+            None => return Some(found),
+        };
+
+        if !self.types.coerces(found, expected) && !self.coerce_to_dyn(expr, found, expected) {
+            self.emitter.emit_err(ViperError::MismatchedTypes {
+                expected: self.types.display(expected).to_string(),
+                found: self.types.display(found).to_string(),
+                span: expr.span().clone(),
+            });
+        }
+
+        return Some(found);
+    }
+
+    /// Turn a pointer to a struct into a `*dyn` pointer to an interface
+    /// that the struct implements, recording which vtable it needs.
+    /// A `*mut dyn` pointer can only be made from a `*mut` pointer
+    /// `&sword` -> `*dyn Weapon`
+    fn coerce_to_dyn(&mut self, expr: &ExprNode, found: TypeId, expected: TypeId) -> bool {
+        let (from, to) = match (self.types.kind(found), self.types.kind(expected)) {
+            (
+                TypeKind::Pointer { mutable: from_mut, pointee: from },
+                TypeKind::Pointer { mutable: to_mut, pointee: to },
+            ) if *from_mut || !*to_mut => (*from, *to),
+            _ => return false,
+        };
+
+        match (self.types.kind(from), self.types.kind(to)) {
+            (TypeKind::Struct(implementor, args), TypeKind::Dyn(interface))
+                if args.is_empty() && self.impls.implements(implementor, interface) => {
+                self.table.insert_coercion(expr.id(), implementor.clone(), interface.clone());
+                return true;
+            }
+            _ => return false,
+        }
+    }
+
+    /// Compute the type of an expression and record it in the table.
+    ///
+    /// `expected` is the type that the context would like the expression
+    /// to have, which is used to infer the type of literals. It is only a
+    /// hint; use [TypeChecker::check_against] to require a type.
+    fn check_expr(&mut self, expr: &ExprNode, expected: Option<TypeId>) -> Option<TypeId> {
+        let ty = self.infer(expr, expected);
+        if let Some(ty) = ty {
+            self.table.insert(expr.id(), ty);
+        }
+
+        return ty;
+    }
+
+    fn infer(&mut self, expr: &ExprNode, expected: Option<TypeId>) -> Option<TypeId> {
+        match expr.inner() {
+            Expr::True | Expr::False => Some(TypeId::BOOL),
+            Expr::Null => self.check_null(expected, expr.span()),
+            Expr::Ok(value) => self.check_result_value(value, true, expected, expr.span()),
+            Expr::Err(value) => self.check_result_value(value, false, expected, expr.span()),
+            Expr::Try(value) => self.check_try(value, expr.span()),
+
+            // Layouts are worked out once every type has been checked
+            Expr::SizeOf(_) | Expr::AlignOf(_) | Expr::OffsetOf(_, _) => Some(TypeId::U64),
+            Expr::Break | Expr::Continue => Some(TypeId::VOID),
+            Expr::Integer(_) => {
+                match expected {
+                    Some(ty) if self.types.is_integer(ty) => Some(ty),
+                    _ => Some(DEFAULT_INTEGER),
+                }
+            }
+            Expr::Float(_) => {
+                match expected {
+                    Some(ty) if self.types.is_float(ty) => Some(ty),
+                    _ => Some(DEFAULT_FLOAT),
+                }
+            }
+            Expr::StringLiteral(_) => {
+                Some(self.types.pointer(TypeId::U8, false))
+            }
+            Expr::Identifier(name) => {
+                self.lookup_value(name, expr.span())
+            }
+            Expr::Path(path) => {
+                self.path_type(path)
+            }
+            Expr::ProcedureCall(call) => {
+                self.check_call(expr.id(), call, expected, expr.span())
+            }
+            Expr::BinaryOperation(op, lhs, rhs) => {
+                self.check_binary(expr.id(), *op, lhs, rhs, expected, expr.span())
+            }
+            Expr::Index(object, index) => {
+                self.check_index(expr.id(), object, index, expr.span())
+            }
+            Expr::Tuple(elements) => {
+                self.check_tuple(elements, expected)
+            }
+            Expr::ArrayLiteral(elements) => {
+                self.check_array_literal(elements, expected)
+            }
+            Expr::ArrayRepeat(value, length) => {
+                self.check_array_repeat(value, length, expected)
+            }
+            Expr::UnaryOperation(op, operand) => {
+                self.check_unary(expr.id(), *op, operand, expected, expr.span())
+            }
+            Expr::Cast(value, ty) => {
+                let to = self.lower(ty);
+                let from = self.check_expr(value, None);
+                if let Err(err) = check_cast(self.types, from?, to?, expr.span()) {
+                    self.emitter.emit_err(err);
+                }
+
+                to
+            }
+            Expr::Let(init) => {
+                let ty = match init.dtype() {
+                    Some(dtype) => {
+                        let dtype = self.lower(dtype);
+                        for value in init.values() {
+                            self.check_against(value, dtype);
+                        }
+                        dtype
+                    }
+                    // `let x;`, `let x = null;` and `let x = Ok(1);` leave nothing to infer the type from
+                    None if init.values().iter().all(|value| needs_context(value)) => {
+                        self.emitter.emit_err(ViperError::TypeAnnotationRequired {
+                            name: init.name(),
+                            span: expr.span().clone(),
+                        });
+                        None
+                    }
+                    None => {
+                        let mut ty = None;
+                        for value in init.values() {
+                            ty = self.check_expr(value, None);
+                        }
+                        ty
+                    }
+                };
+
+                self.declare_pattern(init, ty, expr.span());
+                Some(TypeId::VOID)
+            }
+            Expr::Assignment(assignment) => {
+                let target = assignment.target();
+                let value = assignment.value();
+
+                match assignment.op() {
+                    Some(op) => {
+                        // `x += 1` has to be valid as `x = x + 1`
+                        let result = self.check_binary(expr.id(), op, target, value, None, expr.span());
+                        let target_ty = self.table.get(target.id());
+
+                        if let (Some(result), Some(target_ty)) = (result, target_ty) {
+                            if !self.types.coerces(result, target_ty) {
+                                self.emitter.emit_err(ViperError::MismatchedTypes {
+                                    expected: self.types.display(target_ty).to_string(),
+                                    found: self.types.display(result).to_string(),
+                                    span: expr.span().clone(),
+                                });
+                            }
+                        }
+                    }
+                    None => {
+                        let target_ty = self.check_expr(target, None);
+                        self.check_against(value, target_ty);
+                    }
+                }
+
+                Some(TypeId::VOID)
+            }
+            Expr::Return(value) => {
+                self.check_against(value, self.return_type);
+                Some(TypeId::VOID)
+            }
+            Expr::Yield(value) => {
+                self.check_yield(value, expr.span());
+                Some(TypeId::VOID)
+            }
+            Expr::Const(def) => {
+                let ty = self.lower(def.ty());
+                self.check_against(def.value(), ty);
+                Some(TypeId::VOID)
+            }
+            Expr::If(conditional) => {
+                self.check_value_if(conditional, expr.span(), expected)
+            }
+            Expr::WhileLoop(whileloop) => {
+                self.check_against(whileloop.condition(), Some(TypeId::BOOL));
+                self.check_stmt(whileloop.body());
+                Some(TypeId::VOID)
+            }
+            Expr::CodeBlock(block) => {
+                self.check_value_block(block, expected)
+            }
+            Expr::ProcedureDefinition(def) => {
+                self.check_procedure(def, expected)
+            }
+            Expr::StructDef(def) => {
+                for constant in def.consts() {
+                    let ty = self.lower(constant.ty());
+                    self.check_against(constant.value(), ty);
+                }
+
+                let outer = self.enter_generics(def.generics());
+                for method in def.methods() {
+                    self.check_operator_declaration(method.name(), method.is_static(), method.parameters(), method.ret(), method.span());
+
+                    let params: Vec<Binding> = def.receiver(method).into_iter()
+                        .chain(method.parameters().iter().cloned())
+                        .collect();
+                    let params = self.lower_params(&params);
+                    self.check_body(params, method.ret(), method.body(), false);
+                }
+                self.exit_generics(outer);
+                Some(TypeId::VOID)
+            }
+            Expr::InterfaceDef(def) => {
+                for method in def.methods() {
+                    self.check_operator_declaration(method.name(), false, method.parameters(), method.ret(), method.span());
+                }
+                Some(TypeId::VOID)
+            }
+            Expr::ImplDef(def) => {
+                self.check_impl(def);
+                Some(TypeId::VOID)
+            }
+            Expr::ObjInitialization(init) => {
+                self.check_obj_init(init, expected, expr.span())
+            }
+            Expr::MethodCall(call) => {
+                self.check_method_call(expr.id(), call)
+            }
+            Expr::MemberFieldAccess(field) => {
+                self.check_field(field)
+            }
+        }
+    }
+
+    /// Check an expression whose value is not used.
+    ///
+    /// Blocks and `if`s in statement position do not produce a value,
+    /// so a `yield` within them belongs to an enclosing block that does
+    fn check_stmt(&mut self, expr: &ExprNode) {
+        match expr.inner() {
+            Expr::CodeBlock(block) => {
+                self.check_block(block);
+                self.table.insert(expr.id(), TypeId::VOID);
+            }
+            Expr::If(conditional) => {
+                self.enter_condition(conditional);
+                self.check_stmt(conditional.body());
+                self.locals.pop();
+                if let Some(else_clause) = conditional.else_clause() {
+                    self.check_stmt(&else_clause);
+                }
+                self.table.insert(expr.id(), TypeId::VOID);
+            }
+            _ => {
+                self.check_expr(expr, None);
+            }
+        }
+    }
+
+    /// Check the statements of a block in a new scope
+    fn check_block(&mut self, block: &CodeBlock) {
+        self.locals.push(HashMap::new());
+        for expr in block.exprs() {
+            self.check_stmt(expr);
+        }
+        self.locals.pop();
+    }
+
+    /// Get the type of a block used as a value.
+    ///
+    /// It has the type of its `yield`s, which must all agree.
+    /// A block without a `yield` is `void`, unless it returns
+    /// from the procedure, in which case it never has a value
+    fn check_value_block(&mut self, block: &CodeBlock, expected: Option<TypeId>) -> Option<TypeId> {
+        self.yield_targets.push(YieldTarget {
+            expected,
+            ty: None,
+            yielded: false,
+        });
+        self.check_block(block);
+        let target = self.yield_targets.pop()?;
+
+        if target.yielded {
+            return target.ty;
+        }
+
+        let returns = block.exprs().iter().any(|expr| matches!(expr.inner(), Expr::Return(_)));
+        match returns {
+            true => None,
+            false => Some(TypeId::VOID),
+        }
+    }
+
+    /// Check a `yield` against the block that it produces the value of.
+    /// The first `yield` decides the type of the block when the
+    /// context does not
+    fn check_yield(&mut self, value: &ExprNode, span: &Span) {
+        let expected = match self.yield_targets.last() {
+            Some(target) => target.ty.or(target.expected),
+            None => {
+                self.emitter.emit_err(ViperError::YieldOutsideBlock {
+                    span: span.clone(),
+                });
+                self.check_expr(value, None);
+                return;
+            }
+        };
+
+        let found = self.check_against(value, expected);
+        if let Some(target) = self.yield_targets.last_mut() {
+            if !target.yielded {
+                // A mismatch with the context has already been reported,
+                // so the block keeps the type that the context wants
+                target.ty = target.expected.or(found);
+                target.yielded = true;
+            }
+        }
+    }
+
+    /// Get the type of an `if` used as a value.
+    ///
+    /// Every branch must yield the same type, and there has to be an
+    /// `else` unless the branches are `void`
+    fn check_value_if(&mut self, conditional: &Conditional, span: &Span, expected: Option<TypeId>) -> Option<TypeId> {
+        self.enter_condition(conditional);
+        let body = self.check_expr(conditional.body(), expected);
+        self.locals.pop();
+
+        let else_clause = match conditional.else_clause() {
+            Some(else_clause) => else_clause,
+
+            // The last branch of an `if` with no `else`
+            None if conditional.condition().is_some() => {
+                if let Some(ty) = body.filter(|ty| *ty != TypeId::VOID) {
+                    self.emitter.emit_err(ViperError::MissingElse {
+                        ty: self.types.display(ty).to_string(),
+                        span: span.clone(),
+                    });
+                    return None;
+                }
+                return Some(TypeId::VOID);
+            }
+            None => return body,
+        };
+
+        let other = self.check_expr(&else_clause, body.or(expected));
+        let (body, other) = match (body, other) {
+            (Some(body), Some(other)) => (body, other),
+
+            // Branches that never have a value fit with any other
+            (body, other) => return body.or(other),
+        };
+
+        if self.types.coerces(other, body) {
+            return Some(body);
+        }
+        if self.types.coerces(body, other) {
+            return Some(other);
+        }
+
+        self.emitter.emit_err(ViperError::IncompatibleBranches {
+            expected: self.types.display(body).to_string(),
+            found: self.types.display(other).to_string(),
+            span: else_clause.span().clone(),
+        });
+        return Some(body);
+    }
+
+    /// Check the condition of an `if` and enter the scope of its body,
+    /// which the caller leaves once the body is checked.
+    /// The variable of an `if let` is only in scope for the body, and
+    /// holds the value within the optional value it is given
+    /// `if let p = maybe` with `maybe: ?*i32` makes `p` a `*i32`
+    fn enter_condition(&mut self, conditional: &Conditional) {
+        self.locals.push(HashMap::new());
+        let condition = match conditional.condition() {
+            Some(condition) => condition,
+            None => return,
+        };
+
+        let init = match condition.inner() {
+            Expr::Let(init) => init,
+            _ => {
+                self.check_against(&condition, Some(TypeId::BOOL));
+                return;
+            }
+        };
+
+        let value = &init.values()[0];
+        let inner = self.check_expr(value, None).and_then(|ty| match self.types.kind(ty) {
+            TypeKind::Optional(inner) => Some(*inner),
+            _ => {
+                self.emitter.emit_err(ViperError::NotOptional {
+                    ty: self.types.display(ty).to_string(),
+                    span: value.span().clone(),
+                });
+                None
+            }
+        });
+
+        self.table.insert(condition.id(), TypeId::BOOL);
+        self.declare_pattern(init, inner, condition.span());
+    }
+
+    /// Get the type of `null`, which is the optional type that the context expects.
+    /// Nothing but an optional type can be `null`
+    fn check_null(&mut self, expected: Option<TypeId>, span: &Span) -> Option<TypeId> {
+        // Nothing is expected of `let x = null;`, which is reported where it is declared
+        let expected = expected?;
+        match self.types.kind(expected) {
+            TypeKind::Optional(_) => Some(expected),
+            _ => {
+                self.emitter.emit_err(ViperError::NullToNonOptional {
+                    ty: self.types.display(expected).to_string(),
+                    span: span.clone(),
+                });
+                None
+            }
+        }
+    }
+
+    /// Get the type of `Ok(value)` or `Err(error)`, which is the `Result` that
+    /// the context expects. Only one half of the `Result` is given, so the
+    /// other could never be worked out without it
+    fn check_result_value(&mut self, value: &ExprNode, is_ok: bool, expected: Option<TypeId>, span: &Span) -> Option<TypeId> {
+        // Nothing is expected of `let r = Ok(1);`, which is reported where it is declared
+        let expected = match expected {
+            Some(expected) => expected,
+            None => {
+                self.check_expr(value, None);
+                return None;
+            }
+        };
+
+        match self.types.kind(expected).clone() {
+            TypeKind::Result { ok, err } => {
+                let half = match is_ok {
+                    true => ok,
+                    false => err,
+                };
+                self.check_against(value, Some(half));
+                return Some(expected);
+            }
+            _ => {
+                self.check_expr(value, None);
+                let value = match is_ok {
+                    true => "Ok",
+                    false => "Err",
+                };
+                self.emitter.emit_err(ViperError::UnexpectedResult {
+                    value: value.to_string(),
+                    ty: self.types.display(expected).to_string(),
+                    span: span.clone(),
+                });
+                return None;
+            }
+        }
+    }
+
+    /// Get the type of `value?`, which is what `value` holds when it is `Ok`.
+    /// When it is `Err` the error is returned, so the procedure has to
+    /// return a `Result` that can hold the error
+    fn check_try(&mut self, value: &ExprNode, span: &Span) -> Option<TypeId> {
+        let ty = self.check_expr(value, None)?;
+        let (ok, err) = match self.types.kind(ty) {
+            TypeKind::Result { ok, err } => (*ok, *err),
+            _ => {
+                self.emitter.emit_err(ViperError::TryOnNonResult {
+                    ty: self.types.display(ty).to_string(),
+                    span: span.clone(),
+                });
+                return None;
+            }
+        };
+
+        if let Some(ret) = self.return_type {
+            let passes_on = match self.types.kind(ret) {
+                TypeKind::Result { ok: _, err: returned } => self.types.coerces(err, *returned),
+                _ => false,
+            };
+            if !passes_on {
+                self.emitter.emit_err(ViperError::TryIncompatibleReturn {
+                    err: self.types.display(err).to_string(),
+                    ret: self.types.display(ret).to_string(),
+                    span: span.clone(),
+                });
+            }
+        }
+
+        return Some(ok);
+    }
+
+    /// Check the body of a procedure or lambda, and get its type.
+    /// The parameters of a lambda that have no type take the
+    /// parameter types of the `fn` type it is `expected` to be
+    fn check_procedure(&mut self, def: &ProcedureDef, expected: Option<TypeId>) -> Option<TypeId> {
+        let outer = self.enter_generics(def.generics());
+        let is_lambda = def.kind() == &ProcedureKind::Lambda;
+
+        let mut params = self.lower_params(def.parameters());
+        let expected_params = match expected.map(|expected| self.types.kind(expected)) {
+            Some(TypeKind::Function { params: expected, ret: _ }) if expected.len() == params.len() => Some(expected.clone()),
+            _ => None,
+        };
+        for (i, param) in def.parameters().iter().enumerate() {
+            if param.ty() != &Type::Inferred {
+                continue;
+            }
+
+            params[i].1 = expected_params.as_ref().map(|expected| expected[i]);
+            if params[i].1.is_none() {
+                self.emitter.emit_err(ViperError::TypeAnnotationRequired {
+                    name: param.ident().clone(),
+                    span: param.span().clone(),
+                });
+            }
+        }
+
+        let ty = params.iter().map(|(_, ty)| *ty).collect::<Option<Vec<TypeId>>>()
+            .zip(self.lower(def.ret()))
+            .map(|(params, ret)| self.types.function(params, ret));
+
+        self.check_body(params, def.ret(), def.body(), is_lambda);
+        self.exit_generics(outer);
+        return ty;
+    }
+
+    /// Bring the generic parameters of an item and their bounds into scope as types.
+    /// Returns the generic parameters and bounds that were in scope before
+    fn enter_generics(&mut self, generics: &[GenericParam]) -> (HashMap<Ident, TypeId>, HashMap<Ident, Vec<Ident>>) {
+        let mut inner = self.generics.clone();
+        let mut bounds = self.bounds.clone();
+        for param in generics {
+            let ty = self.types.intern(TypeKind::Param(param.name().clone()));
+            inner.insert(param.name().clone(), ty);
+            bounds.insert(param.name().clone(), param.bounds().iter().map(|b| b.to_string()).collect());
+        }
+
+        let outer_bounds = std::mem::replace(&mut self.bounds, bounds);
+        return (std::mem::replace(&mut self.generics, inner), outer_bounds);
+    }
+
+    /// Put back the generic parameters that [TypeChecker::enter_generics] replaced
+    fn exit_generics(&mut self, outer: (HashMap<Ident, TypeId>, HashMap<Ident, Vec<Ident>>)) {
+        (self.generics, self.bounds) = outer;
+    }
+
+    /// Check the methods of an `impl` against the interface they implement,
+    /// then check their bodies the same as the methods of the struct.
+    /// An `impl` for something that can not implement an interface has
+    /// already been reported, so only its bodies are checked
+    fn check_impl(&mut self, def: &ImplDef) {
+        let name = def.interface().to_string();
+        let valid = self.structs.get(&def.target().to_string()).is_some_and(|s| s.generics().is_empty());
+        let interface = self.impls.interface(&name).filter(|_| valid);
+
+        if let Some(interface) = interface {
+            for declared in interface.methods() {
+                if !def.methods().iter().any(|m| m.name() == declared.name()) {
+                    self.emitter.emit_err(ViperError::MissingInterfaceMethod(Box::new(MissingInterfaceMethod {
+                        interface: name.clone(),
+                        method: declared.name().clone(),
+                        target: def.target().to_string(),
+                        span: def.span().clone(),
+                        declared: declared.span().clone(),
+                    })));
+                }
+            }
+
+            for method in def.methods() {
+                match interface.method(method.name()) {
+                    Some(declared) => self.check_impl_method(&name, declared, method),
+                    None => {
+                        self.emitter.emit_err(ViperError::NotInterfaceMethod {
+                            interface: name.clone(),
+                            method: method.name().clone(),
+                            span: method.span().clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        for method in def.methods() {
+            let params: Vec<Binding> = def.receiver(method).into_iter()
+                .chain(method.parameters().iter().cloned())
+                .collect();
+            let params = self.lower_params(&params);
+            self.check_body(params, method.ret(), method.body(), false);
+        }
+    }
+
+    /// Check that a method of an `impl` has the signature that its interface declares
+    fn check_impl_method(&mut self, interface: &str, declared: &InterfaceMethod, method: &StructMethod) {
+        let lower_all = |checker: &mut Self, params: &[Binding]| -> Vec<Option<TypeId>> {
+            params.iter().map(|p| checker.lower(p.ty())).collect()
+        };
+
+        let expected = (lower_all(self, declared.parameters()), self.lower(declared.ret()));
+        let found = (lower_all(self, method.parameters()), self.lower(method.ret()));
+        let same = !method.is_static() && method.is_mutating() == declared.is_mutating() && expected == found;
+
+        if !same {
+            self.emitter.emit_err(ViperError::InterfaceMethodMismatch(Box::new(InterfaceMethodMismatch {
+                interface: interface.to_string(),
+                method: method.name().clone(),
+                expected: signature(false, declared.is_mutating(), declared.parameters(), declared.ret()),
+                found: signature(method.is_static(), method.is_mutating(), method.parameters(), method.ret()),
+                span: method.span().clone(),
+            })));
+        }
+    }
+
+    /// Report a method named like an operator method that can not be used for its operator
+    fn check_operator_declaration(&mut self, name: &str, is_static: bool, params: &[Binding], ret: &Type, span: &Span) {
+        let ret = self.lower(ret);
+        if let Err(err) = check_operator_method(self.types, name, is_static, params.len(), ret, span) {
+            self.emitter.emit_err(err);
+        }
+    }
+
+    /// Lower a syntactic type with the generic parameters in scope
+    fn lower(&mut self, ty: &Type) -> Option<TypeId> {
+        return self.types.lower_with(ty, &self.generics);
+    }
+
+    /// Lower the declared types of parameters, keeping their names
+    fn lower_params(&mut self, params: &[Binding]) -> Vec<(Ident, Option<TypeId>)> {
+        return params.iter()
+            .map(|p| (p.ident().clone(), self.lower(p.ty())))
+            .collect();
+    }
+
+    /// Check a body with its parameters in scope and its return type
+    /// as the type that `return` must produce.
+    ///
+    /// Lambdas can see the locals of the procedure they are in.
+    /// Everything else starts with no locals.
+    fn check_body(&mut self, params: Vec<(Ident, Option<TypeId>)>, ret: &Type, body: &ExprNode, sees_locals: bool) {
+        let outer_locals = match sees_locals {
+            true => None,
+            false => Some(std::mem::take(&mut self.locals)),
+        };
+        let ret = self.lower(ret);
+        let outer_return = std::mem::replace(&mut self.return_type, ret);
+
+        // A `yield` can not produce a value for a block outside of the body
+        let outer_targets = std::mem::take(&mut self.yield_targets);
+
+        self.locals.push(params.into_iter().collect());
+        self.check_stmt(body);
+        self.locals.pop();
+
+        self.yield_targets = outer_targets;
+        self.return_type = outer_return;
+        if let Some(outer_locals) = outer_locals {
+            self.locals = outer_locals;
+        }
+    }
+
+    fn check_binary(
+        &mut self,
+        id: NodeId,
+        op: BinaryOperator,
+        lhs: &ExprNode,
+        rhs: &ExprNode,
+        expected: Option<TypeId>,
+        span: &Span,
+    ) -> Option<TypeId> {
+        // Only operators that produce the type of their operands
+        // pass on what type is expected of them
+        let operand_expected = match op {
+            BinaryOperator::Plus
+            | BinaryOperator::Minus
+            | BinaryOperator::Times
+            | BinaryOperator::Divide
+            | BinaryOperator::Modulo
+            | BinaryOperator::BinAnd
+            | BinaryOperator::BinOr
+            | BinaryOperator::BinXor
+            | BinaryOperator::BShiftLeft
+            | BinaryOperator::BShiftRight => expected,
+            _ => None,
+        };
+        let is_shift = matches!(op, BinaryOperator::BShiftLeft | BinaryOperator::BShiftRight);
+
+        // A literal or `null` takes the type of the other operand, so
+        // when only the left side is one, check the right first
+        // `1 + x` -> the `1` has the type of `x`
+        // `null == o` -> the `null` has the type of `o`
+        let takes_type = |expr: &ExprNode| is_literal(expr) || matches!(expr.inner(), Expr::Null);
+        let (lhs_ty, rhs_ty) = if takes_type(lhs) && !takes_type(rhs) && !is_shift {
+            let rhs_ty = self.check_expr(rhs, operand_expected);
+            let lhs_ty = self.check_expr(lhs, rhs_ty.or(operand_expected));
+            (lhs_ty, rhs_ty)
+        } else {
+            let lhs_ty = self.check_expr(lhs, operand_expected);
+
+            // An operator on a struct value calls the method that overloads it
+            let method = binary_method(op);
+            let overload = lhs_ty.zip(method).and_then(|(ty, method)| self.overload(ty, method));
+            if let (Some(overload), Some(method)) = (overload, method) {
+                let result = self.check_overload(id, method, overload, &[rhs]);
+                return match op {
+                    BinaryOperator::Plus
+                    | BinaryOperator::Minus
+                    | BinaryOperator::Times
+                    | BinaryOperator::Divide
+                    | BinaryOperator::Modulo
+                    | BinaryOperator::BinAnd
+                    | BinaryOperator::BinOr
+                    | BinaryOperator::BinXor
+                    | BinaryOperator::BShiftLeft
+                    | BinaryOperator::BShiftRight => result,
+
+                    // `op_eq` and `op_cmp` are compared to give the result
+                    _ => result.and(Some(TypeId::BOOL)),
+                };
+            }
+
+            let rhs_expected = match is_shift {
+                true => None,
+                false => lhs_ty,
+            };
+            let rhs_ty = self.check_expr(rhs, rhs_expected);
+            (lhs_ty, rhs_ty)
+        };
+        let (lhs_ty, rhs_ty) = (lhs_ty?, rhs_ty?);
+
+        if let Some(result) = pointer_arithmetic(self.types, op, lhs_ty, rhs_ty, span) {
+            return self.report(result);
+        }
+
+        let types = &self.types;
+        let same = lhs_ty == rhs_ty;
+        let result = match op {
+            BinaryOperator::Plus
+            | BinaryOperator::Minus
+            | BinaryOperator::Times
+            | BinaryOperator::Divide => {
+                (same && types.is_numeric(lhs_ty)).then_some(lhs_ty)
+            }
+            BinaryOperator::Modulo
+            | BinaryOperator::BinAnd
+            | BinaryOperator::BinOr
+            | BinaryOperator::BinXor => {
+                (same && types.is_integer(lhs_ty)).then_some(lhs_ty)
+            }
+            BinaryOperator::BShiftLeft
+            | BinaryOperator::BShiftRight => {
+                (types.is_integer(lhs_ty) && types.is_integer(rhs_ty)).then_some(lhs_ty)
+            }
+            BinaryOperator::LessThan
+            | BinaryOperator::GreaterThan
+            | BinaryOperator::LessThanEqualTo
+            | BinaryOperator::GreaterThanEqualTo => {
+                (same && (types.is_numeric(lhs_ty) || types.is_pointer(lhs_ty))).then_some(TypeId::BOOL)
+            }
+            BinaryOperator::EqualTo
+            | BinaryOperator::NotEqualTo => {
+                // Any optional can be compared with `null`, whatever it holds
+                let is_null = matches!(lhs.inner(), Expr::Null) || matches!(rhs.inner(), Expr::Null);
+                let comparable = types.is_numeric(lhs_ty)
+                    || types.is_pointer(lhs_ty)
+                    || lhs_ty == TypeId::BOOL
+                    || (is_null && types.is_optional(lhs_ty));
+                (same && comparable).then_some(TypeId::BOOL)
+            }
+            BinaryOperator::LogicalAnd
+            | BinaryOperator::LogicalOr => {
+                (same && lhs_ty == TypeId::BOOL).then_some(TypeId::BOOL)
+            }
+            BinaryOperator::InvalidBinary => None,
+        };
+
+        if result.is_none() {
+            self.emitter.emit_err(ViperError::InvalidBinaryOperands(Box::new(InvalidBinaryOperands {
+                op: op.to_string(),
+                lhs: self.types.display(lhs_ty).to_string(),
+                rhs: self.types.display(rhs_ty).to_string(),
+                span: span.clone(),
+            })));
+        }
+
+        return result;
+    }
+
+    fn check_unary(
+        &mut self,
+        id: NodeId,
+        op: UnaryOperator,
+        operand: &ExprNode,
+        expected: Option<TypeId>,
+        span: &Span,
+    ) -> Option<TypeId> {
+        let operand_expected = match op {
+            UnaryOperator::AddressOf | UnaryOperator::AddressOfMut => {
+                expected.and_then(|ty| match self.types.kind(ty) {
+                    TypeKind::Pointer { mutable: _, pointee } => Some(*pointee),
+                    _ => None,
+                })
+            }
+            UnaryOperator::Minus | UnaryOperator::BinaryNot => expected,
+            _ => None,
+        };
+        let ty = self.check_expr(operand, operand_expected)?;
+
+        if let Some(method) = unary_method(op) {
+            if let Some(overload) = self.overload(ty, method) {
+                return self.check_overload(id, method, overload, &[]);
+            }
+        }
+
+        if let Some(result) = pointer_unary(self.types, op, ty, span) {
+            return self.report(result);
+        }
+
+        let valid = match op {
+            UnaryOperator::Minus => self.types.is_numeric(ty),
+            UnaryOperator::BinaryNot => self.types.is_integer(ty),
+            UnaryOperator::LogicalNot => ty == TypeId::BOOL,
+            _ => false,
+        };
+
+        if !valid {
+            self.emitter.emit_err(ViperError::InvalidUnaryOperand {
+                op: op.to_string(),
+                ty: self.types.display(ty).to_string(),
+                span: span.clone(),
+            });
+            return None;
+        }
+
+        return Some(ty);
+    }
+
+    /// Get the type of indexing into a value.
+    /// Arrays and slices give their elements, and structs call their `op_index`
+    fn check_index(&mut self, id: NodeId, object: &ExprNode, index: &ExprNode, span: &Span) -> Option<TypeId> {
+        let object_ty = self.check_expr(object, None);
+        if let Some(overload) = object_ty.and_then(|ty| self.overload(ty, INDEX_METHOD)) {
+            return self.check_overload(id, INDEX_METHOD, overload, &[index]);
+        }
+
+        let index_ty = self.check_expr(index, None);
+        let (object_ty, index_ty) = (object_ty?, index_ty?);
+
+        let element = match self.types.kind(object_ty) {
+            TypeKind::Array { element, length: _ } | TypeKind::Slice(element) => Some(*element),
+            _ => None,
+        };
+
+        match element {
+            Some(element) if self.types.is_integer(index_ty) => Some(element),
+            _ => {
+                self.emitter.emit_err(ViperError::InvalidIndex {
+                    ty: self.types.display(object_ty).to_string(),
+                    index: self.types.display(index_ty).to_string(),
+                    span: span.clone(),
+                });
+                None
+            }
+        }
+    }
+
+    /// Find the method that overloads an operator for values of type `ty`.
+    /// Only struct values and values of bound generic parameters have them.
+    /// Methods that can not be used for their operator have already been
+    /// reported where they are declared, so they are left out
+    fn overload(&mut self, ty: TypeId, method: &str) -> Option<Overload<'a>> {
+        let overload = match self.types.kind(ty) {
+            TypeKind::Struct(_, _) => {
+                let def = receiver_struct(self.types, self.structs, ty)?;
+                let found = def.methods().iter().find(|m| m.name() == method)?;
+                Overload::Method(found, self.struct_bindings(def, ty))
+            }
+            TypeKind::Param(_) => {
+                let (interface, slot) = self.interface_method(ty, method)?;
+                Overload::Interface(interface, slot)
+            }
+            _ => return None,
+        };
+
+        let fits = match &overload {
+            Overload::Method(found, bindings) => {
+                let ret = self.types.lower_with(found.ret(), bindings);
+                fits_operator(method, found.is_static(), found.parameters().len(), ret)
+            }
+            Overload::Interface(interface, slot) => {
+                let found = &interface.methods()[*slot];
+                let ret = self.lower(found.ret());
+                fits_operator(method, false, found.parameters().len(), ret)
+            }
+        };
+
+        return fits.then_some(overload);
+    }
+
+    /// Check the operands of an overloaded operator against the parameters
+    /// of the method it calls. The first operand is the receiver, so only
+    /// the others are given. Returns the type that the method returns
+    fn check_overload(&mut self, id: NodeId, method: &str, overload: Overload<'a>, operands: &[&ExprNode]) -> Option<TypeId> {
+        self.table.insert_operator(id, method.to_string());
+
+        let (params, ret, bindings) = match overload {
+            Overload::Method(found, bindings) => (found.parameters(), found.ret(), bindings),
+            Overload::Interface(interface, slot) => {
+                self.table.insert_dispatch(id, interface.name().clone(), slot);
+                let found = &interface.methods()[slot];
+                (found.parameters(), found.ret(), self.generics.clone())
+            }
+        };
+
+        for (param, operand) in params.iter().zip(operands) {
+            let expected = self.types.lower_with(param.ty(), &bindings);
+            self.check_against(operand, expected);
+        }
+
+        return self.types.lower_with(ret, &bindings);
+    }
+
+    /// Check the arguments of a call against the parameters of the procedure
+    fn check_call(&mut self, id: NodeId, call: &ProcedureCall, expected: Option<TypeId>, span: &Span) -> Option<TypeId> {
+        if let Some(callee) = self.generic_callee(call.name()) {
+            return self.check_generic_call(id, call, callee, expected, span);
+        }
+
+        let callee = self.path_type(call.name());
+        let kind = callee.map(|callee| self.types.kind(callee).clone());
+
+        match kind {
+            Some(TypeKind::Function { params, ret }) => {
+                if params.len() != call.arguments().len() {
+                    self.emitter.emit_err(ViperError::ArgumentCountMismatch {
+                        name: call.name().to_string(),
+                        expected: params.len(),
+                        found: call.arguments().len(),
+                        span: span.clone(),
+                    });
+                }
+
+                for (i, arg) in call.arguments().iter().enumerate() {
+                    self.check_against(arg, params.get(i).copied());
+                }
+
+                return Some(ret);
+            }
+            Some(_) => {
+                self.emitter.emit_err(ViperError::NotCallable {
+                    ty: self.types.display(callee?).to_string(),
+                    span: call.name().span(),
+                });
+            }
+            None => {}
+        }
+
+        for arg in call.arguments() {
+            self.check_expr(arg, None);
+        }
+
+        return None;
+    }
+
+    /// Get what a path names, when it is a generic procedure or a static
+    /// method of a generic struct
+    fn generic_callee(&self, path: &Path) -> Option<GenericCallee<'a>> {
+        if path.is_ident() {
+            let name = path.last().name().to_string();
+            if self.is_local(&name) {
+                return None;
+            }
+
+            let def = self.procedures.get(&name).filter(|def| !def.generics().is_empty())?;
+            return Some(GenericCallee { generics: def.generics(), params: def.parameters(), ret: def.ret() });
+        }
+
+        let segments = path.segments();
+        let def = self.structs.get(segments[0].name()).filter(|def| !def.generics().is_empty())?;
+        let method = def.methods().iter().find(|m| m.is_static() && m.name() == segments[1].name())?;
+
+        return Some(GenericCallee { generics: def.generics(), params: method.parameters(), ret: method.ret() });
+    }
+
+    /// Check a call of a generic procedure or of a static method of a generic struct.
+    /// The type arguments that are worked out get recorded for the call
+    fn check_generic_call(
+        &mut self,
+        id: NodeId,
+        call: &ProcedureCall,
+        callee: GenericCallee<'_>,
+        expected: Option<TypeId>,
+        span: &Span,
+    ) -> Option<TypeId> {
+        let GenericCallee { generics, params, ret } = callee;
+        if params.len() != call.arguments().len() {
+            self.emitter.emit_err(ViperError::ArgumentCountMismatch {
+                name: call.name().to_string(),
+                expected: params.len(),
+                found: call.arguments().len(),
+                span: span.clone(),
+            });
+        }
+
+        for arg in call.arguments().iter().skip(params.len()) {
+            self.check_expr(arg, None);
+        }
+
+        let values: Vec<(&Type, &ExprNode)> = params.iter()
+            .zip(call.arguments())
+            .map(|(param, arg)| (param.ty(), arg.as_ref()))
+            .collect();
+
+        let bindings = self.infer_type_args(&call.name().to_string(), generics, &values, ret, expected, span)?;
+        let args = generics.iter().map(|g| bindings[g.name()]).collect();
+        self.table.insert_args(id, args);
+
+        return self.types.lower_with(ret, &bindings);
+    }
+
+    /// Work out the type arguments of a generic item from the values given
+    /// for its parameters, and from the type the context expects it to produce.
+    ///
+    /// Values that are not literals are checked first, as their type does not
+    /// depend on the context. Literals then take the type of their parameter,
+    /// or decide it when nothing else has. Every value ends up checked
+    /// against its parameter.
+    fn infer_type_args(
+        &mut self,
+        name: &str,
+        generics: &[GenericParam],
+        values: &[(&Type, &ExprNode)],
+        ret: &Type,
+        expected: Option<TypeId>,
+        span: &Span,
+    ) -> Option<HashMap<Ident, TypeId>> {
+        let mut bindings = HashMap::new();
+        if let Some(expected) = expected {
+            self.unify(ret, expected, generics, &mut bindings);
+        }
+
+        // The types of the values that are checked before their parameter is known
+        let mut found: Vec<Option<Option<TypeId>>> = vec![None; values.len()];
+        for (i, (ty, value)) in values.iter().enumerate() {
+            if mentions_generic(ty, generics) && !is_literal(value) {
+                let value_ty = self.check_expr(value, None);
+                if let Some(value_ty) = value_ty {
+                    self.unify(ty, value_ty, generics, &mut bindings);
+                }
+                found[i] = Some(value_ty);
+            }
+        }
+
+        for (i, (ty, value)) in values.iter().enumerate() {
+            if found[i].is_some() {
+                continue;
+            }
+
+            match self.types.lower_with(ty, &bindings) {
+                Some(param) => {
+                    self.check_against(value, Some(param));
+                }
+                None => {
+                    if let Some(value_ty) = self.check_expr(value, None) {
+                        self.unify(ty, value_ty, generics, &mut bindings);
+                    }
+                }
+            }
+        }
+
+        if let Some(param) = generics.iter().find(|g| !bindings.contains_key(g.name())) {
+            self.emitter.emit_err(ViperError::CannotInferTypeArguments {
+                name: name.to_string(),
+                param: param.name().clone(),
+                span: span.clone(),
+            });
+            return None;
+        }
+
+        for param in generics {
+            self.check_bounds(param, bindings[param.name()], span);
+        }
+
+        for (i, (ty, value)) in values.iter().enumerate() {
+            let (Some(Some(value_ty)), Some(param)) = (found[i], self.types.lower_with(ty, &bindings)) else {
+                continue;
+            };
+
+            if !self.types.coerces(value_ty, param) {
+                self.emitter.emit_err(ViperError::MismatchedTypes {
+                    expected: self.types.display(param).to_string(),
+                    found: self.types.display(value_ty).to_string(),
+                    span: value.span().clone(),
+                });
+            }
+        }
+
+        return Some(bindings);
+    }
+
+    /// Report each bound of a generic parameter that its type argument does not implement
+    fn check_bounds(&mut self, param: &GenericParam, ty: TypeId, span: &Span) {
+        for bound in param.bounds() {
+            let interface = bound.to_string();
+
+            // An undefined interface has already been reported
+            if self.impls.interface(&interface).is_some() && !self.implements(ty, &interface) {
+                self.emitter.emit_err(ViperError::UnsatisfiedBound {
+                    ty: self.types.display(ty).to_string(),
+                    interface,
+                    span: span.clone(),
+                });
+            }
+        }
+    }
+
+    /// Whether a type implements an interface.
+    /// A generic parameter implements the interfaces it is bound by
+    fn implements(&self, ty: TypeId, interface: &str) -> bool {
+        match self.types.kind(ty) {
+            TypeKind::Struct(name, args) => args.is_empty() && self.impls.implements(name, interface),
+            TypeKind::Param(name) => {
+                self.bounds.get(name).map(|bounds| bounds.iter().any(|b| b == interface)).unwrap_or(false)
+            }
+            _ => false,
+        }
+    }
+
+    /// Match a syntactic type that mentions generic parameters against a type,
+    /// binding each parameter that is not bound yet to the type in its place
+    /// `Pair<T, u8>` against `Pair<i32, u8>` binds `T` to `i32`
+    fn unify(&self, pattern: &Type, ty: TypeId, generics: &[GenericParam], bindings: &mut HashMap<Ident, TypeId>) {
+        match (pattern, self.types.kind(ty)) {
+            (Type::Concrete { name, args }, _) if args.is_empty() && is_generic(name, generics) => {
+                bindings.entry(name.last().name().to_string()).or_insert(ty);
+            }
+            (Type::Concrete { name, args }, TypeKind::Struct(found, found_args))
+                if name.to_string() == *found && args.len() == found_args.len() => {
+                for (arg, found) in args.iter().zip(found_args) {
+                    self.unify(arg, *found, generics, bindings);
+                }
+            }
+            (Type::Concrete { name, args }, TypeKind::Result { ok, err })
+                if name.is_ident() && TypeInterner::builtin_generic(name.last().name()) == Some(args.len()) => {
+                self.unify(&args[0], *ok, generics, bindings);
+                self.unify(&args[1], *err, generics, bindings);
+            }
+            (Type::Pointer { mutable: _, inner }, TypeKind::Pointer { mutable: _, pointee })
+            | (Type::Reference { mutable: _, inner }, TypeKind::Pointer { mutable: _, pointee }) => {
+                self.unify(inner, *pointee, generics, bindings);
+            }
+            (Type::Array { element, length: _ }, TypeKind::Array { element: found, length: _ })
+            | (Type::Slice(element), TypeKind::Slice(found))
+            | (Type::Optional(element), TypeKind::Optional(found)) => {
+                self.unify(element, *found, generics, bindings);
+            }
+
+            // A value is given where an optional one is expected
+            (Type::Optional(inner), _) => {
+                self.unify(inner, ty, generics, bindings);
+            }
+            (Type::Tuple(elements), TypeKind::Tuple(found)) if elements.len() == found.len() => {
+                for (element, found) in elements.iter().zip(found) {
+                    self.unify(element, *found, generics, bindings);
+                }
+            }
+            (Type::Procedure { name: _, params, return_type }, TypeKind::Function { params: found, ret })
+            | (Type::Function { params, return_type }, TypeKind::Function { params: found, ret })
+                if params.len() == found.len() => {
+                for (param, found) in params.iter().zip(found) {
+                    self.unify(param, *found, generics, bindings);
+                }
+                self.unify(return_type, *ret, generics, bindings);
+            }
+            _ => {}
+        }
+    }
+
+    /// Map the generic parameters of a struct onto the type
+    /// arguments of `ty`, which is the struct or a pointer to it
+    fn struct_bindings(&self, def: &StructDef, ty: TypeId) -> HashMap<Ident, TypeId> {
+        let ty = match self.types.kind(ty) {
+            TypeKind::Pointer { mutable: _, pointee } => *pointee,
+            _ => ty,
+        };
+
+        match self.types.kind(ty) {
+            TypeKind::Struct(_, args) => {
+                def.generics().iter()
+                    .map(|g| g.name().clone())
+                    .zip(args.iter().copied())
+                    .collect()
+            }
+            _ => HashMap::new(),
+        }
+    }
+
+    /// Check a call of a method on a value against the method's parameters
+    fn check_method_call(&mut self, id: NodeId, call: &MethodCall) -> Option<TypeId> {
+        let receiver = self.check_expr(call.receiver(), None);
+        let def = receiver.and_then(|ty| receiver_struct(self.types, self.structs, ty));
+        let method = def.and_then(|def| def.methods().iter().find(|m| m.name() == call.name()));
+
+        if let Some((interface, slot)) = receiver.and_then(|ty| self.interface_method(ty, call.name())) {
+            return self.check_interface_call(id, call, interface, slot);
+        }
+
+        let (def, method) = match (def, method) {
+            (Some(def), Some(method)) => (def, method),
+            _ => {
+                if let Some(receiver) = receiver {
+                    self.emitter.emit_err(ViperError::UndefinedMember {
+                        parent: self.types.display(receiver).to_string(),
+                        member: call.name().clone(),
+                        span: call.span().clone(),
+                    });
+                }
+
+                for arg in call.arguments() {
+                    self.check_expr(arg, None);
+                }
+                return None;
+            }
+        };
+
+        if method.is_static() {
+            self.emitter.emit_err(ViperError::StaticMethodOnValue(Box::new(StaticMethodOnValue {
+                parent: def.identifier().clone(),
+                method: method.name().clone(),
+                span: call.span().clone(),
+                declared: method.span().clone(),
+            })));
+        }
+
+        // A method of a generic struct uses the type arguments of the value it is called on
+        let bindings = self.struct_bindings(def, receiver?);
+
+        let params = method.parameters();
+        if params.len() != call.arguments().len() {
+            self.emitter.emit_err(ViperError::ArgumentCountMismatch {
+                name: call.name().clone(),
+                expected: params.len(),
+                found: call.arguments().len(),
+                span: call.span().clone(),
+            });
+        }
+
+        for (i, arg) in call.arguments().iter().enumerate() {
+            let expected = params.get(i).and_then(|p| self.types.lower_with(p.ty(), &bindings));
+            self.check_against(arg, expected);
+        }
+
+        return self.types.lower_with(method.ret(), &bindings);
+    }
+
+    /// Find the interface method that a method call goes to when it is called on a
+    /// `*dyn` pointer, or on a value of a generic parameter that is bound by the interface.
+    /// Returns the interface and the method's slot in its vtable
+    fn interface_method(&self, receiver: TypeId, name: &str) -> Option<(&'a InterfaceDef, usize)> {
+        let ty = match self.types.kind(receiver) {
+            TypeKind::Pointer { mutable: _, pointee } => *pointee,
+            _ => receiver,
+        };
+
+        let interfaces = match self.types.kind(ty) {
+            TypeKind::Dyn(interface) => vec![interface.clone()],
+            TypeKind::Param(param) => self.bounds.get(param).cloned().unwrap_or_default(),
+            _ => return None,
+        };
+
+        let impls = self.impls;
+        return interfaces.iter()
+            .filter_map(|interface| impls.interface(interface))
+            .find_map(|interface| {
+                interface.methods().iter()
+                    .position(|m| m.name() == name)
+                    .map(|slot| (interface, slot))
+            });
+    }
+
+    /// Check a call of an interface method against the parameters the interface declares
+    fn check_interface_call(&mut self, id: NodeId, call: &MethodCall, interface: &InterfaceDef, slot: usize) -> Option<TypeId> {
+        let method = &interface.methods()[slot];
+        self.table.insert_dispatch(id, interface.name().clone(), slot);
+
+        let params = method.parameters();
+        if params.len() != call.arguments().len() {
+            self.emitter.emit_err(ViperError::ArgumentCountMismatch {
+                name: call.name().clone(),
+                expected: params.len(),
+                found: call.arguments().len(),
+                span: call.span().clone(),
+            });
+        }
+
+        for (i, arg) in call.arguments().iter().enumerate() {
+            let expected = params.get(i).and_then(|p| self.lower(p.ty()));
+            self.check_against(arg, expected);
+        }
+
+        return self.lower(method.ret());
+    }
+
+    /// Get the type of a tuple expression.
+    /// Each element takes its expected type from the tuple that is expected
+    /// `let t: (u8, f64) = (1, 2.0);` makes the `1` a `u8`
+    fn check_tuple(&mut self, elements: &[ExprNode], expected: Option<TypeId>) -> Option<TypeId> {
+        let expected = match expected.map(|ty| self.types.kind(ty)) {
+            Some(TypeKind::Tuple(types)) if types.len() == elements.len() => types.clone(),
+            _ => vec![],
+        };
+
+        let mut types = vec![];
+        for (i, element) in elements.iter().enumerate() {
+            types.push(self.check_expr(element, expected.get(i).copied()));
+        }
+
+        let types = types.into_iter().collect::<Option<Vec<TypeId>>>()?;
+        return Some(self.types.intern(TypeKind::Tuple(types)));
+    }
+
+    /// Get the type of an array literal.
+    /// The elements have the element type of the array that is expected,
+    /// or else the type of the first element. An empty array has no
+    /// element to go by, and `let a = [];` is reported where it is declared
+    /// `let a: [u8; 3] = [1, 2, 3];` makes each element a `u8`
+    fn check_array_literal(&mut self, elements: &[ExprNode], expected: Option<TypeId>) -> Option<TypeId> {
+        let mut element = self.expected_element(expected);
+        for value in elements {
+            match element {
+                Some(element) => {
+                    self.check_against(value, Some(element));
+                }
+                None => {
+                    element = self.check_expr(value, None);
+                }
+            }
+        }
+
+        return Some(self.types.intern(TypeKind::Array {
+            element: element?,
+            length: elements.len(),
+        }));
+    }
+
+    /// Get the type of an array that repeats one value for every element
+    /// `[0; 64]`
+    fn check_array_repeat(&mut self, value: &ExprNode, length: &ArrayLength, expected: Option<TypeId>) -> Option<TypeId> {
+        let expected_element = self.expected_element(expected);
+        let found = self.check_against(value, expected_element);
+        let element = expected_element.or(found)?;
+
+        // Lengths that are not constants were reported by the name resolver
+        let length = match length {
+            ArrayLength::Literal(length) => *length,
+            ArrayLength::Named(path) => self.types.length(path)?,
+        };
+
+        return Some(self.types.intern(TypeKind::Array { element, length }));
+    }
+
+    /// Get the element type of the array that the context expects, if it expects one
+    fn expected_element(&self, expected: Option<TypeId>) -> Option<TypeId> {
+        match expected.map(|ty| self.types.kind(ty)) {
+            Some(TypeKind::Array { element, length: _ }) => Some(*element),
+            _ => None,
+        }
+    }
+
+    /// Get the type of a field of a struct value, or of an element of a tuple
+    fn check_field(&mut self, field: &Field) -> Option<TypeId> {
+        let object = self.check_expr(field.object(), None)?;
+        if let Some(element) = self.tuple_element(object, field.name()) {
+            return Some(element);
+        }
+
+        let def = receiver_struct(self.types, self.structs, object);
+        let declared = def
+            .and_then(|def| def.fields().iter().find(|f| f.binding().ident() == field.name()));
+
+        match (def, declared) {
+            (Some(def), Some(declared)) => {
+                let bindings = self.struct_bindings(def, object);
+                self.types.lower_with(declared.binding().ty(), &bindings)
+            }
+            _ => {
+                self.emitter.emit_err(ViperError::UndefinedMember {
+                    parent: self.types.display(object).to_string(),
+                    member: field.name().clone(),
+                    span: field.span().clone(),
+                });
+                None
+            }
+        }
+    }
+
+    /// Get the type of the element of a tuple, or of the tuple a pointer
+    /// points to, at the position `name`
+    /// `pair.0`
+    fn tuple_element(&self, ty: TypeId, name: &str) -> Option<TypeId> {
+        let tuple = match self.types.kind(ty) {
+            TypeKind::Pointer { pointee, .. } => *pointee,
+            _ => ty,
+        };
+
+        match self.types.kind(tuple) {
+            TypeKind::Tuple(elements) => elements.get(name.parse::<usize>().ok()?).copied(),
+            _ => None,
+        }
+    }
+
+    /// Check the fields of a struct initializer against the struct definition.
+    /// The type arguments of a generic struct are worked out from the
+    /// values of its fields and the type the context expects
+    fn check_obj_init(&mut self, init: &ObjInit, expected: Option<TypeId>, span: &Span) -> Option<TypeId> {
+        let name = init.name().to_string();
+        let def = match self.structs.get(&name) {
+            Some(def) => def,
+            None => {
+                for field in init.initializations() {
+                    self.check_expr(field.value(), None);
+                }
+                return None;
+            }
+        };
+
+        let mut values = vec![];
+        let mut seen = HashSet::new();
+        for field in init.initializations() {
+            if !seen.insert(field.name()) {
+                self.emitter.emit_err(ViperError::DuplicateField {
+                    structure: def.identifier().clone(),
+                    field: field.name().clone(),
+                    span: field.span().clone(),
+                });
+                self.check_expr(field.value(), None);
+                continue;
+            }
+
+            let declared = def.fields().iter()
+                .find(|f| f.binding().ident() == field.name());
+
+            match declared {
+                Some(declared) => values.push((declared.binding().ty(), field.value())),
+                None => {
+                    self.check_expr(field.value(), None);
+                }
+            }
+        }
+
+        let ty = def.self_type(span);
+        let bindings = self.infer_type_args(&name, def.generics(), &values, &ty, expected, span);
+
+        for field in def.fields() {
+            let name = field.binding().ident();
+            if !init.initializations().iter().any(|f| f.name() == name) {
+                self.emitter.emit_err(ViperError::MissingField {
+                    structure: def.identifier().clone(),
+                    field: name.clone(),
+                    span: span.clone(),
+                });
+            }
+        }
+
+        return self.types.lower_with(&ty, &bindings?);
+    }
+
+    /// Get the type of the item that a path refers to
+    /// `foo`       -> the type of the variable or procedure `foo`
+    /// `User::new` -> the type of the static method `new`
+    /// `User::MAX` -> the type of the constant `MAX`
+    fn path_type(&mut self, path: &Path) -> Option<TypeId> {
+        if path.is_ident() {
+            return self.lookup_value(&path.last().name().to_string(), &path.span());
+        }
+
+        let segments = path.segments();
+        let def = self.structs.get(segments[0].name())?;
+        if let Some(constant) = def.consts().iter().find(|c| c.name() == segments[1].name()) {
+            return self.lower(constant.ty());
+        }
+
+        let method = def.methods().iter().find(|m| m.name() == segments[1].name())?;
+
+        if !method.is_static() {
+            self.emitter.emit_err(ViperError::MethodWithoutReceiver {
+                parent: def.identifier().clone(),
+                method: method.name().clone(),
+                span: path.span(),
+            });
+            return None;
+        }
+
+        // The type arguments of a static method are worked out from its calls
+        if let Some(param) = def.generics().first() {
+            self.emitter.emit_err(ViperError::CannotInferTypeArguments {
+                name: path.to_string(),
+                param: param.name().clone(),
+                span: path.span(),
+            });
+            return None;
+        }
+
+        let signature = Type::Function {
+            params: method.parameters().iter().map(|p| p.ty().clone()).collect(),
+            return_type: Arc::from(method.ret().clone()),
+        };
+
+        return self.types.lower(&signature);
+    }
+
+    /// Get the type of a name used as a value.
+    /// A generic procedure can only be called, as its type
+    /// arguments are worked out from its calls
+    fn lookup_value(&mut self, name: &Ident, span: &Span) -> Option<TypeId> {
+        if !self.is_local(name) {
+            let generic = self.procedures.get(name).and_then(|def| def.generics().first());
+            if let Some(param) = generic {
+                self.emitter.emit_err(ViperError::CannotInferTypeArguments {
+                    name: name.clone(),
+                    param: param.name().clone(),
+                    span: span.clone(),
+                });
+                return None;
+            }
+        }
+
+        return self.lookup(name);
+    }
+
+    /// Whether a name is a local variable at the current point
+    fn is_local(&self, name: &Ident) -> bool {
+        return self.locals.iter().any(|scope| scope.contains_key(name));
+    }
+
+    /// Look up the type of a value by name.
+    /// Locals are searched first, then the items of the module
+    fn lookup(&mut self, name: &Ident) -> Option<TypeId> {
+        if let Some(local) = self.locals.iter().rev().find_map(|scope| scope.get(name)) {
+            return *local;
+        }
+
+        let symbol = self.file_scope.borrow().lookup(name)?;
+        match symbol.kind() {
+            // Structs and interfaces are types, and modules hold items. Neither are values
+            SymbolKind::Struct | SymbolKind::Interface | SymbolKind::Module => None,
+            _ => self.types.lower(symbol.dtype()),
+        }
+    }
+
+    /// Declare a local variable in the innermost scope
+    fn declare_local(&mut self, name: Ident, ty: Option<TypeId>) {
+        if let Some(scope) = self.locals.last_mut() {
+            scope.insert(name, ty);
+        }
+    }
+
+    /// Declare the variables of a `let` with the parts of `ty` they get, and
+    /// record the type of each under its name in the pattern.
+    /// The variables have no known type if the pattern does not fit `ty`
+    fn declare_pattern(&mut self, init: &VariableInitialization, ty: Option<TypeId>, span: &Span) {
+        let fits = match ty {
+            Some(ty) => self.check_pattern(init, ty, span),
+            None => false,
+        };
+
+        for binding in init.bindings() {
+            let element = match fits {
+                true => ty.and_then(|ty| self.element_at(ty, binding.path())),
+                false => None,
+            };
+            if let Some(element) = element {
+                self.table.insert(binding.id(), element);
+            }
+            self.declare_local(binding.name().clone(), element);
+        }
+    }
+
+    /// Check that the pattern of a `let` has the shape of `ty`
+    /// `let (a, (b, c))` needs a tuple whose second element is a tuple of two
+    fn check_pattern(&mut self, init: &VariableInitialization, ty: TypeId, span: &Span) -> bool {
+        if self.pattern_fits(&init.targets()[0], ty) {
+            return true;
+        }
+
+        self.emitter.emit_err(ViperError::PatternMismatch {
+            pattern: init.name(),
+            ty: self.types.display(ty).to_string(),
+            span: span.clone(),
+        });
+        return false;
+    }
+
+    /// Whether a pattern, or a part of one, has the shape of `ty`.
+    /// A name fits any type
+    fn pattern_fits(&self, pattern: &ExprNode, ty: TypeId) -> bool {
+        let patterns = match pattern.inner() {
+            Expr::Tuple(patterns) => patterns,
+            _ => return true,
+        };
+
+        match self.types.kind(ty) {
+            TypeKind::Tuple(elements) if elements.len() == patterns.len() => {
+                patterns.iter().zip(elements).all(|(pattern, element)| self.pattern_fits(pattern, *element))
+            }
+            _ => false,
+        }
+    }
+
+    /// Get the element of nested tuples at `path`
+    /// `[1, 0]` in `(i32, (bool, f64))` -> `bool`
+    fn element_at(&self, ty: TypeId, path: &[usize]) -> Option<TypeId> {
+        let mut ty = ty;
+        for index in path {
+            ty = match self.types.kind(ty) {
+                TypeKind::Tuple(elements) => *elements.get(*index)?,
+                _ => return None,
+            };
+        }
+
+        return Some(ty);
+    }
+
+    /// Report the error of a failed check
+    fn report(&mut self, result: Result<TypeId, ViperError>) -> Option<TypeId> {
+        match result {
+            Ok(ty) => Some(ty),
+            Err(err) => {
+                self.emitter.emit_err(err);
+                None
+            }
+        }
+    }
+}
+
+/// Get the struct that a method or field is looked up in.
+/// A pointer to a struct is followed to the struct it points to,
+/// so that `self.name` works on the `self` pointer
+pub fn receiver_struct<'s>(
+    types: &TypeInterner,
+    structs: &'s HashMap<Ident, StructDef>,
+    ty: TypeId,
+) -> Option<&'s StructDef> {
+    let ty = match types.kind(ty) {
+        TypeKind::Pointer { mutable: _, pointee } => *pointee,
+        _ => ty,
+    };
+
+    match types.kind(ty) {
+        TypeKind::Struct(name, _) => structs.get(name),
+        _ => None,
+    }
+}
+
+/// Whether a path names one of the generic parameters in `generics`
+fn is_generic(name: &Path, generics: &[GenericParam]) -> bool {
+    return name.is_ident() && generics.iter().any(|g| g.name() == name.last().name());
+}
+
+/// Whether a syntactic type has any of the generic parameters in `generics` within it
+/// `*Pair<T, u8>` mentions `T`
+fn mentions_generic(ty: &Type, generics: &[GenericParam]) -> bool {
+    match ty {
+        Type::Concrete { name, args } => {
+            is_generic(name, generics) || args.iter().any(|arg| mentions_generic(arg, generics))
+        }
+        Type::Procedure { name: _, params, return_type }
+        | Type::Function { params, return_type } => {
+            params.iter().any(|p| mentions_generic(p, generics)) || mentions_generic(return_type, generics)
+        }
+        Type::Pointer { mutable: _, inner }
+        | Type::Reference { mutable: _, inner }
+        | Type::Array { element: inner, length: _ }
+        | Type::Slice(inner)
+        | Type::Optional(inner) => mentions_generic(inner, generics),
+        Type::Tuple(elements) => elements.iter().any(|e| mentions_generic(e, generics)),
+        Type::Dyn(_) | Type::Void | Type::Inferred => false,
+    }
+}
+
+/// Write out the signature of a method the way it is declared, without its name
+/// `mut method (i32): void`
+fn signature(is_static: bool, is_mutating: bool, params: &[Binding], ret: &Type) -> String {
+    let kind = match (is_static, is_mutating) {
+        (true, _) => "static",
+        (false, true) => "mut method",
+        (false, false) => "method",
+    };
+    let params: Vec<String> = params.iter().map(|p| p.ty().to_string()).collect();
+
+    return format!("{kind} ({}): {ret}", params.join(", "));
+}
+
+/// Whether an expression is a numeric literal, which can take its type from context
+/// `1`, `-2.5`
+fn is_literal(expr: &ExprNode) -> bool {
+    match expr.inner() {
+        Expr::Integer(_) | Expr::Float(_) => true,
+        Expr::UnaryOperation(UnaryOperator::Minus, inner) => is_literal(inner),
+        _ => false,
+    }
+}
+
+/// Whether an expression gets its whole type from the context it is used in
+/// `null`, `Ok(1)`, `Err(error)`, `[]`
+fn needs_context(expr: &ExprNode) -> bool {
+    match expr.inner() {
+        Expr::Null | Expr::Ok(_) | Expr::Err(_) => true,
+        Expr::ArrayLiteral(elements) => elements.is_empty(),
+        _ => false,
+    }
+}
@@ -0,0 +1,292 @@
+use std::collections::HashMap;
+
+use viper_ast::{Binding, Expr, ExprNode, Ident, ObjInit, Path, StructDef, Visibility};
+use viper_core::{
+    _type::{ArrayLength, Type},
+    emitter::Emitter,
+    error::{PrivateMember, ViperError},
+    span::Span,
+    types::TypeInterner,
+};
+
+use crate::typecheck::{receiver_struct, TypeTable};
+
+/// Checks that private fields and methods are only used
+/// from within the methods of the struct that defines them.
+///
+/// Structs are only visible within the module that defines them,
+/// so the resolver already rejects using one from another module.
+pub struct VisibilityChecker<'a> {
+    emitter: &'a mut dyn Emitter,
+
+    /// Every struct that is visible from the file being checked
+    structs: &'a HashMap<Ident, StructDef>,
+
+    /// Types of the checked expressions, used to find
+    /// the struct that a field or method belongs to
+    types: &'a TypeInterner,
+    table: &'a TypeTable,
+
+    /// The struct whose methods are being checked, if any
+    current: Option<Ident>,
+}
+
+impl<'a> VisibilityChecker<'a> {
+    /// Create a new [VisibilityChecker] that can see the structs in `structs`.
+    /// This runs after type checking, which fills in `table`
+    pub fn new(
+        emitter: &'a mut dyn Emitter,
+        structs: &'a HashMap<Ident, StructDef>,
+        types: &'a TypeInterner,
+        table: &'a TypeTable,
+    ) -> VisibilityChecker<'a> {
+        VisibilityChecker {
+            emitter,
+            structs,
+            types,
+            table,
+            current: None,
+        }
+    }
+
+    /// Check the top-level items of a file
+    pub fn check_items(&mut self, items: &[ExprNode]) {
+        for item in items {
+            self.check_expr(item);
+        }
+    }
+
+    fn check_expr(&mut self, expr: &ExprNode) {
+        match expr.inner() {
+            Expr::Path(path) => {
+                self.check_path(path);
+            }
+            Expr::ProcedureCall(call) => {
+                self.check_path(call.name());
+                for arg in call.arguments() {
+                    self.check_expr(arg);
+                }
+            }
+            Expr::ObjInitialization(init) => {
+                self.check_obj_init(init);
+            }
+
+            // Types can use the constants of a struct as array lengths
+            Expr::Let(init) => {
+                if let Some(ty) = init.dtype() {
+                    self.check_type(ty);
+                }
+                expr.for_each_child(&mut |child| self.check_expr(child));
+            }
+            Expr::ProcedureDefinition(def) => {
+                self.check_signature(def.parameters(), def.ret());
+                expr.for_each_child(&mut |child| self.check_expr(child));
+            }
+            Expr::Cast(_, ty) | Expr::SizeOf(ty) | Expr::AlignOf(ty) | Expr::OffsetOf(ty, _) => {
+                self.check_type(ty);
+                expr.for_each_child(&mut |child| self.check_expr(child));
+            }
+            Expr::ArrayRepeat(_, length) => {
+                self.check_length(length);
+                expr.for_each_child(&mut |child| self.check_expr(child));
+            }
+            Expr::MethodCall(call) => {
+                self.check_method(call.receiver(), call.name(), call.span());
+                expr.for_each_child(&mut |child| self.check_expr(child));
+            }
+
+            // Overloaded operators call their method on the first operand
+            Expr::BinaryOperation(_, operand, _) | Expr::Index(operand, _) | Expr::UnaryOperation(_, operand) => {
+                self.check_operator(expr, operand);
+                expr.for_each_child(&mut |child| self.check_expr(child));
+            }
+            Expr::Assignment(assignment) => {
+                self.check_operator(expr, assignment.target());
+                expr.for_each_child(&mut |child| self.check_expr(child));
+            }
+            Expr::MemberFieldAccess(field) => {
+                if let Some(def) = self.receiver(field.object()) {
+                    let declared = def.fields().iter().find(|f| f.binding().ident() == field.name());
+                    if let Some(declared) = declared {
+                        self.check_member(
+                            def,
+                            "field",
+                            declared.visibility(),
+                            field.name(),
+                            field.span(),
+                            declared.binding().span()
+                        );
+                    }
+                }
+
+                self.check_expr(field.object());
+            }
+            Expr::StructDef(def) => {
+                let outer = self.current.replace(def.identifier().clone());
+                for field in def.fields() {
+                    self.check_type(field.binding().ty());
+                }
+                for constant in def.consts() {
+                    self.check_type(constant.ty());
+                    self.check_expr(constant.value());
+                }
+                for method in def.methods() {
+                    self.check_signature(method.parameters(), method.ret());
+                    self.check_expr(method.body());
+                }
+                self.current = outer;
+            }
+
+            // The methods of an `impl` can see the private members of their struct
+            Expr::ImplDef(def) => {
+                let outer = self.current.replace(def.target().to_string());
+                for method in def.methods() {
+                    self.check_signature(method.parameters(), method.ret());
+                    self.check_expr(method.body());
+                }
+                self.current = outer;
+            }
+            _ => {
+                expr.for_each_child(&mut |child| self.check_expr(child));
+            }
+        }
+    }
+
+    /// Get the struct that a field or method of `expr` is looked up in
+    fn receiver(&self, expr: &ExprNode) -> Option<&'a StructDef> {
+        let ty = self.table.get(expr.id())?;
+        return receiver_struct(self.types, self.structs, ty);
+    }
+
+    /// Check that a method called on `receiver` is visible
+    fn check_method(&mut self, receiver: &ExprNode, name: &Ident, span: &Span) {
+        if let Some(def) = self.receiver(receiver) {
+            let method = def.methods().iter().find(|m| m.name() == name);
+            if let Some(method) = method {
+                self.check_member(def, "method", method.visibility(), name, span, method.span());
+            }
+        }
+    }
+
+    /// Check that the method an operator calls is visible, if it is overloaded
+    fn check_operator(&mut self, expr: &ExprNode, operand: &ExprNode) {
+        let table = self.table;
+        if let Some(method) = table.operator(expr.id()) {
+            self.check_method(operand, method, expr.span());
+        }
+    }
+
+    /// Check a path to a method or constant of a struct
+    /// `User::new`, `User::MAX`
+    fn check_path(&mut self, path: &Path) {
+        if path.segments().len() != 2 {
+            return;
+        }
+
+        let (parent, member) = (&path.segments()[0], &path.segments()[1]);
+        let def = match self.structs.get(parent.name()) {
+            Some(def) => def,
+            None => return,
+        };
+
+        let method = def.methods().iter().find(|m| m.name() == member.name());
+        if let Some(method) = method {
+            self.check_member(def, "method", method.visibility(), member.name(), member.span(), method.span());
+        }
+
+        let constant = def.consts().iter().find(|c| c.name() == member.name());
+        if let Some(constant) = constant {
+            self.check_member(def, "constant", constant.visibility(), member.name(), member.span(), constant.span());
+        }
+    }
+
+    /// Check the types of the parameters and the return type of a procedure or method
+    fn check_signature(&mut self, parameters: &[Binding], ret: &Type) {
+        for param in parameters {
+            self.check_type(param.ty());
+        }
+        self.check_type(ret);
+    }
+
+    /// Check the constants that a type uses as array lengths
+    /// `[i32; Buffer::SIZE]`
+    fn check_type(&mut self, ty: &Type) {
+        match ty {
+            Type::Array { element, length } => {
+                self.check_length(length);
+                self.check_type(element);
+            }
+            Type::Concrete { args: elements, .. } | Type::Tuple(elements) => {
+                for element in elements {
+                    self.check_type(element);
+                }
+            }
+            Type::Procedure { params, return_type, .. } | Type::Function { params, return_type } => {
+                for param in params {
+                    self.check_type(param);
+                }
+                self.check_type(return_type);
+            }
+            Type::Pointer { inner, .. }
+            | Type::Reference { inner, .. }
+            | Type::Slice(inner)
+            | Type::Optional(inner) => {
+                self.check_type(inner);
+            }
+            Type::Dyn(_) | Type::Void | Type::Inferred => {}
+        }
+    }
+
+    /// Check the constant that gives an array its length, if it is named
+    fn check_length(&mut self, length: &ArrayLength) {
+        if let ArrayLength::Named(path) = length {
+            self.check_path(path);
+        }
+    }
+
+    /// Check that every field being initialized is visible
+    fn check_obj_init(&mut self, init: &ObjInit) {
+        if let Some(def) = self.structs.get(&init.name().to_string()) {
+            for field in init.initializations() {
+                let declared = def.fields().iter().find(|f| f.binding().ident() == field.name());
+                if let Some(declared) = declared {
+                    self.check_member(
+                        def,
+                        "field",
+                        declared.visibility(),
+                        field.name(),
+                        field.span(),
+                        declared.binding().span()
+                    );
+                }
+            }
+        }
+
+        for field in init.initializations() {
+            self.check_expr(field.value());
+        }
+    }
+
+    /// Report the use of a private member of `def` from outside of its methods
+    fn check_member(
+        &mut self,
+        def: &StructDef,
+        kind: &str,
+        visibility: &Visibility,
+        member: &str,
+        span: &Span,
+        declared: &Span,
+    ) {
+        if matches!(visibility, Visibility::Public) || self.current.as_ref() == Some(def.identifier()) {
+            return;
+        }
+
+        self.emitter.emit_err(ViperError::PrivateMember(Box::new(PrivateMember {
+            kind: kind.to_string(),
+            parent: def.identifier().clone(),
+            member: member.to_string(),
+            span: span.clone(),
+            declared: declared.clone(),
+        })));
+    }
+}